@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity 0.8.10;
+
+// Compiled by compile.sh with solc 0.8.10, optimizer enabled for 100000
+// runs, targeting london, as the Aave v3 core contracts. Until it is next
+// run, the bytecode in FlashLiquidationReceiver.json is assembled by hand
+// to implement this contract, without the checks added by solc.
+
+interface IERC20 {
+  function approve(address spender, uint256 amount) external returns (bool);
+
+  function balanceOf(address account) external view returns (uint256);
+
+  function transfer(address to, uint256 amount) external returns (bool);
+}
+
+interface IPool {
+  function liquidationCall(
+    address collateralAsset,
+    address debtAsset,
+    address user,
+    uint256 debtToCover,
+    bool receiveAToken
+  ) external;
+}
+
+interface ISwapRouter {
+  struct ExactInputSingleParams {
+    address tokenIn;
+    address tokenOut;
+    uint24 fee;
+    address recipient;
+    uint256 deadline;
+    uint256 amountIn;
+    uint256 amountOutMinimum;
+    uint160 sqrtPriceLimitX96;
+  }
+
+  function exactInputSingle(ExactInputSingleParams calldata params)
+    external
+    payable
+    returns (uint256 amountOut);
+}
+
+// Receiver of an Aave flashLoanSimple, that uses the loan of the debt
+// asset to liquidate a user, sells the collateral received on Uniswap,
+// and sends the profit left after repaying the loan to the initiator.
+// The whole flash loan reverts if the liquidation does not repay it.
+// Each liquidator has its own receiver, which only accepts flash loans
+// of the pool started by that liquidator.
+contract FlashLiquidationReceiver {
+  address private immutable pool;
+  address private immutable liquidator;
+
+  constructor(address pool_, address liquidator_) {
+    pool = pool_;
+    liquidator = liquidator_;
+  }
+
+  function executeOperation(
+    address asset,
+    uint256 amount,
+    uint256 premium,
+    address initiator,
+    bytes calldata params
+  ) external returns (bool) {
+    require(msg.sender == pool);
+    require(initiator == liquidator);
+
+    (address collateralAsset, address user, address swapRouter, uint24 fee) = abi.decode(
+      params,
+      (address, address, address, uint24)
+    );
+
+    // The pool pulls the loan and premium back after this call
+    IERC20(asset).approve(msg.sender, type(uint256).max);
+    IPool(msg.sender).liquidationCall(collateralAsset, asset, user, amount, false);
+
+    if (collateralAsset != asset) {
+      uint256 collateral = IERC20(collateralAsset).balanceOf(address(this));
+      IERC20(collateralAsset).approve(swapRouter, type(uint256).max);
+      ISwapRouter(swapRouter).exactInputSingle(
+        ISwapRouter.ExactInputSingleParams({
+          tokenIn: collateralAsset,
+          tokenOut: asset,
+          fee: fee,
+          recipient: address(this),
+          deadline: type(uint256).max,
+          amountIn: collateral,
+          amountOutMinimum: 0,
+          sqrtPriceLimitX96: 0
+        })
+      );
+    }
+
+    uint256 balance = IERC20(asset).balanceOf(address(this));
+    require(balance >= amount + premium);
+    IERC20(asset).transfer(initiator, balance - (amount + premium));
+
+    return true;
+  }
+}
@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity 0.8.10;
+
+// Compiled by compile.sh with solc 0.8.10, optimizer enabled for 100000
+// runs, targeting london, as the Aave v3 core contracts. Until it is next
+// run, the bytecode in LiquidationBundleExecutor.json is assembled by hand
+// to implement this contract, without the checks added by solc.
+
+interface IERC20 {
+  function approve(address spender, uint256 amount) external returns (bool);
+
+  function balanceOf(address account) external view returns (uint256);
+
+  function transfer(address to, uint256 amount) external returns (bool);
+
+  function transferFrom(
+    address from,
+    address to,
+    uint256 amount
+  ) external returns (bool);
+}
+
+interface IPool {
+  function liquidationCall(
+    address collateralAsset,
+    address debtAsset,
+    address user,
+    uint256 debtToCover,
+    bool receiveAToken
+  ) external;
+}
+
+interface ISwapRouter {
+  struct ExactInputSingleParams {
+    address tokenIn;
+    address tokenOut;
+    uint24 fee;
+    address recipient;
+    uint256 deadline;
+    uint256 amountIn;
+    uint256 amountOutMinimum;
+    uint160 sqrtPriceLimitX96;
+  }
+
+  function exactInputSingle(ExactInputSingleParams calldata params)
+    external
+    payable
+    returns (uint256 amountOut);
+}
+
+// Executes an adversarial liquidation bundle in a single transaction.
+// Sells collateral on Uniswap to push down its price, liquidates the
+// users made liquidatable by the lower price, then buys back the debt
+// asset received from the manipulation to restore the price. Funds are
+// pulled from, and returned to, the caller. If any step fails the whole
+// bundle reverts.
+contract LiquidationBundleExecutor {
+  function _swap(
+    address swapRouter,
+    address tokenIn,
+    address tokenOut,
+    uint24 fee,
+    uint256 amountIn
+  ) internal returns (uint256) {
+    return
+      ISwapRouter(swapRouter).exactInputSingle(
+        ISwapRouter.ExactInputSingleParams({
+          tokenIn: tokenIn,
+          tokenOut: tokenOut,
+          fee: fee,
+          recipient: address(this),
+          deadline: type(uint256).max,
+          amountIn: amountIn,
+          amountOutMinimum: 0,
+          sqrtPriceLimitX96: 0
+        })
+      );
+  }
+
+  function execute(
+    address pool,
+    address collateralAsset,
+    address debtAsset,
+    address swapRouter,
+    uint24 fee,
+    uint256 collateralAmount,
+    uint256 debtAmount,
+    address[] calldata users
+  ) external {
+    IERC20(collateralAsset).transferFrom(msg.sender, address(this), collateralAmount);
+    IERC20(debtAsset).transferFrom(msg.sender, address(this), debtAmount);
+    IERC20(collateralAsset).approve(swapRouter, type(uint256).max);
+    IERC20(debtAsset).approve(swapRouter, type(uint256).max);
+    IERC20(debtAsset).approve(pool, type(uint256).max);
+
+    // Front-run, pushing down the price of the collateral
+    uint256 manipulationOut;
+    if (collateralAmount != 0) {
+      manipulationOut = _swap(swapRouter, collateralAsset, debtAsset, fee, collateralAmount);
+    }
+
+    // Liquidate as much debt as the close factor allows
+    for (uint256 i = 0; i < users.length; i++) {
+      IPool(pool).liquidationCall(collateralAsset, debtAsset, users[i], type(uint256).max, false);
+    }
+
+    // Back-run, unwinding the price manipulation
+    if (collateralAmount != 0) {
+      _swap(swapRouter, debtAsset, collateralAsset, fee, manipulationOut);
+    }
+
+    IERC20(collateralAsset).transfer(msg.sender, IERC20(collateralAsset).balanceOf(address(this)));
+    IERC20(debtAsset).transfer(msg.sender, IERC20(debtAsset).balanceOf(address(this)));
+  }
+}
@@ -0,0 +1,63 @@
+#!/usr/bin/env bash
+# Compile the liquidation contracts of the simulation, and update their
+# artifacts in this directory and their bytecode in
+# src/aave/protocol/aave_bytecode.rs
+#
+# Uses the compiler settings of the Aave v3 core contracts: solc 0.8.10
+# with the optimizer enabled for 100000 runs, targeting london. The
+# compiler is `solc` on the path, or set with SOLC.
+set -euo pipefail
+
+CONTRACTS=(FlashLiquidationReceiver LiquidationBundleExecutor)
+
+DIR="$(cd "$(dirname "$0")" && pwd)"
+BYTECODE="$DIR/../../src/aave/protocol/aave_bytecode.rs"
+SOLC="${SOLC:-solc}"
+
+if ! "$SOLC" --version | grep -q "Version: 0.8.10+"; then
+  echo "solc 0.8.10 is required, set SOLC to its path" >&2
+  exit 1
+fi
+
+OUTPUT="$(mktemp)"
+trap 'rm -f "$OUTPUT"' EXIT
+
+for NAME in "${CONTRACTS[@]}"; do
+  "$SOLC" --optimize --optimize-runs 100000 --evm-version london \
+    --combined-json abi,bin "$DIR/$NAME.sol" >"$OUTPUT"
+  python3 - "$OUTPUT" "$DIR" "$NAME" "$BYTECODE" <<'EOF'
+import json
+import re
+import sys
+
+output, directory, name, bytecode_path = sys.argv[1:]
+with open(output) as f:
+    contracts = json.load(f)["contracts"]
+contract = next(v for k, v in contracts.items() if k.endswith(":" + name))
+abi = contract["abi"]
+if isinstance(abi, str):
+    abi = json.loads(abi)
+
+with open(f"{directory}/{name}.abi", "w") as f:
+    f.write(json.dumps(abi, indent=2) + "\n")
+with open(f"{directory}/{name}.json", "w") as f:
+    artifact = {
+        "bytecode": "0x" + contract["bin"],
+        "constructor_args": [],
+        "deploy_address": "0x0000000000000000000000000000000000000000",
+        "name": name,
+    }
+    f.write(json.dumps(artifact, indent=2) + "\n")
+
+constant = re.sub(r"(?<!^)(?=[A-Z])", "_", name).upper()
+with open(bytecode_path) as f:
+    source = f.read()
+source = re.sub(
+    rf'// .*\npub const {constant}: &str = "[0-9a-f]*";',
+    f'// Compiled by contracts/aave/compile.sh\npub const {constant}: &str = "{contract["bin"]}";',
+    source,
+)
+with open(bytecode_path, "w") as f:
+    f.write(source)
+EOF
+done
@@ -0,0 +1,140 @@
+//! Agent sets that can be cloned
+//!
+//! Counterparts of the `verbs_rs` [AgentVec](verbs_rs::agent::AgentVec)
+//! and [SingletonAgent](verbs_rs::agent::SingletonAgent) sets that
+//! implement [Clone], so the state of all agents can be saved
+//! mid-run and used as the starting point of several continuations.
+//!
+
+use super::OutcomeObserver;
+use crate::aave::types::TransactionOutcome;
+use alloy_primitives::Address;
+use rand::RngCore;
+use std::mem;
+use verbs_rs::agent::{Agent, AgentSet, RecordedAgent, RecordedAgentSet};
+use verbs_rs::contract::Transaction;
+use verbs_rs::env::{Env, Validator};
+use verbs_rs::DB;
+
+/// Outcomes of the transactions sent by an agent
+pub(super) fn outcomes_of(
+    outcomes: &[TransactionOutcome],
+    agent: Address,
+) -> Vec<TransactionOutcome> {
+    outcomes
+        .iter()
+        .filter(|x| x.sender == agent)
+        .cloned()
+        .collect()
+}
+
+/// Vector of agents of a single type, and their records
+#[derive(Clone)]
+pub struct CloneableAgentVec<R, A: Agent + RecordedAgent<R>> {
+    agents: Vec<A>,
+    records: Vec<Vec<R>>,
+}
+
+impl<R, A: Agent + RecordedAgent<R>> CloneableAgentVec<R, A> {
+    pub fn from(agents: Vec<A>) -> Self {
+        CloneableAgentVec {
+            agents,
+            records: Vec::new(),
+        }
+    }
+
+    /// Mutable access to the agents in the set
+    pub fn agents_mut(&mut self) -> &mut Vec<A> {
+        &mut self.agents
+    }
+}
+
+impl<R, A: Agent + RecordedAgent<R> + OutcomeObserver> CloneableAgentVec<R, A> {
+    /// Give each agent the outcomes of its own transactions
+    pub fn observe_outcomes(&mut self, outcomes: &[TransactionOutcome]) {
+        for agent in self.agents.iter_mut() {
+            let address = agent.get_address();
+            agent.observe_outcomes(outcomes_of(outcomes, address));
+        }
+    }
+}
+
+impl<R, A: Agent + RecordedAgent<R>> RecordedAgentSet<R> for CloneableAgentVec<R, A> {
+    fn take_records(&mut self) -> Vec<Vec<R>> {
+        mem::take(&mut self.records)
+    }
+}
+
+impl<R: 'static, A: Agent + RecordedAgent<R> + 'static> AgentSet for CloneableAgentVec<R, A> {
+    fn call<D: DB, V: Validator, RG: RngCore>(
+        &mut self,
+        rng: &mut RG,
+        env: &mut Env<D, V>,
+    ) -> Vec<Transaction> {
+        self.agents
+            .iter_mut()
+            .flat_map(|x| x.update(rng, env))
+            .collect()
+    }
+
+    fn record<D: DB, V: Validator>(&mut self, env: &mut Env<D, V>) {
+        let records: Vec<R> = self.agents.iter_mut().map(|x| x.record(env)).collect();
+        self.records.push(records);
+    }
+
+    fn get_addresses(&self) -> Vec<Address> {
+        self.agents.iter().map(|x| x.get_address()).collect()
+    }
+}
+
+/// Set containing a single agent, and its records
+#[derive(Clone)]
+pub struct CloneableSingletonAgent<R, A: Agent + RecordedAgent<R>> {
+    agent: A,
+    records: Vec<R>,
+}
+
+impl<R, A: Agent + RecordedAgent<R>> CloneableSingletonAgent<R, A> {
+    pub fn from(agent: A) -> Self {
+        CloneableSingletonAgent {
+            agent,
+            records: Vec::new(),
+        }
+    }
+
+    /// Mutable access to the agent
+    pub fn agent_mut(&mut self) -> &mut A {
+        &mut self.agent
+    }
+
+    /// Take ownership of recorded data of the agent
+    pub fn take_records(&mut self) -> Vec<R> {
+        mem::take(&mut self.records)
+    }
+}
+
+impl<R, A: Agent + RecordedAgent<R> + OutcomeObserver> CloneableSingletonAgent<R, A> {
+    /// Give the agent the outcomes of its own transactions
+    pub fn observe_outcomes(&mut self, outcomes: &[TransactionOutcome]) {
+        let address = self.agent.get_address();
+        self.agent.observe_outcomes(outcomes_of(outcomes, address));
+    }
+}
+
+impl<R: 'static, A: Agent + RecordedAgent<R> + 'static> AgentSet for CloneableSingletonAgent<R, A> {
+    fn call<D: DB, V: Validator, RG: RngCore>(
+        &mut self,
+        rng: &mut RG,
+        env: &mut Env<D, V>,
+    ) -> Vec<Transaction> {
+        self.agent.update(rng, env)
+    }
+
+    fn record<D: DB, V: Validator>(&mut self, env: &mut Env<D, V>) {
+        self.records.push(self.agent.record(env));
+    }
+
+    fn get_addresses(&self) -> Vec<Address> {
+        vec![self.agent.get_address()]
+    }
+}
@@ -1,51 +1,271 @@
-use crate::aave::calls;
+use super::OutcomeObserver;
+use crate::aave::protocol::aave_abi;
+use crate::aave::types::{
+    BorrowerAttributes, BorrowerPopulation, BorrowerRecord, InterestRateMode, TransactionOutcome,
+};
+use crate::aave::{calls, limits};
 
 use alloy_primitives::{Address, Uint, U256};
+use alloy_sol_types::SolCall;
 use rand::Rng;
 use verbs_rs::agent::{Agent, RecordedAgent};
 use verbs_rs::contract::Transaction;
 use verbs_rs::env::{Env, Validator};
+use verbs_rs::utils::scale_data_value;
 use verbs_rs::DB;
 
+/// Agent supplying collateral to Aave and borrowing against it
+///
+/// The attributes of the agent are drawn from a [BorrowerPopulation]
+/// the first time it is updated. After supplying collateral the agent
+/// borrows up to its target health factor, and when its health factor
+/// leaves the range between its repay and re-leverage thresholds it
+/// either repays debt, tops up collateral, withdraws excess collateral
+/// or borrows more, to restore its target health factor. Agents
+/// drawn into an e-mode category opt in to it along with their first
+/// supply, borrowing at the loan to value of the category.
+///
+/// Supplies and borrows are limited to the headroom under the caps of
+/// the reserves, and under the debt ceiling of isolated collateral. If
+/// the first supply of the agent reverts, for example as others used
+/// the headroom under the supply cap first, it is retried.
+///
+/// Agents borrow at a stable or variable rate, and if the rate of the
+/// other mode becomes cheaper by more than their swap spread, swap
+/// their debt to the other mode. Stable borrows are also limited to
+/// the share of the liquidity of the reserve that the pool allows to
+/// be borrowed at a stable rate.
+///
+/// Agents can also mirror an existing position, in which case they
+/// start with collateral of token a and debt of token b already in
+/// place, opened at initialisation.
+#[derive(Clone)]
 pub struct BorrowAgent {
     address: Address,
-    activation_rate: f64,
-    borrow_token_decimals: U256,
-    _borrow_token_ltv: U256,
+    population: BorrowerPopulation,
+    attributes: Option<BorrowerAttributes>,
     has_supplied: bool,
-    has_borrowed: bool,
+    /// The first supply of the agent is in the last block
+    opening: bool,
+    has_closed: bool,
+    mirrored: bool,
     pool_address: Address,
     oracle_address: Address,
-    supply_token_address: Address,
-    borrow_token_address: Address,
+    token_a_address: Address,
+    token_b_address: Address,
+    token_a_decimals: U256,
+    token_b_decimals: U256,
+}
+
+/// Convert an amount in the Aave base currency to an amount of a token
+pub fn base_to_token(amount_base: f64, price: U256, decimals: U256) -> U256 {
+    let price = scale_data_value(price, 8, 8);
+    let decimals = decimals.to::<i32>();
+    U256::from((amount_base / price * 10f64.powi(decimals)) as u128)
 }
 
 impl BorrowAgent {
     pub fn new(
         idx: usize,
-        activation_rate: f64,
-        borrow_token_ltv: U256,
-        borrow_token_decimals: U256,
+        population: BorrowerPopulation,
         pool_address: Address,
         oracle_address: Address,
-        supply_token_address: Address,
-        borrow_token_address: Address,
+        token_a_address: Address,
+        token_b_address: Address,
+        token_a_decimals: U256,
+        token_b_decimals: U256,
     ) -> Self {
         let address = Address::from(Uint::from(idx));
 
         BorrowAgent {
             address,
-            activation_rate,
-            _borrow_token_ltv: borrow_token_ltv,
-            borrow_token_decimals,
+            population,
+            attributes: None,
             has_supplied: false,
-            has_borrowed: false,
+            opening: false,
+            has_closed: false,
+            mirrored: false,
             pool_address,
             oracle_address,
-            supply_token_address,
-            borrow_token_address,
+            token_a_address,
+            token_b_address,
+            token_a_decimals,
+            token_b_decimals,
+        }
+    }
+
+    /// Agent mirroring an existing position of the user at `address`
+    ///
+    /// The position should be opened separately, for example with
+    /// [open_mirrored_positions](crate::aave::deployment::open_mirrored_positions).
+    pub fn mirror(
+        address: Address,
+        population: BorrowerPopulation,
+        pool_address: Address,
+        oracle_address: Address,
+        token_a_address: Address,
+        token_b_address: Address,
+        token_a_decimals: U256,
+        token_b_decimals: U256,
+    ) -> Self {
+        BorrowAgent {
+            address,
+            has_supplied: true,
+            mirrored: true,
+            ..Self::new(
+                0,
+                population,
+                pool_address,
+                oracle_address,
+                token_a_address,
+                token_b_address,
+                token_a_decimals,
+                token_b_decimals,
+            )
         }
     }
+
+    /// Attributes of the agent, if they have been drawn
+    pub fn get_attributes(&self) -> Option<BorrowerAttributes> {
+        self.attributes
+    }
+
+    /// Address and decimals of the token supplied as collateral, and of the borrowed token
+    fn get_tokens(&self, attributes: &BorrowerAttributes) -> ((Address, U256), (Address, U256)) {
+        let token_a = (self.token_a_address, self.token_a_decimals);
+        let token_b = (self.token_b_address, self.token_b_decimals);
+        match attributes.reverse_assets {
+            true => (token_b, token_a),
+            false => (token_a, token_b),
+        }
+    }
+
+    fn get_price<D: DB, V: Validator>(&self, network: &mut Env<D, V>, token: Address) -> U256 {
+        calls::get_asset_price(network, Address::ZERO, self.oracle_address, token)
+    }
+
+    /// Stable and variable debt of the agent in a token
+    fn get_debt<D: DB, V: Validator>(
+        &self,
+        network: &mut Env<D, V>,
+        token: Address,
+    ) -> (U256, U256) {
+        let reserve = calls::get_reserve_data(network, Address::ZERO, self.pool_address, token);
+        (
+            calls::balance_of(network, self.address, reserve.stableDebtTokenAddress),
+            calls::balance_of(network, self.address, reserve.variableDebtTokenAddress),
+        )
+    }
+
+    /// Interest rate mode of the debt of the agent in a token, or the
+    /// mode it borrows in if it has no debt
+    fn get_rate_mode<D, V>(
+        &self,
+        network: &mut Env<D, V>,
+        token: Address,
+        attributes: &BorrowerAttributes,
+    ) -> InterestRateMode
+    where
+        D: DB,
+        V: Validator,
+    {
+        match self.get_debt(network, token) {
+            (stable, _) if stable > U256::ZERO => InterestRateMode::Stable,
+            (_, variable) if variable > U256::ZERO => InterestRateMode::Variable,
+            _ => attributes.rate_mode,
+        }
+    }
+
+    /// Borrow rate paid by the agent on its debt in a token, less the
+    /// current rate of the other mode, as fractions per year
+    fn get_rate_spread<D, V>(
+        &self,
+        network: &mut Env<D, V>,
+        token: Address,
+        rate_mode: InterestRateMode,
+    ) -> f64
+    where
+        D: DB,
+        V: Validator,
+    {
+        let reserve = calls::get_reserve_data(network, Address::ZERO, self.pool_address, token);
+        let (paid, other) = match rate_mode {
+            InterestRateMode::Stable => (
+                calls::get_user_stable_rate(network, reserve.stableDebtTokenAddress, self.address)
+                    .to::<u128>(),
+                reserve.currentVariableBorrowRate,
+            ),
+            InterestRateMode::Variable => (
+                reserve.currentVariableBorrowRate,
+                reserve.currentStableBorrowRate,
+            ),
+        };
+        (paid as f64 - other as f64) / 1e27
+    }
+
+    /// Supply of a token, limited to the headroom under its supply cap
+    ///
+    /// Supplies that are limited are not checked, as they revert if
+    /// others use the headroom first.
+    fn limited_supply_call<D, V>(
+        &self,
+        network: &mut Env<D, V>,
+        token: Address,
+        amount: U256,
+    ) -> Option<Transaction>
+    where
+        D: DB,
+        V: Validator,
+    {
+        let headroom = limits::supply_headroom(network, self.pool_address, token);
+        let amount = headroom.map_or(amount, |x| amount.min(x));
+        (amount > U256::ZERO).then(|| Transaction {
+            checked: headroom.is_none(),
+            ..calls::supply_call(self.address, self.pool_address, token, amount)
+        })
+    }
+
+    /// Borrow of a token, limited to the headroom under its borrow cap,
+    /// and under the debt ceiling if the collateral of the agent is isolated
+    ///
+    /// Borrows that are limited are not checked, as they revert if
+    /// others use the headroom first.
+    fn limited_borrow_call<D, V>(
+        &self,
+        network: &mut Env<D, V>,
+        (supply_token, borrow_token): (Address, Address),
+        borrow_decimals: U256,
+        amount: U256,
+        rate_mode: InterestRateMode,
+    ) -> Option<Transaction>
+    where
+        D: DB,
+        V: Validator,
+    {
+        let headroom = [
+            limits::borrow_headroom(network, self.pool_address, borrow_token),
+            limits::debt_ceiling_headroom(
+                network,
+                self.pool_address,
+                supply_token,
+                borrow_decimals.to::<u8>(),
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        let amount = headroom.map_or(amount, |x| amount.min(x));
+        (amount > U256::ZERO).then(|| Transaction {
+            checked: headroom.is_none(),
+            ..calls::borrow_call(
+                self.address,
+                self.pool_address,
+                borrow_token,
+                amount,
+                rate_mode,
+            )
+        })
+    }
 }
 
 impl Agent for BorrowAgent {
@@ -55,51 +275,151 @@ impl Agent for BorrowAgent {
         V: Validator,
         R: Rng,
     {
-        if rng.gen::<f64>() < self.activation_rate {
-            if !self.has_supplied {
-                let supply_call = calls::supply_call(
+        let attributes = *self.attributes.get_or_insert_with(|| {
+            let attributes = self.population.sample(rng);
+            // Mirrored positions keep their assets, and variable rate debt
+            BorrowerAttributes {
+                reverse_assets: attributes.reverse_assets && !self.mirrored,
+                rate_mode: match self.mirrored {
+                    true => InterestRateMode::Variable,
+                    false => attributes.rate_mode,
+                },
+                ..attributes
+            }
+        });
+
+        if self.has_closed || rng.gen::<f64>() >= attributes.activation_rate {
+            return Vec::default();
+        }
+
+        let ((supply_token, supply_decimals), (borrow_token, borrow_decimals)) =
+            self.get_tokens(&attributes);
+
+        if !self.has_supplied {
+            let price = self.get_price(network, supply_token);
+            let amount = base_to_token(attributes.position_size, price, supply_decimals);
+            let supply_call = match self.limited_supply_call(network, supply_token, amount) {
+                Some(transaction) => transaction,
+                None => return Vec::default(),
+            };
+            self.has_supplied = true;
+            self.opening = true;
+
+            // Opting in succeeds in either order, as the agent has no debt
+            return match attributes.e_mode {
+                0 => vec![supply_call],
+                category => vec![
+                    calls::set_user_e_mode_call(self.address, self.pool_address, category),
+                    supply_call,
+                ],
+            };
+        }
+
+        let user_data =
+            calls::get_user_data(network, Address::ZERO, self.pool_address, self.address);
+        let collateral = scale_data_value(user_data.totalCollateralBase, 8, 8);
+        let debt = scale_data_value(user_data.totalDebtBase, 8, 8);
+        let liquidation_threshold = scale_data_value(user_data.currentLiquidationThreshold, 4, 4);
+        let health_factor = scale_data_value(user_data.healthFactor, 18, 6);
+
+        let rate_mode = self.get_rate_mode(network, borrow_token, &attributes);
+
+        if debt > 0. && rng.gen::<f64>() < self.population.behaviour.close_rate {
+            // Repay all debt and withdraw all collateral
+            self.has_closed = true;
+            return vec![
+                calls::repay_call(
                     self.address,
                     self.pool_address,
-                    self.supply_token_address,
-                    U256::from(10_u128.pow(18)),
-                );
-                self.has_supplied = true;
-
-                vec![supply_call]
-            } else if !self.has_borrowed {
-                let user_data =
-                    calls::get_user_data(network, Address::ZERO, self.pool_address, self.address);
-                let available_borrow_base = user_data.availableBorrowsBase;
-                let borrow_asset_price = calls::get_asset_price(
-                    network,
-                    Address::ZERO,
-                    self.oracle_address,
-                    self.borrow_token_address,
-                );
-
-                let exp = U256::from(10u128).pow(self.borrow_token_decimals - U256::from(4u128));
-                // Agent borrows a random fraction of the available to borrow amount
-                let u = U256::from(rng.gen_range(8000..9000));
-                let available_borrow = exp * available_borrow_base * u / borrow_asset_price;
-
-                if available_borrow > U256::ZERO {
-                    let borrow_call = calls::borrow_call(
+                    borrow_token,
+                    U256::MAX,
+                    rate_mode,
+                ),
+                calls::withdraw_call(self.address, self.pool_address, supply_token, U256::MAX),
+            ];
+        }
+
+        // Swap the debt to the other mode if its rate is cheaper by more than the spread
+        if let Some(spread) = self.population.behaviour.rate_swap_spread {
+            if debt > 0. && self.get_rate_spread(network, borrow_token, rate_mode) > spread {
+                return vec![calls::swap_borrow_rate_mode_call(
+                    self.address,
+                    self.pool_address,
+                    borrow_token,
+                    rate_mode,
+                )];
+            }
+        }
+
+        // Collateral and debt (in base currency) at the target health factor
+        let target_collateral = debt * attributes.target_health_factor / liquidation_threshold;
+        let target_debt = collateral * liquidation_threshold / attributes.target_health_factor;
+
+        let call = if debt > 0. && health_factor < attributes.repay_threshold {
+            if rng.gen::<f64>() < self.population.behaviour.top_up_probability {
+                let price = self.get_price(network, supply_token);
+                let amount = base_to_token(target_collateral - collateral, price, supply_decimals);
+                self.limited_supply_call(network, supply_token, amount)
+            } else {
+                let price = self.get_price(network, borrow_token);
+                let amount = base_to_token(debt - target_debt, price, borrow_decimals);
+                (amount > U256::ZERO).then(|| {
+                    calls::repay_call(
                         self.address,
                         self.pool_address,
-                        self.borrow_token_address,
-                        available_borrow,
-                    );
-                    self.has_borrowed = true;
-                    vec![borrow_call]
-                } else {
-                    Vec::default()
-                }
+                        borrow_token,
+                        amount,
+                        rate_mode,
+                    )
+                })
+            }
+        } else if debt == 0. || health_factor > attributes.releverage_threshold {
+            if debt > 0. && rng.gen::<f64>() < self.population.behaviour.withdraw_probability {
+                let price = self.get_price(network, supply_token);
+                let amount = base_to_token(collateral - target_collateral, price, supply_decimals);
+                (amount > U256::ZERO).then(|| {
+                    calls::withdraw_call(self.address, self.pool_address, supply_token, amount)
+                })
             } else {
-                Vec::default()
+                // Borrowing is also capped by the loan to value of the
+                // collateral, by the liquidity of the reserve (of which
+                // only a share can be borrowed at a stable rate), and
+                // by the limits of the pool
+                let available = scale_data_value(user_data.availableBorrowsBase, 8, 8);
+                let price = self.get_price(network, borrow_token);
+                let a_token =
+                    calls::get_a_token(network, Address::ZERO, self.pool_address, borrow_token);
+                let liquidity = calls::balance_of(network, a_token, borrow_token);
+                let liquidity = match rate_mode {
+                    InterestRateMode::Stable => {
+                        liquidity
+                            * calls::get_max_stable_rate_borrow_size_percent(
+                                network,
+                                self.pool_address,
+                            )
+                            / U256::from(10000u64)
+                    }
+                    InterestRateMode::Variable => liquidity,
+                };
+                let amount = base_to_token(
+                    (target_debt - debt).min(0.9 * available),
+                    price,
+                    borrow_decimals,
+                )
+                .min(liquidity);
+                self.limited_borrow_call(
+                    network,
+                    (supply_token, borrow_token),
+                    borrow_decimals,
+                    amount,
+                    rate_mode,
+                )
             }
         } else {
-            Vec::default()
-        }
+            None
+        };
+
+        call.into_iter().collect()
     }
 
     fn get_address(&self) -> Address {
@@ -107,8 +427,34 @@ impl Agent for BorrowAgent {
     }
 }
 
-impl RecordedAgent<U256> for BorrowAgent {
-    fn record<D: DB, V: Validator>(&mut self, _env: &mut Env<D, V>) -> U256 {
-        U256::ZERO
+impl OutcomeObserver for BorrowAgent {
+    fn observe_outcomes(&mut self, outcomes: Vec<TransactionOutcome>) {
+        if !self.opening {
+            return;
+        }
+        self.opening = false;
+        let supply_selector = aave_abi::Pool_Implementation::supplyCall::SELECTOR;
+        if outcomes
+            .iter()
+            .any(|x| x.function_selector == supply_selector && !x.success)
+        {
+            self.has_supplied = false;
+        }
+    }
+}
+
+impl RecordedAgent<BorrowerRecord> for BorrowAgent {
+    fn record<D: DB, V: Validator>(&mut self, env: &mut Env<D, V>) -> BorrowerRecord {
+        let health_factor =
+            calls::get_user_data(env, Address::ZERO, self.pool_address, self.address).healthFactor;
+        // Attributes are drawn at the first update, before the first record
+        let (stable_debt, variable_debt) = match self.attributes {
+            Some(attributes) => {
+                let (_, (borrow_token, _)) = self.get_tokens(&attributes);
+                self.get_debt(env, borrow_token)
+            }
+            None => (U256::ZERO, U256::ZERO),
+        };
+        (health_factor, stable_debt, variable_debt)
     }
 }
@@ -1,34 +1,89 @@
+use super::OutcomeObserver;
 use crate::aave::calls;
+use crate::aave::gas::{self, GasPricer};
+use crate::aave::indexer::BorrowerIndex;
+use crate::aave::liquidation::{self, Liquidation, LiquidationTerms};
 use crate::aave::protocol::{aave_abi, uniswap_abi};
 use crate::aave::types;
-use alloy_primitives::{Address, Log, Uint, U256};
-use alloy_sol_types::SolEvent;
+use alloy_primitives::{Address, Uint, I256, U256};
 use rand::Rng;
+use std::collections::BTreeMap;
 use std::mem;
 use verbs_rs::agent::{Agent, RecordedAgent};
 use verbs_rs::contract::Transaction;
-use verbs_rs::env::{Env, RevertError, Validator};
+use verbs_rs::env::{Env, Validator};
 use verbs_rs::utils::{div_u256, scale_data_value, Eth};
 use verbs_rs::DB;
 
+#[derive(Clone)]
 pub struct LiquidationAgent {
     address: Address,
     pool_address: Address,
     oracle_address: Address,
     collateral_token_address: Address,
     debt_token_address: Address,
-    liquidation_addresses: Vec<Address>,
+    /// Assets the liquidator can receive as collateral, or repay as debt
+    assets: Vec<Address>,
+    borrower_index: BorrowerIndex,
     current_user_data: types::UserData,
     adversarial: bool,
     uniswap_pool: Address,
     quoter: Address,
     swap_router: Address,
     uniswap_fee: u32,
+    /// Receiver contract used to fund liquidations with flash loans,
+    /// if the liquidator does not use its own inventory
+    flash_loan_receiver: Option<Address>,
+    /// Executor contract used to submit adversarial liquidations as
+    /// atomic bundles, if the liquidator uses its own inventory
+    bundle_executor: Option<Address>,
+    /// Finite inventory and rebalancing policy, if the liquidator
+    /// does not have effectively unlimited balances
+    capital: Option<types::LiquidatorCapital>,
+    /// Strategy used to bid for priority, if the liquidator bids
+    gas_bid_strategy: Option<types::GasBidStrategy>,
+    gas_pricer: GasPricer,
+    /// Gas expected to be used by a liquidation, updated from the
+    /// outcomes of the liquidations of the liquidator
+    gas_estimate: u64,
+    /// Liquidations lost to other liquidators since the last one won
+    losses: u32,
+    /// Bids submitted in the current step, resolved once processed
+    pending_bids: Vec<types::LiquidationBid>,
+    bids: Vec<types::LiquidationBid>,
+    /// Bundle submitted in the current step, resolved once processed
+    pending_bundle: Option<PendingBundle>,
+    bundles: Vec<types::AdversarialBundle>,
+    /// Outcomes of the transactions of the liquidator in the last block
+    last_outcomes: Vec<types::TransactionOutcome>,
     balance_debt_asset: Vec<U256>,
     balance_collateral_asset: Vec<U256>,
     step: i16,
 }
 
+/// Adversarial bundle awaiting its outcome, with the state of the
+/// liquidator before it was executed
+#[derive(Clone)]
+struct PendingBundle {
+    bundle: types::AdversarialBundle,
+    executor: Address,
+    /// Oracle prices of the collateral and debt assets
+    prices: (U256, U256),
+    decimals: (U256, U256),
+    /// Balances of the collateral and debt assets
+    balances: (U256, U256),
+}
+
+/// Value of a signed amount of an asset in the base currency of the oracle
+fn base_value(amount: I256, price: U256, decimals: U256) -> f64 {
+    let value = amount * I256::from_raw(price) / I256::from_raw(U256::from(10).pow(decimals));
+    let scaled = scale_data_value(value.unsigned_abs(), 8, 8);
+    match value.is_negative() {
+        true => -scaled,
+        false => scaled,
+    }
+}
+
 fn scale_data(
     x: &(
         Address,
@@ -52,14 +107,23 @@ impl LiquidationAgent {
         oracle_address: Address,
         collateral_token_address: Address,
         debt_token_address: Address,
-        liquidation_addresses: Vec<Address>,
+        known_users: Vec<Address>,
         adversarial: bool,
         uniswap_pool: Address,
         quoter: Address,
         swap_router: Address,
         uniswap_fee: u32,
+        flash_loan_receiver: Option<Address>,
+        bundle_executor: Option<Address>,
+        capital: Option<types::LiquidatorCapital>,
+        gas_bid_strategy: Option<types::GasBidStrategy>,
+        gas_pricer: GasPricer,
     ) -> Self {
         let address = Address::from(Uint::from(idx));
+        let gas_estimate = match flash_loan_receiver {
+            Some(_) => gas::FLASH_LOAN_LIQUIDATION_GAS,
+            None => gas::LIQUIDATION_GAS,
+        };
 
         LiquidationAgent {
             address,
@@ -67,76 +131,510 @@ impl LiquidationAgent {
             oracle_address,
             collateral_token_address,
             debt_token_address,
-            liquidation_addresses,
+            assets: vec![collateral_token_address, debt_token_address],
+            borrower_index: BorrowerIndex::new(pool_address, known_users),
             current_user_data: Vec::new(),
             adversarial,
             uniswap_pool,
             quoter,
             swap_router,
             uniswap_fee,
+            flash_loan_receiver,
+            bundle_executor,
+            capital,
+            gas_bid_strategy,
+            gas_pricer,
+            gas_estimate,
+            losses: 0,
+            pending_bids: Vec::new(),
+            bids: Vec::new(),
+            pending_bundle: None,
+            bundles: Vec::new(),
+            last_outcomes: Vec::new(),
             balance_debt_asset: Vec::new(),
             balance_collateral_asset: Vec::new(),
             step: 0i16,
         }
     }
 
-    fn accountability<D, V>(&self, network: &mut Env<D, V>, user: Address, amount: U256) -> bool
+    /// Outcomes of the transactions of the liquidator in the last block,
+    /// including the reason any liquidation reverted
+    pub fn last_outcomes(&self) -> &[types::TransactionOutcome] {
+        &self.last_outcomes
+    }
+
+    /// Switch between the standard and adversarial liquidation strategies
+    pub fn set_adversarial(&mut self, adversarial: bool) {
+        self.adversarial = adversarial;
+    }
+
+    /// Take the bids of the liquidator, and their outcomes
+    pub fn take_bids(&mut self) -> Vec<types::LiquidationBid> {
+        mem::take(&mut self.bids)
+    }
+
+    /// Take the adversarial bundles of the liquidator, and their outcomes
+    pub fn take_bundles(&mut self) -> Vec<types::AdversarialBundle> {
+        mem::take(&mut self.bundles)
+    }
+
+    /// Users with open debt, updated from the pool events of the last block
+    fn get_borrowers<D: DB, V: Validator>(&mut self, network: &mut Env<D, V>) -> Vec<Address> {
+        self.borrower_index.update(network);
+        self.borrower_index.users().copied().collect()
+    }
+
+    /// Most profitable liquidation of a user
+    ///
+    /// Considers each pair of an asset the user has as collateral and
+    /// an asset it has borrowed, and the amount of debt to cover that
+    /// maximises the profit after selling the collateral received for
+    /// the debt asset on Uniswap, covering no more debt than the
+    /// liquidator can fund from `inventory`. The cost of the gas of
+    /// the liquidation is deducted from its profit, and liquidations
+    /// that do not cover it are dropped.
+    fn best_liquidation<D, V>(
+        &self,
+        network: &mut Env<D, V>,
+        user: Address,
+        health_factor: U256,
+        inventory: &BTreeMap<Address, U256>,
+    ) -> Option<Liquidation>
     where
         D: DB,
         V: Validator,
     {
-        let call_result: Result<
-            (
-                aave_abi::Pool_Implementation::liquidationCallReturn,
-                Vec<Log>,
+        let user_configuration =
+            calls::get_user_configuration(network, Address::ZERO, self.pool_address, user);
+        let flash_loan_premium = match self.flash_loan_receiver {
+            Some(_) => calls::get_flash_loan_premium(network, Address::ZERO, self.pool_address),
+            None => 0,
+        };
+
+        // Collateral and debt of the user, price and configuration of each asset
+        let positions: Vec<(Address, U256, U256, U256, types::ReserveConfiguration)> = self
+            .assets
+            .iter()
+            .map(|asset| {
+                let reserve =
+                    calls::get_reserve_data(network, Address::ZERO, self.pool_address, *asset);
+                let configuration =
+                    types::ReserveConfiguration::from_bitmap(reserve.configuration.data);
+                let is_collateral = user_configuration.bit(2 * reserve.id as usize + 1);
+                let collateral = match is_collateral {
+                    true => calls::balance_of(network, user, reserve.aTokenAddress),
+                    false => U256::ZERO,
+                };
+                let debt = calls::balance_of(network, user, reserve.variableDebtTokenAddress)
+                    + calls::balance_of(network, user, reserve.stableDebtTokenAddress);
+                let price =
+                    calls::get_asset_price(network, Address::ZERO, self.oracle_address, *asset);
+                (*asset, collateral, debt, price, configuration)
+            })
+            .collect();
+
+        // Collateral in the e-mode category of the user is liquidated
+        // with the bonus of the category
+        let e_mode = calls::get_user_e_mode(network, Address::ZERO, self.pool_address, user);
+        let e_mode_bonus = match e_mode {
+            0 => None,
+            category => Some(
+                calls::get_e_mode_category_data(network, Address::ZERO, self.pool_address, category)
+                    .liquidationBonus as u64,
             ),
-            RevertError,
-        > = network.direct_call(
+        };
+
+        let mut liquidations = Vec::new();
+
+        for (collateral_asset, user_collateral, _, collateral_price, collateral_config) in
+            positions.iter().filter(|x| x.1 > U256::ZERO)
+        {
+            for (debt_asset, _, user_debt, debt_price, debt_config) in
+                positions.iter().filter(|x| x.2 > U256::ZERO)
+            {
+                let terms = LiquidationTerms {
+                    health_factor,
+                    user_debt: *user_debt,
+                    user_collateral: *user_collateral,
+                    debt_price: *debt_price,
+                    collateral_price: *collateral_price,
+                    debt_decimals: debt_config.decimals,
+                    collateral_decimals: collateral_config.decimals,
+                    liquidation_bonus: match e_mode_bonus {
+                        Some(bonus) if collateral_config.e_mode_category == e_mode => bonus,
+                        _ => collateral_config.liquidation_bonus,
+                    },
+                    liquidation_protocol_fee: collateral_config.liquidation_protocol_fee,
+                    flash_loan_premium,
+                };
+                let budget = inventory[debt_asset];
+                let optimum = liquidation::optimal_debt_to_cover_within(&terms, budget, |amount| {
+                    match collateral_asset == debt_asset {
+                        true => Some(amount),
+                        false => calls::quote_v2_exact_input_swap(
+                            network,
+                            self.address,
+                            *collateral_asset,
+                            *debt_asset,
+                            self.uniswap_fee,
+                            amount,
+                            self.quoter,
+                        )
+                        .map(|x| x.amountOut),
+                    }
+                });
+                if let Some((debt_to_cover, profit)) = optimum {
+                    liquidations.push(Liquidation {
+                        user,
+                        collateral_asset: *collateral_asset,
+                        debt_asset: *debt_asset,
+                        debt_to_cover,
+                        collateral_received: terms.collateral_received(debt_to_cover).0,
+                        profit,
+                        gas_cost: I256::ZERO,
+                    });
+                }
+            }
+        }
+
+        let gas_cost = I256::from_raw(self.gas_pricer.cost(network, self.gas_estimate));
+        liquidations
+            .into_iter()
+            .max_by_key(|x| x.profit)
+            .map(|x| Liquidation {
+                profit: x.profit - gas_cost,
+                gas_cost,
+                ..x
+            })
+            .filter(|x| x.profit > I256::ZERO)
+    }
+
+    /// Amount of each asset the liquidator can spend on liquidations
+    ///
+    /// Unlimited unless the liquidator has a finite inventory, flash
+    /// loan liquidations being funded by the loan.
+    fn inventory<D: DB, V: Validator>(&self, network: &mut Env<D, V>) -> BTreeMap<Address, U256> {
+        self.assets
+            .iter()
+            .map(|asset| {
+                let amount = match (self.capital, self.flash_loan_receiver) {
+                    (Some(_), None) => calls::balance_of(network, self.address, *asset),
+                    _ => U256::MAX,
+                };
+                (*asset, amount)
+            })
+            .collect()
+    }
+
+    /// Most profitable liquidations of users with health factor below 1
+    ///
+    /// Users are liquidated in turn, each liquidation using up part of
+    /// the inventory, so liquidations that can no longer be funded are
+    /// skipped.
+    fn liquidations<D, V>(
+        &self,
+        network: &mut Env<D, V>,
+        users: impl Iterator<Item = (Address, U256)>,
+    ) -> Vec<Liquidation>
+    where
+        D: DB,
+        V: Validator,
+    {
+        let mut inventory = self.inventory(network);
+        let mut liquidations = Vec::new();
+
+        for (user, health_factor) in users.filter(|x| x.1 < U256::to_weth(1u128)) {
+            if let Some(liquidation) =
+                self.best_liquidation(network, user, health_factor, &inventory)
+            {
+                inventory
+                    .entry(liquidation.debt_asset)
+                    .and_modify(|x| *x = x.saturating_sub(liquidation.debt_to_cover));
+                liquidations.push(liquidation);
+            }
+        }
+
+        liquidations
+    }
+
+    /// Swap restoring the share of the value of the inventory held in
+    /// the debt asset (token b) to its target, if it has moved further
+    /// from it than the rebalance threshold
+    fn rebalance_call<D, V>(
+        &self,
+        network: &mut Env<D, V>,
+        capital: types::LiquidatorCapital,
+        balance_collateral_asset: U256,
+        balance_debt_asset: U256,
+    ) -> Option<Transaction>
+    where
+        D: DB,
+        V: Validator,
+    {
+        let mut value = |asset: Address, balance: U256| {
+            let price = calls::get_asset_price(network, Address::ZERO, self.oracle_address, asset);
+            let decimals = calls::get_decimals(network, self.address, asset);
+            balance * price / U256::from(10).pow(decimals)
+        };
+        let collateral_value = value(self.collateral_token_address, balance_collateral_asset);
+        let debt_value = value(self.debt_token_address, balance_debt_asset);
+        let total_value = collateral_value + debt_value;
+
+        if total_value == U256::ZERO {
+            return None;
+        }
+
+        let share = div_u256(debt_value, total_value, 6);
+        let target = capital.target_token_b_share;
+        if (share - target).abs() <= capital.rebalance_threshold {
+            return None;
+        }
+
+        // Sell the share of the balance of the overweight asset above its target
+        let (token_in, token_out, balance, excess) = match share > target {
+            true => (
+                self.debt_token_address,
+                self.collateral_token_address,
+                balance_debt_asset,
+                1. - target / share,
+            ),
+            false => (
+                self.collateral_token_address,
+                self.debt_token_address,
+                balance_collateral_asset,
+                1. - (1. - target) / (1. - share),
+            ),
+        };
+        let amount_in = balance * U256::from((excess * 1e6) as u64) / U256::from(1_000_000);
+
+        Some(calls::uniswap_swap_call(
             self.address,
-            self.pool_address,
-            aave_abi::Pool_Implementation::liquidationCallCall {
-                collateralAsset: self.collateral_token_address,
-                debtAsset: self.debt_token_address,
-                user,
-                debtToCover: amount,
-                receiveAToken: false,
+            self.swap_router,
+            uniswap_abi::SwapRouter::ExactInputSingleParams {
+                amountIn: amount_in,
+                amountOutMinimum: U256::ZERO,
+                deadline: U256::MAX,
+                fee: self.uniswap_fee,
+                recipient: self.address,
+                sqrtPriceLimitX96: U256::ZERO,
+                tokenIn: token_in,
+                tokenOut: token_out,
             },
-            U256::ZERO,
-        );
+        ))
+    }
 
-        match call_result {
-            Ok((_, events)) => {
-                let event = events.last().unwrap().to_owned();
+    /// Transactions executing liquidations, with the bid of the
+    /// liquidator for priority as their priority fee
+    ///
+    /// Liquidations that are not profitable after the bid are dropped.
+    fn bid_transactions(&mut self, liquidations: Vec<Liquidation>) -> Vec<Transaction> {
+        let strategy = match self.gas_bid_strategy {
+            Some(x) => x,
+            None => {
+                return liquidations
+                    .into_iter()
+                    .map(|x| self.liquidation_transaction(x))
+                    .collect()
+            }
+        };
 
-                let decoded_event =
-                    aave_abi::Pool_Implementation::LiquidationCall::decode_log(&event, false)
-                        .unwrap();
+        let mut transactions = Vec::new();
 
-                // close short position in the external market
-                let debt_to_cover = decoded_event.debtToCover;
-                let liquidated_collateral_amount = decoded_event.liquidatedCollateralAmount;
+        for liquidation in liquidations {
+            let expected_profit = scale_data_value(liquidation.profit.into_raw(), 8, 8);
+            let bid = strategy.bid(expected_profit, self.losses);
+            if bid >= expected_profit {
+                continue;
+            }
 
-                let quote = calls::quote_v2_exact_output_swap(
-                    network,
-                    self.address,
-                    self.collateral_token_address,
-                    self.debt_token_address,
-                    self.uniswap_fee,
-                    debt_to_cover,
-                    self.quoter,
-                );
+            let mut transaction = self.liquidation_transaction(liquidation);
+            transaction.gas_priority_fee = Some(gas::priority_fee(bid));
+            transactions.push(transaction);
+
+            self.pending_bids.push(types::LiquidationBid {
+                step: self.step as usize,
+                liquidator: self.address,
+                user: liquidation.user,
+                bid,
+                expected_profit,
+                gas_cost: scale_data_value(liquidation.gas_cost.into_raw(), 8, 8),
+                winner: None,
+                net_profit: 0.,
+            });
+        }
 
-                let amount_collateral_from_swap = match quote {
-                    Some(q) => q.amountIn,
-                    None => liquidated_collateral_amount,
-                };
-                liquidated_collateral_amount > amount_collateral_from_swap
-            }
-            // In the case the call is reverted we continue without liquidation
-            Err(_) => false,
+        transactions
+    }
+
+    /// Transaction executing an adversarial liquidation bundle
+    ///
+    /// The collateral sold to move the price is quoted to receive
+    /// `trade_size` of the debt asset, and the whole inventory of the
+    /// debt asset is made available to the liquidations. `targets`
+    /// are the users with their debt and health factor. Returns `None`
+    /// if the sale cannot be quoted, or the liquidator does not hold
+    /// enough collateral for it.
+    fn bundle_transaction<D, V>(
+        &mut self,
+        network: &mut Env<D, V>,
+        executor: Address,
+        targets: &[(Address, U256, U256)],
+        trade_size: U256,
+        balances: (U256, U256),
+    ) -> Option<Transaction>
+    where
+        D: DB,
+        V: Validator,
+    {
+        let quote = calls::quote_v2_exact_output_swap(
+            network,
+            self.address,
+            self.collateral_token_address,
+            self.debt_token_address,
+            self.uniswap_fee,
+            trade_size,
+            self.quoter,
+        )?;
+        let collateral_amount = quote.amountIn;
+        if collateral_amount > balances.0 {
+            return None;
+        }
+
+        let prices = (
+            calls::get_asset_price(
+                network,
+                Address::ZERO,
+                self.oracle_address,
+                self.collateral_token_address,
+            ),
+            calls::get_asset_price(
+                network,
+                Address::ZERO,
+                self.oracle_address,
+                self.debt_token_address,
+            ),
+        );
+        let decimals = (
+            calls::get_decimals(network, self.address, self.collateral_token_address),
+            calls::get_decimals(network, self.address, self.debt_token_address),
+        );
+
+        // Theoretical bound at the price move of the manipulation, the
+        // users being liquidated up to the close factor at their health
+        // factor after the move
+        let current_sqrt_price_x96 =
+            calls::get_slot0(network, self.address, self.uniswap_pool).sqrtPriceX96;
+        let price_ratio = div_u256(current_sqrt_price_x96, quote.sqrtPriceX96After, 12).powi(2);
+        let debt_covered = targets
+            .iter()
+            .map(|x| {
+                let health_factor = scale_data_value(x.2, 18, 12) / price_ratio;
+                let close_factor =
+                    liquidation::close_factor(U256::from((health_factor * 1e18) as u128));
+                scale_data_value(x.1 * U256::from(close_factor) / U256::from(10000), 8, 8)
+            })
+            .sum();
+        let collateral_config = types::ReserveConfiguration::from_bitmap(
+            calls::get_reserve_data(
+                network,
+                Address::ZERO,
+                self.pool_address,
+                self.collateral_token_address,
+            )
+            .configuration
+            .data,
+        );
+        let profit_bound = liquidation::adversarial_profit_bound(
+            debt_covered,
+            price_ratio,
+            collateral_config.liquidation_bonus,
+            collateral_config.liquidation_protocol_fee,
+            base_value(I256::from_raw(collateral_amount), prices.0, decimals.0)
+                + base_value(I256::from_raw(trade_size), prices.1, decimals.1),
+            self.uniswap_fee,
+        );
+
+        let users: Vec<Address> = targets.iter().map(|x| x.0).collect();
+        self.pending_bundle = Some(PendingBundle {
+            bundle: types::AdversarialBundle {
+                step: self.step as usize,
+                liquidator: self.address,
+                users: users.clone(),
+                success: false,
+                realised_profit: 0f64,
+                liquidation_gain: 0f64,
+                profit_bound,
+            },
+            executor,
+            prices,
+            decimals,
+            balances,
+        });
+
+        Some(calls::liquidation_bundle_call(
+            self.address,
+            executor,
+            self.pool_address,
+            self.collateral_token_address,
+            self.debt_token_address,
+            self.swap_router,
+            self.uniswap_fee,
+            collateral_amount,
+            balances.1,
+            users,
+        ))
+    }
+
+    /// Transaction executing a liquidation, funded by a flash loan
+    /// if the liquidator has a receiver contract
+    fn liquidation_transaction(&self, liquidation: Liquidation) -> Transaction {
+        match self.flash_loan_receiver {
+            Some(receiver) => calls::flash_loan_liquidation_call(
+                self.address,
+                self.pool_address,
+                receiver,
+                liquidation.collateral_asset,
+                liquidation.debt_asset,
+                liquidation.user,
+                liquidation.debt_to_cover,
+                self.swap_router,
+                self.uniswap_fee,
+            ),
+            None => calls::liquidation_call(
+                liquidation.collateral_asset,
+                liquidation.debt_asset,
+                liquidation.user,
+                self.pool_address,
+                self.address,
+                liquidation.debt_to_cover,
+            ),
         }
     }
 
+    /// Swap to restore the balance of `token_out` to its level in the last step
+    fn hedge_call(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        amount_out: U256,
+        amount_in_maximum: U256,
+    ) -> Transaction {
+        calls::uniswap_swap_call_exact_output(
+            self.address,
+            self.swap_router,
+            uniswap_abi::SwapRouter::ExactOutputSingleParams {
+                amountInMaximum: amount_in_maximum,
+                amountOut: amount_out,
+                deadline: U256::MAX,
+                fee: self.uniswap_fee,
+                recipient: self.address,
+                sqrtPriceLimitX96: U256::ZERO,
+                tokenIn: token_in,
+                tokenOut: token_out,
+            },
+        )
+    }
+
     fn _update<D, V, R>(&mut self, _rng: &mut R, network: &mut Env<D, V>) -> Vec<Transaction>
     where
         D: DB,
@@ -149,13 +647,11 @@ impl LiquidationAgent {
         let current_balance_debt_asset =
             calls::balance_of(network, self.address, self.debt_token_address);
 
-        // TODO: Can calculate amount to cover using this
-        //  https://docs.aave.com/developers/guides/liquidations#executing-the-liquidation-call
         let user_data: Vec<(
             Address,
             aave_abi::Pool_Implementation::getUserAccountDataReturn,
         )> = self
-            .liquidation_addresses
+            .get_borrowers(network)
             .iter()
             .map(|x| {
                 (
@@ -167,60 +663,58 @@ impl LiquidationAgent {
 
         self.current_user_data = user_data.iter().map(scale_data).collect();
 
-        let risky_factors: Vec<(Address, U256)> = user_data
-            .into_iter()
-            .map(|x| (x.0, x.1.healthFactor))
-            .filter(|x| x.1 < U256::to_weth(1u128))
-            .collect();
+        // Profitable liquidations of users with health factor below 1
+        let liquidations = self.liquidations(
+            network,
+            user_data.into_iter().map(|x| (x.0, x.1.healthFactor)),
+        );
 
-        let profits: Vec<(Address, bool)> = risky_factors
-            .into_iter()
-            .map(|(x, _)| (x, self.accountability(network, x, U256::MAX)))
-            .filter(|(_, y)| *y)
-            .collect();
+        let mut calls = self.bid_transactions(liquidations);
 
-        // liquidation calls
-        let mut calls: Vec<Transaction> = profits
-            .into_iter()
-            .map(|(x, _)| {
-                calls::liquidation_call(
-                    self.collateral_token_address,
-                    self.debt_token_address,
-                    x,
-                    self.pool_address,
-                    self.address,
-                    U256::MAX,
-                )
-            })
-            .collect();
-        // Create Uniswap swap calls
-        if self.step > 0 {
-            // Check if liquidator has open short position
-            if self
-                .balance_debt_asset
-                .last()
-                .unwrap()
-                .gt(&current_balance_debt_asset)
-            {
-                let debt = self.balance_debt_asset.last().unwrap() - current_balance_debt_asset;
-
-                let swap_call = calls::uniswap_swap_call_exact_output(
-                    self.address,
-                    self.swap_router,
-                    uniswap_abi::SwapRouter::ExactOutputSingleParams {
-                        amountInMaximum: current_balance_collateral_asset,
-                        amountOut: debt,
-                        deadline: U256::MAX,
-                        fee: self.uniswap_fee,
-                        recipient: self.address,
-                        sqrtPriceLimitX96: U256::ZERO,
-                        tokenIn: self.collateral_token_address,
-                        tokenOut: self.debt_token_address,
-                    },
-                );
-                calls.push(swap_call);
+        match self.capital {
+            // Flash loan liquidations sell the collateral in the same transaction
+            _ if self.flash_loan_receiver.is_some() => {}
+            // Liquidators with a finite inventory rebalance it in steps in
+            // which they do not liquidate, so the swap does not spend funds
+            // needed for liquidations
+            Some(capital) if calls.is_empty() => {
+                calls.extend(self.rebalance_call(
+                    network,
+                    capital,
+                    current_balance_collateral_asset,
+                    current_balance_debt_asset,
+                ));
+            }
+            Some(_) => {}
+            // Close short positions from liquidations in the last step, which
+            // spent one of the assets and received the other as collateral
+            None if self.step > 0 => {
+                let last_balance_collateral_asset = *self.balance_collateral_asset.last().unwrap();
+                let last_balance_debt_asset = *self.balance_debt_asset.last().unwrap();
+
+                if last_balance_debt_asset > current_balance_debt_asset
+                    && last_balance_collateral_asset < current_balance_collateral_asset
+                {
+                    calls.push(self.hedge_call(
+                        self.collateral_token_address,
+                        self.debt_token_address,
+                        last_balance_debt_asset - current_balance_debt_asset,
+                        current_balance_collateral_asset,
+                    ));
+                } else if last_balance_collateral_asset > current_balance_collateral_asset
+                    && last_balance_debt_asset < current_balance_debt_asset
+                {
+                    calls.push(self.hedge_call(
+                        self.debt_token_address,
+                        self.collateral_token_address,
+                        last_balance_collateral_asset - current_balance_collateral_asset,
+                        current_balance_debt_asset,
+                    ));
+                }
             }
+            None => {}
         }
+
         // Update running values
         self.balance_collateral_asset
             .push(current_balance_collateral_asset);
@@ -248,13 +742,11 @@ impl LiquidationAgent {
         // Decimals debt asset necessary for internal calculations
         let decimals_debt_asset =
             calls::get_decimals(network, self.address, self.debt_token_address);
-        // TODO: Can calculate amount to cover using this
-        //  https://docs.aave.com/developers/guides/liquidations#executing-the-liquidation-call
         let user_data: Vec<(
             Address,
             aave_abi::Pool_Implementation::getUserAccountDataReturn,
         )> = self
-            .liquidation_addresses
+            .get_borrowers(network)
             .iter()
             .map(|x| {
                 (
@@ -323,24 +815,8 @@ impl LiquidationAgent {
             .map(|x| (x.0, x.1, x.2))
             .collect();
 
-        // // Liquidation
-        let mut calls: Vec<Transaction> = adversarial_liquidations
-            .iter()
-            .filter(|x| x.2 < U256::to_weth(1u128))
-            .map(|x| {
-                calls::liquidation_call(
-                    self.collateral_token_address,
-                    self.debt_token_address,
-                    x.0,
-                    self.pool_address,
-                    self.address,
-                    U256::MAX,
-                )
-            })
-            .collect();
-
         // Front-run trades - Price manipulation
-        let trade_size_price_manipulation = adversarial_liquidations
+        let trade_size_price_manipulation: U256 = adversarial_liquidations
             .iter()
             .filter(|x| x.2 > U256::to_weth(1u128))
             .map(|x| {
@@ -353,23 +829,26 @@ impl LiquidationAgent {
             })
             .sum();
 
-        if trade_size_price_manipulation > U256::ZERO {
-            let swap_call = calls::uniswap_swap_call_exact_output(
-                self.address,
-                self.swap_router,
-                uniswap_abi::SwapRouter::ExactOutputSingleParams {
-                    amountInMaximum: current_balance_collateral_asset,
-                    amountOut: trade_size_price_manipulation,
-                    deadline: U256::MAX,
-                    fee: self.uniswap_fee,
-                    recipient: self.address,
-                    sqrtPriceLimitX96: U256::ZERO,
-                    tokenIn: self.collateral_token_address,
-                    tokenOut: self.debt_token_address,
-                },
-            );
-            calls.push(swap_call);
-        }
+        // Positions only liquidatable after the manipulation are liquidated
+        // in a bundle with it. Flash loan liquidators hold no inventory to
+        // manipulate prices with, so only liquidate positions with HF < 1
+        let calls = match self.bundle_executor {
+            Some(executor) if trade_size_price_manipulation > U256::ZERO => self
+                .bundle_transaction(
+                    network,
+                    executor,
+                    &adversarial_liquidations,
+                    trade_size_price_manipulation,
+                    (current_balance_collateral_asset, current_balance_debt_asset),
+                )
+                .into_iter()
+                .collect(),
+            _ => {
+                let liquidations =
+                    self.liquidations(network, adversarial_liquidations.iter().map(|x| (x.0, x.2)));
+                self.bid_transactions(liquidations)
+            }
+        };
 
         // Update running values
         self.balance_collateral_asset
@@ -400,8 +879,81 @@ impl Agent for LiquidationAgent {
     }
 }
 
+impl OutcomeObserver for LiquidationAgent {
+    fn observe_outcomes(&mut self, outcomes: Vec<types::TransactionOutcome>) {
+        // Liquidations, directly or through a flash loan, call the pool
+        if let Some(outcome) = outcomes
+            .iter()
+            .rev()
+            .find(|x| x.success && x.contract == self.pool_address)
+        {
+            self.gas_estimate = outcome.gas_used;
+        }
+        self.last_outcomes = outcomes;
+    }
+}
+
 impl RecordedAgent<types::UserData> for LiquidationAgent {
-    fn record<D: DB, V: Validator>(&mut self, _env: &mut Env<D, V>) -> types::UserData {
+    fn record<D: DB, V: Validator>(&mut self, env: &mut Env<D, V>) -> types::UserData {
+        // Resolve the bids of this step from the liquidations in the block
+        if !self.pending_bids.is_empty() {
+            let winners = liquidation::first_liquidators(&env.last_events, self.pool_address);
+            for mut bid in mem::take(&mut self.pending_bids) {
+                bid.winner = winners.get(&bid.user).copied();
+                match bid.winner {
+                    Some(winner) if winner == self.address => self.losses = 0,
+                    Some(_) => self.losses += 1,
+                    None => {}
+                }
+                bid.net_profit = match bid.winner == Some(self.address) {
+                    true => bid.expected_profit - bid.bid,
+                    false => -(bid.bid + bid.gas_cost),
+                };
+                self.bids.push(bid);
+            }
+        }
+
+        // Resolve the bundle of this step from its transaction in the block
+        if let Some(pending) = self.pending_bundle.take() {
+            let PendingBundle {
+                mut bundle,
+                executor,
+                prices,
+                decimals,
+                balances,
+            } = pending;
+
+            let liquidated = liquidation::bundle_liquidations(
+                &env.last_events,
+                self.pool_address,
+                executor,
+                self.address,
+            );
+            if let Some((collateral, debt)) = liquidated {
+                bundle.success = true;
+                bundle.liquidation_gain =
+                    base_value(I256::from_raw(collateral), prices.0, decimals.0)
+                        - base_value(I256::from_raw(debt), prices.1, decimals.1);
+            }
+
+            // Net of the gas and bids paid by the transactions of the liquidator
+            let fees = self.gas_pricer.fees_paid(env, &self.last_outcomes);
+            let balance_collateral_asset =
+                calls::balance_of(env, self.address, self.collateral_token_address);
+            let balance_debt_asset = calls::balance_of(env, self.address, self.debt_token_address);
+            bundle.realised_profit = base_value(
+                I256::from_raw(balance_collateral_asset) - I256::from_raw(balances.0),
+                prices.0,
+                decimals.0,
+            ) + base_value(
+                I256::from_raw(balance_debt_asset) - I256::from_raw(balances.1),
+                prices.1,
+                decimals.1,
+            ) - scale_data_value(fees, 8, 8);
+
+            self.bundles.push(bundle);
+        }
+
         mem::take(&mut self.current_user_data)
     }
 }
@@ -0,0 +1,226 @@
+use super::OutcomeObserver;
+use crate::aave::calls;
+use crate::aave::protocol::uniswap_abi;
+use crate::aave::types::{LiquidityStrategy, PositionRecord};
+use crate::aave::uniswap_math;
+
+use alloy_primitives::{Address, Uint, U256};
+use rand::RngCore;
+use verbs_rs::agent::{Agent, RecordedAgent};
+use verbs_rs::contract::Transaction;
+use verbs_rs::env::{Env, Validator};
+use verbs_rs::DB;
+
+// Number of steps between collecting fees from a position
+const COLLECT_INTERVAL: usize = 50;
+
+/// Agent providing liquidity to the Uniswap pool
+///
+/// The agent holds a single position, minted, collected from and
+/// burnt through the `NonfungiblePositionManager`. Positions are
+/// placed according to its [LiquidityStrategy], and fees are
+/// collected every [COLLECT_INTERVAL] steps, and whenever a position
+/// is burnt.
+#[derive(Clone)]
+pub struct LiquidityProviderAgent {
+    address: Address,
+    pool: Address,
+    nft_position_manager: Address,
+    token0: Address,
+    token1: Address,
+    fee: u32,
+    tick_spacing: i32,
+    strategy: LiquidityStrategy,
+    liquidity: u128,
+    step: usize,
+    fees_collected: (U256, U256),
+}
+
+impl LiquidityProviderAgent {
+    pub fn new<D, V>(
+        network: &mut Env<D, V>,
+        idx: usize,
+        pool: Address,
+        nft_position_manager: Address,
+        fee: u32,
+        strategy: LiquidityStrategy,
+        liquidity: u128,
+    ) -> Self
+    where
+        D: DB,
+        V: Validator,
+    {
+        let address = Address::from(Uint::from(idx));
+        let token0 = calls::get_token0(network, address, pool);
+        let token1 = calls::get_token1(network, address, pool);
+        let tick_spacing = calls::get_tick_spacing(network, address, pool);
+
+        Self {
+            address,
+            pool,
+            nft_position_manager,
+            token0,
+            token1,
+            fee,
+            tick_spacing,
+            strategy,
+            liquidity,
+            step: 0,
+            fees_collected: (U256::ZERO, U256::ZERO),
+        }
+    }
+
+    /// Range of ticks of a new position given the current tick of the pool
+    fn get_position_ticks(&self, tick: i32) -> (i32, i32) {
+        let max_tick = uniswap_math::MAX_TICK / self.tick_spacing * self.tick_spacing;
+        let min_tick = -max_tick;
+
+        match self.strategy {
+            LiquidityStrategy::FullRange => (min_tick, max_tick),
+            LiquidityStrategy::FixedWidth { width }
+            | LiquidityStrategy::RebalanceOnExit { width } => {
+                let tick = tick.div_euclid(self.tick_spacing) * self.tick_spacing;
+                (
+                    (tick - width * self.tick_spacing).max(min_tick),
+                    (tick + (width + 1) * self.tick_spacing).min(max_tick),
+                )
+            }
+        }
+    }
+
+    fn mint_call(&self, sqrt_price_x96: U256, tick: i32) -> Option<Transaction> {
+        let (tick_lower, tick_upper) = self.get_position_ticks(tick);
+        let (amount0, amount1) = uniswap_math::get_amounts_for_liquidity(
+            sqrt_price_x96,
+            uniswap_math::get_sqrt_ratio_at_tick(tick_lower)?,
+            uniswap_math::get_sqrt_ratio_at_tick(tick_upper)?,
+            self.liquidity,
+        )?;
+
+        Some(calls::mint_position_call(
+            self.address,
+            self.nft_position_manager,
+            uniswap_abi::NonfungiblePositionManager::MintParams {
+                token0: self.token0,
+                token1: self.token1,
+                fee: self.fee,
+                tickLower: tick_lower,
+                tickUpper: tick_upper,
+                amount0Desired: amount0,
+                amount1Desired: amount1,
+                amount0Min: U256::ZERO,
+                amount1Min: U256::ZERO,
+                recipient: self.address,
+                deadline: U256::MAX,
+            },
+        ))
+    }
+
+    /// Add the fees currently owed to a position to the fees collected
+    fn add_collectable_fees<D: DB, V: Validator>(
+        &mut self,
+        network: &mut Env<D, V>,
+        token_id: U256,
+    ) {
+        let (amount0, amount1) = calls::get_collectable_amounts(
+            network,
+            self.address,
+            self.nft_position_manager,
+            token_id,
+        );
+        self.fees_collected.0 += amount0;
+        self.fees_collected.1 += amount1;
+    }
+}
+
+impl Agent for LiquidityProviderAgent {
+    fn update<D, V, R>(&mut self, _rng: &mut R, network: &mut Env<D, V>) -> Vec<Transaction>
+    where
+        D: DB,
+        V: Validator,
+        R: RngCore,
+    {
+        let mut calls: Vec<Transaction> = Vec::new();
+        let slot0 = calls::get_slot0(network, self.address, self.pool);
+        let collect = self.step.is_multiple_of(COLLECT_INTERVAL);
+        let mut active_position = false;
+
+        for token_id in calls::get_position_ids(network, self.address, self.nft_position_manager) {
+            let position =
+                calls::get_position(network, self.address, self.nft_position_manager, token_id);
+            let in_range = position.tickLower <= slot0.tick && slot0.tick < position.tickUpper;
+            let rebalance =
+                matches!(self.strategy, LiquidityStrategy::RebalanceOnExit { .. }) && !in_range;
+
+            if position.liquidity == 0 || rebalance {
+                // Withdraw everything from the position, and burn it
+                self.add_collectable_fees(network, token_id);
+                if position.liquidity > 0 {
+                    calls.push(calls::decrease_liquidity_call(
+                        self.address,
+                        self.nft_position_manager,
+                        token_id,
+                        position.liquidity,
+                    ));
+                }
+                calls.push(calls::collect_call(
+                    self.address,
+                    self.nft_position_manager,
+                    token_id,
+                ));
+                calls.push(calls::burn_position_call(
+                    self.address,
+                    self.nft_position_manager,
+                    token_id,
+                ));
+            } else {
+                active_position = true;
+                if collect {
+                    self.add_collectable_fees(network, token_id);
+                    calls.push(calls::collect_call(
+                        self.address,
+                        self.nft_position_manager,
+                        token_id,
+                    ));
+                }
+            }
+        }
+
+        if !active_position {
+            if let Some(call) = self.mint_call(slot0.sqrtPriceX96, slot0.tick) {
+                calls.push(call);
+            }
+        }
+
+        self.step += 1;
+        calls
+    }
+
+    fn get_address(&self) -> Address {
+        self.address
+    }
+}
+
+impl OutcomeObserver for LiquidityProviderAgent {}
+
+impl RecordedAgent<PositionRecord> for LiquidityProviderAgent {
+    fn record<D: DB, V: Validator>(&mut self, env: &mut Env<D, V>) -> PositionRecord {
+        let position = calls::get_position_ids(env, self.address, self.nft_position_manager)
+            .into_iter()
+            .map(|token_id| {
+                calls::get_position(env, self.address, self.nft_position_manager, token_id)
+            })
+            .find(|position| position.liquidity > 0);
+
+        match position {
+            Some(position) => (
+                position.tickLower,
+                position.tickUpper,
+                position.liquidity,
+                self.fees_collected.0,
+                self.fees_collected.1,
+            ),
+            None => (0, 0, 0, self.fees_collected.0, self.fees_collected.1),
+        }
+    }
+}
@@ -1,34 +1,237 @@
+mod agent_sets;
 mod borrow_agent;
 mod liquidation_agent;
+mod liquidity_provider_agent;
+mod rate_rebalancer;
+mod risk_steward;
+mod sandwich_agent;
 mod uniswap_agent;
 mod uniswap_noise_agent;
 
-use super::types::UserData;
+use super::events;
+use super::gas;
+use super::types::{
+    AdversarialBundle, BorrowerRecord, Branch, EventRecord, LiquidationBid, ParameterChange,
+    PositionRecord, Sandwich, TransactionOutcome, UserData,
+};
+pub use agent_sets::{CloneableAgentVec, CloneableSingletonAgent};
 use alloy_primitives::U256;
-pub use borrow_agent::BorrowAgent;
+pub use borrow_agent::{base_to_token, BorrowAgent};
 pub use liquidation_agent::LiquidationAgent;
+pub use liquidity_provider_agent::LiquidityProviderAgent;
 use rand::RngCore;
+pub use rate_rebalancer::RateRebalancer;
+pub use risk_steward::RiskSteward;
+pub use sandwich_agent::SandwichAgent;
 use serde::{Deserialize, Serialize};
-pub use uniswap_agent::UniswapPriceAgent;
+pub use uniswap_agent::{ExternalMarket, UniswapPriceAgent};
 pub use uniswap_noise_agent::UniswapNoiseAgent;
-use verbs_rs::agent::{AgentSet, AgentVec, SimState, SingletonAgent};
+use verbs_rs::agent::{Agent, AgentSet, RecordedAgentSet, SimState};
 use verbs_rs::contract::Transaction;
 use verbs_rs::env::{Env, Validator};
 use verbs_rs::DB;
 
-#[derive(SimState)]
+#[derive(Clone)]
 pub struct AgentStates {
-    pub borrow_agents: AgentVec<U256, BorrowAgent>,
-    pub liquidation_agents: AgentVec<UserData, LiquidationAgent>,
-    pub uniswap_price_agent: SingletonAgent<(i128, i128), UniswapPriceAgent>,
-    pub uniswap_noise_agents: AgentVec<U256, UniswapNoiseAgent>,
+    pub borrow_agents: CloneableAgentVec<BorrowerRecord, BorrowAgent>,
+    pub liquidation_agents: CloneableAgentVec<UserData, LiquidationAgent>,
+    pub uniswap_price_agent: CloneableSingletonAgent<(i128, i128), UniswapPriceAgent>,
+    pub uniswap_noise_agents: CloneableAgentVec<U256, UniswapNoiseAgent>,
+    pub liquidity_provider_agents: CloneableAgentVec<PositionRecord, LiquidityProviderAgent>,
+    /// Keeper rebalancing stable borrow rates, if there is one
+    pub rate_rebalancer: Option<RateRebalancer>,
+    /// Risk steward adjusting the parameters of the reserves, if there is one
+    pub risk_steward: Option<RiskSteward>,
+    /// Searchers, updated after the other agents so they can inspect
+    /// the transactions submitted in the step
+    pub searcher_agents: Vec<SandwichAgent>,
+    /// Outcomes of all the transactions processed so far
+    pub outcomes: Vec<TransactionOutcome>,
+    /// Base fee of each block processed so far, in wei
+    pub base_fees: Vec<U256>,
+}
+
+/// Agents told the outcomes of their own transactions once each block
+/// is processed, so they can act on them in their next update
+pub trait OutcomeObserver {
+    /// Receive the outcomes of the transactions of the agent in the
+    /// last block, by default they are ignored
+    fn observe_outcomes(&mut self, _outcomes: Vec<TransactionOutcome>) {}
+}
+
+impl SimState for AgentStates {
+    fn call_agents<D: DB, V: Validator, R: RngCore>(
+        &mut self,
+        rng: &mut R,
+        env: &mut Env<D, V>,
+    ) -> Vec<Transaction> {
+        let mut transactions = Vec::<Transaction>::new();
+        transactions.extend(self.borrow_agents.call(rng, env));
+        transactions.extend(self.liquidation_agents.call(rng, env));
+        transactions.extend(self.uniswap_price_agent.call(rng, env));
+        transactions.extend(self.uniswap_noise_agents.call(rng, env));
+        transactions.extend(self.liquidity_provider_agents.call(rng, env));
+        if let Some(rebalancer) = self.rate_rebalancer.as_mut() {
+            transactions.extend(rebalancer.update(rng, env));
+        }
+        if let Some(steward) = self.risk_steward.as_mut() {
+            transactions.extend(steward.update(rng, env));
+        }
+
+        let bundles: Vec<Transaction> = self
+            .searcher_agents
+            .iter_mut()
+            .flat_map(|x| x.search(rng, env, &transactions))
+            .collect();
+        transactions.extend(bundles);
+        transactions
+    }
+
+    fn record_agents<D: DB, V: Validator>(&mut self, env: &mut Env<D, V>) {
+        self.borrow_agents.record(env);
+        self.liquidation_agents.record(env);
+        self.uniswap_price_agent.record(env);
+        self.uniswap_noise_agents.record(env);
+        self.liquidity_provider_agents.record(env);
+        for searcher in self.searcher_agents.iter_mut() {
+            searcher.record(env);
+        }
+        self.base_fees.push(gas::base_fee(env));
+    }
+}
+
+impl AgentStates {
+    /// Give agents the outcomes of their transactions in the last block,
+    /// and store them
+    pub fn observe_outcomes(&mut self, outcomes: Vec<TransactionOutcome>) {
+        self.borrow_agents.observe_outcomes(&outcomes);
+        self.liquidation_agents.observe_outcomes(&outcomes);
+        self.uniswap_price_agent.observe_outcomes(&outcomes);
+        self.uniswap_noise_agents.observe_outcomes(&outcomes);
+        self.liquidity_provider_agents.observe_outcomes(&outcomes);
+        for searcher in self.searcher_agents.iter_mut() {
+            let address = searcher.get_address();
+            searcher.observe_outcomes(agent_sets::outcomes_of(&outcomes, address));
+        }
+        if let Some(steward) = self.risk_steward.as_mut() {
+            let address = steward.get_address();
+            steward.observe_outcomes(agent_sets::outcomes_of(&outcomes, address));
+        }
+        self.outcomes.extend(outcomes);
+    }
+
+    /// Take the outcomes of all the transactions processed so far
+    pub fn take_outcomes(&mut self) -> Vec<TransactionOutcome> {
+        std::mem::take(&mut self.outcomes)
+    }
+
+    /// Take the base fee of each block processed so far
+    pub fn take_base_fees(&mut self) -> Vec<U256> {
+        std::mem::take(&mut self.base_fees)
+    }
+
+    /// Apply the counterfactual changes of a branch to the agents
+    pub fn apply_branch(&mut self, branch: &Branch) {
+        if let Some(adversarial) = branch.adversarial {
+            for agent in self.liquidation_agents.agents_mut().iter_mut() {
+                agent.set_adversarial(adversarial);
+            }
+        }
+        if let Some(shock) = branch.price_shock {
+            self.uniswap_price_agent
+                .agent_mut()
+                .apply_price_shock(shock);
+        }
+    }
+
+    /// Take the bids of all liquidators, in step order
+    pub fn take_liquidation_bids(&mut self) -> Vec<LiquidationBid> {
+        let mut bids: Vec<LiquidationBid> = self
+            .liquidation_agents
+            .agents_mut()
+            .iter_mut()
+            .flat_map(|x| x.take_bids())
+            .collect();
+        bids.sort_by_key(|x| x.step);
+        bids
+    }
+
+    /// Take the adversarial bundles of all liquidators, in step order
+    pub fn take_adversarial_bundles(&mut self) -> Vec<AdversarialBundle> {
+        let mut bundles: Vec<AdversarialBundle> = self
+            .liquidation_agents
+            .agents_mut()
+            .iter_mut()
+            .flat_map(|x| x.take_bundles())
+            .collect();
+        bundles.sort_by_key(|x| x.step);
+        bundles
+    }
+
+    /// Take the sandwiches of all searchers, in step order
+    pub fn take_sandwiches(&mut self) -> Vec<Sandwich> {
+        let mut sandwiches: Vec<Sandwich> = self
+            .searcher_agents
+            .iter_mut()
+            .flat_map(|x| x.take_sandwiches())
+            .collect();
+        sandwiches.sort_by_key(|x| x.step);
+        sandwiches
+    }
+
+    /// Take the parameter changes submitted by the risk steward, in step order
+    pub fn take_parameter_changes(&mut self) -> Vec<ParameterChange> {
+        self.risk_steward
+            .as_mut()
+            .map(|x| x.take_changes())
+            .unwrap_or_default()
+    }
+
+    /// Collect the records and outputs of the agents into the data of
+    /// a simulation run with the given seed
+    pub fn into_sim_data(mut self, seed: u64) -> SimData {
+        let transaction_outcomes = self.take_outcomes();
+        SimData {
+            seed,
+            borrow_agents: self.borrow_agents.take_records(),
+            liquidation_agents: self.liquidation_agents.take_records(),
+            uniswap_price_agent: self.uniswap_price_agent.take_records(),
+            uniswap_noise_agents: self.uniswap_noise_agents.take_records(),
+            liquidity_provider_agents: self.liquidity_provider_agents.take_records(),
+            liquidation_bids: self.take_liquidation_bids(),
+            adversarial_bundles: self.take_adversarial_bundles(),
+            sandwiches: self.take_sandwiches(),
+            parameter_changes: self.take_parameter_changes(),
+            base_fees: self.take_base_fees(),
+            events: events::decode_events(&transaction_outcomes),
+            transaction_outcomes,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SimData {
     pub seed: u64,
-    pub borrow_agents: Vec<Vec<U256>>,
+    pub borrow_agents: Vec<Vec<BorrowerRecord>>,
     pub liquidation_agents: Vec<Vec<UserData>>,
     pub uniswap_price_agent: Vec<(i128, i128)>,
     pub uniswap_noise_agents: Vec<Vec<U256>>,
+    pub liquidity_provider_agents: Vec<Vec<PositionRecord>>,
+    /// Bids of liquidators for priority, and who won each liquidation
+    pub liquidation_bids: Vec<LiquidationBid>,
+    /// Adversarial liquidation bundles, with their realised profit,
+    /// liquidation gain and theoretical profit bound
+    pub adversarial_bundles: Vec<AdversarialBundle>,
+    /// Sandwiches of Uniswap swaps submitted by searchers
+    pub sandwiches: Vec<Sandwich>,
+    /// Changes of reserve parameters submitted by the risk steward
+    pub parameter_changes: Vec<ParameterChange>,
+    /// Base fee of each block, in wei
+    pub base_fees: Vec<U256>,
+    /// Outcomes of every transaction, including why reverted
+    /// transactions failed and the gas they used
+    pub transaction_outcomes: Vec<TransactionOutcome>,
+    /// Events emitted by the protocol contracts, in the order they
+    /// were emitted
+    pub events: Vec<EventRecord>,
 }
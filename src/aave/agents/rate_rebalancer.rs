@@ -0,0 +1,97 @@
+use super::OutcomeObserver;
+use crate::aave::calls;
+use crate::aave::indexer::BorrowerIndex;
+use crate::aave::protocol::aave_abi;
+use alloy_primitives::{Address, Uint, U256};
+use rand::Rng;
+use verbs_rs::agent::Agent;
+use verbs_rs::contract::Transaction;
+use verbs_rs::env::{Env, Validator};
+use verbs_rs::DB;
+
+/// Keeper rebalancing the stable borrow rates of Aave users
+///
+/// The pool lets anyone rebalance the stable rate of the debt of a
+/// user up to the current stable rate of the reserve, once the reserve
+/// is heavily used and its liquidity rate is low relative to its
+/// maximum variable rate. Each step the keeper checks the users in its
+/// [BorrowerIndex] paying less than the current stable rate, and
+/// rebalances those the pool would allow.
+#[derive(Clone)]
+pub struct RateRebalancer {
+    address: Address,
+    pool_address: Address,
+    tokens: Vec<Address>,
+    borrower_index: BorrowerIndex,
+}
+
+impl RateRebalancer {
+    /// Keeper rebalancing the stable rates of debt in the given tokens
+    ///
+    /// # Arguments
+    ///
+    /// * `idx` - Index of the agent, used as its address
+    /// * `pool_address` - Address of the Aave pool
+    /// * `tokens` - Reserves whose stable rates are rebalanced
+    /// * `known_users` - Users that may already have open debt
+    ///
+    pub fn new(
+        idx: usize,
+        pool_address: Address,
+        tokens: Vec<Address>,
+        known_users: Vec<Address>,
+    ) -> Self {
+        RateRebalancer {
+            address: Address::from(Uint::from(idx)),
+            pool_address,
+            tokens,
+            borrower_index: BorrowerIndex::new(pool_address, known_users),
+        }
+    }
+}
+
+impl Agent for RateRebalancer {
+    fn update<D, V, R>(&mut self, _rng: &mut R, network: &mut Env<D, V>) -> Vec<Transaction>
+    where
+        D: DB,
+        V: Validator,
+        R: Rng,
+    {
+        self.borrower_index.update(network);
+        let users: Vec<Address> = self.borrower_index.users().copied().collect();
+
+        let mut transactions = Vec::new();
+        for &token in self.tokens.iter() {
+            let reserve = calls::get_reserve_data(network, Address::ZERO, self.pool_address, token);
+            let current_rate = U256::from(reserve.currentStableBorrowRate);
+            for &user in users.iter() {
+                if calls::balance_of(network, user, reserve.stableDebtTokenAddress) == U256::ZERO
+                    || calls::get_user_stable_rate(network, reserve.stableDebtTokenAddress, user)
+                        >= current_rate
+                {
+                    continue;
+                }
+                // The pool checks whether the reserve can be rebalanced
+                let call = aave_abi::Pool_Implementation::rebalanceStableBorrowRateCall {
+                    asset: token,
+                    user,
+                };
+                if calls::try_call(network, self.address, self.pool_address, call).is_ok() {
+                    transactions.push(calls::rebalance_stable_borrow_rate_call(
+                        self.address,
+                        self.pool_address,
+                        token,
+                        user,
+                    ));
+                }
+            }
+        }
+        transactions
+    }
+
+    fn get_address(&self) -> Address {
+        self.address
+    }
+}
+
+impl OutcomeObserver for RateRebalancer {}
@@ -0,0 +1,380 @@
+use super::OutcomeObserver;
+use crate::aave::calls;
+use crate::aave::indexer::BorrowerIndex;
+use crate::aave::limits;
+use crate::aave::types::{
+    ParameterChange, ReserveObservation, ReserveParameters, RiskParameter, RiskStewardParameters,
+    TransactionOutcome,
+};
+use alloy_primitives::{Address, Uint, U256};
+use rand::Rng;
+use std::collections::VecDeque;
+use verbs_rs::agent::Agent;
+use verbs_rs::contract::Transaction;
+use verbs_rs::env::{Env, Validator};
+use verbs_rs::utils::scale_data_value;
+use verbs_rs::DB;
+
+/// Amount of a token with 18 decimals in whole tokens, without the
+/// clamping of `scale_data_value` at `u64::MAX`
+fn to_tokens(amount: U256) -> f64 {
+    amount.saturating_to::<u128>() as f64 / 1e18
+}
+
+/// Risk steward adjusting the parameters of Aave reserves
+///
+/// The steward holds the risk admin role of the `ACLManager`, so can
+/// set the collateral parameters, reserve factor and caps of reserves
+/// through the pool configurator. Each step it samples the oracle price
+/// of each reserve, and every review period it observes the utilisation,
+/// volatility and cap usage of the reserves, and the bad debt of the
+/// users in its [BorrowerIndex]. Its policies then set the parameters
+/// of each reserve, and the parameters that differ from the current
+/// ones are submitted to the configurator. Each change is logged with
+/// whether it was applied.
+#[derive(Clone)]
+pub struct RiskSteward {
+    address: Address,
+    pool_address: Address,
+    pool_configurator: Address,
+    oracle_address: Address,
+    tokens: Vec<Address>,
+    parameters: RiskStewardParameters,
+    /// Time step of the simulation, in years
+    dt: f64,
+    borrower_index: BorrowerIndex,
+    /// Parameters of each reserve when the steward was first updated
+    initial: Vec<ReserveParameters>,
+    /// Recent oracle prices of each reserve
+    prices: Vec<VecDeque<f64>>,
+    /// Bad debt of the pool at the last review
+    bad_debt: f64,
+    step: usize,
+    /// Changes made by each transaction of the last step, resolved
+    /// once their outcomes are observed
+    pending: Vec<Vec<ParameterChange>>,
+    changes: Vec<ParameterChange>,
+}
+
+impl RiskSteward {
+    /// Risk steward of the given reserves
+    ///
+    /// # Arguments
+    ///
+    /// * `idx` - Index of the agent, used as its address
+    /// * `pool_address` - Address of the Aave pool
+    /// * `pool_configurator` - Address of the pool configurator
+    /// * `oracle_address` - Address of the Aave oracle
+    /// * `tokens` - Reserves whose parameters are adjusted
+    /// * `parameters` - Policies and review schedule of the steward
+    /// * `dt` - Time step of the simulation, in years
+    /// * `known_users` - Users that may already have open debt
+    ///
+    /// # Panics
+    ///
+    /// Panics if the review period is 0.
+    ///
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        idx: usize,
+        pool_address: Address,
+        pool_configurator: Address,
+        oracle_address: Address,
+        tokens: Vec<Address>,
+        parameters: RiskStewardParameters,
+        dt: f64,
+        known_users: Vec<Address>,
+    ) -> Self {
+        assert!(
+            parameters.review_period > 0,
+            "Review period of the risk steward must be at least 1 step"
+        );
+        let prices = vec![VecDeque::new(); tokens.len()];
+        RiskSteward {
+            address: Address::from(Uint::from(idx)),
+            pool_address,
+            pool_configurator,
+            oracle_address,
+            tokens,
+            parameters,
+            dt,
+            borrower_index: BorrowerIndex::new(pool_address, known_users),
+            initial: Vec::new(),
+            prices,
+            bad_debt: 0.,
+            step: 0,
+            pending: Vec::new(),
+            changes: Vec::new(),
+        }
+    }
+
+    /// Take the parameter changes submitted so far, in step order
+    pub fn take_changes(&mut self) -> Vec<ParameterChange> {
+        std::mem::take(&mut self.changes)
+    }
+
+    fn read_parameters<D: DB, V: Validator>(
+        &self,
+        network: &mut Env<D, V>,
+        token: Address,
+    ) -> ReserveParameters {
+        let configuration =
+            calls::get_reserve_configuration(network, Address::ZERO, self.pool_address, token);
+        ReserveParameters {
+            ltv: configuration.ltv,
+            liquidation_threshold: configuration.liquidation_threshold,
+            liquidation_bonus: configuration.liquidation_bonus,
+            reserve_factor: configuration.reserve_factor,
+            supply_cap: configuration.supply_cap,
+            borrow_cap: configuration.borrow_cap,
+        }
+    }
+
+    /// Debt of indexed users in excess of their collateral, in the base currency
+    fn total_bad_debt<D: DB, V: Validator>(&self, network: &mut Env<D, V>) -> f64 {
+        self.borrower_index
+            .users()
+            .map(|&user| {
+                let user_data =
+                    calls::get_user_data(network, Address::ZERO, self.pool_address, user);
+                let collateral = scale_data_value(user_data.totalCollateralBase, 8, 8);
+                let debt = scale_data_value(user_data.totalDebtBase, 8, 8);
+                (debt - collateral).max(0.)
+            })
+            .sum()
+    }
+
+    /// Annualised volatility of the log returns of the sampled prices of a reserve
+    fn volatility(&self, idx: usize) -> f64 {
+        let returns: Vec<f64> = self.prices[idx]
+            .iter()
+            .zip(self.prices[idx].iter().skip(1))
+            .map(|(a, b)| (b / a).ln())
+            .collect();
+        if returns.len() < 2 {
+            return 0.;
+        }
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let variance = returns.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.);
+        (variance / self.dt).sqrt()
+    }
+
+    fn observe<D: DB, V: Validator>(
+        &self,
+        network: &mut Env<D, V>,
+        idx: usize,
+        parameters: &ReserveParameters,
+        bad_debt_increase: f64,
+    ) -> ReserveObservation {
+        let token = self.tokens[idx];
+        let reserve = calls::get_reserve_data(network, Address::ZERO, self.pool_address, token);
+        let debt = calls::total_supply(network, reserve.stableDebtTokenAddress)
+            + calls::total_supply(network, reserve.variableDebtTokenAddress);
+        let liquidity = calls::balance_of(network, reserve.aTokenAddress, token);
+        let debt = to_tokens(debt);
+        let liquidity = to_tokens(liquidity);
+        let utilisation = match debt + liquidity {
+            x if x > 0. => debt / x,
+            _ => 0.,
+        };
+
+        let usage = |cap: u64, headroom: Option<U256>| match headroom {
+            Some(headroom) if cap > 0 => 1. - to_tokens(headroom) / cap as f64,
+            _ => 0.,
+        };
+        let supply_headroom = limits::supply_headroom(network, self.pool_address, token);
+        let borrow_headroom = limits::borrow_headroom(network, self.pool_address, token);
+
+        ReserveObservation {
+            utilisation,
+            volatility: self.volatility(idx),
+            supply_cap_usage: usage(parameters.supply_cap, supply_headroom),
+            borrow_cap_usage: usage(parameters.borrow_cap, borrow_headroom),
+            bad_debt_increase,
+        }
+    }
+
+    /// Transactions setting the parameters of a reserve that differ
+    /// from its current ones, and the changes made by each
+    fn submit(
+        &self,
+        token: Address,
+        current: &ReserveParameters,
+        target: &ReserveParameters,
+    ) -> Vec<(Transaction, Vec<ParameterChange>)> {
+        let change = |parameter: RiskParameter, old_value: u64, new_value: u64| {
+            (old_value != new_value).then_some(ParameterChange {
+                step: self.step,
+                asset: token,
+                parameter,
+                old_value,
+                new_value,
+                success: false,
+            })
+        };
+
+        let mut submitted = Vec::new();
+        let collateral: Vec<ParameterChange> = [
+            change(RiskParameter::Ltv, current.ltv, target.ltv),
+            change(
+                RiskParameter::LiquidationThreshold,
+                current.liquidation_threshold,
+                target.liquidation_threshold,
+            ),
+            change(
+                RiskParameter::LiquidationBonus,
+                current.liquidation_bonus,
+                target.liquidation_bonus,
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !collateral.is_empty() {
+            submitted.push((
+                calls::configure_reserve_as_collateral_call(
+                    self.address,
+                    self.pool_configurator,
+                    token,
+                    target.ltv,
+                    target.liquidation_threshold,
+                    target.liquidation_bonus,
+                ),
+                collateral,
+            ));
+        }
+        if let Some(x) = change(
+            RiskParameter::ReserveFactor,
+            current.reserve_factor,
+            target.reserve_factor,
+        ) {
+            submitted.push((
+                calls::set_reserve_factor_call(
+                    self.address,
+                    self.pool_configurator,
+                    token,
+                    target.reserve_factor,
+                ),
+                vec![x],
+            ));
+        }
+        if let Some(x) = change(
+            RiskParameter::SupplyCap,
+            current.supply_cap,
+            target.supply_cap,
+        ) {
+            submitted.push((
+                calls::set_supply_cap_call(
+                    self.address,
+                    self.pool_configurator,
+                    token,
+                    target.supply_cap,
+                ),
+                vec![x],
+            ));
+        }
+        if let Some(x) = change(
+            RiskParameter::BorrowCap,
+            current.borrow_cap,
+            target.borrow_cap,
+        ) {
+            submitted.push((
+                calls::set_borrow_cap_call(
+                    self.address,
+                    self.pool_configurator,
+                    token,
+                    target.borrow_cap,
+                ),
+                vec![x],
+            ));
+        }
+        submitted
+    }
+
+    /// Apply the policies to each reserve, returning the transactions
+    /// setting the parameters that changed
+    fn review<D: DB, V: Validator>(&mut self, network: &mut Env<D, V>) -> Vec<Transaction> {
+        let bad_debt = self.total_bad_debt(network);
+        let bad_debt_increase = (bad_debt - self.bad_debt).max(0.);
+        self.bad_debt = bad_debt;
+
+        let mut transactions = Vec::new();
+        for (idx, &token) in self.tokens.iter().enumerate() {
+            let current = self.read_parameters(network, token);
+            let observation = self.observe(network, idx, &current, bad_debt_increase);
+            let mut target = self
+                .parameters
+                .policies
+                .iter()
+                .fold(current, |parameters, policy| {
+                    policy.apply(&observation, &self.initial[idx], &parameters)
+                });
+            // The configurator requires the loan to value to be at most
+            // the liquidation threshold, and the collateral seized with
+            // the bonus to cover the debt repaid
+            target.ltv = target.ltv.min(target.liquidation_threshold);
+            if let Some(max_bonus) = 100_000_000u64.checked_div(target.liquidation_threshold) {
+                target.liquidation_bonus = target.liquidation_bonus.min(max_bonus);
+            }
+
+            for (transaction, changes) in self.submit(token, &current, &target) {
+                transactions.push(transaction);
+                self.pending.push(changes);
+            }
+        }
+        transactions
+    }
+}
+
+impl Agent for RiskSteward {
+    fn update<D, V, R>(&mut self, _rng: &mut R, network: &mut Env<D, V>) -> Vec<Transaction>
+    where
+        D: DB,
+        V: Validator,
+        R: Rng,
+    {
+        self.borrower_index.update(network);
+        if self.initial.is_empty() {
+            self.initial = self
+                .tokens
+                .iter()
+                .map(|&token| self.read_parameters(network, token))
+                .collect();
+        }
+
+        for (idx, &token) in self.tokens.iter().enumerate() {
+            let price = calls::get_asset_price(network, Address::ZERO, self.oracle_address, token);
+            let prices = &mut self.prices[idx];
+            prices.push_back(scale_data_value(price, 8, 8));
+            if prices.len() > self.parameters.volatility_window + 1 {
+                prices.pop_front();
+            }
+        }
+
+        let transactions = match (self.step + 1) % self.parameters.review_period {
+            0 => self.review(network),
+            _ => Vec::new(),
+        };
+        self.step += 1;
+        transactions
+    }
+
+    fn get_address(&self) -> Address {
+        self.address
+    }
+}
+
+impl OutcomeObserver for RiskSteward {
+    fn observe_outcomes(&mut self, outcomes: Vec<TransactionOutcome>) {
+        let mut outcomes = outcomes.into_iter();
+        for changes in std::mem::take(&mut self.pending) {
+            let success = outcomes.next().map(|x| x.success).unwrap_or(false);
+            self.changes.extend(
+                changes
+                    .into_iter()
+                    .map(|x| ParameterChange { success, ..x }),
+            );
+        }
+    }
+}
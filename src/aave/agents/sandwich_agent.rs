@@ -0,0 +1,416 @@
+use super::OutcomeObserver;
+use crate::aave::calls;
+use crate::aave::gas::{self, GasPricer};
+use crate::aave::protocol::uniswap_abi;
+use crate::aave::types;
+use crate::aave::uniswap_math::{self, PoolState, PoolTicks};
+use crate::aave::validator::OrderedGasPriorityValidator;
+use alloy_primitives::{Address, Uint, I256, U256};
+use alloy_sol_types::{SolCall, SolEvent};
+use rand::Rng;
+use verbs_rs::contract::Transaction;
+use verbs_rs::env::{Env, Validator};
+use verbs_rs::utils::scale_data_value;
+use verbs_rs::DB;
+
+// Number of golden section iterations used to size the front-run
+const SEARCH_ITERATIONS: usize = 32;
+
+/// Searcher sandwiching the Uniswap swaps of other agents
+///
+/// Each step the searcher inspects the transactions submitted by the
+/// other agents, and simulates a front-run in the same direction as
+/// the swap that a bundle builder would place in the victim slot of a
+/// bundle, followed by the victim swap and a back-run selling the
+/// output of the front-run. If the simulated sandwich is profitable it
+/// submits the front-run and back-run as a bundle, with a gap in their
+/// nonces for the victim, bidding a share of its expected profit in
+/// the base currency of the Aave oracle.
+///
+/// Sandwiches are only placed around their victims by validators that
+/// build bundles, with other validators the front-run and back-run
+/// execute next to each other.
+#[derive(Clone)]
+pub struct SandwichAgent {
+    address: Address,
+    pool: Address,
+    fee: u32,
+    swap_router: Address,
+    quoter: Address,
+    oracle: Address,
+    gas_pricer: GasPricer,
+    /// Share of the expected profit of a sandwich bid for priority
+    bid_share: f64,
+    token0: Address,
+    token1: Address,
+    decimals0: U256,
+    decimals1: U256,
+    step: usize,
+    /// Sandwich submitted in the current step, resolved once processed
+    pending: Option<PendingSandwich>,
+    sandwiches: Vec<types::Sandwich>,
+    /// Outcomes of the transactions of the searcher in the last block
+    last_outcomes: Vec<types::TransactionOutcome>,
+}
+
+/// Sandwich awaiting its outcome, with the balance of the searcher
+/// in the token of the front-run before it was executed
+#[derive(Clone)]
+struct PendingSandwich {
+    sandwich: types::Sandwich,
+    decimals: U256,
+    balance: U256,
+}
+
+/// Swap of another agent through the router, on the pool of the searcher
+#[derive(Clone, Copy, Debug)]
+struct VictimSwap {
+    sender: Address,
+    zero_for_one: bool,
+    exact_input: bool,
+    amount: U256,
+    /// Minimum amount out of an exact input swap, or maximum amount
+    /// in of an exact output swap
+    amount_limit: U256,
+    sqrt_price_limit_x96: U256,
+}
+
+/// Value of a signed amount of a token in whole tokens
+fn token_value(amount: I256, decimals: U256) -> f64 {
+    let scaled = scale_data_value(amount.unsigned_abs(), decimals.to::<usize>(), 8);
+    match amount.is_negative() {
+        true => -scaled,
+        false => scaled,
+    }
+}
+
+impl SandwichAgent {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<D, V>(
+        network: &mut Env<D, V>,
+        idx: usize,
+        pool: Address,
+        fee: u32,
+        swap_router: Address,
+        quoter: Address,
+        oracle: Address,
+        gas_pricer: GasPricer,
+        bid_share: f64,
+    ) -> Self
+    where
+        D: DB,
+        V: Validator,
+    {
+        let address = Address::from(Uint::from(idx));
+        let token0 = calls::get_token0(network, address, pool);
+        let token1 = calls::get_token1(network, address, pool);
+        let decimals0 = calls::get_decimals(network, address, token0);
+        let decimals1 = calls::get_decimals(network, address, token1);
+
+        Self {
+            address,
+            pool,
+            fee,
+            swap_router,
+            quoter,
+            oracle,
+            gas_pricer,
+            bid_share,
+            token0,
+            token1,
+            decimals0,
+            decimals1,
+            step: 0,
+            pending: None,
+            sandwiches: Vec::new(),
+            last_outcomes: Vec::new(),
+        }
+    }
+
+    pub fn get_address(&self) -> Address {
+        self.address
+    }
+
+    /// Take the sandwiches submitted since the last call
+    pub fn take_sandwiches(&mut self) -> Vec<types::Sandwich> {
+        std::mem::take(&mut self.sandwiches)
+    }
+
+    /// Outcomes of the transactions of the searcher in the last block
+    pub fn last_outcomes(&self) -> &[types::TransactionOutcome] {
+        &self.last_outcomes
+    }
+
+    /// Decode a swap through the router on the pool of the searcher
+    fn decode_swap(&self, transaction: &Transaction) -> Option<VictimSwap> {
+        if transaction.transact_to != self.swap_router || transaction.nonce.is_some() {
+            return None;
+        }
+
+        let (token_in, token_out, fee, swap) = if let Ok(call) =
+            uniswap_abi::SwapRouter::exactInputSingleCall::abi_decode(&transaction.args, true)
+        {
+            let params = call.params;
+            let swap = VictimSwap {
+                sender: transaction.callee,
+                zero_for_one: params.tokenIn == self.token0,
+                exact_input: true,
+                amount: params.amountIn,
+                amount_limit: params.amountOutMinimum,
+                sqrt_price_limit_x96: params.sqrtPriceLimitX96,
+            };
+            (params.tokenIn, params.tokenOut, params.fee, swap)
+        } else {
+            let call =
+                uniswap_abi::SwapRouter::exactOutputSingleCall::abi_decode(&transaction.args, true)
+                    .ok()?;
+            let params = call.params;
+            let swap = VictimSwap {
+                sender: transaction.callee,
+                zero_for_one: params.tokenIn == self.token0,
+                exact_input: false,
+                amount: params.amountOut,
+                amount_limit: params.amountInMaximum,
+                sqrt_price_limit_x96: params.sqrtPriceLimitX96,
+            };
+            (params.tokenIn, params.tokenOut, params.fee, swap)
+        };
+
+        let on_pool = (token_in == self.token0 && token_out == self.token1)
+            || (token_in == self.token1 && token_out == self.token0);
+        (on_pool && fee == self.fee && swap.amount > U256::ZERO).then_some(swap)
+    }
+
+    /// Profit of a sandwich with a front-run of `amount_in`
+    ///
+    /// Returns `None` if the victim swap would revert after the
+    /// front-run, or any of the swaps cannot be executed.
+    fn sandwich_profit<T: uniswap_math::TickData>(
+        pool: &PoolState,
+        ticks: &mut T,
+        victim: &VictimSwap,
+        amount_in: U256,
+    ) -> Option<I256> {
+        let zero_for_one = victim.zero_for_one;
+        let (front_run, pool) =
+            uniswap_math::get_swap(pool, ticks, zero_for_one, true, amount_in, U256::ZERO)?;
+        let (swap, pool) = uniswap_math::get_swap(
+            &pool,
+            ticks,
+            zero_for_one,
+            victim.exact_input,
+            victim.amount,
+            victim.sqrt_price_limit_x96,
+        )?;
+
+        let victim_reverts = match victim.exact_input {
+            true => swap.amount_out < victim.amount_limit,
+            false => {
+                swap.amount_in > victim.amount_limit
+                    || (victim.sqrt_price_limit_x96 == U256::ZERO
+                        && swap.amount_out < victim.amount)
+            }
+        };
+        if victim_reverts {
+            return None;
+        }
+
+        let (back_run, _) = uniswap_math::get_swap(
+            &pool,
+            ticks,
+            !zero_for_one,
+            true,
+            front_run.amount_out,
+            U256::ZERO,
+        )?;
+
+        Some(I256::from_raw(back_run.amount_out) - I256::from_raw(front_run.amount_in))
+    }
+
+    /// Front-run and back-run of the most profitable sandwich of the
+    /// swap placed in the victim slot of bundles
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - Random generator
+    /// * `network` - Simulation environment
+    /// * `pending` - Transactions submitted by the other agents in this step
+    ///
+    pub fn search<D, V, R>(
+        &mut self,
+        rng: &mut R,
+        network: &mut Env<D, V>,
+        pending: &[Transaction],
+    ) -> Vec<Transaction>
+    where
+        D: DB,
+        V: Validator,
+        R: Rng,
+    {
+        // Builders place the first ordinary transaction, in gas
+        // priority order, calling the router between the two legs
+        let victim = OrderedGasPriorityValidator {}
+            .order_transactions(rng, pending.to_vec())
+            .into_iter()
+            .find(|x| x.transact_to == self.swap_router && x.nonce.is_none())
+            .and_then(|x| self.decode_swap(&x));
+
+        let Some(victim) = victim else {
+            return Vec::new();
+        };
+
+        let (token_in, token_out, decimals) = match victim.zero_for_one {
+            true => (self.token0, self.token1, self.decimals0),
+            false => (self.token1, self.token0, self.decimals1),
+        };
+        let budget = calls::balance_of(network, self.address, token_in);
+        if budget == U256::ZERO {
+            return Vec::new();
+        }
+
+        let pool = PoolState::get(network, self.address, self.pool, self.fee);
+        let mut ticks = PoolTicks {
+            network,
+            caller: self.address,
+            pool: self.pool,
+        };
+
+        let scale = 1u64 << 40;
+        let mut profit = |x: f64| -> (U256, Option<I256>) {
+            let amount = budget * U256::from((x * scale as f64) as u64) / U256::from(scale);
+            let profit = Self::sandwich_profit(&pool, &mut ticks, &victim, amount);
+            (amount, profit)
+        };
+
+        let ratio = (5f64.sqrt() - 1.) / 2.;
+        let (mut a, mut b) = (0f64, 1f64);
+        let mut c = b - ratio * (b - a);
+        let mut d = a + ratio * (b - a);
+        let mut profit_c = profit(c);
+        let mut profit_d = profit(d);
+
+        for _ in 0..SEARCH_ITERATIONS {
+            if profit_c.1 >= profit_d.1 {
+                b = d;
+                d = c;
+                profit_d = profit_c;
+                c = b - ratio * (b - a);
+                profit_c = profit(c);
+            } else {
+                a = c;
+                c = d;
+                profit_c = profit_d;
+                d = a + ratio * (b - a);
+                profit_d = profit(d);
+            }
+        }
+
+        let best = [profit_c, profit_d]
+            .into_iter()
+            .filter_map(|(amount, profit)| profit.map(|x| (amount, x)))
+            .filter(|(amount, profit)| *amount > U256::ZERO && *profit > I256::ZERO)
+            .max_by_key(|(_, profit)| *profit);
+        let Some((amount_in, expected_profit)) = best else {
+            return Vec::new();
+        };
+
+        // The front-run executes on the current state of the pool, so
+        // its output is quoted, and sold in full by the back-run
+        let quote = calls::quote_v2_exact_input_swap(
+            network,
+            self.address,
+            token_in,
+            token_out,
+            self.fee,
+            amount_in,
+            self.quoter,
+        );
+        let Some(quote) = quote else {
+            return Vec::new();
+        };
+
+        // A share of the expected profit is bid in the base currency, as
+        // liquidators bid, and paid whether or not the bundle is profitable
+        let price = calls::get_asset_price(network, self.address, self.oracle, token_in);
+        let bid = self.bid_share * token_value(expected_profit, decimals) * f64::from(price) / 1e8;
+        let swap = |token_in: Address, token_out: Address, amount: U256, nonce: u64| {
+            let mut transaction = calls::uniswap_swap_call(
+                self.address,
+                self.swap_router,
+                uniswap_abi::SwapRouter::ExactInputSingleParams {
+                    amountIn: amount,
+                    amountOutMinimum: U256::ZERO,
+                    deadline: U256::MAX,
+                    fee: self.fee,
+                    recipient: self.address,
+                    sqrtPriceLimitX96: U256::ZERO,
+                    tokenIn: token_in,
+                    tokenOut: token_out,
+                },
+            );
+            transaction.nonce = Some(nonce);
+            transaction
+        };
+
+        self.pending = Some(PendingSandwich {
+            sandwich: types::Sandwich {
+                step: self.step,
+                searcher: self.address,
+                victim: victim.sender,
+                token: token_in,
+                front_run: token_value(I256::from_raw(amount_in), decimals),
+                expected_profit: token_value(expected_profit, decimals),
+                bid,
+                included: false,
+                realised_profit: 0.,
+            },
+            decimals,
+            balance: budget,
+        });
+
+        // The victim is placed in the slot left by the gap in the nonces,
+        // and the bid is paid once, by the front-run that ranks the bundle
+        let mut front_run = swap(token_in, token_out, amount_in, 0);
+        front_run.gas_priority_fee = Some(gas::priority_fee(bid));
+        vec![front_run, swap(token_out, token_in, quote.amountOut, 2)]
+    }
+
+    /// Resolve the sandwich submitted in this step from the processed block
+    pub fn record<D: DB, V: Validator>(&mut self, env: &mut Env<D, V>) {
+        if let Some(pending) = self.pending.take() {
+            let PendingSandwich {
+                mut sandwich,
+                decimals,
+                balance,
+            } = pending;
+
+            // Both legs swap on the pool with the searcher as recipient
+            let swaps = env
+                .last_events
+                .iter()
+                .flat_map(|x| x.logs.iter())
+                .filter(|x| x.address == self.pool)
+                .filter_map(|x| uniswap_abi::UniswapV3Pool::Swap::decode_log(x, true).ok())
+                .filter(|x| x.recipient == self.address)
+                .count();
+            sandwich.included = swaps == 2;
+
+            // Net of the gas and bids paid by the transactions of the searcher
+            let fees = self.gas_pricer.fees_paid(env, &self.last_outcomes);
+            let price = calls::get_asset_price(env, self.address, self.oracle, sandwich.token);
+            let balance_after = calls::balance_of(env, self.address, sandwich.token);
+            sandwich.realised_profit = token_value(
+                I256::from_raw(balance_after) - I256::from_raw(balance),
+                decimals,
+            ) - f64::from(fees) / f64::from(price);
+            self.sandwiches.push(sandwich);
+        }
+        self.step += 1;
+    }
+}
+
+impl OutcomeObserver for SandwichAgent {
+    fn observe_outcomes(&mut self, outcomes: Vec<types::TransactionOutcome>) {
+        self.last_outcomes = outcomes;
+    }
+}
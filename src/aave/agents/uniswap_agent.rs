@@ -1,9 +1,13 @@
+use super::OutcomeObserver;
 use crate::aave::calls;
+use crate::aave::gas;
 use crate::aave::protocol::uniswap_abi;
+use crate::aave::types::{self, TransactionOutcome};
+use crate::aave::uniswap_math;
 
 use alloy_primitives::{Address, Uint, U256};
 use funty::Fundamental;
-use rand::RngCore;
+use rand::{Rng, RngCore};
 use rand_distr::{Distribution, Normal};
 use verbs_rs::agent::{Agent, RecordedAgent};
 use verbs_rs::contract::Transaction;
@@ -11,6 +15,7 @@ use verbs_rs::env::{Env, Validator};
 use verbs_rs::utils::div_u256;
 use verbs_rs::DB;
 
+#[derive(Clone)]
 pub struct UniswapPriceAgent {
     address: Address,
     pool: Address,
@@ -19,10 +24,16 @@ pub struct UniswapPriceAgent {
     token_b: Address, // stable coin (debt in Aave in simulation)
     token0: Address,
     token1: Address,
-    external_market: Gbm,
+    external_market: ExternalMarket,
     step: u32,
     transient_price_impact: f64,
     dt: f64,
+    token1_decimals: i32,
+    /// Gas expected to be used by a swap, updated from the outcomes
+    /// of the swaps of the agent
+    gas_estimate: u64,
+    /// Token standing for ETH when pricing gas
+    gas_token: types::GasToken,
 }
 
 impl UniswapPriceAgent {
@@ -38,15 +49,19 @@ impl UniswapPriceAgent {
         mu: f64,
         dt: f64,
         sigma: f64,
+        price_process: types::PriceProcess,
+        gas_token: types::GasToken,
     ) -> Self
     where
         D: DB,
         V: Validator,
     {
         let address = Address::from(Uint::from(idx));
-        let external_market = Gbm::new(dt, mu, sigma, token_a_price, token_a_price, token_b_price);
+        let external_market =
+            ExternalMarket::new(dt, mu, sigma, price_process, token_a_price, token_b_price);
         let token0 = calls::get_token0(network, address, pool);
         let token1 = calls::get_token1(network, address, pool);
+        let token1_decimals: u8 = calls::get_decimals(network, address, token1).to();
 
         Self {
             address,
@@ -60,27 +75,37 @@ impl UniswapPriceAgent {
             step: 0u32,
             transient_price_impact: 0.,
             dt,
+            token1_decimals: token1_decimals as i32,
+            gas_estimate: gas::SWAP_GAS,
+            gas_token,
         }
     }
 
     /// Gets the swap parameters so that, after the swap, the price in Uniswap is the same as the price in the external market.
-    /// We know that in Uniswap v3 (or v2), we have
-    /// L = \frac{\Delta y}{\Delta \sqrt{P}}
-    /// where y is the numeraire (in our case the debt asset), and P is the price of the collateral in terms of the numeraire.
-    pub fn get_swap_size_to_increase_uniswap_price(
+    /// The swap is sized by stepping through the initialised ticks of the pool, so the
+    /// price reaches the target even if the swap crosses ticks. The amount in includes the pool fee.
+    /// Returns `None` if the external price is not above the Uniswap price, or the swap is empty.
+    pub fn get_swap_size_to_increase_uniswap_price<D, V>(
         &mut self,
-        sqrt_price_external_market: u128,
-        sqrt_price_uniswap_x96: u128,
-        liquidity: u128,
-    ) -> Option<uniswap_abi::SwapRouter::ExactInputSingleParams> {
-        let change_sqrt_price = sqrt_price_external_market - sqrt_price_uniswap_x96;
-
-        let mut change_token1 = U256::from(liquidity) / U256::from(2).pow(U256::from(48));
-        change_token1 *= U256::from(change_sqrt_price) / U256::from(2).pow(U256::from(48));
+        network: &mut Env<D, V>,
+        sqrt_price_external_market: U256,
+    ) -> Option<uniswap_abi::SwapRouter::ExactInputSingleParams>
+    where
+        D: DB,
+        V: Validator,
+    {
+        let swap = self.get_swap_to_price(network, sqrt_price_external_market, false)?;
+        self.exact_input_params(&swap)
+    }
 
-        if change_token1 > U256::ZERO {
+    /// Parameters of a swap of token1 for token0, if it is not empty
+    fn exact_input_params(
+        &self,
+        swap: &uniswap_math::SwapAmounts,
+    ) -> Option<uniswap_abi::SwapRouter::ExactInputSingleParams> {
+        if swap.amount_in > U256::ZERO {
             Some(uniswap_abi::SwapRouter::ExactInputSingleParams {
-                amountIn: change_token1,
+                amountIn: swap.amount_in,
                 amountOutMinimum: U256::ZERO,
                 deadline: U256::MAX,
                 fee: self.fee,
@@ -94,20 +119,30 @@ impl UniswapPriceAgent {
         }
     }
 
-    pub fn get_swap_size_to_decrease_uniswap_price(
+    /// Gets the swap parameters so that, after the swap, the price in Uniswap is the same as the price in the external market.
+    /// The swap is specified by its exact output of token1, summed over the initialised ticks it crosses.
+    /// Returns `None` if the external price is not below the Uniswap price, or the swap is empty.
+    pub fn get_swap_size_to_decrease_uniswap_price<D, V>(
         &mut self,
-        sqrt_price_external_market: u128,
-        sqrt_price_uniswap_x96: u128,
-        liquidity: u128,
-    ) -> Option<uniswap_abi::SwapRouter::ExactOutputSingleParams> {
-        let change_sqrt_price = sqrt_price_uniswap_x96 - sqrt_price_external_market;
-
-        let mut change_token1 = U256::from(liquidity) / U256::from(2).pow(U256::from(48));
-        change_token1 *= U256::from(change_sqrt_price) / U256::from(2).pow(U256::from(48));
+        network: &mut Env<D, V>,
+        sqrt_price_external_market: U256,
+    ) -> Option<uniswap_abi::SwapRouter::ExactOutputSingleParams>
+    where
+        D: DB,
+        V: Validator,
+    {
+        let swap = self.get_swap_to_price(network, sqrt_price_external_market, true)?;
+        self.exact_output_params(&swap)
+    }
 
-        if change_token1 > U256::ZERO {
+    /// Parameters of a swap of token0 for token1, if it is not empty
+    fn exact_output_params(
+        &self,
+        swap: &uniswap_math::SwapAmounts,
+    ) -> Option<uniswap_abi::SwapRouter::ExactOutputSingleParams> {
+        if swap.amount_out > U256::ZERO {
             Some(uniswap_abi::SwapRouter::ExactOutputSingleParams {
-                amountOut: change_token1,
+                amountOut: swap.amount_out,
                 amountInMaximum: U256::MAX,
                 deadline: U256::MAX,
                 fee: self.fee,
@@ -121,6 +156,76 @@ impl UniswapPriceAgent {
         }
     }
 
+    /// Amounts of the swap moving the pool to `target`, if the price moves in the given direction
+    fn get_swap_to_price<D, V>(
+        &mut self,
+        network: &mut Env<D, V>,
+        target: U256,
+        zero_for_one: bool,
+    ) -> Option<uniswap_math::SwapAmounts>
+    where
+        D: DB,
+        V: Validator,
+    {
+        let pool = uniswap_math::PoolState::get(network, self.address, self.pool, self.fee);
+        if (target < pool.sqrt_price_x96) != zero_for_one {
+            return None;
+        }
+        let mut ticks = uniswap_math::PoolTicks {
+            network,
+            caller: self.address,
+            pool: self.pool,
+        };
+
+        uniswap_math::get_swap_to_price(&pool, &mut ticks, target)
+    }
+
+    /// Profit of a swap, valuing the amounts at the external market
+    /// price, in the base currency
+    ///
+    /// # Arguments
+    ///
+    /// * `swap` - Amounts of the swap
+    /// * `sqrt_price_x96` - Square root external price of token0 in
+    ///   terms of token1, as a Q64.96 value
+    /// * `zero_for_one` - Whether token0 is paid into the pool
+    ///
+    fn arbitrage_profit(
+        &self,
+        swap: &uniswap_math::SwapAmounts,
+        sqrt_price_x96: U256,
+        zero_for_one: bool,
+    ) -> f64 {
+        let sqrt_price = f64::from(sqrt_price_x96) / 2f64.powi(96);
+        let price = sqrt_price * sqrt_price;
+        let (amount_in, amount_out) = (f64::from(swap.amount_in), f64::from(swap.amount_out));
+        let profit = match zero_for_one {
+            true => amount_out - amount_in * price,
+            false => amount_out * price - amount_in,
+        };
+        let token1_price = match self.token1 == self.token_b {
+            true => self.external_market.token_b_price,
+            false => self.external_market.token_a_price_with_impact,
+        };
+        profit / 10f64.powi(self.token1_decimals) * token1_price.as_f64() / 1e8
+    }
+
+    /// Cost of the gas of a swap at the current base fee, in the base
+    /// currency, at the external price of the gas token
+    fn gas_cost<D: DB, V: Validator>(&self, network: &mut Env<D, V>) -> f64 {
+        let cost = gas::gas_cost(self.gas_estimate, gas::base_fee(network));
+        let price = match self.gas_token {
+            types::GasToken::TokenA => self.external_market.token_a_price,
+            types::GasToken::TokenB => self.external_market.token_b_price,
+        };
+        f64::from(cost) / 1e18 * price.as_f64() / 1e8
+    }
+
+    /// Multiply the external market price of token a by `factor`
+    pub fn apply_price_shock(&mut self, factor: f64) {
+        self.external_market.apply_shock(factor);
+    }
+
     fn get_price_impact(&mut self, sqrt_price_uniswap_x96: U256) -> f64 {
         // price_uniswap is price of token0 in terms of token1
         let price_uniswap: f64 = f64::powi(
@@ -165,38 +270,48 @@ impl Agent for UniswapPriceAgent {
 
         let mut calls: Vec<Transaction> = Vec::new();
 
-        let sqrt_price_uniswap_x96: u128 = u128::try_from(sqrt_price_uniswap_x96).unwrap();
         let sqrt_price_external_market = if self.token1 == self.token_b {
             self.external_market.get_sqrt_price_token_a_x96()
         } else {
             self.external_market.get_sqrt_price_token_b_x96()
         };
-        let liquidity = calls::get_liquidity(network, self.address, self.pool);
+        // The external price can leave the range representable by
+        // Uniswap, in which case the agent does not trade
+        let sqrt_price_external_market = match sqrt_price_external_market {
+            Some(x) => x,
+            None => {
+                self.step += 1;
+                return calls;
+            }
+        };
 
         // find swap parameters so that price of uniswap after the swap matches the price of the external market
         // sqrt_price_external_market > sqrt_price_uniswap_x96, the uniswap agent wants to buy collateral asset (and sell debt asset) to increase the price of Uniswap
         // sqrt_price_external_market < sqrt_price_uniswap_x96, the uniswap agent wants to sell collateral asset (and buy debt asset) to decrease the price of Uniswap
-        if sqrt_price_external_market > sqrt_price_uniswap_x96 {
-            let params_swap = self.get_swap_size_to_increase_uniswap_price(
-                sqrt_price_external_market,
-                sqrt_price_uniswap_x96,
-                liquidity,
-            );
-            if let Some(params) = params_swap {
+        let zero_for_one = sqrt_price_external_market <= sqrt_price_uniswap_x96;
+        let swap = self.get_swap_to_price(network, sqrt_price_external_market, zero_for_one);
+
+        // Arbitrage that does not cover the cost of its gas is skipped
+        let gas_cost = self.gas_cost(network);
+        let swap = swap.filter(|x| {
+            gas_cost == 0.
+                || self.arbitrage_profit(x, sqrt_price_external_market, zero_for_one) >= gas_cost
+        });
+
+        if let Some(swap) = swap {
+            if zero_for_one {
+                if let Some(params) = self.exact_output_params(&swap) {
+                    let call = calls::uniswap_swap_call_exact_output(
+                        self.address,
+                        self.swap_router,
+                        params,
+                    );
+                    calls.push(call);
+                }
+            } else if let Some(params) = self.exact_input_params(&swap) {
                 let call = calls::uniswap_swap_call(self.address, self.swap_router, params);
                 calls.push(call);
             }
-        } else {
-            let params_swap = self.get_swap_size_to_decrease_uniswap_price(
-                sqrt_price_external_market,
-                sqrt_price_uniswap_x96,
-                liquidity,
-            );
-            if let Some(params) = params_swap {
-                let call =
-                    calls::uniswap_swap_call_exact_output(self.address, self.swap_router, params);
-                calls.push(call);
-            }
         }
         self.step += 1;
         calls
@@ -207,6 +322,14 @@ impl Agent for UniswapPriceAgent {
     }
 }
 
+impl OutcomeObserver for UniswapPriceAgent {
+    fn observe_outcomes(&mut self, outcomes: Vec<TransactionOutcome>) {
+        if let Some(outcome) = outcomes.iter().rev().find(|x| x.success) {
+            self.gas_estimate = outcome.gas_used;
+        }
+    }
+}
+
 impl RecordedAgent<(i128, i128)> for UniswapPriceAgent {
     fn record<D: DB, V: Validator>(&mut self, _env: &mut Env<D, V>) -> (i128, i128) {
         (
@@ -216,7 +339,13 @@ impl RecordedAgent<(i128, i128)> for UniswapPriceAgent {
     }
 }
 
-pub struct Gbm {
+/// External market price of token a, in terms of token b
+///
+/// The price follows a [types::PriceProcess], and the price the
+/// Uniswap pool is moved to also includes the transient impact of
+/// trades in the pool.
+#[derive(Clone)]
+pub struct ExternalMarket {
     token_a_price: i128,
     token_b_price: i128,
     token_a_price_with_impact: i128,
@@ -224,25 +353,33 @@ pub struct Gbm {
     mu: f64,
     // gbm vol
     sigma: f64,
+    process: types::PriceProcess,
+    /// Price of token a at the peg of a depeg process
+    peg_price: f64,
+    /// Log deviation of the price of token a from its peg
+    deviation: f64,
     normal: Normal<f64>,
     dt: f64,
 }
 
-impl Gbm {
+impl ExternalMarket {
     pub fn new(
         dt: f64,
         mu: f64,
         sigma: f64,
+        process: types::PriceProcess,
         token_a_price: i128,
-        token_a_price_with_impact: i128,
         token_b_price: i128,
     ) -> Self {
         Self {
             token_a_price,
             token_b_price,
-            token_a_price_with_impact,
+            token_a_price_with_impact: token_a_price,
             mu,
             sigma,
+            process,
+            peg_price: token_a_price.as_f64(),
+            deviation: 0.,
             normal: Normal::new(0., 1.).unwrap(),
             dt,
         }
@@ -251,9 +388,30 @@ impl Gbm {
     pub fn update<R: RngCore>(&mut self, rng: &mut R, price_impact: f64) {
         let z1 = self.normal.sample(rng);
 
+        let new_price_a = match self.process {
+            types::PriceProcess::Gbm => {
+                self.token_a_price.as_f64()
+                    * f64::exp(
+                        (self.mu - 0.5 * self.sigma) * self.dt + self.sigma * self.dt.sqrt() * z1,
+                    )
+            }
+            types::PriceProcess::Depeg {
+                reversion,
+                depeg_rate,
+                depeg_size,
+            } => {
+                let depeg = rng.gen::<f64>() < depeg_rate * self.dt;
+                self.deviation += -reversion * self.deviation * self.dt
+                    + self.sigma * self.dt.sqrt() * z1
+                    + match depeg {
+                        true => (1. - depeg_size).ln(),
+                        false => 0.,
+                    };
+                self.peg_price * self.deviation.exp()
+            }
+        };
+
         // We keep the price of token_b constant
-        let new_price_a = self.token_a_price.as_f64()
-            * f64::exp((self.mu - 0.5 * self.sigma) * self.dt + self.sigma * self.dt.sqrt() * z1);
         let new_price_a_with_impact: f64 = new_price_a + price_impact;
         let new_price_b = self.token_b_price; //self.token_b_price.as_f64() * z2;
         self.token_a_price = new_price_a.as_i128();
@@ -261,29 +419,30 @@ impl Gbm {
         self.token_a_price_with_impact = new_price_a_with_impact.as_i128();
     }
 
-    pub fn get_sqrt_price_token_a_x96(&mut self) -> u128 {
-        let n: i32 = 20;
-        let price: f64 = f64::sqrt(div_u256(
-            U256::from(self.token_a_price_with_impact),
-            U256::from(self.token_b_price),
-            10,
-        )) * 2f64.powi(n);
-        let mut price: u128 = price.as_u128();
-        // `<<` is a left shift operator, which is equivalent to multiplying times 2^n
-        price <<= 96 - n;
-        price
+    /// Instantaneous multiplicative jump in the price of token a
+    pub fn apply_shock(&mut self, factor: f64) {
+        self.deviation += factor.ln();
+        self.token_a_price = (self.token_a_price.as_f64() * factor).as_i128();
+        self.token_a_price_with_impact =
+            (self.token_a_price_with_impact.as_f64() * factor).as_i128();
     }
 
-    pub fn get_sqrt_price_token_b_x96(&mut self) -> u128 {
-        let n: i32 = 20;
-        let price: f64 = f64::sqrt(div_u256(
-            U256::from(self.token_b_price),
-            U256::from(self.token_a_price_with_impact),
-            10,
-        )) * 2f64.powi(n);
-        let mut price: u128 = price.as_u128();
-        price <<= 96 - n;
-        price
+    /// Square root price of token a in terms of token b, as a Q64.96 value
+    ///
+    /// Returns `None` if the price is not positive or outside the range of Uniswap prices.
+    pub fn get_sqrt_price_token_a_x96(&mut self) -> Option<U256> {
+        uniswap_math::price_to_sqrt_price_x96(
+            self.token_a_price_with_impact.as_f64() / self.token_b_price.as_f64(),
+        )
+    }
+
+    /// Square root price of token b in terms of token a, as a Q64.96 value
+    ///
+    /// Returns `None` if the price is not positive or outside the range of Uniswap prices.
+    pub fn get_sqrt_price_token_b_x96(&mut self) -> Option<U256> {
+        uniswap_math::price_to_sqrt_price_x96(
+            self.token_b_price.as_f64() / self.token_a_price_with_impact.as_f64(),
+        )
     }
 
     pub fn get_price_token_a(&mut self) -> f64 {
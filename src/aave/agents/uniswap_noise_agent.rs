@@ -1,3 +1,4 @@
+use super::OutcomeObserver;
 use crate::aave::calls;
 use crate::aave::protocol::uniswap_abi;
 
@@ -8,6 +9,7 @@ use verbs_rs::contract::Transaction;
 use verbs_rs::env::{Env, Validator};
 use verbs_rs::DB;
 
+#[derive(Clone)]
 pub struct UniswapNoiseAgent {
     address: Address,
     fee: u32,
@@ -101,6 +103,8 @@ impl Agent for UniswapNoiseAgent {
     }
 }
 
+impl OutcomeObserver for UniswapNoiseAgent {}
+
 impl RecordedAgent<U256> for UniswapNoiseAgent {
     fn record<D: DB, V: Validator>(&mut self, _env: &mut Env<D, V>) -> U256 {
         U256::ZERO
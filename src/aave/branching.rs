@@ -0,0 +1,145 @@
+//! Counterfactual continuations from a saved mid-run state
+//!
+//! A [Checkpoint] stores the EVM database, block time, number and
+//! base fee, events, agent states and random generator of a
+//! simulation at a given step. Each continuation restored from the checkpoint starts from
+//! an identical state and consumes the same random stream, so
+//! differences between continuations are due only to the changes
+//! applied to them.
+//!
+
+use super::agents::AgentStates;
+use super::gas;
+use super::outcomes::process_block;
+use super::types::BaseFee;
+use alloy_primitives::U256;
+use kdam::tqdm;
+use rand::RngCore;
+use rand_xoshiro::Xoroshiro128StarStar;
+use std::ops::Range;
+use verbs_rs::agent::SimState;
+use verbs_rs::contract::Event;
+use verbs_rs::env::{Env, Validator};
+use verbs_rs::{LocalDB, DB};
+
+// Represents blocks updating every 15s, as in verbs_rs::sim_runner
+const BLOCK_INTERVAL: u64 = 15;
+
+/// Saved state of a simulation at a given step
+pub struct Checkpoint {
+    /// Step the continuations start from
+    pub step: usize,
+    timestamp: U256,
+    block_number: U256,
+    base_fee: U256,
+    db: LocalDB,
+    last_events: Vec<Event>,
+    event_history: Vec<Event>,
+    agents: AgentStates,
+    rng: Xoroshiro128StarStar,
+}
+
+impl Checkpoint {
+    /// Save the current state of a simulation
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - Simulation environment
+    /// * `agents` - Simulation agents
+    /// * `rng` - Random generator used to run the simulation
+    /// * `step` - Number of steps already run
+    ///
+    pub fn save<V: Validator>(
+        env: &mut Env<LocalDB, V>,
+        agents: &AgentStates,
+        rng: &Xoroshiro128StarStar,
+        step: usize,
+    ) -> Self {
+        let evm = &env.evm_state().context.evm;
+
+        Checkpoint {
+            step,
+            timestamp: evm.env.block.timestamp,
+            block_number: evm.env.block.number,
+            base_fee: evm.env.block.basefee,
+            db: evm.db.clone(),
+            last_events: copy_events(&env.last_events),
+            event_history: copy_events(&env.event_history),
+            agents: agents.clone(),
+            rng: rng.clone(),
+        }
+    }
+
+    /// Create a new simulation from the saved state
+    ///
+    /// # Arguments
+    ///
+    /// * `validator` - Validator used by the restored environment
+    ///
+    pub fn restore<V: Validator>(
+        &self,
+        validator: V,
+    ) -> (Env<LocalDB, V>, AgentStates, Xoroshiro128StarStar) {
+        let mut env = Env::<LocalDB, V>::init(self.timestamp, self.block_number, validator);
+        env.evm_state().context.evm.db = self.db.clone();
+        gas::set_base_fee(&mut env, self.base_fee);
+        env.last_events = copy_events(&self.last_events);
+        env.event_history = copy_events(&self.event_history);
+
+        (env, self.agents.clone(), self.rng.clone())
+    }
+}
+
+// Events are not Clone, so are copied field by field
+fn copy_events(events: &[Event]) -> Vec<Event> {
+    events
+        .iter()
+        .map(|x| Event {
+            success: x.success,
+            function_selector: x.function_selector,
+            logs: x.logs.clone(),
+            step: x.step,
+            sequence: x.sequence,
+        })
+        .collect()
+}
+
+/// Run a range of simulation steps
+///
+/// Performs the same updates as [verbs_rs::sim_runner::run], but
+/// with an externally owned random generator, so a run can be
+/// stopped, saved and continued. Blocks are processed with
+/// [process_block], and agents are given the outcomes of their
+/// transactions before they are recorded. The base fee of the next
+/// block is then drawn from `base_fee`.
+///
+/// # Arguments
+///
+/// * `env` - Simulation environment
+/// * `agents` - Simulation agents
+/// * `rng` - Random generator
+/// * `steps` - Range of step numbers to run
+/// * `base_fee` - Process followed by the base fee
+///
+pub fn run_steps<D, V, R>(
+    env: &mut Env<D, V>,
+    agents: &mut AgentStates,
+    rng: &mut R,
+    steps: Range<usize>,
+    base_fee: &BaseFee,
+) where
+    D: DB,
+    V: Validator,
+    R: RngCore,
+{
+    for i in tqdm!(steps) {
+        env.clear_events();
+        let transactions = agents.call_agents(rng, env);
+        env.increment_time(rng, BLOCK_INTERVAL);
+        let outcomes = process_block(env, transactions, rng, i);
+        agents.observe_outcomes(outcomes);
+        agents.record_agents(env);
+        let next_base_fee = base_fee.next(rng, gas::base_fee(env));
+        gas::set_base_fee(env, next_base_fee);
+    }
+}
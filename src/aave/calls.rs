@@ -1,6 +1,35 @@
 use crate::aave::protocol::{aave_abi, periphery_abi, uniswap_abi};
-use alloy_primitives::{Address, I256, U256};
-use verbs_rs::{contract::Transaction, env::Env, env::Validator, DB};
+use crate::aave::types;
+use alloy_primitives::{Address, Log, I256, U256};
+use alloy_sol_types::{SolCall, SolValue};
+use verbs_rs::env::{Env, RevertError, Validator};
+use verbs_rs::{contract::Transaction, DB};
+
+/// Call a contract function without committing changes, for calls
+/// that are expected to revert in some states
+///
+/// [Env::direct_call] does not restore the EVM state if the call
+/// reverts, leaving the environment unusable, so calls that may
+/// revert should go through this function.
+pub fn try_call<D, V, T>(
+    network: &mut Env<D, V>,
+    caller: Address,
+    contract: Address,
+    call_args: T,
+) -> Result<(T::Return, Vec<Log>), RevertError>
+where
+    D: DB,
+    V: Validator,
+    T: SolCall,
+{
+    let result = network.direct_call_raw(caller, contract, call_args.abi_encode(), U256::ZERO)?;
+    let output = result.output().cloned().unwrap_or_default();
+    let decoded = match T::abi_decode_returns(&output, true) {
+        Ok(x) => x,
+        Err(_) => panic!("Decoding error from {}", T::SIGNATURE),
+    };
+    Ok((decoded, result.into_logs()))
+}
 
 pub fn supply_call(
     user_address: Address,
@@ -29,6 +58,7 @@ pub fn borrow_call(
     pool_address: Address,
     token_address: Address,
     amount: U256,
+    rate_mode: types::InterestRateMode,
 ) -> Transaction {
     Transaction::new(
         user_address,
@@ -36,7 +66,7 @@ pub fn borrow_call(
         aave_abi::Pool_Implementation::borrowCall {
             asset: token_address,
             amount,
-            interestRateMode: U256::from(2u128),
+            interestRateMode: rate_mode.into(),
             referralCode: 0,
             onBehalfOf: user_address,
         },
@@ -47,71 +77,568 @@ pub fn borrow_call(
     )
 }
 
-pub fn liquidation_call(
-    collateral_token_address: Address,
-    debt_token_address: Address,
+pub fn repay_call(
+    user_address: Address,
+    pool_address: Address,
+    token_address: Address,
+    amount: U256,
+    rate_mode: types::InterestRateMode,
+) -> Transaction {
+    Transaction::new(
+        user_address,
+        pool_address,
+        aave_abi::Pool_Implementation::repayCall {
+            asset: token_address,
+            amount,
+            interestRateMode: rate_mode.into(),
+            onBehalfOf: user_address,
+        },
+        None,
+        None,
+        U256::ZERO,
+        false,
+    )
+}
+
+pub fn withdraw_call(
+    user_address: Address,
+    pool_address: Address,
+    token_address: Address,
+    amount: U256,
+) -> Transaction {
+    Transaction::new(
+        user_address,
+        pool_address,
+        aave_abi::Pool_Implementation::withdrawCall {
+            asset: token_address,
+            amount,
+            to: user_address,
+        },
+        None,
+        None,
+        U256::ZERO,
+        false,
+    )
+}
+
+/// Swap all the debt of a user in a token from `rate_mode` to the other mode
+pub fn swap_borrow_rate_mode_call(
+    user_address: Address,
+    pool_address: Address,
+    token_address: Address,
+    rate_mode: types::InterestRateMode,
+) -> Transaction {
+    Transaction::new(
+        user_address,
+        pool_address,
+        aave_abi::Pool_Implementation::swapBorrowRateModeCall {
+            asset: token_address,
+            interestRateMode: rate_mode.into(),
+        },
+        None,
+        None,
+        U256::ZERO,
+        false,
+    )
+}
+
+/// Rebalance the stable rate of the debt of a user in a token to the
+/// current stable rate of the reserve
+pub fn rebalance_stable_borrow_rate_call(
+    caller: Address,
+    pool_address: Address,
+    token_address: Address,
+    user_address: Address,
+) -> Transaction {
+    Transaction::new(
+        caller,
+        pool_address,
+        aave_abi::Pool_Implementation::rebalanceStableBorrowRateCall {
+            asset: token_address,
+            user: user_address,
+        },
+        None,
+        None,
+        U256::ZERO,
+        false,
+    )
+}
+
+/// Set the loan to value, liquidation threshold and liquidation bonus
+/// of a reserve, in basis points, as a risk or pool admin
+pub fn configure_reserve_as_collateral_call(
+    caller: Address,
+    pool_configurator_address: Address,
+    token_address: Address,
+    ltv: u64,
+    liquidation_threshold: u64,
+    liquidation_bonus: u64,
+) -> Transaction {
+    Transaction::new(
+        caller,
+        pool_configurator_address,
+        aave_abi::PoolConfigurator_Implementation::configureReserveAsCollateralCall {
+            asset: token_address,
+            ltv: U256::from(ltv),
+            liquidationThreshold: U256::from(liquidation_threshold),
+            liquidationBonus: U256::from(liquidation_bonus),
+        },
+        None,
+        None,
+        U256::ZERO,
+        false,
+    )
+}
+
+/// Set the reserve factor of a reserve, in basis points, as a risk or pool admin
+pub fn set_reserve_factor_call(
+    caller: Address,
+    pool_configurator_address: Address,
+    token_address: Address,
+    reserve_factor: u64,
+) -> Transaction {
+    Transaction::new(
+        caller,
+        pool_configurator_address,
+        aave_abi::PoolConfigurator_Implementation::setReserveFactorCall {
+            asset: token_address,
+            newReserveFactor: U256::from(reserve_factor),
+        },
+        None,
+        None,
+        U256::ZERO,
+        false,
+    )
+}
+
+/// Set the supply cap of a reserve, in whole tokens, as a risk or pool admin
+pub fn set_supply_cap_call(
+    caller: Address,
+    pool_configurator_address: Address,
+    token_address: Address,
+    supply_cap: u64,
+) -> Transaction {
+    Transaction::new(
+        caller,
+        pool_configurator_address,
+        aave_abi::PoolConfigurator_Implementation::setSupplyCapCall {
+            asset: token_address,
+            newSupplyCap: U256::from(supply_cap),
+        },
+        None,
+        None,
+        U256::ZERO,
+        false,
+    )
+}
+
+/// Set the borrow cap of a reserve, in whole tokens, as a risk or pool admin
+pub fn set_borrow_cap_call(
+    caller: Address,
+    pool_configurator_address: Address,
+    token_address: Address,
+    borrow_cap: u64,
+) -> Transaction {
+    Transaction::new(
+        caller,
+        pool_configurator_address,
+        aave_abi::PoolConfigurator_Implementation::setBorrowCapCall {
+            asset: token_address,
+            newBorrowCap: U256::from(borrow_cap),
+        },
+        None,
+        None,
+        U256::ZERO,
+        false,
+    )
+}
+
+/// Opt a user in to an e-mode category, or out of e-mode with category 0
+pub fn set_user_e_mode_call(
+    user_address: Address,
+    pool_address: Address,
+    category_id: u8,
+) -> Transaction {
+    Transaction::new(
+        user_address,
+        pool_address,
+        aave_abi::Pool_Implementation::setUserEModeCall {
+            categoryId: category_id,
+        },
+        None,
+        None,
+        U256::ZERO,
+        true,
+    )
+}
+
+pub fn liquidation_call(
+    collateral_token_address: Address,
+    debt_token_address: Address,
+    user_address: Address,
+    pool_address: Address,
+    liquidator_address: Address,
+    amount: U256,
+) -> Transaction {
+    Transaction::new(
+        liquidator_address,
+        pool_address,
+        aave_abi::Pool_Implementation::liquidationCallCall {
+            collateralAsset: collateral_token_address,
+            debtAsset: debt_token_address,
+            user: user_address,
+            debtToCover: amount,
+            receiveAToken: false,
+        },
+        None,
+        None,
+        U256::ZERO,
+        false,
+    )
+}
+
+/// Liquidation funded by a flash loan of the debt asset
+///
+/// The [FlashLiquidationReceiver](aave_abi::FlashLiquidationReceiver)
+/// contract liquidates the user with the loan, sells the collateral
+/// received for the debt asset on Uniswap, and sends the profit left
+/// after repaying the loan to the liquidator. The whole transaction
+/// reverts if the loan cannot be repaid.
+pub fn flash_loan_liquidation_call(
+    liquidator_address: Address,
+    pool_address: Address,
+    receiver_address: Address,
+    collateral_token_address: Address,
+    debt_token_address: Address,
+    user_address: Address,
+    amount: U256,
+    swap_router: Address,
+    uniswap_fee: u32,
+) -> Transaction {
+    let params = (
+        collateral_token_address,
+        user_address,
+        swap_router,
+        U256::from(uniswap_fee),
+    )
+        .abi_encode();
+
+    Transaction::new(
+        liquidator_address,
+        pool_address,
+        aave_abi::Pool_Implementation::flashLoanSimpleCall {
+            receiverAddress: receiver_address,
+            asset: debt_token_address,
+            amount,
+            params: params.into(),
+            referralCode: 0u16,
+        },
+        None,
+        None,
+        U256::ZERO,
+        false,
+    )
+}
+
+/// Adversarial liquidation bundle, executed atomically
+///
+/// The [LiquidationBundleExecutor](aave_abi::LiquidationBundleExecutor)
+/// contract pulls `collateral_amount` and `debt_amount` from the
+/// liquidator, sells the collateral on Uniswap to push down its price,
+/// liquidates each user for as much debt as the close factor allows,
+/// buys back the debt asset received from the sale, and returns its
+/// balances to the liquidator. If any step fails the whole bundle
+/// reverts.
+pub fn liquidation_bundle_call(
+    liquidator_address: Address,
+    executor_address: Address,
+    pool_address: Address,
+    collateral_token_address: Address,
+    debt_token_address: Address,
+    swap_router: Address,
+    uniswap_fee: u32,
+    collateral_amount: U256,
+    debt_amount: U256,
+    users: Vec<Address>,
+) -> Transaction {
+    Transaction::new(
+        liquidator_address,
+        executor_address,
+        aave_abi::LiquidationBundleExecutor::executeCall {
+            pool: pool_address,
+            collateralAsset: collateral_token_address,
+            debtAsset: debt_token_address,
+            swapRouter: swap_router,
+            fee: uniswap_fee,
+            collateralAmount: collateral_amount,
+            debtAmount: debt_amount,
+            users,
+        },
+        None,
+        None,
+        U256::ZERO,
+        false,
+    )
+}
+
+/// Premium paid on flash loans, in basis points
+pub fn get_flash_loan_premium<D, V>(
+    network: &mut Env<D, V>,
+    caller: Address,
+    pool_address: Address,
+) -> u64
+where
+    D: DB,
+    V: Validator,
+{
+    network
+        .direct_call(
+            caller,
+            pool_address,
+            aave_abi::Pool_Implementation::FLASHLOAN_PREMIUM_TOTALCall {},
+            U256::ZERO,
+        )
+        .unwrap()
+        .0
+        ._0 as u64
+}
+
+pub fn get_reserve_configuration_data<D, V>(
+    network: &mut Env<D, V>,
+    admin_address: Address,
+    data_provider_address: Address,
+    token_address: Address,
+) -> aave_abi::PoolDataProvider::getReserveConfigurationDataReturn
+where
+    D: DB,
+    V: Validator,
+{
+    network
+        .direct_call(
+            admin_address,
+            data_provider_address,
+            aave_abi::PoolDataProvider::getReserveConfigurationDataCall {
+                asset: token_address,
+            },
+            U256::ZERO,
+        )
+        .unwrap()
+        .0
+}
+
+pub fn get_asset_price<D, V>(
+    network: &mut Env<D, V>,
+    admin_address: Address,
+    oracle_address: Address,
+    token_address: Address,
+) -> U256
+where
+    D: DB,
+    V: Validator,
+{
+    network
+        .direct_call(
+            admin_address,
+            oracle_address,
+            aave_abi::AaveOracle::getAssetPriceCall {
+                asset: token_address,
+            },
+            U256::ZERO,
+        )
+        .unwrap()
+        .0
+        ._0
+}
+
+/// Address of the aToken of a reserve, which holds its available liquidity
+pub fn get_a_token<D, V>(
+    network: &mut Env<D, V>,
+    caller: Address,
+    pool_address: Address,
+    token_address: Address,
+) -> Address
+where
+    D: DB,
+    V: Validator,
+{
+    network
+        .direct_call(
+            caller,
+            pool_address,
+            aave_abi::Pool_Implementation::getReserveDataCall {
+                asset: token_address,
+            },
+            U256::ZERO,
+        )
+        .unwrap()
+        .0
+        ._0
+        .aTokenAddress
+}
+
+/// Data of a reserve, including its id and the addresses of its tokens
+pub fn get_reserve_data<D, V>(
+    network: &mut Env<D, V>,
+    caller: Address,
+    pool_address: Address,
+    token_address: Address,
+) -> aave_abi::Pool_Implementation::ReserveData
+where
+    D: DB,
+    V: Validator,
+{
+    network
+        .direct_call(
+            caller,
+            pool_address,
+            aave_abi::Pool_Implementation::getReserveDataCall {
+                asset: token_address,
+            },
+            U256::ZERO,
+        )
+        .unwrap()
+        .0
+        ._0
+}
+
+/// Risk parameters of a reserve, read from the pool
+pub fn get_reserve_configuration<D, V>(
+    network: &mut Env<D, V>,
+    caller: Address,
+    pool_address: Address,
+    token_address: Address,
+) -> types::ReserveConfiguration
+where
+    D: DB,
+    V: Validator,
+{
+    let configuration = network
+        .direct_call(
+            caller,
+            pool_address,
+            aave_abi::Pool_Implementation::getConfigurationCall {
+                asset: token_address,
+            },
+            U256::ZERO,
+        )
+        .unwrap()
+        .0
+        ._0
+        .data;
+    types::ReserveConfiguration::from_bitmap(configuration)
+}
+
+/// Bitmap of the reserves a user borrows, or uses as collateral
+///
+/// Bit `2 * id` is set if the user borrows the reserve with id `id`,
+/// and bit `2 * id + 1` if it is used as collateral.
+pub fn get_user_configuration<D, V>(
+    network: &mut Env<D, V>,
+    caller: Address,
+    pool_address: Address,
+    user_address: Address,
+) -> U256
+where
+    D: DB,
+    V: Validator,
+{
+    network
+        .direct_call(
+            caller,
+            pool_address,
+            aave_abi::Pool_Implementation::getUserConfigurationCall { user: user_address },
+            U256::ZERO,
+        )
+        .unwrap()
+        .0
+        ._0
+        .data
+}
+
+/// Stable borrow rate of the debt of a user, in ray
+pub fn get_user_stable_rate<D, V>(
+    network: &mut Env<D, V>,
+    stable_debt_token: Address,
     user_address: Address,
+) -> U256
+where
+    D: DB,
+    V: Validator,
+{
+    network
+        .direct_call(
+            Address::ZERO,
+            stable_debt_token,
+            aave_abi::StableDebtToken::getUserStableRateCall { user: user_address },
+            U256::ZERO,
+        )
+        .unwrap()
+        .0
+        ._0
+}
+
+/// Largest share of the liquidity of a reserve that can be borrowed at
+/// a stable rate in one borrow, in basis points
+pub fn get_max_stable_rate_borrow_size_percent<D, V>(
+    network: &mut Env<D, V>,
     pool_address: Address,
-    liquidator_address: Address,
-    amount: U256,
-) -> Transaction {
-    Transaction::new(
-        liquidator_address,
-        pool_address,
-        aave_abi::Pool_Implementation::liquidationCallCall {
-            collateralAsset: collateral_token_address,
-            debtAsset: debt_token_address,
-            user: user_address,
-            debtToCover: amount,
-            receiveAToken: false,
-        },
-        None,
-        None,
-        U256::ZERO,
-        false,
-    )
+) -> U256
+where
+    D: DB,
+    V: Validator,
+{
+    network
+        .direct_call(
+            Address::ZERO,
+            pool_address,
+            aave_abi::Pool_Implementation::MAX_STABLE_RATE_BORROW_SIZE_PERCENTCall {},
+            U256::ZERO,
+        )
+        .unwrap()
+        .0
+        ._0
 }
 
-pub fn get_reserve_configuration_data<D, V>(
+/// E-mode category of a user, 0 if it is not in e-mode
+pub fn get_user_e_mode<D, V>(
     network: &mut Env<D, V>,
-    admin_address: Address,
-    data_provider_address: Address,
-    token_address: Address,
-) -> aave_abi::PoolDataProvider::getReserveConfigurationDataReturn
+    caller: Address,
+    pool_address: Address,
+    user_address: Address,
+) -> u8
 where
     D: DB,
     V: Validator,
 {
     network
         .direct_call(
-            admin_address,
-            data_provider_address,
-            aave_abi::PoolDataProvider::getReserveConfigurationDataCall {
-                asset: token_address,
-            },
+            caller,
+            pool_address,
+            aave_abi::Pool_Implementation::getUserEModeCall { user: user_address },
             U256::ZERO,
         )
         .unwrap()
         .0
+        ._0
+        .to::<u8>()
 }
 
-pub fn get_asset_price<D, V>(
+/// Risk parameters of an e-mode category
+pub fn get_e_mode_category_data<D, V>(
     network: &mut Env<D, V>,
-    admin_address: Address,
-    oracle_address: Address,
-    token_address: Address,
-) -> U256
+    caller: Address,
+    pool_address: Address,
+    category_id: u8,
+) -> aave_abi::Pool_Implementation::EModeCategory
 where
     D: DB,
     V: Validator,
 {
     network
         .direct_call(
-            admin_address,
-            oracle_address,
-            aave_abi::AaveOracle::getAssetPriceCall {
-                asset: token_address,
-            },
+            caller,
+            pool_address,
+            aave_abi::Pool_Implementation::getEModeCategoryDataCall { id: category_id },
             U256::ZERO,
         )
         .unwrap()
@@ -241,6 +768,24 @@ where
         ._0
 }
 
+/// Total supply of a token, including aTokens and debt tokens
+pub fn total_supply<D, V>(network: &mut Env<D, V>, token: Address) -> U256
+where
+    D: DB,
+    V: Validator,
+{
+    network
+        .direct_call(
+            Address::ZERO,
+            token,
+            periphery_abi::MintableERC20::totalSupplyCall {},
+            U256::ZERO,
+        )
+        .unwrap()
+        .0
+        ._0
+}
+
 pub fn get_slot0<D, V>(
     network: &mut Env<D, V>,
     caller: Address,
@@ -291,7 +836,8 @@ where
     D: DB,
     V: Validator,
 {
-    let quote = network.direct_call(
+    let quote = try_call(
+        network,
         caller,
         quoter,
         uniswap_abi::Quoter_v2::quoteExactOutputSingleCall {
@@ -303,7 +849,39 @@ where
                 tokenOut: token_out,
             },
         },
-        U256::ZERO,
+    );
+    match quote {
+        Ok(result) => Some(result.0),
+        Err(_) => None,
+    }
+}
+
+pub fn quote_v2_exact_input_swap<D, V>(
+    network: &mut Env<D, V>,
+    caller: Address,
+    token_in: Address,
+    token_out: Address,
+    fee: u32,
+    amount_in: U256,
+    quoter: Address,
+) -> Option<uniswap_abi::Quoter_v2::quoteExactInputSingleReturn>
+where
+    D: DB,
+    V: Validator,
+{
+    let quote = try_call(
+        network,
+        caller,
+        quoter,
+        uniswap_abi::Quoter_v2::quoteExactInputSingleCall {
+            params: uniswap_abi::Quoter_v2::QuoteExactInputSingleParams {
+                amountIn: amount_in,
+                fee,
+                sqrtPriceLimitX96: U256::ZERO,
+                tokenIn: token_in,
+                tokenOut: token_out,
+            },
+        },
     );
     match quote {
         Ok(result) => Some(result.0),
@@ -364,3 +942,233 @@ where
         .0
         ._0
 }
+
+pub fn get_tick_spacing<D, V>(network: &mut Env<D, V>, caller: Address, pool: Address) -> i32
+where
+    D: DB,
+    V: Validator,
+{
+    network
+        .direct_call(
+            caller,
+            pool,
+            uniswap_abi::UniswapV3Pool::tickSpacingCall {},
+            U256::ZERO,
+        )
+        .unwrap()
+        .0
+        ._0
+}
+
+/// Word of the bitmap of initialised ticks of a Uniswap pool
+pub fn get_tick_bitmap<D, V>(
+    network: &mut Env<D, V>,
+    caller: Address,
+    pool: Address,
+    word_position: i16,
+) -> U256
+where
+    D: DB,
+    V: Validator,
+{
+    network
+        .direct_call(
+            caller,
+            pool,
+            uniswap_abi::UniswapV3Pool::tickBitmapCall { _0: word_position },
+            U256::ZERO,
+        )
+        .unwrap()
+        .0
+        ._0
+}
+
+/// Net liquidity added to a Uniswap pool when its price crosses a tick upwards
+pub fn get_tick_liquidity_net<D, V>(
+    network: &mut Env<D, V>,
+    caller: Address,
+    pool: Address,
+    tick: i32,
+) -> i128
+where
+    D: DB,
+    V: Validator,
+{
+    network
+        .direct_call(
+            caller,
+            pool,
+            uniswap_abi::UniswapV3Pool::ticksCall { _0: tick },
+            U256::ZERO,
+        )
+        .unwrap()
+        .0
+        .liquidityNet
+}
+
+/// Ids of the Uniswap positions owned by an address
+pub fn get_position_ids<D, V>(
+    network: &mut Env<D, V>,
+    owner: Address,
+    nft_position_manager: Address,
+) -> Vec<U256>
+where
+    D: DB,
+    V: Validator,
+{
+    let n_positions = network
+        .direct_call(
+            owner,
+            nft_position_manager,
+            uniswap_abi::NonfungiblePositionManager::balanceOfCall { owner },
+            U256::ZERO,
+        )
+        .unwrap()
+        .0
+        ._0;
+
+    (0..n_positions.to::<u64>())
+        .map(|i| {
+            network
+                .direct_call(
+                    owner,
+                    nft_position_manager,
+                    uniswap_abi::NonfungiblePositionManager::tokenOfOwnerByIndexCall {
+                        owner,
+                        index: U256::from(i),
+                    },
+                    U256::ZERO,
+                )
+                .unwrap()
+                .0
+                ._0
+        })
+        .collect()
+}
+
+pub fn get_position<D, V>(
+    network: &mut Env<D, V>,
+    caller: Address,
+    nft_position_manager: Address,
+    token_id: U256,
+) -> uniswap_abi::NonfungiblePositionManager::positionsReturn
+where
+    D: DB,
+    V: Validator,
+{
+    network
+        .direct_call(
+            caller,
+            nft_position_manager,
+            uniswap_abi::NonfungiblePositionManager::positionsCall { tokenId: token_id },
+            U256::ZERO,
+        )
+        .unwrap()
+        .0
+}
+
+pub fn mint_position_call(
+    owner: Address,
+    nft_position_manager: Address,
+    params: uniswap_abi::NonfungiblePositionManager::MintParams,
+) -> Transaction {
+    Transaction::new(
+        owner,
+        nft_position_manager,
+        uniswap_abi::NonfungiblePositionManager::mintCall { params },
+        None,
+        None,
+        U256::ZERO,
+        false,
+    )
+}
+
+pub fn decrease_liquidity_call(
+    owner: Address,
+    nft_position_manager: Address,
+    token_id: U256,
+    liquidity: u128,
+) -> Transaction {
+    Transaction::new(
+        owner,
+        nft_position_manager,
+        uniswap_abi::NonfungiblePositionManager::decreaseLiquidityCall {
+            params: uniswap_abi::NonfungiblePositionManager::DecreaseLiquidityParams {
+                tokenId: token_id,
+                liquidity,
+                amount0Min: U256::ZERO,
+                amount1Min: U256::ZERO,
+                deadline: U256::MAX,
+            },
+        },
+        None,
+        None,
+        U256::ZERO,
+        false,
+    )
+}
+
+fn collect_params(
+    owner: Address,
+    token_id: U256,
+) -> uniswap_abi::NonfungiblePositionManager::collectCall {
+    uniswap_abi::NonfungiblePositionManager::collectCall {
+        params: uniswap_abi::NonfungiblePositionManager::CollectParams {
+            tokenId: token_id,
+            recipient: owner,
+            amount0Max: u128::MAX,
+            amount1Max: u128::MAX,
+        },
+    }
+}
+
+/// Collect all tokens owed to a position, including accrued fees
+pub fn collect_call(owner: Address, nft_position_manager: Address, token_id: U256) -> Transaction {
+    Transaction::new(
+        owner,
+        nft_position_manager,
+        collect_params(owner, token_id),
+        None,
+        None,
+        U256::ZERO,
+        false,
+    )
+}
+
+/// Amounts of each token that collecting from a position would currently return
+pub fn get_collectable_amounts<D, V>(
+    network: &mut Env<D, V>,
+    owner: Address,
+    nft_position_manager: Address,
+    token_id: U256,
+) -> (U256, U256)
+where
+    D: DB,
+    V: Validator,
+{
+    match try_call(
+        network,
+        owner,
+        nft_position_manager,
+        collect_params(owner, token_id),
+    ) {
+        Ok((amounts, _)) => (amounts.amount0, amounts.amount1),
+        Err(_) => (U256::ZERO, U256::ZERO),
+    }
+}
+
+pub fn burn_position_call(
+    owner: Address,
+    nft_position_manager: Address,
+    token_id: U256,
+) -> Transaction {
+    Transaction::new(
+        owner,
+        nft_position_manager,
+        uniswap_abi::NonfungiblePositionManager::burnCall { tokenId: token_id },
+        None,
+        None,
+        U256::ZERO,
+        false,
+    )
+}
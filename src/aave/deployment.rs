@@ -1,9 +1,17 @@
-use crate::aave::agents::{BorrowAgent, LiquidationAgent, UniswapNoiseAgent, UniswapPriceAgent};
-use crate::aave::protocol::{aave_abi, periphery_abi};
+use crate::aave::agents::{
+    base_to_token, BorrowAgent, CloneableAgentVec, CloneableSingletonAgent, LiquidationAgent,
+    LiquidityProviderAgent, RateRebalancer, RiskSteward, SandwichAgent, UniswapNoiseAgent,
+    UniswapPriceAgent,
+};
+use crate::aave::calls;
+use crate::aave::gas::GasPricer;
+use crate::aave::protocol::{aave_abi, aave_bytecode, periphery_abi, AaveAddresses};
 use crate::aave::types;
-use alloy_primitives::{Address, U256};
-use verbs_rs::agent::{AgentVec, SingletonAgent};
+use alloy_primitives::{Address, Uint, U256};
+use alloy_sol_types::SolValue;
+use verbs_rs::agent::Agent;
 use verbs_rs::env::{Env, Validator};
+use verbs_rs::utils::constructor_data;
 use verbs_rs::DB;
 
 pub fn admin_mint_and_supply<D, V>(
@@ -240,64 +248,297 @@ where
     env
 }
 
+/// Initialise `n_agents` borrowers drawn from a population, followed
+/// by a borrower mirroring each of the given positions
 pub fn initialise_borrow_agents(
     n_agents: usize,
-    activation_rate: f64,
-    borrow_token_ltv: U256,
-    borrow_token_decimals: U256,
+    population: types::BorrowerPopulation,
+    positions: &[types::BorrowerPosition],
     supply_token: Address,
     borrow_token: Address,
+    supply_token_decimals: U256,
+    borrow_token_decimals: U256,
     pool: Address,
     oracle: Address,
-) -> AgentVec<U256, BorrowAgent> {
+) -> CloneableAgentVec<types::BorrowerRecord, BorrowAgent> {
     let agents = (1000..1000 + n_agents)
         .map(|i| {
             BorrowAgent::new(
                 i,
-                activation_rate,
-                borrow_token_ltv,
-                borrow_token_decimals,
+                population,
                 pool,
                 oracle,
                 supply_token,
                 borrow_token,
+                supply_token_decimals,
+                borrow_token_decimals,
             )
         })
+        .chain(positions.iter().map(|position| {
+            BorrowAgent::mirror(
+                position.user,
+                population,
+                pool,
+                oracle,
+                supply_token,
+                borrow_token,
+                supply_token_decimals,
+                borrow_token_decimals,
+            )
+        }))
         .collect();
-    AgentVec::from(agents)
+    CloneableAgentVec::from(agents)
+}
+
+/// Initialise a keeper rebalancing the stable rates of debt in the given tokens
+pub fn initialise_rate_rebalancer(
+    pool: Address,
+    tokens: Vec<Address>,
+    known_users: Vec<Address>,
+) -> RateRebalancer {
+    RateRebalancer::new(7000, pool, tokens, known_users)
+}
+
+/// Initialise a risk steward adjusting the parameters of the given tokens
+///
+/// The admin grants the steward the risk admin role, so it can update
+/// reserves through the pool configurator.
+#[allow(clippy::too_many_arguments)]
+pub fn initialise_risk_steward<D: DB, V: Validator>(
+    env: &mut Env<D, V>,
+    admin_address: Address,
+    aave_addresses: &AaveAddresses,
+    tokens: Vec<Address>,
+    parameters: types::RiskStewardParameters,
+    dt: f64,
+    known_users: Vec<Address>,
+) -> RiskSteward {
+    let steward = RiskSteward::new(
+        8000,
+        aave_addresses.pool,
+        aave_addresses.pool_configurator,
+        aave_addresses.oracle,
+        tokens,
+        parameters,
+        dt,
+        known_users,
+    );
+    env.direct_execute(
+        admin_address,
+        aave_addresses.acl_manager,
+        aave_abi::ACLManager::addRiskAdminCall {
+            admin: steward.get_address(),
+        },
+        U256::ZERO,
+    )
+    .unwrap();
+    steward
+}
+
+/// Open positions supplying token a and borrowing token b, with the
+/// collateral and debt values of the given positions
+///
+/// Users should hold, and have approved the pool to use, enough of both
+/// tokens. Positions with debt above the loan to value of their collateral
+/// are opened by temporarily supplying extra collateral. Positions in
+/// any e-mode category are opened in the local e-mode category, at its
+/// loan to value.
+///
+/// # Panics
+///
+/// Panics if a position cannot be opened, for example if it has a
+/// health factor below 1 at the local liquidation threshold.
+///
+pub fn open_mirrored_positions<D, V>(
+    mut env: Env<D, V>,
+    positions: &[types::BorrowerPosition],
+    pool: Address,
+    oracle: Address,
+    supply_token: Address,
+    borrow_token: Address,
+    supply_token_ltv: U256,
+    e_mode_category: &types::EModeCategory,
+) -> Env<D, V>
+where
+    D: DB,
+    V: Validator,
+{
+    let supply_decimals = calls::get_decimals(&mut env, Address::ZERO, supply_token);
+    let borrow_decimals = calls::get_decimals(&mut env, Address::ZERO, borrow_token);
+    let supply_price = calls::get_asset_price(&mut env, Address::ZERO, oracle, supply_token);
+    let borrow_price = calls::get_asset_price(&mut env, Address::ZERO, oracle, borrow_token);
+    for position in positions {
+        let ltv = match position.e_mode {
+            0 => supply_token_ltv.to::<u64>(),
+            _ => {
+                env.direct_execute(
+                    position.user,
+                    pool,
+                    aave_abi::Pool_Implementation::setUserEModeCall {
+                        categoryId: e_mode_category.id,
+                    },
+                    U256::ZERO,
+                )
+                .expect("Could not set e-mode of mirrored position");
+                u64::from(e_mode_category.ltv)
+            }
+        };
+        // Leave a margin below the loan to value, for rounding
+        let ltv = 0.99 * ltv as f64 / 10_000.;
+        let collateral = base_to_token(position.collateral, supply_price, supply_decimals);
+        let extra_collateral = base_to_token(
+            position.debt / ltv - position.collateral,
+            supply_price,
+            supply_decimals,
+        );
+        let debt = base_to_token(position.debt, borrow_price, borrow_decimals);
+
+        env.direct_execute(
+            position.user,
+            pool,
+            aave_abi::Pool_Implementation::supplyCall {
+                asset: supply_token,
+                amount: collateral + extra_collateral,
+                onBehalfOf: position.user,
+                referralCode: 0u16,
+            },
+            U256::ZERO,
+        )
+        .expect("Could not supply collateral of mirrored position");
+
+        if debt > U256::ZERO {
+            env.direct_execute(
+                position.user,
+                pool,
+                aave_abi::Pool_Implementation::borrowCall {
+                    asset: borrow_token,
+                    amount: debt,
+                    interestRateMode: types::InterestRateMode::Variable.into(),
+                    referralCode: 0u16,
+                    onBehalfOf: position.user,
+                },
+                U256::ZERO,
+            )
+            .expect("Could not borrow debt of mirrored position");
+        }
+
+        if extra_collateral > U256::ZERO {
+            env.direct_execute(
+                position.user,
+                pool,
+                aave_abi::Pool_Implementation::withdrawCall {
+                    asset: supply_token,
+                    amount: extra_collateral,
+                    to: position.user,
+                },
+                U256::ZERO,
+            )
+            .expect("Could not withdraw extra collateral, the mirrored position may have a health factor below 1");
+        }
+    }
+
+    env
+}
+
+/// Deploy the receiver contract used by flash loan liquidations
+///
+/// The receiver only accepts flash loans of the pool started by its
+/// liquidator.
+///
+/// # Arguments
+///
+/// * `env` - Simulation environment
+/// * `deployer` - Address deploying the receiver
+/// * `pool` - Address of the Aave pool
+/// * `liquidator` - Liquidator using the receiver
+///
+pub fn deploy_flash_liquidation_receiver<D, V>(
+    env: &mut Env<D, V>,
+    deployer: Address,
+    pool: Address,
+    liquidator: Address,
+) -> Address
+where
+    D: DB,
+    V: Validator,
+{
+    env.deploy_contract(
+        deployer,
+        "Flash Liquidation Receiver",
+        constructor_data(
+            aave_bytecode::FLASH_LIQUIDATION_RECEIVER,
+            Some((pool, liquidator).abi_encode_params()),
+        ),
+    )
+}
+
+/// Deploy the executor contract used by adversarial liquidation bundles
+pub fn deploy_liquidation_bundle_executor<D, V>(env: &mut Env<D, V>, deployer: Address) -> Address
+where
+    D: DB,
+    V: Validator,
+{
+    env.deploy_contract(
+        deployer,
+        "Liquidation Bundle Executor",
+        constructor_data(aave_bytecode::LIQUIDATION_BUNDLE_EXECUTOR, None),
+    )
 }
 
-pub fn initialise_liquidation_agents(
+pub fn initialise_liquidation_agents<D, V>(
+    env: &mut Env<D, V>,
+    deployer: Address,
     n_agents: usize,
     collateral_token: Address,
     debt_token: Address,
     pool: Address,
     oracle: Address,
-    liquidation_addresses: Vec<Address>,
+    known_users: Vec<Address>,
     adversarial: bool,
     uniswap_pool: Address,
     quoter: Address,
     swap_router: Address,
     uniswap_fee: u32,
-) -> AgentVec<types::UserData, LiquidationAgent> {
+    flash_loan_liquidation: bool,
+    bundle_executor: Option<Address>,
+    capital: Option<types::LiquidatorCapital>,
+    gas_bid_strategies: &[types::GasBidStrategy],
+    gas_pricer: GasPricer,
+) -> CloneableAgentVec<types::UserData, LiquidationAgent>
+where
+    D: DB,
+    V: Validator,
+{
     let agents = (2000..2000 + n_agents)
-        .map(|i| {
+        .enumerate()
+        .map(|(n, i)| {
+            // Each liquidator has its own flash loan receiver
+            let flash_loan_receiver = flash_loan_liquidation.then(|| {
+                deploy_flash_liquidation_receiver(env, deployer, pool, Address::from(Uint::from(i)))
+            });
             LiquidationAgent::new(
                 i,
                 pool,
                 oracle,
                 collateral_token,
                 debt_token,
-                liquidation_addresses.clone(),
+                known_users.clone(),
                 adversarial,
                 uniswap_pool,
                 quoter,
                 swap_router,
                 uniswap_fee,
+                flash_loan_receiver,
+                bundle_executor,
+                capital,
+                // Strategies are assigned to liquidators in turn
+                (!gas_bid_strategies.is_empty())
+                    .then(|| gas_bid_strategies[n % gas_bid_strategies.len()]),
+                gas_pricer,
             )
         })
         .collect();
-    AgentVec::from(agents)
+    CloneableAgentVec::from(agents)
 }
 
 pub fn initialise_uniswap_price_agent<D, V>(
@@ -311,12 +552,14 @@ pub fn initialise_uniswap_price_agent<D, V>(
     mu: f64,
     dt: f64,
     sigma: f64,
-) -> SingletonAgent<(i128, i128), UniswapPriceAgent>
+    price_process: types::PriceProcess,
+    gas_token: types::GasToken,
+) -> CloneableSingletonAgent<(i128, i128), UniswapPriceAgent>
 where
     D: DB,
     V: Validator,
 {
-    SingletonAgent::from(UniswapPriceAgent::new(
+    CloneableSingletonAgent::from(UniswapPriceAgent::new(
         env,
         3000,
         pool,
@@ -328,6 +571,8 @@ where
         mu,
         dt,
         sigma,
+        price_process,
+        gas_token,
     ))
 }
 
@@ -338,7 +583,7 @@ pub fn initialise_uniswap_noise_agents<D, V>(
     swap_router: Address,
     token_a: Address,
     token_b: Address,
-) -> AgentVec<U256, UniswapNoiseAgent>
+) -> CloneableAgentVec<U256, UniswapNoiseAgent>
 where
     D: DB,
     V: Validator,
@@ -346,5 +591,66 @@ where
     let agents = (4000..4000 + n_agents)
         .map(|i| UniswapNoiseAgent::new(env, i, fee, swap_router, token_a, token_b))
         .collect();
-    AgentVec::from(agents)
+    CloneableAgentVec::from(agents)
+}
+
+pub fn initialise_liquidity_provider_agents<D, V>(
+    env: &mut Env<D, V>,
+    n_agents: usize,
+    pool: Address,
+    nft_position_manager: Address,
+    fee: u32,
+    strategy: types::LiquidityStrategy,
+    liquidity: u128,
+) -> CloneableAgentVec<types::PositionRecord, LiquidityProviderAgent>
+where
+    D: DB,
+    V: Validator,
+{
+    let agents = (5000..5000 + n_agents)
+        .map(|i| {
+            LiquidityProviderAgent::new(
+                env,
+                i,
+                pool,
+                nft_position_manager,
+                fee,
+                strategy,
+                liquidity,
+            )
+        })
+        .collect();
+    CloneableAgentVec::from(agents)
+}
+
+pub fn initialise_searcher_agents<D, V>(
+    env: &mut Env<D, V>,
+    n_agents: usize,
+    pool: Address,
+    fee: u32,
+    swap_router: Address,
+    quoter: Address,
+    oracle: Address,
+    gas_pricer: GasPricer,
+    bid_share: f64,
+) -> Vec<SandwichAgent>
+where
+    D: DB,
+    V: Validator,
+{
+    (6000..6000 + n_agents)
+        .map(|i| {
+            SandwichAgent::new(
+                env,
+                i,
+                pool,
+                fee,
+                swap_router,
+                quoter,
+                oracle,
+                gas_pricer,
+                bid_share,
+            )
+        })
+        .collect()
 }
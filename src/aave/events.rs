@@ -0,0 +1,187 @@
+//! Decoded stream of the events emitted by protocol contracts
+//!
+//! The logs of every transaction processed in a simulation are
+//! decoded into typed [ProtocolEvent] records, each with the step,
+//! transaction and log it came from, so the history of the Aave
+//! pool, its tokens, the Uniswap pool and the price aggregators can
+//! be reconstructed from the output of a run. Logs of other events
+//! are skipped.
+//!
+
+use super::protocol::aave_abi::{AToken, MockAggregator, Pool_Implementation};
+use super::protocol::uniswap_abi::UniswapV3Pool;
+use super::types::{EventRecord, ProtocolEvent, TransactionOutcome};
+use alloy_primitives::{Log, U256};
+use alloy_sol_types::SolEvent;
+
+/// Decode a log emitted by a protocol contract
+///
+/// aTokens and variable debt tokens emit `Mint` and `Burn` events
+/// with the same signature, so both are decoded as token events and
+/// told apart by the address of the token.
+///
+/// # Arguments
+///
+/// * `log` - Log emitted by a transaction
+///
+pub fn decode_log(log: &Log) -> Option<ProtocolEvent> {
+    let event = match log.topics().first().copied()? {
+        Pool_Implementation::Supply::SIGNATURE_HASH => {
+            let x = Pool_Implementation::Supply::decode_log(log, true).ok()?;
+            ProtocolEvent::Supply {
+                reserve: x.reserve,
+                user: x.user,
+                on_behalf_of: x.onBehalfOf,
+                amount: x.amount,
+            }
+        }
+        Pool_Implementation::Borrow::SIGNATURE_HASH => {
+            let x = Pool_Implementation::Borrow::decode_log(log, true).ok()?;
+            ProtocolEvent::Borrow {
+                reserve: x.reserve,
+                user: x.user,
+                on_behalf_of: x.onBehalfOf,
+                amount: x.amount,
+                interest_rate_mode: x.interestRateMode,
+                borrow_rate: x.borrowRate,
+            }
+        }
+        Pool_Implementation::Repay::SIGNATURE_HASH => {
+            let x = Pool_Implementation::Repay::decode_log(log, true).ok()?;
+            ProtocolEvent::Repay {
+                reserve: x.reserve,
+                user: x.user,
+                repayer: x.repayer,
+                amount: x.amount,
+                use_a_tokens: x.useATokens,
+            }
+        }
+        Pool_Implementation::SwapBorrowRateMode::SIGNATURE_HASH => {
+            let x = Pool_Implementation::SwapBorrowRateMode::decode_log(log, true).ok()?;
+            ProtocolEvent::SwapBorrowRateMode {
+                reserve: x.reserve,
+                user: x.user,
+                interest_rate_mode: x.interestRateMode,
+            }
+        }
+        Pool_Implementation::RebalanceStableBorrowRate::SIGNATURE_HASH => {
+            let x = Pool_Implementation::RebalanceStableBorrowRate::decode_log(log, true).ok()?;
+            ProtocolEvent::RebalanceStableBorrowRate {
+                reserve: x.reserve,
+                user: x.user,
+            }
+        }
+        Pool_Implementation::LiquidationCall::SIGNATURE_HASH => {
+            let x = Pool_Implementation::LiquidationCall::decode_log(log, true).ok()?;
+            ProtocolEvent::LiquidationCall {
+                collateral_asset: x.collateralAsset,
+                debt_asset: x.debtAsset,
+                user: x.user,
+                debt_to_cover: x.debtToCover,
+                liquidated_collateral_amount: x.liquidatedCollateralAmount,
+                liquidator: x.liquidator,
+                receive_a_token: x.receiveAToken,
+            }
+        }
+        Pool_Implementation::ReserveDataUpdated::SIGNATURE_HASH => {
+            let x = Pool_Implementation::ReserveDataUpdated::decode_log(log, true).ok()?;
+            ProtocolEvent::ReserveDataUpdated {
+                reserve: x.reserve,
+                liquidity_rate: x.liquidityRate,
+                stable_borrow_rate: x.stableBorrowRate,
+                variable_borrow_rate: x.variableBorrowRate,
+                liquidity_index: x.liquidityIndex,
+                variable_borrow_index: x.variableBorrowIndex,
+            }
+        }
+        UniswapV3Pool::Swap::SIGNATURE_HASH => {
+            let x = UniswapV3Pool::Swap::decode_log(log, true).ok()?;
+            ProtocolEvent::Swap {
+                sender: x.sender,
+                recipient: x.recipient,
+                amount0: x.amount0,
+                amount1: x.amount1,
+                sqrt_price_x96: U256::from(x.sqrtPriceX96),
+                liquidity: x.liquidity,
+                tick: x.tick,
+            }
+        }
+        UniswapV3Pool::Mint::SIGNATURE_HASH => {
+            let x = UniswapV3Pool::Mint::decode_log(log, true).ok()?;
+            ProtocolEvent::Mint {
+                sender: x.sender,
+                owner: x.owner,
+                tick_lower: x.tickLower,
+                tick_upper: x.tickUpper,
+                amount: x.amount,
+                amount0: x.amount0,
+                amount1: x.amount1,
+            }
+        }
+        UniswapV3Pool::Burn::SIGNATURE_HASH => {
+            let x = UniswapV3Pool::Burn::decode_log(log, true).ok()?;
+            ProtocolEvent::Burn {
+                owner: x.owner,
+                tick_lower: x.tickLower,
+                tick_upper: x.tickUpper,
+                amount: x.amount,
+                amount0: x.amount0,
+                amount1: x.amount1,
+            }
+        }
+        AToken::Mint::SIGNATURE_HASH => {
+            let x = AToken::Mint::decode_log(log, true).ok()?;
+            ProtocolEvent::TokenMint {
+                caller: x.caller,
+                on_behalf_of: x.onBehalfOf,
+                value: x.value,
+                balance_increase: x.balanceIncrease,
+                index: x.index,
+            }
+        }
+        AToken::Burn::SIGNATURE_HASH => {
+            let x = AToken::Burn::decode_log(log, true).ok()?;
+            ProtocolEvent::TokenBurn {
+                from: x.from,
+                target: x.target,
+                value: x.value,
+                balance_increase: x.balanceIncrease,
+                index: x.index,
+            }
+        }
+        MockAggregator::PriceUpdate::SIGNATURE_HASH => {
+            let x = MockAggregator::PriceUpdate::decode_log(log, true).ok()?;
+            ProtocolEvent::PriceUpdate {
+                old_price: x.old_price,
+                new_price: x.new_price,
+            }
+        }
+        _ => return None,
+    };
+    Some(event)
+}
+
+/// Decode the events emitted by a sequence of transactions
+///
+/// Returns the decoded events in the order they were emitted.
+///
+/// # Arguments
+///
+/// * `outcomes` - Outcomes of processed transactions
+///
+pub fn decode_events(outcomes: &[TransactionOutcome]) -> Vec<EventRecord> {
+    outcomes
+        .iter()
+        .flat_map(|outcome| {
+            outcome.logs.iter().enumerate().filter_map(|(i, log)| {
+                decode_log(log).map(|event| EventRecord {
+                    step: outcome.step,
+                    transaction: outcome.sequence,
+                    log_index: i,
+                    address: log.address,
+                    event,
+                })
+            })
+        })
+        .collect()
+}
@@ -1,8 +1,9 @@
 use super::agents::AgentStates;
 use super::calls;
 use super::deployment;
+use super::gas;
 use super::protocol::{aave_abi, aave_bytecode, uniswap_abi, uniswap_bytecode};
-use super::types::ForkedSimParameters;
+use super::types::{ForkedSimParameters, GasToken, LiquidityStrategy, PriceProcess};
 use alloy_primitives::{Address, U256};
 use alloy_sol_types::SolValue;
 use verbs_rs::agent::AgentSet;
@@ -99,7 +100,7 @@ pub fn initialise_sim<V: Validator>(
         aave_addresses.data_provider,
         periphery_addresses.token_a,
     );
-    let _token_a_decimals = token_a_config.decimals;
+    let token_a_decimals = token_a_config.decimals;
     let _token_a_ltv = token_a_config.ltv;
 
     let token_b_config = calls::get_reserve_configuration_data(
@@ -109,7 +110,7 @@ pub fn initialise_sim<V: Validator>(
         periphery_addresses.token_b,
     );
     let token_b_decimals = token_b_config.decimals;
-    let token_b_ltv = token_b_config.ltv;
+    let _token_b_ltv = token_b_config.ltv;
 
     let uniswap_pool_address = env
         .direct_call(
@@ -128,27 +129,56 @@ pub fn initialise_sim<V: Validator>(
 
     let borrow_agents = deployment::initialise_borrow_agents(
         params.n_borrowers,
-        params.borrow_activation_rate,
-        token_b_ltv,
-        token_b_decimals,
+        params.borrower_population,
+        &[],
         periphery_addresses.token_a,
         periphery_addresses.token_b,
+        token_a_decimals,
+        token_b_decimals,
         aave_addresses.pool,
         aave_addresses.oracle,
     );
 
+    // Liquidators discover borrowers from pool events, which only cover
+    // the simulation, so start from the users already in debt in the fork
+    let known_users = params
+        .borrower_positions
+        .iter()
+        .map(|x| x.user)
+        .chain(params.fork_users.iter().copied())
+        .collect();
+
+    // Liquidators using their own inventory submit adversarial
+    // liquidations as atomic bundles through an executor contract
+    let bundle_executor = (!params.flash_loan_liquidation)
+        .then(|| deployment::deploy_liquidation_bundle_executor(&mut env, admin_address));
+
+    // Gas is priced at the oracle price of the token standing for ETH
+    let gas_token = match params.gas_token {
+        GasToken::TokenA => periphery_addresses.token_a,
+        GasToken::TokenB => periphery_addresses.token_b,
+    };
+    let gas_pricer = gas::GasPricer::new(aave_addresses.oracle, gas_token);
+
     let liquidation_agents = deployment::initialise_liquidation_agents(
+        &mut env,
+        admin_address,
         params.n_liquidators,
         periphery_addresses.token_a,
         periphery_addresses.token_b,
         aave_addresses.pool,
         aave_addresses.oracle,
-        borrow_agents.get_addresses(),
+        known_users,
         params.adversarial,
         uniswap_pool_address,
         uniswap_addresses.quoter_address,
         uniswap_addresses.swap_router,
         params.uniswap_fee,
+        params.flash_loan_liquidation,
+        bundle_executor,
+        params.liquidator_capital,
+        &params.gas_bid_strategies,
+        gas_pricer,
     );
 
     // Get initial prices from the fork
@@ -179,6 +209,8 @@ pub fn initialise_sim<V: Validator>(
         params.prices_mu,
         params.prices_dt,
         params.prices_sigma,
+        PriceProcess::Gbm,
+        params.gas_token,
     );
 
     // Uniswap noise agent
@@ -191,10 +223,36 @@ pub fn initialise_sim<V: Validator>(
         periphery_addresses.token_b,
     );
 
+    // Forked runs use the liquidity of the live pool
+    let liquidity_provider_agents = deployment::initialise_liquidity_provider_agents(
+        &mut env,
+        0,
+        uniswap_pool_address,
+        uniswap_addresses.nft_position_manager,
+        params.uniswap_fee,
+        LiquidityStrategy::FullRange,
+        0,
+    );
+
+    let searcher_agents = deployment::initialise_searcher_agents(
+        &mut env,
+        params.n_searchers,
+        uniswap_pool_address,
+        params.uniswap_fee,
+        uniswap_addresses.swap_router,
+        uniswap_addresses.quoter_address,
+        aave_addresses.oracle,
+        gas_pricer,
+        params.searcher_bid_share,
+    );
+    let searcher_addresses: Vec<Address> =
+        searcher_agents.iter().map(|x| x.get_address()).collect();
+
     env.insert_accounts(start_balance, borrow_agents.get_addresses());
     env.insert_accounts(start_balance, liquidation_agents.get_addresses());
     env.insert_accounts(start_balance, uniswap_price_agent.get_addresses());
     env.insert_accounts(start_balance, uniswap_noise_agents.get_addresses());
+    env.insert_accounts(start_balance, searcher_addresses.clone());
 
     // Initialise accounts used to mint dai and dal tokens
     let dai_admin = address_from_hex(DAI_ADMIN);
@@ -210,23 +268,88 @@ pub fn initialise_sim<V: Validator>(
         initial_token_amount,
     );
 
-    let env = deployment::approve_and_mint_weth(
-        env,
-        liquidation_agents.get_addresses(),
-        periphery_addresses.token_a,
-        uniswap_addresses.swap_router,
-        initial_token_amount,
-    );
-
+    // Borrowers repay debt, including accrued interest, in token b
     let env = deployment::approve_and_mint_dai(
         env,
-        liquidation_agents.get_addresses(),
+        borrow_agents.get_addresses(),
         periphery_addresses.token_b,
         dai_admin,
         aave_addresses.pool,
         initial_token_amount,
     );
 
+    // Liquidators hold a finite inventory if their capital is set,
+    // flash loan liquidators hold no inventory
+    let (liquidator_token_a, liquidator_token_b) = match params.liquidator_capital {
+        Some(capital) => (
+            (capital.token_a * 10f64.powi(18)) as u128,
+            (capital.token_b * 10f64.powi(18)) as u128,
+        ),
+        None => (initial_token_amount, initial_token_amount),
+    };
+    let env = match params.flash_loan_liquidation {
+        true => env,
+        false => {
+            let env = deployment::approve_and_mint_weth(
+                env,
+                liquidation_agents.get_addresses(),
+                periphery_addresses.token_a,
+                uniswap_addresses.swap_router,
+                liquidator_token_a,
+            );
+
+            let env = deployment::approve_and_mint_dai(
+                env,
+                liquidation_agents.get_addresses(),
+                periphery_addresses.token_b,
+                dai_admin,
+                aave_addresses.pool,
+                liquidator_token_b,
+            );
+
+            // Liquidators can also repay weth debt, and sell dai collateral,
+            // from the balances already minted
+            let env = deployment::approve_and_mint_weth(
+                env,
+                liquidation_agents.get_addresses(),
+                periphery_addresses.token_a,
+                aave_addresses.pool,
+                0,
+            );
+
+            deployment::approve_and_mint_dai(
+                env,
+                liquidation_agents.get_addresses(),
+                periphery_addresses.token_b,
+                dai_admin,
+                uniswap_addresses.swap_router,
+                0,
+            )
+        }
+    };
+
+    // The executor pulls the inventory used by bundles from liquidators
+    let env = match bundle_executor {
+        Some(executor) => {
+            let env = deployment::approve_and_mint_weth(
+                env,
+                liquidation_agents.get_addresses(),
+                periphery_addresses.token_a,
+                executor,
+                0,
+            );
+            deployment::approve_and_mint_dai(
+                env,
+                liquidation_agents.get_addresses(),
+                periphery_addresses.token_b,
+                dai_admin,
+                executor,
+                0,
+            )
+        }
+        None => env,
+    };
+
     let env = deployment::approve_and_mint_weth(
         env,
         uniswap_price_agent.get_addresses(),
@@ -269,7 +392,7 @@ pub fn initialise_sim<V: Validator>(
         initial_token_amount,
     );
 
-    let mut env = deployment::approve_and_mint_dai(
+    let env = deployment::approve_and_mint_dai(
         env,
         uniswap_noise_agents.get_addresses(),
         periphery_addresses.token_b,
@@ -278,6 +401,25 @@ pub fn initialise_sim<V: Validator>(
         initial_token_amount,
     );
 
+    // Approve Uniswap router contract to use searcher tokens
+    let searcher_capital = (params.searcher_capital * 10f64.powi(18)) as u128;
+    let env = deployment::approve_and_mint_weth(
+        env,
+        searcher_addresses.clone(),
+        periphery_addresses.token_a,
+        uniswap_addresses.swap_router,
+        searcher_capital,
+    );
+
+    let mut env = deployment::approve_and_mint_dai(
+        env,
+        searcher_addresses,
+        periphery_addresses.token_b,
+        dai_admin,
+        uniswap_addresses.swap_router,
+        searcher_capital,
+    );
+
     // Replace chainlink with our price aggregation
     let token_a_aggregator_address: Address = env.deploy_contract(
         admin_address,
@@ -348,6 +490,8 @@ pub fn initialise_sim<V: Validator>(
     )
     .unwrap();
 
+    gas::set_base_fee(&mut env, params.base_fee.initial());
+
     (
         env,
         AgentStates {
@@ -355,6 +499,12 @@ pub fn initialise_sim<V: Validator>(
             liquidation_agents,
             uniswap_price_agent,
             uniswap_noise_agents,
+            liquidity_provider_agents,
+            rate_rebalancer: None,
+            risk_steward: None,
+            searcher_agents,
+            outcomes: Vec::new(),
+            base_fees: Vec::new(),
         },
         periphery_addresses,
         uniswap_addresses,
@@ -0,0 +1,130 @@
+//! Base fee of each block and the cost of gas
+//!
+//! The base fee is set on the block environment of the EVM, and
+//! updated each step by the [BaseFee] process of the simulation.
+//! Transactions are still executed with a zero gas price, so gas is
+//! not charged to the ETH balances of agents. Instead the cost of the
+//! gas used by each transaction at the base fee is recorded in its
+//! outcome, agents deduct the cost of the gas they expect to use
+//! from the expected profit of their trades, and the cost of the gas
+//! they used is deducted from their realised profits. Bids for
+//! priority are recorded and deducted in the same way, as the
+//! priority fee of each transaction.
+//!
+//! Gas is priced in the base currency of the Aave oracle at the price
+//! of the [GasToken] of the simulation, token a by default, i.e. WETH
+//! when running from a fork.
+//!
+//! [BaseFee]: super::types::BaseFee
+//! [GasToken]: super::types::GasToken
+
+use super::calls;
+use super::types::TransactionOutcome;
+use alloy_primitives::{Address, U256};
+use verbs_rs::env::{Env, Validator};
+use verbs_rs::DB;
+
+/// Gas used by a liquidation funded from the inventory of the liquidator
+pub const LIQUIDATION_GAS: u64 = 380_000;
+/// Gas used by a liquidation funded by a flash loan, including the sale
+/// of the collateral and the repayment of the loan
+pub const FLASH_LOAN_LIQUIDATION_GAS: u64 = 570_000;
+/// Gas used by a single pool Uniswap swap
+pub const SWAP_GAS: u64 = 120_000;
+
+/// Base fee of the current block, in wei
+pub fn base_fee<D: DB, V: Validator>(network: &mut Env<D, V>) -> U256 {
+    network.evm_state().context.evm.env.block.basefee
+}
+
+/// Set the base fee of the current block, in wei
+///
+/// The check that transactions pay at least the base fee is disabled,
+/// as transactions are executed with a zero gas price.
+pub fn set_base_fee<D: DB, V: Validator>(network: &mut Env<D, V>, base_fee: U256) {
+    let env = &mut network.evm_state().context.evm.env;
+    env.block.basefee = base_fee;
+    env.cfg.disable_base_fee = true;
+}
+
+/// Cost of an amount of gas at a base fee, in wei
+pub fn gas_cost(gas: u64, base_fee: U256) -> U256 {
+    U256::from(gas) * base_fee
+}
+
+/// Priority fee of a transaction bidding for its position in the block
+///
+/// Bids of liquidators and searchers are in the base currency of the
+/// Aave oracle, and set as the priority fee of their transactions with
+/// the 8 decimals of the base currency, so validators order them in
+/// the same unit. The priority fee is the whole bid of the transaction,
+/// rather than a price per unit of gas.
+pub fn priority_fee(bid: f64) -> U256 {
+    U256::from((bid * 1e8) as u128)
+}
+
+/// Prices gas in the base currency of the Aave oracle
+#[derive(Clone, Copy, Debug)]
+pub struct GasPricer {
+    oracle: Address,
+    /// Token standing for ETH, priced by the oracle
+    native_token: Address,
+}
+
+impl GasPricer {
+    pub fn new(oracle: Address, native_token: Address) -> Self {
+        GasPricer {
+            oracle,
+            native_token,
+        }
+    }
+
+    /// Cost of an amount of gas at the current base fee, in the base
+    /// currency of the oracle (with 8 decimals)
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - Simulation environment
+    /// * `gas` - Amount of gas
+    ///
+    pub fn cost<D, V>(&self, network: &mut Env<D, V>, gas: u64) -> U256
+    where
+        D: DB,
+        V: Validator,
+    {
+        let cost = gas_cost(gas, base_fee(network));
+        self.value(network, cost)
+    }
+
+    /// Fees paid by transactions, the cost of the gas they used at the
+    /// base fee of their blocks and their priority fees, in the base
+    /// currency of the oracle (with 8 decimals)
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - Simulation environment
+    /// * `outcomes` - Outcomes of the transactions
+    ///
+    pub fn fees_paid<D, V>(&self, network: &mut Env<D, V>, outcomes: &[TransactionOutcome]) -> U256
+    where
+        D: DB,
+        V: Validator,
+    {
+        let gas_cost = outcomes.iter().map(|x| x.gas_cost).sum();
+        let priority_fees: U256 = outcomes.iter().map(|x| x.priority_fee).sum();
+        self.value(network, gas_cost) + priority_fees
+    }
+
+    /// Value of an amount of wei in the base currency of the oracle
+    fn value<D, V>(&self, network: &mut Env<D, V>, wei: U256) -> U256
+    where
+        D: DB,
+        V: Validator,
+    {
+        if wei == U256::ZERO {
+            return U256::ZERO;
+        }
+        let price = calls::get_asset_price(network, Address::ZERO, self.oracle, self.native_token);
+        wei * price / U256::from(10).pow(U256::from(18))
+    }
+}
@@ -0,0 +1,119 @@
+//! Index of Aave users with open debt, built from pool events
+//!
+//! Rather than monitoring a fixed list of addresses, agents can
+//! keep a [BorrowerIndex] that follows the `Borrow`, `Repay` and
+//! `LiquidationCall` events emitted by the pool during the
+//! simulation. Any user whose debt may have changed is checked
+//! against the pool, so the index covers positions opened by any
+//! agent. Users with positions that already exist, for example in
+//! a fork, can be added when the index is created.
+//!
+
+use crate::aave::calls;
+use crate::aave::protocol::aave_abi::Pool_Implementation;
+use alloy_primitives::{Address, Log, U256};
+use alloy_sol_types::SolEvent;
+use std::collections::BTreeSet;
+use std::mem;
+use verbs_rs::env::{Env, Validator};
+use verbs_rs::DB;
+
+/// Set of users of an Aave pool with open debt
+#[derive(Clone)]
+pub struct BorrowerIndex {
+    pool_address: Address,
+    /// Number of events in the environment history already indexed
+    cursor: usize,
+    /// Users whose debt has to be checked at the next update
+    pending: BTreeSet<Address>,
+    users: BTreeSet<Address>,
+}
+
+/// User whose debt may have been changed by a pool event
+fn event_user(log: &Log) -> Option<Address> {
+    match log.topics().first().copied()? {
+        Pool_Implementation::Borrow::SIGNATURE_HASH => {
+            Pool_Implementation::Borrow::decode_log(log, true)
+                .ok()
+                .map(|x| x.onBehalfOf)
+        }
+        Pool_Implementation::Repay::SIGNATURE_HASH => {
+            Pool_Implementation::Repay::decode_log(log, true)
+                .ok()
+                .map(|x| x.user)
+        }
+        Pool_Implementation::LiquidationCall::SIGNATURE_HASH => {
+            Pool_Implementation::LiquidationCall::decode_log(log, true)
+                .ok()
+                .map(|x| x.user)
+        }
+        _ => None,
+    }
+}
+
+impl BorrowerIndex {
+    /// Create an index of the users of a pool
+    ///
+    /// # Arguments
+    ///
+    /// * `pool_address` - Address of the Aave pool
+    /// * `known_users` - Users that may already have open debt,
+    ///   they are checked at the first update
+    ///
+    pub fn new(pool_address: Address, known_users: Vec<Address>) -> Self {
+        BorrowerIndex {
+            pool_address,
+            cursor: 0,
+            pending: known_users.into_iter().collect(),
+            users: BTreeSet::new(),
+        }
+    }
+
+    /// Index the events emitted since the last update
+    ///
+    /// Reads the pool events moved into the event history of the
+    /// environment since the last update, and checks the debt of
+    /// each user they refer to, adding or removing them from the
+    /// index.
+    pub fn update<D: DB, V: Validator>(&mut self, network: &mut Env<D, V>) {
+        assert!(
+            self.cursor <= network.event_history.len(),
+            "Borrower index is ahead of the event history"
+        );
+
+        let users = network.event_history[self.cursor..]
+            .iter()
+            .filter(|x| x.success)
+            .flat_map(|x| x.logs.iter())
+            .filter(|x| x.address == self.pool_address)
+            .filter_map(event_user);
+        self.pending.extend(users);
+        self.cursor = network.event_history.len();
+
+        for user in mem::take(&mut self.pending) {
+            let user_data = calls::get_user_data(network, Address::ZERO, self.pool_address, user);
+            if user_data.totalDebtBase > U256::ZERO {
+                self.users.insert(user);
+            } else {
+                self.users.remove(&user);
+            }
+        }
+    }
+
+    /// Users with open debt, in address order
+    pub fn users(&self) -> impl Iterator<Item = &Address> {
+        self.users.iter()
+    }
+
+    pub fn contains(&self, user: &Address) -> bool {
+        self.users.contains(user)
+    }
+
+    pub fn len(&self) -> usize {
+        self.users.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+}
@@ -1,69 +1,101 @@
 use super::agents;
 use super::calls;
-use super::types::SimParameters;
-use super::{deployment, protocol};
+use super::types::{GasToken, LiquidityProfile, SimParameters};
+use super::{deployment, gas, protocol, uniswap_math};
 
 use self::agents::AgentStates;
 use self::protocol::uniswap_abi;
-use alloy_primitives::{I256, U256};
-use verbs_rs::agent::AgentSet;
+use alloy_primitives::{Address, I256, U256};
+use verbs_rs::agent::{Agent, AgentSet};
 use verbs_rs::env::Env;
 use verbs_rs::env::Validator;
 use verbs_rs::utils::address_from_hex;
-use verbs_rs::utils::div_u256;
 use verbs_rs::LocalDB;
 
-use std::collections::HashMap;
-
-/// Initial ticks where Uniswap is initialized and where liquidity is minted
-fn get_init_ticks(params: &SimParameters) -> (i32, i32) {
-    let _tick_spacing: HashMap<u32, f64> =
-        HashMap::from([(100, 1.), (500, 10.), (3000, 60.), (10000, 200.)]);
-    let price: f64 = div_u256(
-        U256::from(params.token_a_initial_price),
-        U256::from(params.token_b_initial_price),
-        10,
-    );
-    let tick = price.log(1.0001).floor();
-    let tick_spacing = _tick_spacing.get(&params.uniswap_fee).copied().unwrap();
-    let tick_lower: f64 = tick - tick.rem_euclid(tick_spacing);
-    let tick_upper: f64 = tick_lower + tick_spacing;
-
-    // We make the tick range larger so that there is liquidity in a wide tick range and there can be tick crossing
-    // TODO: monitor if we should make it larger.
-    let width_tick_range = 2000.;
-    let tick_lower = (tick_lower - width_tick_range * tick_spacing) as i32;
-    let tick_upper = (tick_upper + width_tick_range * tick_spacing) as i32;
-
-    (tick_lower, tick_upper)
-}
-
-/// Get Amount of each token is minted initially minted, according to the CPMM equations
-/// L^2 = xy, P = y/x ==> x = L / sqrt(P), y = L * sqrt(P)
-fn get_init_token_amounts(params: &SimParameters) -> (f64, f64) {
-    let price: f64 = div_u256(
-        U256::from(params.token_a_initial_price),
-        U256::from(params.token_b_initial_price),
-        10,
-    );
-    (
-        params.liquidity / price.sqrt(),
-        params.liquidity * price.sqrt(),
-    )
+/// Positions (tick lower, tick upper, liquidity) minted in the Uniswap pool at initialisation
+///
+/// Liquidity is in raw pool units, with both tokens having 18 decimals.
+///
+/// # Arguments
+///
+/// * `params` - Simulation parameters
+/// * `tick` - Initial tick of the pool
+/// * `tick_spacing` - Tick spacing of the pool
+///
+pub fn get_init_positions(
+    params: &SimParameters,
+    tick: i32,
+    tick_spacing: i32,
+) -> Vec<(i32, i32, u128)> {
+    let to_raw = |liquidity: f64| (liquidity * 10f64.powi(18)) as u128;
+    let max_tick = uniswap_math::MAX_TICK / tick_spacing * tick_spacing;
+    let align =
+        |tick: i32| (tick.div_euclid(tick_spacing) * tick_spacing).clamp(-max_tick, max_tick);
+    // Lowest tick of the tick spacing containing the initial price
+    let tick_lower = align(tick);
+
+    let positions: Vec<(i32, i32, u128)> = match &params.liquidity_profile {
+        LiquidityProfile::Uniform { width } => vec![(
+            align(tick_lower - width * tick_spacing),
+            align(tick_lower + (width + 1) * tick_spacing),
+            to_raw(params.liquidity),
+        )],
+        LiquidityProfile::Gaussian { sigma, n_bands } => {
+            // Width of each band in tick spacings
+            let band_width = (3. * sigma / *n_bands as f64).ceil().max(1.);
+            (-n_bands..*n_bands)
+                .map(|i| {
+                    let offset = i as f64 * band_width;
+                    let mid = offset + 0.5 * band_width;
+                    let liquidity = params.liquidity * (-0.5 * (mid / sigma).powi(2)).exp();
+                    (
+                        align(tick_lower + (offset * tick_spacing as f64) as i32),
+                        align(tick_lower + ((offset + band_width) * tick_spacing as f64) as i32),
+                        to_raw(liquidity),
+                    )
+                })
+                .collect()
+        }
+        LiquidityProfile::Bands(bands) => bands
+            .iter()
+            .map(|band| {
+                assert!(
+                    band.tick_lower % tick_spacing == 0 && band.tick_upper % tick_spacing == 0,
+                    "Liquidity band ticks must be multiples of the tick spacing"
+                );
+                (band.tick_lower, band.tick_upper, to_raw(band.liquidity))
+            })
+            .collect(),
+        LiquidityProfile::RelativeBands(bands) => {
+            let round = |offset: i32| {
+                let spacings = ((tick + offset) as f64 / tick_spacing as f64).round() as i32;
+                align(spacings * tick_spacing)
+            };
+            bands
+                .iter()
+                .map(|band| {
+                    (
+                        round(band.tick_lower),
+                        round(band.tick_upper),
+                        to_raw(band.liquidity),
+                    )
+                })
+                .collect()
+        }
+    };
+
+    positions
+        .into_iter()
+        .filter(|(lower, upper, liquidity)| lower < upper && *liquidity > 0)
+        .collect()
 }
 
 /// get the initial sqrt price for Uniswap pool initialisation
-fn get_sqrt_price_token_a_x96(params: &SimParameters) -> u128 {
-    let n: i32 = 20;
-    let price: f64 = f64::sqrt(div_u256(
-        U256::from(params.token_a_initial_price),
-        U256::from(params.token_b_initial_price),
-        10,
-    )) * 2f64.powi(n);
-    let mut price: u128 = price as u128;
-    // `<<` is a left shift operator, which is equivalent to multiplying times 2^n
-    price <<= 96 - n;
-    price
+pub fn get_sqrt_price_token_a_x96(params: &SimParameters) -> U256 {
+    uniswap_math::price_to_sqrt_price_x96(
+        params.token_a_initial_price as f64 / params.token_b_initial_price as f64,
+    )
+    .expect("Initial price is outside the range of Uniswap prices")
 }
 
 pub fn initialise_sim<V: Validator>(
@@ -90,10 +122,12 @@ pub fn initialise_sim<V: Validator>(
             params.token_b_liquidation_threshold,
             params.token_a_base_ltv,
             params.token_b_base_ltv,
+            &params.e_mode_category,
+            &params.reserve_limits,
             params.uniswap_fee,
         );
 
-    let _token_a_config = calls::get_reserve_configuration_data(
+    let token_a_config = calls::get_reserve_configuration_data(
         &mut env,
         admin_address,
         aave_addresses.data_provider,
@@ -106,33 +140,54 @@ pub fn initialise_sim<V: Validator>(
         periphery_addresses.token_b,
     );
     let token_b_decimals = token_b_config.decimals;
-    let token_b_ltv = token_b_config.ltv;
 
     let borrow_agents = deployment::initialise_borrow_agents(
         params.n_borrowers,
-        params.borrow_activation_rate,
-        token_b_ltv,
-        token_b_decimals,
+        params.borrower_population,
+        &params.borrower_positions,
         periphery_addresses.token_a,
         periphery_addresses.token_b,
+        token_a_config.decimals,
+        token_b_decimals,
         aave_addresses.pool,
         aave_addresses.oracle,
     );
 
-    let borrower_addresses = borrow_agents.get_addresses();
+    // Liquidators discover borrowers from pool events, the mirrored
+    // positions are opened at initialisation so are added directly
+    let known_users: Vec<Address> = params.borrower_positions.iter().map(|x| x.user).collect();
+
+    // Liquidators using their own inventory submit adversarial
+    // liquidations as atomic bundles through an executor contract
+    let bundle_executor = (!params.flash_loan_liquidation)
+        .then(|| deployment::deploy_liquidation_bundle_executor(&mut env, admin_address));
+
+    // Gas is priced at the oracle price of the token standing for ETH
+    let gas_token = match params.gas_token {
+        GasToken::TokenA => periphery_addresses.token_a,
+        GasToken::TokenB => periphery_addresses.token_b,
+    };
+    let gas_pricer = gas::GasPricer::new(aave_addresses.oracle, gas_token);
 
     let liquidation_agents = deployment::initialise_liquidation_agents(
+        &mut env,
+        admin_address,
         params.n_liquidators,
         periphery_addresses.token_a,
         periphery_addresses.token_b,
         aave_addresses.pool,
         aave_addresses.oracle,
-        borrower_addresses,
+        known_users.clone(),
         params.adversarial,
         uniswap_addresses.pool,
         uniswap_addresses.quoter_address,
         uniswap_addresses.swap_router,
         params.uniswap_fee,
+        params.flash_loan_liquidation,
+        bundle_executor,
+        params.liquidator_capital,
+        &params.gas_bid_strategies,
+        gas_pricer,
     );
 
     let uniswap_price_agent = deployment::initialise_uniswap_price_agent(
@@ -146,6 +201,8 @@ pub fn initialise_sim<V: Validator>(
         params.prices_mu,
         params.prices_dt,
         params.prices_sigma,
+        params.price_process,
+        params.gas_token,
     );
 
     // Uniswap noise agent
@@ -158,10 +215,64 @@ pub fn initialise_sim<V: Validator>(
         periphery_addresses.token_b,
     );
 
+    let liquidity_provider_agents = deployment::initialise_liquidity_provider_agents(
+        &mut env,
+        params.n_liquidity_providers,
+        uniswap_addresses.pool,
+        uniswap_addresses.nft_position_manager,
+        params.uniswap_fee,
+        params.liquidity_provider_strategy,
+        (params.liquidity_provider_liquidity * 10f64.powi(18)) as u128,
+    );
+
+    let searcher_agents = deployment::initialise_searcher_agents(
+        &mut env,
+        params.n_searchers,
+        uniswap_addresses.pool,
+        params.uniswap_fee,
+        uniswap_addresses.swap_router,
+        uniswap_addresses.quoter_address,
+        aave_addresses.oracle,
+        gas_pricer,
+        params.searcher_bid_share,
+    );
+    let searcher_addresses: Vec<Address> =
+        searcher_agents.iter().map(|x| x.get_address()).collect();
+
+    let rate_rebalancer = params.stable_rate_rebalancer.then(|| {
+        deployment::initialise_rate_rebalancer(
+            aave_addresses.pool,
+            vec![periphery_addresses.token_a, periphery_addresses.token_b],
+            known_users.clone(),
+        )
+    });
+
+    let risk_steward = params.risk_steward.clone().map(|parameters| {
+        deployment::initialise_risk_steward(
+            &mut env,
+            admin_address,
+            &aave_addresses,
+            vec![periphery_addresses.token_a, periphery_addresses.token_b],
+            parameters,
+            params.prices_dt,
+            known_users,
+        )
+    });
+
     env.insert_accounts(start_balance, borrow_agents.get_addresses());
     env.insert_accounts(start_balance, liquidation_agents.get_addresses());
     env.insert_accounts(start_balance, uniswap_price_agent.get_addresses());
     env.insert_accounts(start_balance, uniswap_noise_agents.get_addresses());
+    env.insert_accounts(start_balance, liquidity_provider_agents.get_addresses());
+    env.insert_accounts(start_balance, searcher_addresses.clone());
+    env.insert_accounts(
+        start_balance,
+        rate_rebalancer.iter().map(|x| x.get_address()).collect(),
+    );
+    env.insert_accounts(
+        start_balance,
+        risk_steward.iter().map(|x| x.get_address()).collect(),
+    );
 
     let env = deployment::approve_and_mint(
         env,
@@ -172,24 +283,93 @@ pub fn initialise_sim<V: Validator>(
         10u128.pow(35),
     );
 
-    // Approve Aave and Uniswap contracts to use liquidator_agents tokens
+    // Borrowers repay debt, including accrued interest, in token b
     let env = deployment::approve_and_mint(
         env,
-        liquidation_agents.get_addresses(),
+        borrow_agents.get_addresses(),
         periphery_addresses.faucet,
         periphery_addresses.token_b,
         aave_addresses.pool,
         10u128.pow(35),
     );
 
-    let env = deployment::approve_and_mint(
-        env,
-        liquidation_agents.get_addresses(),
-        periphery_addresses.faucet,
-        periphery_addresses.token_a,
-        uniswap_addresses.swap_router,
-        10u128.pow(35),
-    );
+    // Liquidators hold a finite inventory if their capital is set,
+    // otherwise effectively unlimited balances
+    let (liquidator_token_a, liquidator_token_b) = match params.liquidator_capital {
+        Some(capital) => (
+            (capital.token_a * 10f64.powi(18)) as u128,
+            (capital.token_b * 10f64.powi(18)) as u128,
+        ),
+        None => (10u128.pow(35), 10u128.pow(35)),
+    };
+
+    // Approve Aave and Uniswap contracts to use liquidator_agents tokens,
+    // flash loan liquidators hold no inventory
+    let env = match params.flash_loan_liquidation {
+        true => env,
+        false => {
+            let env = deployment::approve_and_mint(
+                env,
+                liquidation_agents.get_addresses(),
+                periphery_addresses.faucet,
+                periphery_addresses.token_b,
+                aave_addresses.pool,
+                liquidator_token_b,
+            );
+
+            let env = deployment::approve_and_mint(
+                env,
+                liquidation_agents.get_addresses(),
+                periphery_addresses.faucet,
+                periphery_addresses.token_a,
+                uniswap_addresses.swap_router,
+                liquidator_token_a,
+            );
+
+            // Liquidators can also repay token a debt, and sell token b
+            // collateral, from the balances already minted
+            let env = deployment::approve_and_mint(
+                env,
+                liquidation_agents.get_addresses(),
+                periphery_addresses.faucet,
+                periphery_addresses.token_a,
+                aave_addresses.pool,
+                0,
+            );
+
+            deployment::approve_and_mint(
+                env,
+                liquidation_agents.get_addresses(),
+                periphery_addresses.faucet,
+                periphery_addresses.token_b,
+                uniswap_addresses.swap_router,
+                0,
+            )
+        }
+    };
+
+    // The executor pulls the inventory used by bundles from liquidators
+    let env = match bundle_executor {
+        Some(executor) => {
+            let env = deployment::approve_and_mint(
+                env,
+                liquidation_agents.get_addresses(),
+                periphery_addresses.faucet,
+                periphery_addresses.token_a,
+                executor,
+                0,
+            );
+            deployment::approve_and_mint(
+                env,
+                liquidation_agents.get_addresses(),
+                periphery_addresses.faucet,
+                periphery_addresses.token_b,
+                executor,
+                0,
+            )
+        }
+        None => env,
+    };
 
     // Approve Uniswap contracts to use uniswap_agent tokens
     let env = deployment::approve_and_mint(
@@ -247,13 +427,58 @@ pub fn initialise_sim<V: Validator>(
         10u128.pow(35),
     );
 
+    // Approve Uniswap position manager to use liquidity provider tokens
+    let env = deployment::approve_and_mint(
+        env,
+        liquidity_provider_agents.get_addresses(),
+        periphery_addresses.faucet,
+        periphery_addresses.token_a,
+        uniswap_addresses.nft_position_manager,
+        10u128.pow(35),
+    );
+
+    let env = deployment::approve_and_mint(
+        env,
+        liquidity_provider_agents.get_addresses(),
+        periphery_addresses.faucet,
+        periphery_addresses.token_b,
+        uniswap_addresses.nft_position_manager,
+        10u128.pow(35),
+    );
+
+    // Approve Uniswap router contract to use searcher tokens
+    let searcher_capital = (params.searcher_capital * 10f64.powi(18)) as u128;
+    let env = deployment::approve_and_mint(
+        env,
+        searcher_addresses.clone(),
+        periphery_addresses.faucet,
+        periphery_addresses.token_a,
+        uniswap_addresses.swap_router,
+        searcher_capital,
+    );
+
+    let env = deployment::approve_and_mint(
+        env,
+        searcher_addresses,
+        periphery_addresses.faucet,
+        periphery_addresses.token_b,
+        uniswap_addresses.swap_router,
+        searcher_capital,
+    );
+
+    // Liquidity of token b supplied by the admin fills half of its
+    // supply cap, leaving headroom for other suppliers
+    let token_b_liquidity = match params.reserve_limits.token_b.supply_cap {
+        0 => 10u128.pow(35),
+        cap => u128::from(cap) * 10u128.pow(18) / 2,
+    };
     let mut env = deployment::admin_mint_and_supply(
         env,
         admin_address,
         periphery_addresses.faucet,
         aave_addresses.pool,
         periphery_addresses.token_b,
-        10u128.pow(35),
+        token_b_liquidity,
     );
 
     // Uniswap pool initialisation
@@ -262,39 +487,39 @@ pub fn initialise_sim<V: Validator>(
         admin_address,
         uniswap_addresses.pool,
         uniswap_abi::UniswapV3Pool::initializeCall {
-            sqrtPriceX96: U256::from(sqrt_price_x96),
+            sqrtPriceX96: sqrt_price_x96,
         },
         U256::ZERO,
     )
     .unwrap();
 
-    let (tick_lower, tick_upper) = get_init_ticks(&params);
     let caller = uniswap_price_agent.get_addresses()[0];
-    let _slot0 = calls::get_slot0(&mut env, caller, uniswap_addresses.pool);
-    let _price: u128 = _slot0.sqrtPriceX96.try_into().unwrap();
-    let (init_token_a_amount, init_token_b_amount) = get_init_token_amounts(&params);
-    let token_a_decimals = calls::get_decimals(&mut env, caller, periphery_addresses.token_a);
-    let token_b_decimals = calls::get_decimals(&mut env, caller, periphery_addresses.token_b);
-    let init_token_a_amount =
-        U256::from(init_token_a_amount as i64) * U256::from(10).pow(token_a_decimals);
-    let init_token_b_amount =
-        U256::from(init_token_b_amount as i64) * U256::from(10).pow(token_b_decimals);
-
-    let _output_mint = env
-        .direct_execute(
+    let tick = calls::get_slot0(&mut env, caller, uniswap_addresses.pool).tick;
+    let tick_spacing = calls::get_tick_spacing(&mut env, caller, uniswap_addresses.pool);
+
+    for (tick_lower, tick_upper, liquidity) in get_init_positions(&params, tick, tick_spacing) {
+        let (amount0, amount1) = uniswap_math::get_amounts_for_liquidity(
+            sqrt_price_x96,
+            uniswap_math::get_sqrt_ratio_at_tick(tick_lower).unwrap(),
+            uniswap_math::get_sqrt_ratio_at_tick(tick_upper).unwrap(),
+            liquidity,
+        )
+        .expect("Initial liquidity overflows");
+
+        env.direct_execute(
             caller,
             uniswap_addresses.nft_position_manager,
             uniswap_abi::NonfungiblePositionManager::mintCall {
                 params: uniswap_abi::NonfungiblePositionManager::MintParams {
-                    amount0Desired: init_token_a_amount, //U256::from(10).pow(U256::from(19)),
+                    amount0Desired: amount0,
                     amount0Min: U256::ZERO,
-                    amount1Desired: init_token_b_amount, //U256::from(10).pow(U256::from(22)),
+                    amount1Desired: amount1,
                     amount1Min: U256::ZERO,
                     deadline: U256::MAX,
                     fee: params.uniswap_fee,
                     recipient: caller,
-                    tickLower: tick_lower, //tick_lower,
-                    tickUpper: tick_upper, //tick_upper,
+                    tickLower: tick_lower,
+                    tickUpper: tick_upper,
                     token0: periphery_addresses.token_a,
                     token1: periphery_addresses.token_b,
                 },
@@ -302,6 +527,7 @@ pub fn initialise_sim<V: Validator>(
             U256::ZERO,
         )
         .unwrap();
+    }
 
     // sanity checks for Uniswap deployment and minting
     let token0 = env
@@ -335,6 +561,20 @@ pub fn initialise_sim<V: Validator>(
         I256::try_from(params.token_b_initial_price).unwrap(),
     );
 
+    // Positions are opened once the price of token a is set by the Uniswap pool
+    let mut env = deployment::open_mirrored_positions(
+        env,
+        &params.borrower_positions,
+        aave_addresses.pool,
+        aave_addresses.oracle,
+        periphery_addresses.token_a,
+        periphery_addresses.token_b,
+        token_a_config.ltv,
+        &params.e_mode_category,
+    );
+
+    gas::set_base_fee(&mut env, params.base_fee.initial());
+
     (
         env,
         AgentStates {
@@ -342,6 +582,12 @@ pub fn initialise_sim<V: Validator>(
             liquidation_agents,
             uniswap_price_agent,
             uniswap_noise_agents,
+            liquidity_provider_agents,
+            rate_rebalancer,
+            risk_steward,
+            searcher_agents,
+            outcomes: Vec::new(),
+            base_fees: Vec::new(),
         },
         periphery_addresses,
         uniswap_addresses,
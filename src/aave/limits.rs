@@ -0,0 +1,95 @@
+//! Headroom under the supply and borrow caps of Aave reserves, and
+//! under the debt ceilings of isolated collateral
+//!
+//! Supplies and borrows exceeding these limits revert, so agents size
+//! their transactions to fit within the remaining headroom. As other
+//! transactions in the same block may use the headroom first, agents
+//! should still expect the reverts `SUPPLY_CAP_EXCEEDED`,
+//! `BORROW_CAP_EXCEEDED` and `DEBT_CEILING_EXCEEDED`.
+//!
+//! Caps are in whole tokens, and debt ceilings in the base currency
+//! with 2 decimals. As in the pool, the debt counted against a debt
+//! ceiling is the amount borrowed, so assets borrowable in isolation
+//! are assumed to be stablecoins.
+
+use super::calls;
+use super::types::ReserveConfiguration;
+use alloy_primitives::{Address, U256};
+use verbs_rs::env::{Env, Validator};
+use verbs_rs::DB;
+
+const RAY: u128 = 10u128.pow(27);
+
+fn cap_amount(cap: u64, decimals: u8) -> U256 {
+    U256::from(cap) * U256::from(10u64).pow(U256::from(decimals))
+}
+
+/// Amount of an asset that can still be supplied, `None` if it is uncapped
+pub fn supply_headroom<D, V>(network: &mut Env<D, V>, pool: Address, asset: Address) -> Option<U256>
+where
+    D: DB,
+    V: Validator,
+{
+    let reserve = calls::get_reserve_data(network, Address::ZERO, pool, asset);
+    let configuration = ReserveConfiguration::from_bitmap(reserve.configuration.data);
+    if configuration.supply_cap == 0 {
+        return None;
+    }
+    // Amounts accrued to the treasury count towards the cap
+    let supplied = calls::total_supply(network, reserve.aTokenAddress)
+        + U256::from(reserve.accruedToTreasury) * U256::from(reserve.liquidityIndex)
+            / U256::from(RAY);
+    Some(cap_amount(configuration.supply_cap, configuration.decimals).saturating_sub(supplied))
+}
+
+/// Amount of an asset that can still be borrowed, `None` if it is uncapped
+pub fn borrow_headroom<D, V>(network: &mut Env<D, V>, pool: Address, asset: Address) -> Option<U256>
+where
+    D: DB,
+    V: Validator,
+{
+    let reserve = calls::get_reserve_data(network, Address::ZERO, pool, asset);
+    let configuration = ReserveConfiguration::from_bitmap(reserve.configuration.data);
+    if configuration.borrow_cap == 0 {
+        return None;
+    }
+    let borrowed = calls::total_supply(network, reserve.stableDebtTokenAddress)
+        + calls::total_supply(network, reserve.variableDebtTokenAddress);
+    Some(cap_amount(configuration.borrow_cap, configuration.decimals).saturating_sub(borrowed))
+}
+
+/// Amount of an asset that can still be borrowed against isolated
+/// collateral, `None` if the collateral is not isolated
+///
+/// # Arguments
+///
+/// * `network` - Simulation environment
+/// * `pool` - Address of the Aave pool
+/// * `collateral` - Only collateral asset of the borrower
+/// * `borrow_decimals` - Decimals of the borrowed asset
+///
+pub fn debt_ceiling_headroom<D, V>(
+    network: &mut Env<D, V>,
+    pool: Address,
+    collateral: Address,
+    borrow_decimals: u8,
+) -> Option<U256>
+where
+    D: DB,
+    V: Validator,
+{
+    let reserve = calls::get_reserve_data(network, Address::ZERO, pool, collateral);
+    let configuration = ReserveConfiguration::from_bitmap(reserve.configuration.data);
+    if configuration.debt_ceiling == 0 {
+        return None;
+    }
+    let headroom = configuration
+        .debt_ceiling
+        .saturating_sub(reserve.isolationModeTotalDebt as u64);
+    // The pool truncates borrowed amounts to the 2 decimals of the ceiling
+    let headroom = U256::from(headroom);
+    Some(match borrow_decimals.checked_sub(2) {
+        Some(x) => headroom * U256::from(10u64).pow(U256::from(x)),
+        None => headroom / U256::from(10u64).pow(U256::from(2 - borrow_decimals)),
+    })
+}
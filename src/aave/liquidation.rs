@@ -0,0 +1,364 @@
+//! Sizing of Aave liquidations
+//!
+//! Mirrors the calculation in the `LiquidationLogic` of Aave v3 of the
+//! amount of debt that can be covered in a liquidation, and of the
+//! collateral received for it, so that liquidators can choose the
+//! amount of debt to cover that maximises their profit, rather than
+//! covering as much as possible.
+//!
+//! The close factor limits the share of the debt of a user that can be
+//! covered in one liquidation, to 50% if the health factor of the user
+//! is above [CLOSE_FACTOR_HF_THRESHOLD], and 100% below it. The
+//! collateral received is the value of the covered debt plus the
+//! liquidation bonus of the collateral reserve (or of the e-mode
+//! category of the user, if the collateral is in it), minus the share
+//! of the bonus paid to the protocol.
+//!
+
+use crate::aave::protocol::aave_abi::Pool_Implementation;
+use crate::aave::protocol::periphery_abi::MintableERC20;
+use alloy_primitives::{Address, I256, U256};
+use alloy_sol_types::SolEvent;
+use std::collections::BTreeMap;
+use verbs_rs::contract::Event;
+
+/// Health factor below which the full debt of a user can be covered
+pub const CLOSE_FACTOR_HF_THRESHOLD: u128 = 950_000_000_000_000_000;
+/// Share of the debt of a user that can be covered, in basis points
+pub const DEFAULT_LIQUIDATION_CLOSE_FACTOR: u64 = 5000;
+/// Share of the debt that can be covered below [CLOSE_FACTOR_HF_THRESHOLD]
+pub const MAX_LIQUIDATION_CLOSE_FACTOR: u64 = 10000;
+
+const PERCENTAGE_FACTOR: u64 = 10000;
+// Number of golden section iterations used to find the optimal amount
+const SEARCH_ITERATIONS: usize = 24;
+
+/// Multiply by a percentage in basis points, rounding half up as `PercentageMath`
+fn percent_mul(value: U256, percentage: u64) -> U256 {
+    (value * U256::from(percentage) + U256::from(PERCENTAGE_FACTOR / 2))
+        / U256::from(PERCENTAGE_FACTOR)
+}
+
+/// Divide by a percentage in basis points, rounding half up as `PercentageMath`
+fn percent_div(value: U256, percentage: u64) -> U256 {
+    (value * U256::from(PERCENTAGE_FACTOR) + U256::from(percentage / 2)) / U256::from(percentage)
+}
+
+/// Share of the debt of a user that can be covered in a liquidation, in basis points
+pub fn close_factor(health_factor: U256) -> u64 {
+    match health_factor > U256::from(CLOSE_FACTOR_HF_THRESHOLD) {
+        true => DEFAULT_LIQUIDATION_CLOSE_FACTOR,
+        false => MAX_LIQUIDATION_CLOSE_FACTOR,
+    }
+}
+
+/// Position of a user in a collateral and a debt asset, and the
+/// parameters of their reserves that determine its liquidation
+#[derive(Clone, Copy, Debug)]
+pub struct LiquidationTerms {
+    pub health_factor: U256,
+    /// Debt of the user in the debt asset
+    pub user_debt: U256,
+    /// Collateral of the user in the collateral asset
+    pub user_collateral: U256,
+    /// Price of the debt asset in the base currency
+    pub debt_price: U256,
+    /// Price of the collateral asset in the base currency
+    pub collateral_price: U256,
+    pub debt_decimals: u8,
+    pub collateral_decimals: u8,
+    /// Liquidation bonus of the collateral reserve, or of the e-mode
+    /// category of the user, in basis points
+    pub liquidation_bonus: u64,
+    /// Share of the bonus paid to the protocol, in basis points
+    pub liquidation_protocol_fee: u64,
+    /// Premium paid on the debt covered if it is borrowed with a
+    /// flash loan, in basis points
+    pub flash_loan_premium: u64,
+}
+
+impl LiquidationTerms {
+    /// Value of an amount of collateral in the debt asset
+    fn collateral_to_debt(&self, amount: U256) -> U256 {
+        self.collateral_price * amount * U256::from(10).pow(U256::from(self.debt_decimals))
+            / (self.debt_price * U256::from(10).pow(U256::from(self.collateral_decimals)))
+    }
+
+    /// Value of an amount of the debt asset in collateral
+    fn debt_to_collateral(&self, amount: U256) -> U256 {
+        self.debt_price * amount * U256::from(10).pow(U256::from(self.collateral_decimals))
+            / (self.collateral_price * U256::from(10).pow(U256::from(self.debt_decimals)))
+    }
+
+    /// Largest amount of debt that can be covered
+    ///
+    /// Limited by the close factor, and by the debt that can be
+    /// covered by the collateral of the user (including the bonus).
+    pub fn max_debt_to_cover(&self) -> U256 {
+        let close_factor_limit = percent_mul(self.user_debt, close_factor(self.health_factor));
+        let collateral_limit = percent_div(
+            self.collateral_to_debt(self.user_collateral),
+            self.liquidation_bonus,
+        );
+        close_factor_limit.min(collateral_limit)
+    }
+
+    /// Collateral received by the liquidator and the debt actually
+    /// covered, when covering `debt_to_cover`
+    pub fn collateral_received(&self, debt_to_cover: U256) -> (U256, U256) {
+        let debt_to_cover = debt_to_cover.min(percent_mul(
+            self.user_debt,
+            close_factor(self.health_factor),
+        ));
+        let max_collateral = percent_mul(
+            self.debt_to_collateral(debt_to_cover),
+            self.liquidation_bonus,
+        );
+
+        let (collateral, debt_covered) = match max_collateral > self.user_collateral {
+            true => (
+                self.user_collateral,
+                percent_div(
+                    self.collateral_to_debt(self.user_collateral),
+                    self.liquidation_bonus,
+                ),
+            ),
+            false => (max_collateral, debt_to_cover),
+        };
+
+        let bonus = collateral - percent_div(collateral, self.liquidation_bonus);
+        let protocol_fee = percent_mul(bonus, self.liquidation_protocol_fee);
+
+        (collateral - protocol_fee, debt_covered)
+    }
+
+    /// Value of an amount of the debt asset in the base currency
+    pub fn debt_value(&self, amount: I256) -> I256 {
+        amount * I256::from_raw(self.debt_price)
+            / I256::from_raw(U256::from(10).pow(U256::from(self.debt_decimals)))
+    }
+}
+
+/// Liquidation of a user with the amount of debt to cover that maximises profit
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Liquidation {
+    pub user: Address,
+    pub collateral_asset: Address,
+    pub debt_asset: Address,
+    pub debt_to_cover: U256,
+    /// Collateral received by the liquidator
+    pub collateral_received: U256,
+    /// Expected profit in the base currency of the Aave oracle, net
+    /// of the cost of the gas of the liquidation
+    pub profit: I256,
+    /// Expected cost of the gas of the liquidation at the base fee,
+    /// in the base currency of the Aave oracle
+    pub gas_cost: I256,
+}
+
+/// Amount of debt to cover that maximises the profit of a liquidation
+///
+/// The profit of covering an amount of debt is the value of the
+/// collateral received, when sold for the debt asset, less the debt
+/// covered, and the premium if it is borrowed with a flash loan. The
+/// bonus is linear in the amount covered, and the cost of selling the
+/// collateral (for example the slippage of a swap) grows with it, so
+/// profit is maximised with a golden section search over
+/// amounts up to [LiquidationTerms::max_debt_to_cover]. Partial
+/// liquidations are chosen when the cost of selling the collateral
+/// outweighs the bonus on the full amount.
+///
+/// Returns the amount and the profit in the base currency, if a
+/// profitable amount is found.
+///
+/// # Arguments
+///
+/// * `terms` - Position of the user and parameters of the reserves
+/// * `proceeds` - Amount of the debt asset received for an amount of
+///   collateral, or `None` if it cannot be sold
+///
+pub fn optimal_debt_to_cover<F>(terms: &LiquidationTerms, proceeds: F) -> Option<(U256, I256)>
+where
+    F: FnMut(U256) -> Option<U256>,
+{
+    optimal_debt_to_cover_within(terms, U256::MAX, proceeds)
+}
+
+/// Amount of debt to cover that maximises the profit of a liquidation,
+/// covering at most `budget` of the debt asset
+///
+/// Used by liquidators with a finite inventory, that cannot cover more
+/// debt than they hold. Returns `None` if no profitable amount can be
+/// funded.
+pub fn optimal_debt_to_cover_within<F>(
+    terms: &LiquidationTerms,
+    budget: U256,
+    mut proceeds: F,
+) -> Option<(U256, I256)>
+where
+    F: FnMut(U256) -> Option<U256>,
+{
+    let max_debt = terms.max_debt_to_cover().min(budget);
+    if max_debt == U256::ZERO {
+        return None;
+    }
+
+    let scale = 1u64 << 40;
+    let mut profit = |x: f64| -> (U256, Option<I256>) {
+        let amount = max_debt * U256::from((x * scale as f64) as u64) / U256::from(scale);
+        let (collateral, debt_covered) = terms.collateral_received(amount);
+        let cost = debt_covered + percent_mul(amount, terms.flash_loan_premium);
+        let profit = proceeds(collateral)
+            .map(|x| terms.debt_value(I256::from_raw(x) - I256::from_raw(cost)));
+        (amount, profit)
+    };
+
+    let ratio = (5f64.sqrt() - 1.) / 2.;
+    let (mut a, mut b) = (0f64, 1f64);
+    let mut c = b - ratio * (b - a);
+    let mut d = a + ratio * (b - a);
+    let mut profit_c = profit(c);
+    let mut profit_d = profit(d);
+
+    for _ in 0..SEARCH_ITERATIONS {
+        if profit_c.1 >= profit_d.1 {
+            b = d;
+            d = c;
+            profit_d = profit_c;
+            c = b - ratio * (b - a);
+            profit_c = profit(c);
+        } else {
+            a = c;
+            c = d;
+            profit_c = profit_d;
+            d = a + ratio * (b - a);
+            profit_d = profit(d);
+        }
+    }
+
+    // The profit is often maximised by covering as much as possible
+    [profit_c, profit_d, profit(1.)]
+        .into_iter()
+        .filter_map(|(amount, profit)| profit.map(|x| (amount, x)))
+        .filter(|(amount, profit)| *amount > U256::ZERO && *profit > I256::ZERO)
+        .max_by_key(|(_, profit)| *profit)
+}
+
+/// Upper bound on the profit of an adversarial liquidation bundle
+///
+/// Following [SSRN 4540333](https://papers.ssrn.com/sol3/papers.cfm?abstract_id=4540333),
+/// pushing down the price of the collateral by `price_ratio` (its
+/// price before the manipulation over its price after) raises the
+/// collateral seized for the debt covered by the same ratio. The
+/// collateral seized is valued at the price before the manipulation,
+/// less the share of the bonus paid to the protocol, and the
+/// manipulation is unwound at the cost of the Uniswap fee on the
+/// input of both legs of the round trip. The collateral of the users
+/// is assumed to cover the debt, so the bound is not reached by small
+/// positions.
+///
+/// # Arguments
+///
+/// * `debt_covered` - Value of the debt covered, in the base currency
+/// * `price_ratio` - Price of the collateral before the manipulation
+///   over its price after it
+/// * `liquidation_bonus` - Liquidation bonus of the collateral, in
+///   basis points
+/// * `liquidation_protocol_fee` - Share of the bonus paid to the
+///   protocol, in basis points
+/// * `swap_volume` - Value of the collateral sold to move the price
+///   and of the debt asset received for it, in the base currency
+/// * `uniswap_fee` - Fee of the Uniswap pool, in hundredths of a basis point
+///
+pub fn adversarial_profit_bound(
+    debt_covered: f64,
+    price_ratio: f64,
+    liquidation_bonus: u64,
+    liquidation_protocol_fee: u64,
+    swap_volume: f64,
+    uniswap_fee: u32,
+) -> f64 {
+    let bonus = liquidation_bonus as f64 / PERCENTAGE_FACTOR as f64;
+    let protocol_fee = liquidation_protocol_fee as f64 / PERCENTAGE_FACTOR as f64;
+    let seized = debt_covered * bonus * price_ratio;
+    let received = seized - protocol_fee * (seized - seized / bonus);
+
+    let swap_fees = swap_volume * uniswap_fee as f64 / 1e6;
+
+    received - debt_covered - swap_fees
+}
+
+/// First liquidator of each user liquidated in a block
+///
+/// The liquidator of a liquidation funded by a flash loan is the
+/// initiator of the loan, otherwise the caller of `liquidationCall`.
+///
+/// # Arguments
+///
+/// * `events` - Events of the transactions of the block, in order
+/// * `pool_address` - Address of the Aave pool
+///
+pub fn first_liquidators(events: &[Event], pool_address: Address) -> BTreeMap<Address, Address> {
+    let mut liquidators = BTreeMap::new();
+
+    for event in events {
+        let logs = event.logs.iter().filter(|x| x.address == pool_address);
+        let flash_loan_initiator = logs
+            .clone()
+            .filter_map(|x| Pool_Implementation::FlashLoan::decode_log(x, true).ok())
+            .map(|x| x.initiator)
+            .next();
+
+        for liquidation in
+            logs.filter_map(|x| Pool_Implementation::LiquidationCall::decode_log(x, true).ok())
+        {
+            liquidators
+                .entry(liquidation.user)
+                .or_insert(flash_loan_initiator.unwrap_or(liquidation.liquidator));
+        }
+    }
+
+    liquidators
+}
+
+/// Collateral seized and debt repaid by the liquidations of a bundle
+///
+/// The transaction of the bundle is identified by the transfer of its
+/// proceeds from the executor back to the liquidator. Returns `None`
+/// if no such transaction is found, i.e. the bundle reverted.
+///
+/// # Arguments
+///
+/// * `events` - Events of the transactions of the block, in order
+/// * `pool_address` - Address of the Aave pool
+/// * `executor` - Address of the bundle executor contract
+/// * `liquidator` - Address of the liquidator that submitted the bundle
+///
+pub fn bundle_liquidations(
+    events: &[Event],
+    pool_address: Address,
+    executor: Address,
+    liquidator: Address,
+) -> Option<(U256, U256)> {
+    let event = events.iter().find(|event| {
+        event
+            .logs
+            .iter()
+            .filter_map(|x| MintableERC20::Transfer::decode_log(x, true).ok())
+            .any(|x| x.from == executor && x.to == liquidator)
+    })?;
+
+    Some(
+        event
+            .logs
+            .iter()
+            .filter(|x| x.address == pool_address)
+            .filter_map(|x| Pool_Implementation::LiquidationCall::decode_log(x, true).ok())
+            .fold((U256::ZERO, U256::ZERO), |(collateral, debt), x| {
+                (
+                    collateral + x.liquidatedCollateralAmount,
+                    debt + x.debtToCover,
+                )
+            }),
+    )
+}
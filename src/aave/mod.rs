@@ -1,16 +1,27 @@
-mod agents;
-mod calls;
-mod deployment;
+pub mod agents;
+pub mod branching;
+pub mod calls;
+pub mod deployment;
+pub mod events;
 mod fork_initialisation;
-mod initialisation;
-mod protocol;
+pub mod gas;
+pub mod indexer;
+pub mod initialisation;
+pub mod limits;
+pub mod liquidation;
+pub mod outcomes;
+pub mod protocol;
 pub mod types;
+pub mod uniswap_math;
+pub mod validator;
 
 pub use agents::SimData;
-use verbs_rs::env::GasPriorityValidator;
-use verbs_rs::{agent::RecordedAgentSet, sim_runner};
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
 
+use self::branching::Checkpoint;
 use self::initialisation::initialise_sim;
+use self::validator::PolicyValidator;
 
 pub fn aave_sim_from_fork(
     seed: u64,
@@ -20,33 +31,88 @@ pub fn aave_sim_from_fork(
 ) -> SimData {
     println!("Initialising Simulation");
 
-    let validator = GasPriorityValidator {};
+    let validator = PolicyValidator::from(&params.validator);
+    let base_fee = params.base_fee;
 
     let (mut env, mut agent_sets, _, _, _) =
         fork_initialisation::initialise_sim(params, alchemy_key, validator);
 
     println!("Running");
 
-    sim_runner::run(&mut env, &mut agent_sets, seed, n_steps);
-    SimData {
-        seed,
-        borrow_agents: agent_sets.borrow_agents.take_records(),
-        liquidation_agents: agent_sets.liquidation_agents.take_records(),
-        uniswap_price_agent: agent_sets.uniswap_price_agent.take_records(),
-        uniswap_noise_agents: agent_sets.uniswap_noise_agents.take_records(),
-    }
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(seed);
+    branching::run_steps(&mut env, &mut agent_sets, &mut rng, 0..n_steps, &base_fee);
+    agent_sets.into_sim_data(seed)
 }
 
 pub fn aave_sim(seed: u64, n_steps: usize, params: types::SimParameters) -> SimData {
-    let validator = GasPriorityValidator {};
+    let validator = PolicyValidator::from(&params.validator);
+    let base_fee = params.base_fee;
     let (mut env, mut agent_sets, _, _, _) = initialise_sim(params, validator);
 
-    sim_runner::run(&mut env, &mut agent_sets, seed, n_steps);
-    SimData {
-        seed,
-        borrow_agents: agent_sets.borrow_agents.take_records(),
-        liquidation_agents: agent_sets.liquidation_agents.take_records(),
-        uniswap_price_agent: agent_sets.uniswap_price_agent.take_records(),
-        uniswap_noise_agents: agent_sets.uniswap_noise_agents.take_records(),
-    }
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(seed);
+    branching::run_steps(&mut env, &mut agent_sets, &mut rng, 0..n_steps, &base_fee);
+    agent_sets.into_sim_data(seed)
+}
+
+/// Run paired counterfactual continuations of a simulation
+///
+/// Runs the simulation up to `branch_step`, saves its state, and
+/// then runs a continuation up to `n_steps` for each branch. All
+/// continuations share the same random stream, so for example a
+/// run with and without a price shock can be compared directly.
+/// Returns the simulation data of each continuation, including
+/// the shared steps before the branch.
+///
+/// # Arguments
+///
+/// * `seed` - Random seed
+/// * `n_steps` - Total number of simulation steps
+/// * `branch_step` - Step at which the continuations diverge
+/// * `params` - Simulation parameters
+/// * `branches` - Changes applied to each continuation
+///
+pub fn aave_sim_branched(
+    seed: u64,
+    n_steps: usize,
+    branch_step: usize,
+    params: types::SimParameters,
+    branches: &[types::Branch],
+) -> Vec<SimData> {
+    assert!(
+        branch_step <= n_steps,
+        "Branch step must not be after the end of the simulation"
+    );
+
+    let policy = params.validator.clone();
+    let base_fee = params.base_fee;
+    let validator = PolicyValidator::from(&policy);
+    let (mut env, mut agent_sets, _, _, _) = initialise_sim(params, validator);
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(seed);
+
+    branching::run_steps(
+        &mut env,
+        &mut agent_sets,
+        &mut rng,
+        0..branch_step,
+        &base_fee,
+    );
+    let checkpoint = Checkpoint::save(&mut env, &agent_sets, &rng, branch_step);
+
+    branches
+        .iter()
+        .map(|branch| {
+            let (mut env, mut agent_sets, mut rng) =
+                checkpoint.restore(PolicyValidator::from(&policy));
+            agent_sets.apply_branch(branch);
+
+            branching::run_steps(
+                &mut env,
+                &mut agent_sets,
+                &mut rng,
+                checkpoint.step..n_steps,
+                &base_fee,
+            );
+            agent_sets.into_sim_data(seed)
+        })
+        .collect()
 }
@@ -0,0 +1,290 @@
+//! Outcomes of the transactions processed in each block
+//!
+//! `verbs_rs` records an unchecked transaction that reverts as an
+//! event without logs, discarding why it reverted and the gas it
+//! used. Blocks are processed here in the same way as
+//! [Env::process_transactions], but the result of every transaction
+//! is also returned as a [TransactionOutcome], with the error codes
+//! of the Aave and Uniswap contracts decoded into their names.
+//!
+
+use super::gas;
+use super::types::TransactionOutcome;
+use alloy_primitives::{Bytes, FixedBytes, U256};
+use alloy_sol_types::{decode_revert_reason, Revert, SolError};
+use rand::Rng;
+use revm::primitives::{ExecutionResult, TransactTo, TxEnv};
+use revm::{ContextWithHandlerCfg, Evm, Handler};
+use verbs_rs::contract::{Event, Transaction};
+use verbs_rs::env::{Env, Validator};
+use verbs_rs::DB;
+
+/// Names of the Aave v3 error codes, starting from code 1
+const AAVE_ERRORS: [&str; 91] = [
+    "CALLER_NOT_POOL_ADMIN",
+    "CALLER_NOT_EMERGENCY_ADMIN",
+    "CALLER_NOT_POOL_OR_EMERGENCY_ADMIN",
+    "CALLER_NOT_RISK_OR_POOL_ADMIN",
+    "CALLER_NOT_ASSET_LISTING_OR_POOL_ADMIN",
+    "CALLER_NOT_BRIDGE",
+    "ADDRESSES_PROVIDER_NOT_REGISTERED",
+    "INVALID_ADDRESSES_PROVIDER_ID",
+    "NOT_CONTRACT",
+    "CALLER_NOT_POOL_CONFIGURATOR",
+    "CALLER_NOT_ATOKEN",
+    "INVALID_ADDRESSES_PROVIDER",
+    "INVALID_FLASHLOAN_EXECUTOR_RETURN",
+    "RESERVE_ALREADY_ADDED",
+    "NO_MORE_RESERVES_ALLOWED",
+    "EMODE_CATEGORY_RESERVED",
+    "INVALID_EMODE_CATEGORY_ASSIGNMENT",
+    "RESERVE_LIQUIDITY_NOT_ZERO",
+    "FLASHLOAN_PREMIUM_INVALID",
+    "INVALID_RESERVE_PARAMS",
+    "INVALID_EMODE_CATEGORY_PARAMS",
+    "BRIDGE_PROTOCOL_FEE_INVALID",
+    "CALLER_MUST_BE_POOL",
+    "INVALID_MINT_AMOUNT",
+    "INVALID_BURN_AMOUNT",
+    "INVALID_AMOUNT",
+    "RESERVE_INACTIVE",
+    "RESERVE_FROZEN",
+    "RESERVE_PAUSED",
+    "BORROWING_NOT_ENABLED",
+    "STABLE_BORROWING_NOT_ENABLED",
+    "NOT_ENOUGH_AVAILABLE_USER_BALANCE",
+    "INVALID_INTEREST_RATE_MODE_SELECTED",
+    "COLLATERAL_BALANCE_IS_ZERO",
+    "HEALTH_FACTOR_LOWER_THAN_LIQUIDATION_THRESHOLD",
+    "COLLATERAL_CANNOT_COVER_NEW_BORROW",
+    "COLLATERAL_SAME_AS_BORROWING_CURRENCY",
+    "AMOUNT_BIGGER_THAN_MAX_LOAN_SIZE_STABLE",
+    "NO_DEBT_OF_SELECTED_TYPE",
+    "NO_EXPLICIT_AMOUNT_TO_REPAY_ON_BEHALF",
+    "NO_OUTSTANDING_STABLE_DEBT",
+    "NO_OUTSTANDING_VARIABLE_DEBT",
+    "UNDERLYING_BALANCE_ZERO",
+    "INTEREST_RATE_REBALANCE_CONDITIONS_NOT_MET",
+    "HEALTH_FACTOR_NOT_BELOW_THRESHOLD",
+    "COLLATERAL_CANNOT_BE_LIQUIDATED",
+    "SPECIFIED_CURRENCY_NOT_BORROWED_BY_USER",
+    "SAME_BLOCK_BORROW_REPAY",
+    "INCONSISTENT_FLASHLOAN_PARAMS",
+    "BORROW_CAP_EXCEEDED",
+    "SUPPLY_CAP_EXCEEDED",
+    "UNBACKED_MINT_CAP_EXCEEDED",
+    "DEBT_CEILING_EXCEEDED",
+    "UNDERLYING_CLAIMABLE_RIGHTS_NOT_ZERO",
+    "STABLE_DEBT_NOT_ZERO",
+    "VARIABLE_DEBT_SUPPLY_NOT_ZERO",
+    "LTV_VALIDATION_FAILED",
+    "INCONSISTENT_EMODE_CATEGORY",
+    "PRICE_ORACLE_SENTINEL_CHECK_FAILED",
+    "ASSET_NOT_BORROWABLE_IN_ISOLATION",
+    "RESERVE_ALREADY_INITIALIZED",
+    "USER_IN_ISOLATION_MODE",
+    "INVALID_LTV",
+    "INVALID_LIQ_THRESHOLD",
+    "INVALID_LIQ_BONUS",
+    "INVALID_DECIMALS",
+    "INVALID_RESERVE_FACTOR",
+    "INVALID_BORROW_CAP",
+    "INVALID_SUPPLY_CAP",
+    "INVALID_LIQUIDATION_PROTOCOL_FEE",
+    "INVALID_EMODE_CATEGORY",
+    "INVALID_UNBACKED_MINT_CAP",
+    "INVALID_DEBT_CEILING",
+    "INVALID_RESERVE_INDEX",
+    "ACL_ADMIN_CANNOT_BE_ZERO",
+    "INCONSISTENT_PARAMS_LENGTH",
+    "ZERO_ADDRESS_NOT_VALID",
+    "INVALID_EXPIRATION",
+    "INVALID_SIGNATURE",
+    "OPERATION_NOT_SUPPORTED",
+    "DEBT_CEILING_NOT_ZERO",
+    "ASSET_NOT_LISTED",
+    "INVALID_OPTIMAL_USAGE_RATIO",
+    "INVALID_OPTIMAL_STABLE_TO_TOTAL_DEBT_RATIO",
+    "UNDERLYING_CANNOT_BE_RESCUED",
+    "ADDRESSES_PROVIDER_ALREADY_ADDED",
+    "POOL_ADDRESSES_DO_NOT_MATCH",
+    "STABLE_BORROWING_ENABLED",
+    "SILOED_BORROWING_VIOLATION",
+    "RESERVE_DEBT_NOT_ZERO",
+    "FLASHLOAN_DISABLED",
+];
+
+/// Names of the abbreviated revert strings of the Uniswap v3 pools
+/// and the transfer helper of the periphery contracts
+const UNISWAP_ERRORS: [(&str, &str); 17] = [
+    ("LOK", "POOL_LOCKED"),
+    ("TLU", "LOWER_TICK_NOT_BELOW_UPPER_TICK"),
+    ("TLM", "LOWER_TICK_BELOW_MINIMUM"),
+    ("TUM", "UPPER_TICK_ABOVE_MAXIMUM"),
+    ("AI", "POOL_ALREADY_INITIALIZED"),
+    ("M0", "MINT_TOKEN0_NOT_RECEIVED"),
+    ("M1", "MINT_TOKEN1_NOT_RECEIVED"),
+    ("AS", "AMOUNT_SPECIFIED_ZERO"),
+    ("SPL", "SQRT_PRICE_LIMIT_INVALID"),
+    ("IIA", "INSUFFICIENT_INPUT_AMOUNT"),
+    ("L", "LIQUIDITY_ZERO"),
+    ("F0", "FLASH_TOKEN0_NOT_REPAID"),
+    ("F1", "FLASH_TOKEN1_NOT_REPAID"),
+    ("STF", "TRANSFER_FROM_FAILED"),
+    ("ST", "TRANSFER_FAILED"),
+    ("SA", "APPROVE_FAILED"),
+    ("STE", "ETH_TRANSFER_FAILED"),
+];
+
+/// Decode the output of a reverted transaction
+///
+/// Aave reverts with its numeric error codes, and the Uniswap pools
+/// with abbreviated strings, which are replaced by their names. Any
+/// other reason, like the slippage checks of the swap router, is
+/// returned unchanged, and `None` if the transaction reverted
+/// without a reason.
+///
+/// # Arguments
+///
+/// * `output` - Output of the reverted transaction
+///
+pub fn decode_error(output: &[u8]) -> Option<String> {
+    let Ok(revert) = Revert::abi_decode(output, true) else {
+        return decode_revert_reason(output).filter(|x| !x.is_empty());
+    };
+    let reason = revert.reason();
+
+    let aave_error = reason
+        .parse::<usize>()
+        .ok()
+        .and_then(|x| x.checked_sub(1))
+        .and_then(|x| AAVE_ERRORS.get(x));
+    let uniswap_error = UNISWAP_ERRORS
+        .iter()
+        .find(|(code, _)| *code == reason)
+        .map(|(_, name)| name);
+
+    Some(
+        aave_error
+            .or(uniswap_error)
+            .map_or(reason.to_string(), |x| x.to_string()),
+    )
+}
+
+/// Execute a transaction, returning its outcome
+fn execute<D: DB>(
+    evm: &mut Evm<'_, (), D>,
+    transaction: Transaction,
+    step: usize,
+    sequence: usize,
+) -> TransactionOutcome {
+    evm.context.evm.env.tx = TxEnv {
+        caller: transaction.callee,
+        gas_limit: u64::MAX,
+        gas_price: Default::default(),
+        gas_priority_fee: None,
+        transact_to: TransactTo::Call(transaction.transact_to),
+        value: transaction.value,
+        data: Bytes::from(transaction.args),
+        chain_id: None,
+        nonce: None,
+        access_list: Vec::new(),
+        blob_hashes: Vec::new(),
+        max_fee_per_blob_gas: None,
+    };
+
+    let result = match evm.transact_commit() {
+        Ok(result) => result,
+        Err(e) => panic!("Call failed: {:?}", e),
+    };
+    let gas_used = result.gas_used();
+    // Bids are paid whether or not the transaction reverted
+    let priority_fee = transaction.gas_priority_fee.unwrap_or(U256::ZERO);
+    let gas_cost = gas::gas_cost(gas_used, evm.context.evm.env.block.basefee);
+
+    let (success, error, logs) = match result {
+        ExecutionResult::Success { logs, .. } => (true, None, logs),
+        ExecutionResult::Revert { output, .. } => {
+            let error = decode_error(&output);
+            if transaction.checked {
+                panic!(
+                    "Failed to call {:?} from {} due to revert: {:?}",
+                    transaction.function_selector, transaction.callee, error
+                )
+            }
+            (false, error, Vec::new())
+        }
+        ExecutionResult::Halt { reason, .. } => panic!(
+            "Failed to call {:?} from {} due to halt: {:?}",
+            transaction.function_selector, transaction.callee, reason
+        ),
+    };
+
+    TransactionOutcome {
+        step,
+        sequence,
+        sender: transaction.callee,
+        contract: transaction.transact_to,
+        function_selector: FixedBytes(transaction.function_selector),
+        success,
+        error,
+        gas_used,
+        gas_cost,
+        priority_fee,
+        logs,
+    }
+}
+
+/// Process a block of transactions, returning their outcomes
+///
+/// Orders and executes the transactions as
+/// [Env::process_transactions], adding their events to the last
+/// events of the environment. Reverted transactions are recorded
+/// as unsuccessful events.
+///
+/// # Arguments
+///
+/// * `env` - Simulation environment
+/// * `transactions` - Transactions submitted in the block
+/// * `rng` - Random generator
+/// * `step` - Simulation step
+///
+/// # Panics
+///
+/// Panics if a transaction halts, or a checked transaction reverts.
+///
+pub fn process_block<D, V, R>(
+    env: &mut Env<D, V>,
+    transactions: Vec<Transaction>,
+    rng: &mut R,
+    step: usize,
+) -> Vec<TransactionOutcome>
+where
+    D: DB,
+    V: Validator,
+    R: Rng,
+{
+    let transactions = env.validator.order_transactions(rng, transactions);
+
+    let ContextWithHandlerCfg { context, cfg } = env.evm_state.take().expect("No EVM state set");
+    let mut evm = Evm {
+        context,
+        handler: Handler::new(cfg),
+    };
+
+    let outcomes: Vec<TransactionOutcome> = transactions
+        .into_iter()
+        .enumerate()
+        .map(|(i, transaction)| execute(&mut evm, transaction, step, i))
+        .collect();
+    env.evm_state = Some(evm.into_context_with_handler_cfg());
+
+    env.last_events.extend(outcomes.iter().map(|x| Event {
+        success: x.success,
+        function_selector: x.function_selector.0,
+        logs: x.logs.clone(),
+        step,
+        sequence: x.sequence,
+    }));
+    outcomes
+}
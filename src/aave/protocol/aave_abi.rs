@@ -13,11 +13,19 @@ sol!(
 sol!(EModeLogic, "contracts/aave/EModeLogic.abi");
 sol!(EmissionManager, "contracts/aave/EmissionManager.abi");
 sol!(FlashLoanLogic, "contracts/aave/FlashLoanLogic.abi");
+sol!(
+    FlashLiquidationReceiver,
+    "contracts/aave/FlashLiquidationReceiver.abi"
+);
 sol!(IncentivesProxy, "contracts/aave/IncentivesProxy.abi");
 sol!(
     IncentivesV2_Implementation,
     "contracts/aave/IncentivesV2-Implementation.abi"
 );
+sol!(
+    LiquidationBundleExecutor,
+    "contracts/aave/LiquidationBundleExecutor.abi"
+);
 sol!(LiquidationLogic, "contracts/aave/LiquidationLogic.abi");
 sol!(MockAggregator, "contracts/aave/MockAggregator.abi");
 sol!(
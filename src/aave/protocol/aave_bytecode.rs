@@ -20,10 +20,16 @@ pub const EMISSION_MANAGER: &str = "60806040523480156200001157600080fd5b50604051
 
 pub const FLASHLOAN_LOGIC: &str = "612b3361003a600b82828239805160001a60731461002d57634e487b7160e01b600052600060045260246000fd5b30600052607381538281f3fe73000000000000000000000000000000000000000030146080604052600436106100405760003560e01c80632e7263ea14610045578063a1fe0e8d14610067575b600080fd5b81801561005157600080fd5b5061006561006036600461229a565b610087565b005b81801561007357600080fd5b50610065610082366004612437565b610928565b61009a8582602001518360400151610b94565b6101066040518060e00160405280600073ffffffffffffffffffffffffffffffffffffffff16815260200160008152602001600073ffffffffffffffffffffffffffffffffffffffff168152602001600081526020016060815260200160008152602001600081525090565b81602001515167ffffffffffffffff81111561012457610124611fe2565b60405190808252806020026020018201604052801561014d578160200160208202803683370190505b506080820152815173ffffffffffffffffffffffffffffffffffffffff1681526101a0820151610187578161010001518260e0015161018b565b6000805b60c083015260a0820152600060208201525b816020015151816020015110156102fb5781604001518160200151815181106101c8576101c8612501565b60209081029190910101516060820181905260a08201516101e99190610c85565b816080015182602001518151811061020357610203612501565b602002602001018181525050856000836020015183602001518151811061022c5761022c612501565b60209081029190910181015173ffffffffffffffffffffffffffffffffffffffff90811683529082019290925260409081016000206004908101548551606086015193517f4efecaa5000000000000000000000000000000000000000000000000000000008152908516928101929092526024820192909252911690634efecaa590604401600060405180830381600087803b1580156102cb57600080fd5b505af11580156102df573d6000803e3d6000fd5b50505060208201805191506102f38261255f565b90525061019d565b806000015173ffffffffffffffffffffffffffffffffffffffff1663920f5c84836020015184604001518460800151338760a001516040518663ffffffff1660e01b815260040161035095949392919061263e565b6020604051808303816000875af115801561036f573d6000803e3d6000fd5b505050506040513d601f19601f8201168201806040525081019061039391906126f2565b6040518060400160405280600281526020017f31330000000000000000000000000000000000000000000000000000000000008152509061040a576040517f08c379a0000000000000000000000000000000000000000000000000000000008152600401610401919061270f565b60405180910390fd5b50600060208201525b8160200151518160200151101561092057816020015181602001518151811061043e5761043e612501565b6020026020010151816040019073ffffffffffffffffffffffffffffffffffffffff16908173ffffffffffffffffffffffffffffffffffffffff1681525050816040015181602001518151811061049757610497612501565b60209081029190910101516060820152600082606001518260200151815181106104c3576104c3612501565b602002602001015160028111156104dc576104dc612722565b60028111156104ed576104ed612722565b14156105d4576105cf866000836040015173ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff1681526020019081526020016000206040518060c0016040528084606001518152602001846080015185602001518151811061056757610567612501565b602002602001015181526020018460c001518152602001846040015173ffffffffffffffffffffffffffffffffffffffff168152602001856000015173ffffffffffffffffffffffffffffffffffffffff1681526020018560c0015161ffff16815250610cc8565b610908565b73Cf7Ed3AccA5a467e9e704C703E8D87F634fB0Fc9631e6473f987878787604051806101800160405280886040015173ffffffffffffffffffffffffffffffffffffffff1681526020013373ffffffffffffffffffffffffffffffffffffffff168152602001896080015173ffffffffffffffffffffffffffffffffffffffff16815260200188606001518152602001896060015189602001518151811061067e5761067e612501565b6020026020010151600281111561069757610697612722565b60028111156106a8576106a8612722565b81526020018960c0015161ffff1681526020016000151581526020018961012001518152602001896101400151815260200189610160015173ffffffffffffffffffffffffffffffffffffffff1663fca513a86040518163ffffffff1660e01b8152600401602060405180830381865afa15801561072a573d6000803e3d6000fd5b505050506040513d601f19601f8201168201806040525081019061074e9190612751565b73ffffffffffffffffffffffffffffffffffffffff16815260200189610180015160ff16815260200189610160015173ffffffffffffffffffffffffffffffffffffffff16635eb88d3d6040518163ffffffff1660e01b8152600401602060405180830381865afa1580156107c7573d6000803e3d6000fd5b505050506040513d601f19601f820116820180604052508101906107eb9190612751565b73ffffffffffffffffffffffffffffffffffffffff168152506040518663ffffffff1660e01b81526004016108249594939291906127a9565b60006040518083038186803b15801561083c57600080fd5b505af4158015610850573d6000803e3d6000fd5b505050508160c0015161ffff16816040015173ffffffffffffffffffffffffffffffffffffffff16836000015173ffffffffffffffffffffffffffffffffffffffff167fefefaba5e921573100900a3ad9cf29f222d995fb3b6045797eaea7521bd8d6f033856060015187606001518760200151815181106108d4576108d4612501565b602002602001015160028111156108ed576108ed612722565b60006040516108ff94939291906128d1565b60405180910390a45b602081018051906109188261255f565b905250610413565b505050505050565b61093182610fdc565b805160c0820151604083015160009161094a9190610c85565b600480860154855160408088015190517f4efecaa500000000000000000000000000000000000000000000000000000000815294955073ffffffffffffffffffffffffffffffffffffffff90921693634efecaa5936109cb93910173ffffffffffffffffffffffffffffffffffffffff929092168252602082015260400190565b600060405180830381600087803b1580156109e557600080fd5b505af11580156109f9573d6000803e3d6000fd5b505050506020830151604080850151606086015191517f1b11d0ff00000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff861693631b11d0ff93610a6293919287913391600401612911565b6020604051808303816000875af1158015610a81573d6000803e3d6000fd5b505050506040513d601f19601f82011682018060405250810190610aa591906126f2565b6040518060400160405280600281526020017f313300000000000000000000000000000000000000000000000000000000000081525090610b13576040517f08c379a0000000000000000000000000000000000000000000000000000000008152600401610401919061270f565b50610b8e846040518060c00160405280866040015181526020018481526020018660a001518152602001866020015173ffffffffffffffffffffffffffffffffffffffff168152602001866000015173ffffffffffffffffffffffffffffffffffffffff168152602001866080015161ffff16815250610cc8565b50505050565b80518251146040518060400160405280600281526020017f343900000000000000000000000000000000000000000000000000000000000081525090610c07576040517f08c379a0000000000000000000000000000000000000000000000000000000008152600401610401919061270f565b5060005b8251811015610b8e57610c73846000858481518110610c2c57610c2c612501565b602002602001015173ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff168152602001908152602001600020610fdc565b80610c7d8161255f565b915050610c0b565b600081157fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffec7783900484111517610cba57600080fd5b506127109102611388010490565b6000610ce582604001518360200151610c8590919063ffffffff16565b90506000818360200151610cf99190612961565b9050600083602001518460000151610d119190612978565b90506000610d1e86611166565b9050610d2a868261137f565b6101008101516008870154610ddb91610d55916fffffffffffffffffffffffffffffffff169061140a565b826101e0015173ffffffffffffffffffffffffffffffffffffffff166318160ddd6040518163ffffffff1660e01b8152600401602060405180830381865afa158015610da5573d6000803e3d6000fd5b505050506040513d601f19601f82011682018060405250810190610dc99190612990565b610dd39190612978565b879085611461565b6101008201819052610df790610df2908690611511565b611550565b600887018054600090610e1d9084906fffffffffffffffffffffffffffffffff166129a9565b92506101000a8154816fffffffffffffffffffffffffffffffff02191690836fffffffffffffffffffffffffffffffff160217905550610e718186606001518460008a6115f690949392919063ffffffff16565b60808501516101e08201516060870151610ea49273ffffffffffffffffffffffffffffffffffffffff9091169185611937565b6101e081015160808601516040517f6fd9767600000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff91821660048201819052602482015260448101859052911690636fd9767690606401600060405180830381600087803b158015610f2657600080fd5b505af1158015610f3a573d6000803e3d6000fd5b505050508460a0015161ffff16856060015173ffffffffffffffffffffffffffffffffffffffff16866080015173ffffffffffffffffffffffffffffffffffffffff167fefefaba5e921573100900a3ad9cf29f222d995fb3b6045797eaea7521bd8d6f033896000015160006002811115610fb757610fb7612722565b8b60200151604051610fcc94939291906128d1565b60405180910390a4505050505050565b60408051602081019091528154808252671000000000000000161515156040518060400160405280600281526020017f323900000000000000000000000000000000000000000000000000000000000081525090611067576040517f08c379a0000000000000000000000000000000000000000000000000000000008152600401610401919061270f565b5080516701000000000000001615156040518060400160405280600281526020017f3237000000000000000000000000000000000000000000000000000000000000815250906110e4576040517f08c379a0000000000000000000000000000000000000000000000000000000008152600401610401919061270f565b5080516780000000000000001615156040518060400160405280600281526020017f393100000000000000000000000000000000000000000000000000000000000081525090611161576040517f08c379a0000000000000000000000000000000000000000000000000000000008152600401610401919061270f565b505050565b61116e611f35565b611176611f35565b60408051602081018252845481526101c0830181905251901c61ffff166101a082015260018301546fffffffffffffffffffffffffffffffff808216610100840181905260e0840152600285015480821661014085018190526101208501527001000000000000000000000000000000009283900482166101608501528290041661018083015260048085015473ffffffffffffffffffffffffffffffffffffffff9081166101e085015260058601548116610200850152600686015416610220840181905260038601549290920464ffffffffff16610240840152604080517fb1bf962d000000000000000000000000000000000000000000000000000000008152905163b1bf962d928281019260209291908290030181865afa1580156112a3573d6000803e3d6000fd5b505050506040513d601f19601f820116820180604052508101906112c79190612990565b816020018181525081600001818152505080610200015173ffffffffffffffffffffffffffffffffffffffff1663797743386040518163ffffffff1660e01b8152600401608060405180830381865afa158015611328573d6000803e3d6000fd5b505050506040513d601f19601f8201168201806040525081019061134c91906129dd565b64ffffffffff166102608501526060840181905260808401829052604084019290925260c083015260a082015292915050565b60038201544264ffffffffff9081167001000000000000000000000000000000009092041614156113ae575050565b6113b88282611a19565b6113c28282611b3a565b5060030180547fffffffffffffffffffffff0000000000ffffffffffffffffffffffffffffffff167001000000000000000000000000000000004264ffffffffff1602179055565b600081157ffffffffffffffffffffffffffffffffffffffffffe6268e1b017bfe18bffffff8390048411151761143f57600080fd5b506b033b2e3c9fd0803ce800000091026b019d971e4fe8401e74000000010490565b600183015460009081906114b9906fffffffffffffffffffffffffffffffff166b033b2e3c9fd0803ce80000006114a961149a88611cb9565b6114a388611cb9565b90611511565b6114b39190612978565b9061140a565b90506114c481611550565b6001860180547fffffffffffffffffffffffffffffffff00000000000000000000000000000000166fffffffffffffffffffffffffffffffff9290921691909117905590505b9392505050565b600081156b033b2e3c9fd0803ce80000006002840419048411171561153557600080fd5b506b033b2e3c9fd0803ce80000009190910260028204010490565b60006fffffffffffffffffffffffffffffffff8211156115f2576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152602760248201527f53616665436173743a2076616c756520646f65736e27742066697420696e203160448201527f32382062697473000000000000000000000000000000000000000000000000006064820152608401610401565b5090565b6116216040518060800160405280600081526020016000815260200160008152602001600081525090565b61014085015160208601516116359161140a565b60608083019182526007880154604080516101208101825260088b01546fffffffffffffffffffffffffffffffff7001000000000000000000000000000000009091041681526020810188905280820187905260c0808b0151948201949094529351608085015260a0808a0151908501526101a08901519284019290925273ffffffffffffffffffffffffffffffffffffffff87811660e08501526101e0890151811661010085015291517fa589870900000000000000000000000000000000000000000000000000000000815291169163a5898709916117969190600401600061012082019050825182526020830151602083015260408301516040830152606083015160608301526080830151608083015260a083015160a083015260c083015160c083015260e083015173ffffffffffffffffffffffffffffffffffffffff80821660e0850152610100915080828601511682850152505092915050565b606060405180830381865afa1580156117b3573d6000803e3d6000fd5b505050506040513d601f19601f820116820180604052508101906117d79190612a28565b604084015260208301528082526117ed90611550565b6001870180546fffffffffffffffffffffffffffffffff928316700100000000000000000000000000000000029216919091179055602081015161183090611550565b6003870180547fffffffffffffffffffffffffffffffff00000000000000000000000000000000166fffffffffffffffffffffffffffffffff92909216919091179055604081015161188190611550565b6002870180546fffffffffffffffffffffffffffffffff92831670010000000000000000000000000000000002921691909117905580516020808301516040808501516101008a01516101408b0151835196875294860193909352908401526060830152608082015273ffffffffffffffffffffffffffffffffffffffff8516907f804c9b842b2748a22bb64b345453a3de7ca54a6ca45ce00d415894979e22897a9060a00160405180910390a2505050505050565b6040517f23b872dd0000000000000000000000000000000000000000000000000000000080825273ffffffffffffffffffffffffffffffffffffffff8581166004840152841660248301526044820183905290600080606483828a5af16119a2573d6000803e3d6000fd5b506119ac85611cd4565b611a12576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601960248201527f475076323a206661696c6564207472616e7366657246726f6d000000000000006044820152606401610401565b5050505050565b61016081015115611aa9576000611a3a826101600151836102400151611da0565b9050611a538260e001518261140a90919063ffffffff16565b6101008301819052611a6490611550565b6001840180547fffffffffffffffffffffffffffffffff00000000000000000000000000000000166fffffffffffffffffffffffffffffffff92909216919091179055505b805115611b36576000611ac6826101800151836102400151611de5565b9050611ae08261012001518261140a90919063ffffffff16565b6101408301819052611af190611550565b6002840180547fffffffffffffffffffffffffffffffff00000000000000000000000000000000166fffffffffffffffffffffffffffffffff92909216919091179055505b5050565b611b736040518060c001604052806000815260200160008152602001600081526020016000815260200160008152602001600081525090565b6101a0820151611b8257505050565b6101208201518251611b939161140a565b60208201526101408201518251611ba99161140a565b60408201526060820151610260830151610240840151611bd192919064ffffffffff16611dee565b606082018190526040830151611be69161140a565b808252602082015160808401516040840151611c029190612978565b611c0c9190612961565b611c169190612961565b608082018190526101a0830151611c2d9190610c85565b60a082018190521561116157611c58610df28361010001518360a0015161151190919063ffffffff16565b600884018054600090611c7e9084906fffffffffffffffffffffffffffffffff166129a9565b92506101000a8154816fffffffffffffffffffffffffffffffff02191690836fffffffffffffffffffffffffffffffff160217905550505050565b633b9aca008181029081048214611ccf57600080fd5b919050565b6000611d14565b7f08c379a00000000000000000000000000000000000000000000000000000000060005260206004528060245250806044525060646000fd5b3d8015611d535760208114611d8d57611d4e7f475076323a206d616c666f726d6564207472616e7366657220726573756c7400601f611cdb565b611d9a565b823b611d8457611d847f475076323a206e6f74206120636f6e74726163740000000000000000000000006014611cdb565b60019150611d9a565b3d6000803e600051151591505b50919050565b600080611db464ffffffffff841642612961565b611dbe9085612a56565b6301e1338090049050611ddd816b033b2e3c9fd0803ce8000000612978565b949350505050565b600061150a8383425b600080611e0264ffffffffff851684612961565b905080611e1e576b033b2e3c9fd0803ce800000091505061150a565b7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff81016000808060028511611e54576000611e59565b600285035b925066038882915c4000611e6d8a8061140a565b81611e7a57611e7a612a93565b0491506301e13380611e8c838b61140a565b81611e9957611e99612a93565b049050600082611ea98688612a56565b611eb39190612a56565b60029004905060008285611ec7888a612a56565b611ed19190612a56565b611edb9190612a56565b60069004905080826301e13380611ef28a8f612a56565b611efc9190612ac2565b611f12906b033b2e3c9fd0803ce8000000612978565b611f1c9190612978565b611f269190612978565b9b9a5050505050505050505050565b6040518061028001604052806000815260200160008152602001600081526020016000815260200160008152602001600081526020016000815260200160008152602001600081526020016000815260200160008152602001600081526020016000815260200160008152602001611fb96040518060200160405280600081525090565b815260006020820181905260408201819052606082018190526080820181905260a09091015290565b7f4e487b7100000000000000000000000000000000000000000000000000000000600052604160045260246000fd5b6040516101c0810167ffffffffffffffff8111828210171561203557612035611fe2565b60405290565b60405160e0810167ffffffffffffffff8111828210171561203557612035611fe2565b604051601f82017fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe016810167ffffffffffffffff811182821017156120a5576120a5611fe2565b604052919050565b73ffffffffffffffffffffffffffffffffffffffff811681146120cf57600080fd5b50565b8035611ccf816120ad565b600067ffffffffffffffff8211156120f7576120f7611fe2565b5060051b60200190565b600082601f83011261211257600080fd5b81356020612127612122836120dd565b61205e565b82815260059290921b8401810191818101908684111561214657600080fd5b8286015b8481101561216a57803561215d816120ad565b835291830191830161214a565b509695505050505050565b600082601f83011261218657600080fd5b81356020612196612122836120dd565b82815260059290921b840181019181810190868411156121b557600080fd5b8286015b8481101561216a57803583529183019183016121b9565b600082601f8301126121e157600080fd5b813567ffffffffffffffff8111156121fb576121fb611fe2565b61222c60207fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe0601f8401160161205e565b81815284602083860101111561224157600080fd5b816020850160208301376000918101602001919091529392505050565b803561ffff81168114611ccf57600080fd5b803560ff81168114611ccf57600080fd5b80151581146120cf57600080fd5b8035611ccf81612281565b600080600080600060a086880312156122b257600080fd5b85359450602086013593506040860135925060608601359150608086013567ffffffffffffffff808211156122e657600080fd5b908701906101c0828a0312156122fb57600080fd5b612303612011565b61230c836120d2565b815260208301358281111561232057600080fd5b61232c8b828601612101565b60208301525060408301358281111561234457600080fd5b6123508b828601612175565b60408301525060608301358281111561236857600080fd5b6123748b828601612175565b606083015250612386608084016120d2565b608082015260a08301358281111561239d57600080fd5b6123a98b8286016121d0565b60a0830152506123bb60c0840161225e565b60c082015260e0838101359082015261010080840135908201526101208084013590820152610140808401359082015261016091506123fb8284016120d2565b82820152610180915061240f828401612270565b828201526101a0915061242382840161228f565b828201528093505050509295509295909350565b6000806040838503121561244a57600080fd5b82359150602083013567ffffffffffffffff8082111561246957600080fd5b9084019060e0828703121561247d57600080fd5b61248561203b565b61248e836120d2565b815261249c602084016120d2565b6020820152604083013560408201526060830135828111156124bd57600080fd5b6124c9888286016121d0565b6060830152506124db6080840161225e565b608082015260a083013560a082015260c083013560c08201528093505050509250929050565b7f4e487b7100000000000000000000000000000000000000000000000000000000600052603260045260246000fd5b7f4e487b7100000000000000000000000000000000000000000000000000000000600052601160045260246000fd5b60007fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff82141561259157612591612530565b5060010190565b600081518084526020808501945080840160005b838110156125c8578151875295820195908201906001016125ac565b509495945050505050565b6000815180845260005b818110156125f9576020818501810151868301820152016125dd565b8181111561260b576000602083870101525b50601f017fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe0169290920160200192915050565b60a0808252865190820181905260009060209060c0840190828a01845b8281101561268d57815173ffffffffffffffffffffffffffffffffffffffff168452928401929084019060010161265b565b505050838103828501526126a18189612598565b91505082810360408401526126b68187612598565b905073ffffffffffffffffffffffffffffffffffffffff8516606084015282810360808401526126e681856125d3565b98975050505050505050565b60006020828403121561270457600080fd5b815161150a81612281565b60208152600061150a60208301846125d3565b7f4e487b7100000000000000000000000000000000000000000000000000000000600052602160045260246000fd5b60006020828403121561276357600080fd5b815161150a816120ad565b600381106127a5577f4e487b7100000000000000000000000000000000000000000000000000000000600052602160045260246000fd5b9052565b858152602081018590526040810184905260608101839052815173ffffffffffffffffffffffffffffffffffffffff1660808201526102008101602083015173ffffffffffffffffffffffffffffffffffffffff811660a084015250604083015173ffffffffffffffffffffffffffffffffffffffff811660c084015250606083015160e083015260808301516101006128458185018361276e565b60a0850151915061012061285e8186018461ffff169052565b60c086015192506101406128758187018515159052565b60e087015161016087810191909152928701516101808701529086015173ffffffffffffffffffffffffffffffffffffffff9081166101a08701529086015160ff166101c0860152908501519081166101e0850152905061216a565b73ffffffffffffffffffffffffffffffffffffffff851681526020810184905260808101612902604083018561276e565b82606083015295945050505050565b600073ffffffffffffffffffffffffffffffffffffffff808816835286602084015285604084015280851660608401525060a0608083015261295660a08301846125d3565b979650505050505050565b60008282101561297357612973612530565b500390565b6000821982111561298b5761298b612530565b500190565b6000602082840312156129a257600080fd5b5051919050565b60006fffffffffffffffffffffffffffffffff8083168185168083038211156129d4576129d4612530565b01949350505050565b600080600080608085870312156129f357600080fd5b845193506020850151925060408501519150606085015164ffffffffff81168114612a1d57600080fd5b939692955090935050565b600080600060608486031215612a3d57600080fd5b8351925060208401519150604084015190509250925092565b6000817fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0483118215151615612a8e57612a8e612530565b500290565b7f4e487b7100000000000000000000000000000000000000000000000000000000600052601260045260246000fd5b600082612af8577f4e487b7100000000000000000000000000000000000000000000000000000000600052601260045260246000fd5b50049056fea2646970667358221220f6b7aec5628e155472d0b3e77612f92843547f24ef80b5a1076bf15b7acbcdba64736f6c634300080a0033";
 
+// Assembled by hand from contracts/aave/FlashLiquidationReceiver.sol
+pub const FLASH_LIQUIDATION_RECEIVER: &str = "61020f8061002460003960406040380382398051610017528060200151610041526000f360003560e01c631b11d0ff1461001457600080fd5b337f00000000000000000000000000000000000000000000000000000000000000001415610200576064357f000000000000000000000000000000000000000000000000000000000000000014156102005763095ea7b360e01b60005233600452600019602452602060006044600060006004355af115610205576300a718a960e01b600052608435602401356004526004356024526084356044013560445260243560645260006084526000600060a460006000335af115610205576084356024013560043514610195576370a0823160e01b6000523060045260206000602460006000608435602401355af1156102055760005163095ea7b360e01b6000526084356064013560045260001960245260206000604460006000608435602401355af1156102055763414bf38960e01b6000526084356024013560045260043560245260843560840135604452306064526000196084528060a452600060c452600060e452506020600061010460006000608435606401355af115610205575b6370a0823160e01b60005230600452602060006024600060006004355af115610205576000516044356024350180821061020557900363a9059cbb60e01b6000526064356004528060245250602060006044600060006004355af11561020557600160005260206000f35b600080fd5b3d6000803e3d6000fd";
+
 pub const INCENTIVESPROXY: &str = "60a060405234801561001057600080fd5b50604051610a0e380380610a0e83398101604081905261002f91610040565b6001600160a01b0316608052610070565b60006020828403121561005257600080fd5b81516001600160a01b038116811461006957600080fd5b9392505050565b6080516109606100ae6000396000818161014f015281816101a101528181610274015281816104110152818161043a01526105ab01526109606000f3fe60806040526004361061005a5760003560e01c80635c60da1b116100435780635c60da1b14610097578063d1f57894146100d5578063f851a440146100e85761005a565b80633659cfe6146100645780634f1ef28614610084575b6100626100fd565b005b34801561007057600080fd5b5061006261007f3660046106be565b610137565b6100626100923660046106e0565b610189565b3480156100a357600080fd5b506100ac61025a565b60405173ffffffffffffffffffffffffffffffffffffffff909116815260200160405180910390f35b6100626100e3366004610792565b6102cb565b3480156100f457600080fd5b506100ac6103f7565b61010561045c565b6101356101307f360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc5490565b610464565b565b3373ffffffffffffffffffffffffffffffffffffffff7f00000000000000000000000000000000000000000000000000000000000000001614156101815761017e81610488565b50565b61017e6100fd565b3373ffffffffffffffffffffffffffffffffffffffff7f000000000000000000000000000000000000000000000000000000000000000016141561024d576101d083610488565b60008373ffffffffffffffffffffffffffffffffffffffff1683836040516101f9929190610872565b600060405180830381855af49150503d8060008114610234576040519150601f19603f3d011682016040523d82523d6000602084013e610239565b606091505b505090508061024757600080fd5b50505050565b6102556100fd565b505050565b60003373ffffffffffffffffffffffffffffffffffffffff7f00000000000000000000000000000000000000000000000000000000000000001614156102c057507f360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc5490565b6102c86100fd565b90565b60006102f57f360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc5490565b73ffffffffffffffffffffffffffffffffffffffff161461031557600080fd5b61034060017f360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbd610882565b7f360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc1461036e5761036e6108c0565b610377826104d5565b8051156103f35760008273ffffffffffffffffffffffffffffffffffffffff16826040516103a591906108ef565b600060405180830381855af49150503d80600081146103e0576040519150601f19603f3d011682016040523d82523d6000602084013e6103e5565b606091505b505090508061025557600080fd5b5050565b60003373ffffffffffffffffffffffffffffffffffffffff7f00000000000000000000000000000000000000000000000000000000000000001614156102c057507f000000000000000000000000000000000000000000000000000000000000000090565b610135610593565b3660008037600080366000845af43d6000803e808015610483573d6000f35b3d6000fd5b610491816104d5565b60405173ffffffffffffffffffffffffffffffffffffffff8216907fbc7cd75a20ee27fd9adebab32041f755214dbc6bffa90cc0225b39da2e5c2d3b90600090a250565b6104de81610659565b61056f576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152603b60248201527f43616e6e6f742073657420612070726f787920696d706c656d656e746174696f60448201527f6e20746f2061206e6f6e2d636f6e74726163742061646472657373000000000060648201526084015b60405180910390fd5b7f360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc55565b3373ffffffffffffffffffffffffffffffffffffffff7f0000000000000000000000000000000000000000000000000000000000000000161415610135576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152603260248201527f43616e6e6f742063616c6c2066616c6c6261636b2066756e6374696f6e20667260448201527f6f6d207468652070726f78792061646d696e00000000000000000000000000006064820152608401610566565b6000813f7fc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a47081811480159061068d57508115155b949350505050565b803573ffffffffffffffffffffffffffffffffffffffff811681146106b957600080fd5b919050565b6000602082840312156106d057600080fd5b6106d982610695565b9392505050565b6000806000604084860312156106f557600080fd5b6106fe84610695565b9250602084013567ffffffffffffffff8082111561071b57600080fd5b818601915086601f83011261072f57600080fd5b81358181111561073e57600080fd5b87602082850101111561075057600080fd5b6020830194508093505050509250925092565b7f4e487b7100000000000000000000000000000000000000000000000000000000600052604160045260246000fd5b600080604083850312156107a557600080fd5b6107ae83610695565b9150602083013567ffffffffffffffff808211156107cb57600080fd5b818501915085601f8301126107df57600080fd5b8135818111156107f1576107f1610763565b604051601f82017fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe0908116603f0116810190838211818310171561083757610837610763565b8160405282815288602084870101111561085057600080fd5b8260208601602083013760006020848301015280955050505050509250929050565b8183823760009101908152919050565b6000828210156108bb577f4e487b7100000000000000000000000000000000000000000000000000000000600052601160045260246000fd5b500390565b7f4e487b7100000000000000000000000000000000000000000000000000000000600052600160045260246000fd5b6000825160005b8181101561091057602081860181015185830152016108f6565b8181111561091f576000828501525b50919091019291505056fea2646970667358221220f1a1ebca2f78efacc19ba2648500988371e4d1b4f18add3683f91f74a0e968a464736f6c634300080a0033";
 
 pub const INCENTIVES_V2_IMPLEMENTATION: &str = "60a060405260006005553480156200001657600080fd5b506040516200499f3803806200499f83398101604081905262000039916200004b565b6001600160a01b03166080526200007d565b6000602082840312156200005e57600080fd5b81516001600160a01b03811681146200007657600080fd5b9392505050565b6080516148d5620000ca600039600081816104f40152818161060c01528181610c9701528181610fd60152818161167d01528181611833015281816118dc01526119f701526148d56000f3fe608060405234801561001057600080fd5b50600436106101da5760003560e01c806392074b0811610104578063bf90f63a116100a2578063dde43cba11610071578063dde43cba1461062e578063e15ac62314610636578063f5cf673b14610649578063f996868b1461065c57600080fd5b8063bf90f63a146105ce578063c4d66de8146105e1578063c5a7b538146105f4578063cbcbb5071461060757600080fd5b80639ff55db9116100de5780639ff55db91461058d578063b022418c146105a0578063b45ac1a9146105b3578063bb492bf5146105bb57600080fd5b806392074b08146104f2578063955c2ad7146105185780639efd6f721461052b57600080fd5b80635453ba101161017c57806370674ab91161014b57806370674ab9146103a257806374d945ec146103b55780637eff4ba8146103ee578063886fe70b146104ca57600080fd5b80635453ba101461032357806357b89883146103365780635f130b24146103495780636657732f1461038257600080fd5b806331873e2e116101b857806331873e2e1461027657806333028b991461028b5780634c0369c31461029e578063533f542a146102bf57600080fd5b80631b839c77146101df578063236300dc146102055780632a17bf6014610218575b600080fd5b6101f26101ed366004613e6d565b61066f565b6040519081526020015b60405180910390f35b6101f2610213366004613eeb565b6106cf565b610251610226366004613f5f565b73ffffffffffffffffffffffffffffffffffffffff9081166000908152603b60205260409020541690565b60405173ffffffffffffffffffffffffffffffffffffffff90911681526020016101fc565b610289610284366004613f83565b61076c565b005b6101f2610299366004613fb8565b61077d565b6102b16102ac36600461403d565b610929565b6040516101fc9291906140e5565b6101f26102cd36600461413c565b73ffffffffffffffffffffffffffffffffffffffff808316600090815260016020818152604080842086861685528252808420948816845293909101905220546cffffffffffffffffffffffffff169392505050565b610289610331366004613e6d565b610c7f565b6101f261034436600461417c565b610d2c565b610251610357366004613f5f565b73ffffffffffffffffffffffffffffffffffffffff9081166000908152603a60205260409020541690565b610395610390366004613f5f565b610d46565b6040516101fc91906141db565b6101f26103b03660046141ee565b610e98565b6102516103c3366004613f5f565b73ffffffffffffffffffffffffffffffffffffffff9081166000908152603960205260409020541690565b6104aa6103fc366004613e6d565b73ffffffffffffffffffffffffffffffffffffffff91821660009081526001602090815260408083209390941682529190915220546cffffffffffffffffffffffffff8116916affffffffffffffffffffff6d01000000000000000000000000008304169163ffffffff780100000000000000000000000000000000000000000000000082048116927c01000000000000000000000000000000000000000000000000000000009092041690565b6040805194855260208501939093529183015260608201526080016101fc565b6104dd6104d8366004613e6d565b610eaf565b604080519283526020830191909152016101fc565b7f0000000000000000000000000000000000000000000000000000000000000000610251565b610289610526366004614326565b610fbe565b61057b610539366004613f5f565b73ffffffffffffffffffffffffffffffffffffffff16600090815260016020526040902060020154700100000000000000000000000000000000900460ff1690565b60405160ff90911681526020016101fc565b6102b161059b3660046141ee565b6111be565b6101f26105ae366004613e6d565b61136d565b610395611426565b6102b16105c936600461403d565b611495565b6102b16105dc366004614454565b61152e565b6102896105ef366004613f5f565b611549565b610289610602366004614496565b611665565b6102517f000000000000000000000000000000000000000000000000000000000000000081565b6101f2600181565b610289610644366004613e6d565b61181b565b610289610657366004613e6d565b6118c4565b61028961066a3660046144dd565b6119df565b73ffffffffffffffffffffffffffffffffffffffff8281166000908152600160209081526040808320938516835292905220547c0100000000000000000000000000000000000000000000000000000000900463ffffffff165b92915050565b600073ffffffffffffffffffffffffffffffffffffffff8316610753576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601260248201527f494e56414c49445f544f5f41444452455353000000000000000000000000000060448201526064015b60405180910390fd5b61076286868633338888611e53565b9695505050505050565b610778338483856120e4565b505050565b73ffffffffffffffffffffffffffffffffffffffff808416600090815260396020526040812054909133918691168214610813576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601460248201527f434c41494d45525f554e415554484f52495a4544000000000000000000000000604482015260640161074a565b73ffffffffffffffffffffffffffffffffffffffff8616610890576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601460248201527f494e56414c49445f555345525f41444452455353000000000000000000000000604482015260640161074a565b73ffffffffffffffffffffffffffffffffffffffff851661090d576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601260248201527f494e56414c49445f544f5f414444524553530000000000000000000000000000604482015260640161074a565b61091c898989338a8a8a611e53565b9998505050505050505050565b6060806000610939868686612297565b60035490915067ffffffffffffffff8111156109575761095761424b565b604051908082528060200260200182016040528015610980578160200160208202803683370190505b509250825167ffffffffffffffff81111561099d5761099d61424b565b6040519080825280602002602001820160405280156109c6578160200160208202803683370190505b50915060005b8151811015610c745760005b8451811015610c6157600381815481106109f4576109f4614560565b9060005260206000200160009054906101000a900473ffffffffffffffffffffffffffffffffffffffff16858281518110610a3157610a31614560565b602002602001019073ffffffffffffffffffffffffffffffffffffffff16908173ffffffffffffffffffffffffffffffffffffffff168152505060016000848481518110610a8157610a81614560565b60200260200101516000015173ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff1681526020019081526020016000206000016000868381518110610ade57610ade614560565b602002602001015173ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff16815260200190815260200160002060010160008773ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff168152602001908152602001600020600001600d9054906101000a90046fffffffffffffffffffffffffffffffff166fffffffffffffffffffffffffffffffff16848281518110610ba457610ba4614560565b60200260200101818151610bb891906145be565b9052508251839083908110610bcf57610bcf614560565b60200260200101516020015160001415610be857610c4f565b610c2586868381518110610bfe57610bfe614560565b6020026020010151858581518110610c1857610c18614560565b6020026020010151612495565b848281518110610c3757610c37614560565b60200260200101818151610c4b91906145be565b9052505b80610c59816145d6565b9150506109d8565b5080610c6c816145d6565b9150506109cc565b50505b935093915050565b3373ffffffffffffffffffffffffffffffffffffffff7f00000000000000000000000000000000000000000000000000000000000000001614610d1e576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601560248201527f4f4e4c595f454d495353494f4e5f4d414e414745520000000000000000000000604482015260640161074a565b610d288282612563565b5050565b6000610d3d85858533333388611e53565b95945050505050565b73ffffffffffffffffffffffffffffffffffffffff81166000908152600160205260408120600201546060916fffffffffffffffffffffffffffffffff909116908167ffffffffffffffff811115610da057610da061424b565b604051908082528060200260200182016040528015610dc9578160200160208202803683370190505b50905060005b826fffffffffffffffffffffffffffffffff16816fffffffffffffffffffffffffffffffff161015610e905773ffffffffffffffffffffffffffffffffffffffff80861660009081526001602081815260408084206fffffffffffffffffffffffffffffffff871680865293019091529091205484519216918491908110610e5957610e59614560565b73ffffffffffffffffffffffffffffffffffffffff9092166020928302919091019091015280610e888161460f565b915050610dcf565b509392505050565b6000610d3d8383610eaa888888612297565b6126b7565b73ffffffffffffffffffffffffffffffffffffffff8083166000818152600160209081526040808320948616835293815283822084517fb1bf962d0000000000000000000000000000000000000000000000000000000081529451929485949193610fb19385939263b1bf962d92600480830193928290030181865afa158015610f3d573d6000803e3d6000fd5b505050506040513d601f19601f82011682018060405250810190610f61919061463f565b73ffffffffffffffffffffffffffffffffffffffff8816600090815260016020526040902060020154610fac90700100000000000000000000000000000000900460ff16600a614778565b612856565b92509250505b9250929050565b3373ffffffffffffffffffffffffffffffffffffffff7f0000000000000000000000000000000000000000000000000000000000000000161461105d576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601560248201527f4f4e4c595f454d495353494f4e5f4d414e414745520000000000000000000000604482015260640161074a565b60005b81518110156111b15781818151811061107b5761107b614560565b60200260200101516060015173ffffffffffffffffffffffffffffffffffffffff1663b1bf962d6040518163ffffffff1660e01b8152600401602060405180830381865afa1580156110d1573d6000803e3d6000fd5b505050506040513d601f19601f820116820180604052508101906110f5919061463f565b82828151811061110757611107614560565b6020026020010151602001818152505061115b82828151811061112c5761112c614560565b60200260200101516080015183838151811061114a5761114a614560565b602002602001015160a00151612962565b61119f82828151811061117057611170614560565b60200260200101516080015183838151811061118e5761118e614560565b602002602001015160c00151612563565b806111a9816145d6565b915050611060565b506111bb81612ac8565b50565b73ffffffffffffffffffffffffffffffffffffffff808316600090815260396020526040902054606091829133918691168214611257576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601460248201527f434c41494d45525f554e415554484f52495a4544000000000000000000000000604482015260640161074a565b73ffffffffffffffffffffffffffffffffffffffff86166112d4576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601460248201527f494e56414c49445f555345525f41444452455353000000000000000000000000604482015260640161074a565b73ffffffffffffffffffffffffffffffffffffffff8516611351576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601260248201527f494e56414c49445f544f5f414444524553530000000000000000000000000000604482015260640161074a565b61135e8888338989613369565b93509350505094509492505050565b60008060005b600454811015610e9057600160006004838154811061139457611394614560565b60009182526020808320919091015473ffffffffffffffffffffffffffffffffffffffff908116845283820194909452604092830182208885168352815282822093891682526001909301909252902054611412906d010000000000000000000000000090046fffffffffffffffffffffffffffffffff16836145be565b91508061141e816145d6565b915050611373565b6060600380548060200260200160405190810160405280929190818152602001828054801561148b57602002820191906000526020600020905b815473ffffffffffffffffffffffffffffffffffffffff168152600190910190602001808311611460575b5050505050905090565b60608073ffffffffffffffffffffffffffffffffffffffff8316611515576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601260248201527f494e56414c49445f544f5f414444524553530000000000000000000000000000604482015260640161074a565b6115228585333387613369565b91509150935093915050565b60608061153e8484333333613369565b915091509250929050565b60065460019060ff168061155c5750303b155b80611568575060055481115b6115f4576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152602e60248201527f436f6e747261637420696e7374616e63652068617320616c726561647920626560448201527f656e20696e697469616c697a6564000000000000000000000000000000000000606482015260840161074a565b60065460ff1615801561163257600680547fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0016600117905560058290555b801561077857600680547fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff00169055505050565b3373ffffffffffffffffffffffffffffffffffffffff7f00000000000000000000000000000000000000000000000000000000000000001614611704576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601560248201527f4f4e4c595f454d495353494f4e5f4d414e414745520000000000000000000000604482015260640161074a565b73ffffffffffffffffffffffffffffffffffffffff83811660008181526001602090815260408083209487168084529482529182902080547bffffffffffffffffffffffffffffffffffffffffffffffffffffffff81167c010000000000000000000000000000000000000000000000000000000063ffffffff8981168281029384179586905587516d01000000000000000000000000009096046affffffffffffffffffffff16808752968601969096529083041694830185905260608301939093526cffffffffffffffffffffffffff9081169216919091176080820152909291907fac1777479f07f3e7c34da8402139d54027a6a260caaae168bdee825ca5580dc59060a00160405180910390a350505050565b3373ffffffffffffffffffffffffffffffffffffffff7f000000000000000000000000000000000000000000000000000000000000000016146118ba576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601560248201527f4f4e4c595f454d495353494f4e5f4d414e414745520000000000000000000000604482015260640161074a565b610d288282612962565b3373ffffffffffffffffffffffffffffffffffffffff7f00000000000000000000000000000000000000000000000000000000000000001614611963576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601560248201527f4f4e4c595f454d495353494f4e5f4d414e414745520000000000000000000000604482015260640161074a565b73ffffffffffffffffffffffffffffffffffffffff82811660008181526039602052604080822080547fffffffffffffffffffffffff0000000000000000000000000000000000000000169486169485179055517f4925eafc82d0c4d67889898eeed64b18488ab19811e61620f387026dec126a289190a35050565b3373ffffffffffffffffffffffffffffffffffffffff7f00000000000000000000000000000000000000000000000000000000000000001614611a7e576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601560248201527f4f4e4c595f454d495353494f4e5f4d414e414745520000000000000000000000604482015260640161074a565b828114611ae7576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152600d60248201527f494e56414c49445f494e50555400000000000000000000000000000000000000604482015260640161074a565b60005b83811015611e4b5773ffffffffffffffffffffffffffffffffffffffff86166000908152600160205260408120908181888886818110611b2c57611b2c614560565b9050602002016020810190611b419190613f5f565b73ffffffffffffffffffffffffffffffffffffffff16815260208101919091526040016000206002830154909150700100000000000000000000000000000000900460ff168015801590611bb7575081547801000000000000000000000000000000000000000000000000900463ffffffff1615155b611c1d576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601b60248201527f444953545249425554494f4e5f444f45535f4e4f545f45584953540000000000604482015260640161074a565b6000611ca2838b73ffffffffffffffffffffffffffffffffffffffff1663b1bf962d6040518163ffffffff1660e01b8152600401602060405180830381865afa158015611c6e573d6000803e3d6000fd5b505050506040513d601f19601f82011682018060405250810190611c92919061463f565b611c9d85600a614787565b613851565b5083549091506d010000000000000000000000000090046affffffffffffffffffffff16878787818110611cd857611cd8614560565b9050602002016020810190611ced9190614793565b84546affffffffffffffffffffff919091166d0100000000000000000000000000027fffffffffffffffff0000000000000000000000ffffffffffffffffffffffffff909116178455898987818110611d4857611d48614560565b9050602002016020810190611d5d9190613f5f565b73ffffffffffffffffffffffffffffffffffffffff168b73ffffffffffffffffffffffffffffffffffffffff167fac1777479f07f3e7c34da8402139d54027a6a260caaae168bdee825ca5580dc5838b8b8b818110611dbe57611dbe614560565b9050602002016020810190611dd39190614793565b8854604080519384526affffffffffffffffffffff90921660208401527c0100000000000000000000000000000000000000000000000000000000900463ffffffff1690820181905260608201526080810186905260a00160405180910390a350505050508080611e43906145d6565b915050611aea565b505050505050565b600085611e62575060006120d9565b6000611e7885611e738b8b89612297565b6139df565b60005b8881101561205f5760008a8a83818110611e9757611e97614560565b9050602002016020810190611eac9190613f5f565b73ffffffffffffffffffffffffffffffffffffffff81811660009081526001602081815260408084208b861685528252808420948d1684529390910190522054909150611f1c906d010000000000000000000000000090046fffffffffffffffffffffffffffffffff16846145be565b9250888311611f8f5773ffffffffffffffffffffffffffffffffffffffff80821660009081526001602081815260408084208a861685528252808420948c1684529390910190522080547fffffff00000000000000000000000000000000ffffffffffffffffffffffffff16905561204c565b6000611f9b8a856147ae565b9050611fa781856147ae565b9350611fb281613a60565b73ffffffffffffffffffffffffffffffffffffffff92831660009081526001602081815260408084208b881685528252808420968d1684529590910190529290922080546fffffffffffffffffffffffffffffffff939093166d0100000000000000000000000000027fffffff00000000000000000000000000000000ffffffffffffffffffffffffff909316929092179091555061205f565b5080612057816145d6565b915050611e7b565b508061206f5760009150506120d9565b61207a848483613b06565b6040805173ffffffffffffffffffffffffffffffffffffffff8881168252602082018490528087169286821692918916917fc052130bc4ef84580db505783484b067ea8b71b3bca78a7e12db7aea8658f004910160405180910390a490505b979650505050505050565b73ffffffffffffffffffffffffffffffffffffffff841660009081526001602052604090206002015460ff700100000000000000000000000000000000820416600a0a906fffffffffffffffffffffffffffffffff1680612146575050612291565b60005b81816fffffffffffffffffffffffffffffffff16101561228d5773ffffffffffffffffffffffffffffffffffffffff80881660009081526001602081815260408084206fffffffffffffffffffffffffffffffff8716855292830182528084205490941680845291905291812090806121c3838989613851565b915091506000806121d7858d8d878d613c32565b9150915082806121e45750805b1561227b578b73ffffffffffffffffffffffffffffffffffffffff168673ffffffffffffffffffffffffffffffffffffffff168e73ffffffffffffffffffffffffffffffffffffffff167f3303facd24627943a92e9dc87cfbb34b15c49b726eec3ad3487c16be9ab8efe8878887604051612272939291909283526020830191909152604082015260600190565b60405180910390a45b50506001909401935061214992505050565b5050505b50505050565b60608267ffffffffffffffff8111156122b2576122b261424b565b60405190808252806020026020018201604052801561231d57816020015b61230a6040518060600160405280600073ffffffffffffffffffffffffffffffffffffffff16815260200160008152602001600081525090565b8152602001906001900390816122d05790505b50905060005b83811015610e905784848281811061233d5761233d614560565b90506020020160208101906123529190613f5f565b82828151811061236457612364614560565b602090810291909101015173ffffffffffffffffffffffffffffffffffffffff909116905284848281811061239b5761239b614560565b90506020020160208101906123b09190613f5f565b6040517f0afbcdc900000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff85811660048301529190911690630afbcdc9906024016040805180830381865afa15801561241d573d6000803e3d6000fd5b505050506040513d601f19601f8201168201806040525081019061244191906147c5565b83838151811061245357612453614560565b602002602001015160200184848151811061247057612470614560565b602090810291909101015160400191909152528061248d816145d6565b915050612323565b805173ffffffffffffffffffffffffffffffffffffffff90811660009081526001602081815260408084208786168552825280842086519095168452919052812060020154909190829061250190700100000000000000000000000000000000900460ff16600a614778565b9050600061251483866040015184612856565b60208088015173ffffffffffffffffffffffffffffffffffffffff8b166000908152600188019092526040909120549193506120d992509083906cffffffffffffffffffffffffff1685613d91565b60008173ffffffffffffffffffffffffffffffffffffffff166350d25bcd6040518163ffffffff1660e01b8152600401602060405180830381865afa1580156125b0573d6000803e3d6000fd5b505050506040513d601f19601f820116820180604052508101906125d4919061463f565b1361263b576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601860248201527f4f5241434c455f4d5553545f52455455524e5f50524943450000000000000000604482015260640161074a565b73ffffffffffffffffffffffffffffffffffffffff8281166000818152603b602052604080822080547fffffffffffffffffffffffff0000000000000000000000000000000000000000169486169485179055517f1a1cd5483e52e60b9ff7f3b9d1db3bbd9e9d21c6324ad3a8c79dba9b75e62f4d9190a35050565b6000805b8251811015610e90578281815181106126d6576126d6614560565b60200260200101516020015160001415612785576001600084838151811061270057612700614560565b6020908102919091018101515173ffffffffffffffffffffffffffffffffffffffff908116835282820193909352604091820160009081208885168252825282812093891681526001909301905290205461277e906d010000000000000000000000000090046fffffffffffffffffffffffffffffffff16836145be565b9150612844565b6001600084838151811061279b5761279b614560565b6020908102919091018101515173ffffffffffffffffffffffffffffffffffffffff908116835282820193909352604091820160009081208885168252825282812093891681526001909301905290205483516d01000000000000000000000000009091046fffffffffffffffffffffffffffffffff169061282d9087908790879086908110610c1857610c18614560565b61283791906145be565b61284190836145be565b91505b8061284e816145d6565b9150506126bb565b825460009081906cffffffffffffffffffffffffff81169063ffffffff7c010000000000000000000000000000000000000000000000000000000082048116916affffffffffffffffffffff6d0100000000000000000000000000820416917801000000000000000000000000000000000000000000000000909104168115806128de575087155b806128e857504281145b806128f35750828110155b156129075783849550955050505050610c77565b60008342116129165742612918565b835b9050600061292683836147ae565b905060008961293583876147e9565b61293f91906147e9565b8b900490508661294f81836145be565b9850985050505050505050935093915050565b73ffffffffffffffffffffffffffffffffffffffff81166129df576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601860248201527f53545241544547595f43414e5f4e4f545f42455f5a45524f0000000000000000604482015260640161074a565b6001813b151514612a4c576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601960248201527f53545241544547595f4d5553545f42455f434f4e545241435400000000000000604482015260640161074a565b73ffffffffffffffffffffffffffffffffffffffff8281166000818152603a602052604080822080547fffffffffffffffffffffffff0000000000000000000000000000000000000000169486169485179055517f8ca1d928f1d72493a6b78c4f74aabde976bc37ffe2570f2a1ce5a8abd3dde0aa9190a35050565b60005b8151811015610d285760016000838381518110612aea57612aea614560565b6020908102919091018101516060015173ffffffffffffffffffffffffffffffffffffffff16825281019190915260400160002060020154700100000000000000000000000000000000900460ff16612bb6576004828281518110612b5157612b51614560565b6020908102919091018101516060015182546001810184556000938452919092200180547fffffffffffffffffffffffff00000000000000000000000000000000000000001673ffffffffffffffffffffffffffffffffffffffff9092169190911790555b6000828281518110612bca57612bca614560565b60200260200101516060015173ffffffffffffffffffffffffffffffffffffffff1663313ce5676040518163ffffffff1660e01b8152600401602060405180830381865afa158015612c20573d6000803e3d6000fd5b505050506040513d601f19601f82011682018060405250810190612c449190614826565b60016000858581518110612c5a57612c5a614560565b60200260200101516060015173ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff16815260200190815260200160002060020160106101000a81548160ff021916908360ff160217905560ff169050600060016000858581518110612cd757612cd7614560565b60200260200101516060015173ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff1681526020019081526020016000206000016000858581518110612d3457612d34614560565b6020908102919091018101516080015173ffffffffffffffffffffffffffffffffffffffff16825281019190915260400160002080549091507801000000000000000000000000000000000000000000000000900463ffffffff16612fa357838381518110612da557612da5614560565b60200260200101516080015160016000868681518110612dc757612dc7614560565b60200260200101516060015173ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff168152602001908152602001600020600101600060016000888881518110612e2857612e28614560565b60200260200101516060015173ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff16815260200190815260200160002060020160009054906101000a90046fffffffffffffffffffffffffffffffff166fffffffffffffffffffffffffffffffff166fffffffffffffffffffffffffffffffff16815260200190815260200160002060006101000a81548173ffffffffffffffffffffffffffffffffffffffff021916908373ffffffffffffffffffffffffffffffffffffffff16021790555060016000858581518110612f1457612f14614560565b6020908102919091018101516060015173ffffffffffffffffffffffffffffffffffffffff168252810191909152604001600090812060020180546fffffffffffffffffffffffffffffffff1691612f6b8361460f565b91906101000a8154816fffffffffffffffffffffffffffffffff02191690836fffffffffffffffffffffffffffffffff160217905550505b60026000858581518110612fb957612fb9614560565b6020908102919091018101516080015173ffffffffffffffffffffffffffffffffffffffff1682528101919091526040016000205460ff166130e35760016002600086868151811061300d5761300d614560565b60200260200101516080015173ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff16815260200190815260200160002060006101000a81548160ff021916908315150217905550600384848151811061307e5761307e614560565b6020908102919091018101516080015182546001810184556000938452919092200180547fffffffffffffffffffffffff00000000000000000000000000000000000000001673ffffffffffffffffffffffffffffffffffffffff9092169190911790555b6000613114828686815181106130fb576130fb614560565b60200260200101516020015185600a611c9d9190614787565b50825486519192506d010000000000000000000000000081046affffffffffffffffffffff16917c010000000000000000000000000000000000000000000000000000000090910463ffffffff169087908790811061317557613175614560565b60209081029190910101515184546affffffffffffffffffffff9091166d0100000000000000000000000000027fffffffffffffffff0000000000000000000000ffffffffffffffffffffffffff90911617845586518790879081106131dd576131dd614560565b602090810291909101015160400151845463ffffffff9091167c0100000000000000000000000000000000000000000000000000000000027bffffffffffffffffffffffffffffffffffffffffffffffffffffffff909116178455865187908790811061324c5761324c614560565b60200260200101516080015173ffffffffffffffffffffffffffffffffffffffff1687878151811061328057613280614560565b60200260200101516060015173ffffffffffffffffffffffffffffffffffffffff167fac1777479f07f3e7c34da8402139d54027a6a260caaae168bdee825ca5580dc5848a8a815181106132d6576132d6614560565b602002602001015160000151858c8c815181106132f5576132f5614560565b602002602001015160400151896040516133499594939291906affffffffffffffffffffff958616815293909416602084015263ffffffff9182166040840152166060820152608081019190915260a00190565b60405180910390a350505050508080613361906145d6565b915050612acb565b60035460609081908067ffffffffffffffff81111561338a5761338a61424b565b6040519080825280602002602001820160405280156133b3578160200160208202803683370190505b5092508067ffffffffffffffff8111156133cf576133cf61424b565b6040519080825280602002602001820160405280156133f8578160200160208202803683370190505b50915061340a85611e738a8a89612297565b60005b8781101561371957600089898381811061342957613429614560565b905060200201602081019061343e9190613f5f565b905060005b8381101561370457600073ffffffffffffffffffffffffffffffffffffffff1686828151811061347557613475614560565b602002602001015173ffffffffffffffffffffffffffffffffffffffff16141561352457600381815481106134ac576134ac614560565b9060005260206000200160009054906101000a900473ffffffffffffffffffffffffffffffffffffffff168682815181106134e9576134e9614560565b602002602001019073ffffffffffffffffffffffffffffffffffffffff16908173ffffffffffffffffffffffffffffffffffffffff16815250505b73ffffffffffffffffffffffffffffffffffffffff821660009081526001602052604081208751829089908590811061355f5761355f614560565b60209081029190910181015173ffffffffffffffffffffffffffffffffffffffff90811683528282019390935260409182016000908120938d168152600190930190529020546d010000000000000000000000000090046fffffffffffffffffffffffffffffffff16905080156136f157808683815181106135e3576135e3614560565b602002602001018181516135f791906145be565b90525073ffffffffffffffffffffffffffffffffffffffff83166000908152600160205260408120885182908a908690811061363557613635614560565b602002602001015173ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff16815260200190815260200160002060010160008b73ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff168152602001908152602001600020600001600d6101000a8154816fffffffffffffffffffffffffffffffff02191690836fffffffffffffffffffffffffffffffff1602179055505b50806136fc816145d6565b915050613443565b50508080613711906145d6565b91505061340d565b5060005b81811015613845576137628585838151811061373b5761373b614560565b602002602001015185848151811061375557613755614560565b6020026020010151613b06565b8473ffffffffffffffffffffffffffffffffffffffff1684828151811061378b5761378b614560565b602002602001015173ffffffffffffffffffffffffffffffffffffffff168773ffffffffffffffffffffffffffffffffffffffff167fc052130bc4ef84580db505783484b067ea8b71b3bca78a7e12db7aea8658f0048a8786815181106137f4576137f4614560565b602002602001015160405161382b92919073ffffffffffffffffffffffffffffffffffffffff929092168252602082015260400190565b60405180910390a48061383d816145d6565b91505061371d565b50509550959350505050565b600080600080613862878787612856565b91509150600082821461397b576cffffffffffffffffffffffffff8211156138e6576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152600e60248201527f494e4445585f4f564552464c4f57000000000000000000000000000000000000604482015260640161074a565b5086547fffffffffffffffffffffffffffffffffffffff00000000000000000000000000166cffffffffffffffffffffffffff8216178755600161392942613db5565b885463ffffffff919091167801000000000000000000000000000000000000000000000000027fffffffff00000000ffffffffffffffffffffffffffffffffffffffffffffffff9091161788556139d2565b61398442613db5565b885463ffffffff919091167801000000000000000000000000000000000000000000000000027fffffffff00000000ffffffffffffffffffffffffffffffffffffffffffffffff9091161788555b9097909650945050505050565b60005b815181101561077857613a4e828281518110613a0057613a00614560565b60200260200101516000015184848481518110613a1f57613a1f614560565b602002602001015160200151858581518110613a3d57613a3d614560565b6020026020010151604001516120e4565b80613a58816145d6565b9150506139e2565b60006fffffffffffffffffffffffffffffffff821115613b02576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152602760248201527f53616665436173743a2076616c756520646f65736e27742066697420696e203160448201527f3238206269747300000000000000000000000000000000000000000000000000606482015260840161074a565b5090565b73ffffffffffffffffffffffffffffffffffffffff8281166000818152603a60205260408082205490517f16beb9820000000000000000000000000000000000000000000000000000000081528785166004820152602481019390935260448301859052909216919082906316beb982906064016020604051808303816000875af1158015613b99573d6000803e3d6000fd5b505050506040513d601f19601f82011682018060405250810190613bbd9190614849565b9050600181151514613c2b576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152600e60248201527f5452414e534645525f4552524f52000000000000000000000000000000000000604482015260640161074a565b5050505050565b73ffffffffffffffffffffffffffffffffffffffff8416600090815260018601602052604081205481906cffffffffffffffffffffffffff1681858214801590613d825773ffffffffffffffffffffffffffffffffffffffff8916600090815260018b016020526040902080547fffffffffffffffffffffffffffffffffffffff00000000000000000000000000166cffffffffffffffffffffffffff89161790558715613d8257613ce688888589613d91565b9150613cf182613a60565b73ffffffffffffffffffffffffffffffffffffffff8a16600090815260018c01602052604090208054600d90613d4b9084906d010000000000000000000000000090046fffffffffffffffffffffffffffffffff1661486b565b92506101000a8154816fffffffffffffffffffffffffffffffff02191690836fffffffffffffffffffffffffffffffff1602179055505b90999098509650505050505050565b600080613d9e84866147ae565b613da890876147e9565b9290920495945050505050565b600063ffffffff821115613b02576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152602660248201527f53616665436173743a2076616c756520646f65736e27742066697420696e203360448201527f3220626974730000000000000000000000000000000000000000000000000000606482015260840161074a565b73ffffffffffffffffffffffffffffffffffffffff811681146111bb57600080fd5b60008060408385031215613e8057600080fd5b8235613e8b81613e4b565b91506020830135613e9b81613e4b565b809150509250929050565b60008083601f840112613eb857600080fd5b50813567ffffffffffffffff811115613ed057600080fd5b6020830191508360208260051b8501011115610fb757600080fd5b600080600080600060808688031215613f0357600080fd5b853567ffffffffffffffff811115613f1a57600080fd5b613f2688828901613ea6565b909650945050602086013592506040860135613f4181613e4b565b91506060860135613f5181613e4b565b809150509295509295909350565b600060208284031215613f7157600080fd5b8135613f7c81613e4b565b9392505050565b600080600060608486031215613f9857600080fd5b8335613fa381613e4b565b95602085013595506040909401359392505050565b60008060008060008060a08789031215613fd157600080fd5b863567ffffffffffffffff811115613fe857600080fd5b613ff489828a01613ea6565b90975095505060208701359350604087013561400f81613e4b565b9250606087013561401f81613e4b565b9150608087013561402f81613e4b565b809150509295509295509295565b60008060006040848603121561405257600080fd5b833567ffffffffffffffff81111561406957600080fd5b61407586828701613ea6565b909450925050602084013561408981613e4b565b809150509250925092565b600081518084526020808501945080840160005b838110156140da57815173ffffffffffffffffffffffffffffffffffffffff16875295820195908201906001016140a8565b509495945050505050565b6040815260006140f86040830185614094565b82810360208481019190915284518083528582019282019060005b8181101561412f57845183529383019391830191600101614113565b5090979650505050505050565b60008060006060848603121561415157600080fd5b833561415c81613e4b565b9250602084013561416c81613e4b565b9150604084013561408981613e4b565b6000806000806060858703121561419257600080fd5b843567ffffffffffffffff8111156141a957600080fd5b6141b587828801613ea6565b9095509350506020850135915060408501356141d081613e4b565b939692955090935050565b602081526000613f7c6020830184614094565b6000806000806060858703121561420457600080fd5b843567ffffffffffffffff81111561421b57600080fd5b61422787828801613ea6565b909550935050602085013561423b81613e4b565b915060408501356141d081613e4b565b7f4e487b7100000000000000000000000000000000000000000000000000000000600052604160045260246000fd5b60405160e0810167ffffffffffffffff8111828210171561429d5761429d61424b565b60405290565b604051601f82017fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe016810167ffffffffffffffff811182821017156142ea576142ea61424b565b604052919050565b80356affffffffffffffffffffff8116811461430d57600080fd5b919050565b803563ffffffff8116811461430d57600080fd5b6000602080838503121561433957600080fd5b823567ffffffffffffffff8082111561435157600080fd5b818501915085601f83011261436557600080fd5b8135818111156143775761437761424b565b614385848260051b016142a3565b818152848101925060e09182028401850191888311156143a457600080fd5b938501935b828510156144485780858a0312156143c15760008081fd5b6143c961427a565b6143d2866142f2565b8152868601358782015260406143e9818801614312565b908201526060868101356143fc81613e4b565b9082015260808681013561440f81613e4b565b9082015260a08681013561442281613e4b565b9082015260c08681013561443581613e4b565b90820152845293840193928501926143a9565b50979650505050505050565b6000806020838503121561446757600080fd5b823567ffffffffffffffff81111561447e57600080fd5b61448a85828601613ea6565b90969095509350505050565b6000806000606084860312156144ab57600080fd5b83356144b681613e4b565b925060208401356144c681613e4b565b91506144d460408501614312565b90509250925092565b6000806000806000606086880312156144f557600080fd5b853561450081613e4b565b9450602086013567ffffffffffffffff8082111561451d57600080fd5b61452989838a01613ea6565b9096509450604088013591508082111561454257600080fd5b5061454f88828901613ea6565b969995985093965092949392505050565b7f4e487b7100000000000000000000000000000000000000000000000000000000600052603260045260246000fd5b7f4e487b7100000000000000000000000000000000000000000000000000000000600052601160045260246000fd5b600082198211156145d1576145d161458f565b500190565b60007fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff8214156146085761460861458f565b5060010190565b60006fffffffffffffffffffffffffffffffff808316818114156146355761463561458f565b6001019392505050565b60006020828403121561465157600080fd5b5051919050565b600181815b808511156146b157817fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff048211156146975761469761458f565b808516156146a457918102915b93841c939080029061465d565b509250929050565b6000826146c8575060016106c9565b816146d5575060006106c9565b81600181146146eb57600281146146f557614711565b60019150506106c9565b60ff8411156147065761470661458f565b50506001821b6106c9565b5060208310610133831016604e8410600b8410161715614734575081810a6106c9565b61473e8383614658565b807fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff048211156147705761477061458f565b029392505050565b6000613f7c60ff8416836146b9565b6000613f7c83836146b9565b6000602082840312156147a557600080fd5b613f7c826142f2565b6000828210156147c0576147c061458f565b500390565b600080604083850312156147d857600080fd5b505080516020909101519092909150565b6000817fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff04831182151516156148215761482161458f565b500290565b60006020828403121561483857600080fd5b815160ff81168114613f7c57600080fd5b60006020828403121561485b57600080fd5b81518015158114613f7c57600080fd5b60006fffffffffffffffffffffffffffffffff8083168185168083038211156148965761489661458f565b0194935050505056fea2646970667358221220e7621b63458da30b0651830d876386c44de0de944ba90545023b9bf29248b60e64736f6c634300080a0033";
 
+// Assembled by hand from contracts/aave/LiquidationBundleExecutor.sol
+pub const LIQUIDATION_BUNDLE_EXECUTOR: &str = "6102ab8061000d6000396000f360003560e01c63d096cbcd1461001457600080fd5b6323b872dd60e01b600052336004523060245260a435604452602060006064600060006024355af1156102a1576323b872dd60e01b600052336004523060245260c435604452602060006064600060006044355af1156102a15763095ea7b360e01b600052606435600452600019602452602060006044600060006024355af1156102a15763095ea7b360e01b600052606435600452600019602452602060006044600060006044355af1156102a15763095ea7b360e01b600052600435600452600019602452602060006044600060006044355af1156102a15760a4351561014b5763414bf38960e01b6000526024356004526044356024526084356044523060645260001960845260a43560a452600060c452600060e45260206000610104600060006064355af1156102a157600051610300525b60005b60e435600401358110156101ac576300a718a960e01b6000526024356004526044356024528060200260e435600401016020013560445260001960645260006084526000600060a4600060006004355af1156102a15760010161014e565b5060a435156102035763414bf38960e01b600052604435600452602435602452608435604452306064526000196084526103005160a452600060c452600060e45260206000610104600060006064355af1156102a1575b6370a0823160e01b60005230600452602060006024600060006024355af1156102a15760005163a9059cbb60e01b600052336004528060245250602060006044600060006024355af1156102a1576370a0823160e01b60005230600452602060006024600060006044355af1156102a15760005163a9059cbb60e01b600052336004528060245250602060006044600060006044355af1156102a157005b3d6000803e3d6000fd";
+
 pub const LIQUIDATION_LOGIC: &str = "613db761003a600b82828239805160001a60731461002d57634e487b7160e01b600052600060045260246000fd5b30600052607381538281f3fe730000000000000000000000000000000000000000301460806040526004361061004b5760003560e01c806383c1087d14610050578063a18964a514610072578063d246754414610093575b600080fd5b81801561005c57600080fd5b5061007061006b36600461387f565b61009c565b005b610081670d2f13f7789f000081565b60405190815260200160405180910390f35b61008161271081565b6100a461367a565b60408083015173ffffffffffffffffffffffffffffffffffffffff9081166000908152602089815283822060608701518416835284832060808801519094168352908890529290206100f582610832565b6101608501819052610108908390610a4b565b61018e8989886040518060a001604052808660405180602001604052908160008201548152505081526020018a6000015181526020018a6080015173ffffffffffffffffffffffffffffffffffffffff1681526020018a60c0015173ffffffffffffffffffffffffffffffffffffffff1681526020018a60e0015160ff16815250610ad6565b5060c089018190526101608901516101ad955093508992509050611040565b86602001876040018860600183815250838152508381525050505061021b818460405180608001604052808861016001518152602001886040015181526020018860c00151815260200189610100015173ffffffffffffffffffffffffffffffffffffffff168152506110c6565b610226868487611575565b60a088015273ffffffffffffffffffffffffffffffffffffffff908116610120880152908116610100870152908116610140860181905260808701516040517f70a0823100000000000000000000000000000000000000000000000000000000815292166004830152906370a0823190602401602060405180830381865afa1580156102b6573d6000803e3d6000fd5b505050506040513d601f19601f820116820180604052508101906102da9190613988565b808552610160850151610100860151610120870151606088015160a089015160c08b015161030f968a969594939290916116a9565b60e08701526060860181905260808601919091526040850151141561035d57600382015461035d9082907501000000000000000000000000000000000000000000900461ffff166000611a09565b835160e0850151608086015161037391906139d0565b141561040b5760038301546103a89082907501000000000000000000000000000000000000000000900461ffff166000611a9e565b846080015173ffffffffffffffffffffffffffffffffffffffff16856040015173ffffffffffffffffffffffffffffffffffffffff167f44c58d81365b66dd4b1a7f36c25aa97b8c71c361ee4937adc1a00000227db5dd60405160405180910390a35b6104158585611b27565b6101608401516060808701519086015161043492859290916000611db8565b61044a89898387610160015188606001516120f9565b8460a001511561046757610462898989868989612301565b610472565b6104728386866124f2565b60e08401511561067c576000610487846125ca565b905060006104a2828760e0015161266190919063ffffffff16565b61014087015160808901516040517f1da24f3e00000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff9182166004820152929350600092911690631da24f3e90602401602060405180830381865afa15801561051f573d6000803e3d6000fd5b505050506040513d601f19601f820116820180604052508101906105439190613988565b90508082111561055d5761055781846126a0565b60e08801525b86610140015173ffffffffffffffffffffffffffffffffffffffff1663f866c319896080015189610140015173ffffffffffffffffffffffffffffffffffffffff1663ae1673356040518163ffffffff1660e01b8152600401602060405180830381865afa1580156105d3573d6000803e3d6000fd5b505050506040513d601f19601f820116820180604052508101906105f791906139e8565b8a60e001516040518463ffffffff1660e01b81526004016106469392919073ffffffffffffffffffffffffffffffffffffffff9384168152919092166020820152604081019190915260600190565b600060405180830381600087803b15801561066057600080fd5b505af1158015610674573d6000803e3d6000fd5b505050505050505b6106bb338561016001516101e001518660600151886060015173ffffffffffffffffffffffffffffffffffffffff166126f7909392919063ffffffff16565b6101608401516101e00151608086015160608601516040517f6fd9767600000000000000000000000000000000000000000000000000000000815233600482015273ffffffffffffffffffffffffffffffffffffffff92831660248201526044810191909152911690636fd9767690606401600060405180830381600087803b15801561074757600080fd5b505af115801561075b573d6000803e3d6000fd5b50505050846080015173ffffffffffffffffffffffffffffffffffffffff16856060015173ffffffffffffffffffffffffffffffffffffffff16866040015173ffffffffffffffffffffffffffffffffffffffff167fe413a321e8681d831f4dbccbca790d2952b56f977908e45be37335533e00528687606001518860800151338b60a0015160405161081f9493929190938452602084019290925273ffffffffffffffffffffffffffffffffffffffff1660408301521515606082015260800190565b60405180910390a4505050505050505050565b61083a613722565b610842613722565b60408051602081018252845481526101c0830181905251901c61ffff166101a082015260018301546fffffffffffffffffffffffffffffffff808216610100840181905260e0840152600285015480821661014085018190526101208501527001000000000000000000000000000000009283900482166101608501528290041661018083015260048085015473ffffffffffffffffffffffffffffffffffffffff9081166101e085015260058601548116610200850152600686015416610220840181905260038601549290920464ffffffffff16610240840152604080517fb1bf962d000000000000000000000000000000000000000000000000000000008152905163b1bf962d928281019260209291908290030181865afa15801561096f573d6000803e3d6000fd5b505050506040513d601f19601f820116820180604052508101906109939190613988565b816020018181525081600001818152505080610200015173ffffffffffffffffffffffffffffffffffffffff1663797743386040518163ffffffff1660e01b8152600401608060405180830381865afa1580156109f4573d6000803e3d6000fd5b505050506040513d601f19601f82011682018060405250810190610a189190613a05565b64ffffffffff166102608501526060840181905260808401829052604084019290925260c083015260a082015292915050565b60038201544264ffffffffff908116700100000000000000000000000000000000909204161415610a7a575050565b610a8482826127d2565b610a8e82826128f4565b5060030180547fffffffffffffffffffffff0000000000ffffffffffffffffffffffffffffffff167001000000000000000000000000000000004264ffffffffff1602179055565b600080600080600080610aec8760000151511590565b15610b285750600094508493508392508291507fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff905081611033565b610bd760405180610260016040528060008152602001600081526020016000815260200160008152602001600081526020016000815260200160008152602001600081526020016000815260200160008152602001600081526020016000815260200160008152602001600081526020016000815260200160008152602001600073ffffffffffffffffffffffffffffffffffffffff1681526020016000151581526020016000151581525090565b608088015160ff1615610c1c57608088015160ff16600090815260208a9052604090206060890151610c099190612a74565b6101808401526101c08301526101a08201525b87602001518160c001511015610f3b5760c08101518851610c3c91612b53565b610c505760c0810180516001019052610c1c565b60c0810151600090815260208b9052604090205473ffffffffffffffffffffffffffffffffffffffff166102008201819052610c965760c0810180516001019052610c1c565b61020081015173ffffffffffffffffffffffffffffffffffffffff16600090815260208c8152604091829020825180830190935280549283905260ff60a884901c81166101e0860152603084901c166060850181905261ffff601085901c811660a08701529093166080850152600a9290920a9083015261018082015115801590610d2c5750816101e00151896080015160ff16145b610dd05760608901516102008301516040517fb3596f0700000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff918216600482015291169063b3596f0790602401602060405180830381865afa158015610da7573d6000803e3d6000fd5b505050506040513d601f19601f82011682018060405250810190610dcb9190613988565b610dd7565b8161018001515b825260a082015115801590610df7575060c08201518951610df791612bdb565b15610ee757610e1489604001518284600001518560200151612c5f565b6040830181905261010083018051610e2d9083906139d0565b90525060808901516101e0830151610e489160ff1690612d3a565b1515610240830152608082015115610e9e57816102400151610e6e578160800151610e75565b816101a001515b8260400151610e849190613a50565b8261014001818151610e9691906139d0565b905250610ea7565b60016102208301525b816102400151610ebb578160a00151610ec2565b816101c001515b8260400151610ed19190613a50565b8261016001818151610ee391906139d0565b9052505b60c08201518951610ef791612d4b565b15610f2a57610f1489604001518284600001518560200151612dcd565b8261012001818151610f2691906139d0565b9052505b5060c0810180516001019052610c1c565b610100810151610f4c576000610f67565b80610100015181610140015181610f6557610f65613a8d565b045b610140820152610100810151610f7e576000610f99565b80610100015181610160015181610f9757610f97613a8d565b045b61016082015261012081015115610fdb57610fd6816101200151610fd0836101600151846101000151612f4d90919063ffffffff16565b90612f90565b610ffd565b7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff5b60e0820181905261010082015161012083015161014084015161016085015161022090950151929a509098509650919450925090505b9499939850945094509450565b6000806000806000611056876080015189612fc7565b9092509050600061106782846139d0565b90506000670d2f13f7789f0000881161108257612710611086565b6113885b905060006110948383612f4d565b90506000818b60200151116110ad578a602001516110af565b815b949850929650929450505050505b93509350939050565b6040805160a08101825260008082526020820181905291810182905260608101829052608081019190915260408051602081019091528354815261114c9051670100000000000000811615159167020000000000000082161515916704000000000000008116151591670800000000000000821615159167100000000000000016151590565b1515602086015250505015801580835283516101c0015151671000000000000000811615156060850152670100000000000000161515604084015290611193575080604001515b6040518060400160405280600281526020017f32370000000000000000000000000000000000000000000000000000000000008152509061120a576040517f08c379a00000000000000000000000000000000000000000000000000000000081526004016112019190613abc565b60405180910390fd5b50806020015115801561121f57508060600151155b6040518060400160405280600281526020017f32390000000000000000000000000000000000000000000000000000000000008152509061128d576040517f08c379a00000000000000000000000000000000000000000000000000000000081526004016112019190613abc565b50606082015173ffffffffffffffffffffffffffffffffffffffff1615806112c05750670d2f13f7789f00008260400151105b806113395750816060015173ffffffffffffffffffffffffffffffffffffffff16637a5d20ea6040518163ffffffff1660e01b8152600401602060405180830381865afa158015611315573d6000803e3d6000fd5b505050506040513d601f19601f820116820180604052508101906113399190613b2f565b6040518060400160405280600281526020017f3539000000000000000000000000000000000000000000000000000000000000815250906113a7576040517f08c379a00000000000000000000000000000000000000000000000000000000081526004016112019190613abc565b50670de0b6b3a76400008260400151106040518060400160405280600281526020017f343500000000000000000000000000000000000000000000000000000000000081525090611425576040517f08c379a00000000000000000000000000000000000000000000000000000000081526004016112019190613abc565b50604080516020810190915283549081905260101c61ffff161580159061148157506003830154604080516020810190915285548152611481917501000000000000000000000000000000000000000000900461ffff16612bdb565b15156080820181905260408051808201909152600281527f34360000000000000000000000000000000000000000000000000000000000006020820152906114f6576040517f08c379a00000000000000000000000000000000000000000000000000000000081526004016112019190613abc565b508160200151600014156040518060400160405280600281526020017f34370000000000000000000000000000000000000000000000000000000000008152509061156e576040517f08c379a00000000000000000000000000000000000000000000000000000000081526004016112019190613abc565b5050505050565b6004820154604080516020808201835285549182905291840151606085015160e086015160009586958695869573ffffffffffffffffffffffffffffffffffffffff90931694911c61ffff169260ff16156116985760e08901805160ff908116600090815260208e815260409182902054935182519182019092528d5490819052660100000000000090930473ffffffffffffffffffffffffffffffffffffffff169261162c929182169160a89190911c16612d3a565b156116765760e08a015160ff16600090815260208d90526040902054640100000000900461ffff16935073ffffffffffffffffffffffffffffffffffffffff811615611676578092505b73ffffffffffffffffffffffffffffffffffffffff811615611696578091505b505b929a90995091975095509350505050565b6000806000611719604051806101a00160405280600081526020016000815260200160008152602001600081526020016000815260200160008152602001600081526020016000815260200160008152602001600081526020016000815260200160008152602001600081525090565b6040517fb3596f0700000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff8b8116600483015286169063b3596f0790602401602060405180830381865afa158015611785573d6000803e3d6000fd5b505050506040513d601f19601f820116820180604052508101906117a99190613988565b81526040517fb3596f0700000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff8a8116600483015286169063b3596f0790602401602060405180830381865afa158015611817573d6000803e3d6000fd5b505050506040513d601f19601f8201168201806040525081019061183b9190613988565b6020828101919091526040805191820190528c549081905260301c60ff1660c08201526101c08b01515160301c60ff1660a0820181905260c0820151600a90810a60e08401520a61010082015260408051602081019091528c549081905260981c61ffff1661016082015261010081015181516118b89190613a50565b8160e001518983602001516118cd9190613a50565b6118d79190613a50565b6118e19190613b4c565b606082018190526118f29087612f4d565b6040820181905287101561195f57610120810187905260e081015160208201516119549188916119229190613a50565b610100840151610120850151855161193a9190613a50565b6119449190613a50565b61194e9190613b4c565b90613104565b610140820152611973565b604081015161012082015261014081018890525b610160810151156119e55761012081015161198e9087613104565b81610120015161199e9190613b87565b608082018190526101608201516119b59190612f4d565b61018082018190526101208201516119cd9190613b87565b816101400151826101800151935093509350506119fb565b8061012001518161014001516000935093509350505b985098509895505050505050565b60408051808201909152600281527f3734000000000000000000000000000000000000000000000000000000000000602082015260808310611a78576040517f08c379a00000000000000000000000000000000000000000000000000000000081526004016112019190613abc565b50600182811b1b8115611a9057835481178455611a98565b835481191684555b50505050565b60408051808201909152600281527f3734000000000000000000000000000000000000000000000000000000000000602082015260808310611b0d576040517f08c379a00000000000000000000000000000000000000000000000000000000081526004016112019190613abc565b50600182811b81011b8115611a9057835481178455611a98565b8060600151816020015110611bff5761016081015161022081015160808401516060840151610140909301516040517ff5298aca00000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff928316600482015260248101949094526044840152169063f5298aca906064016020604051808303816000875af1158015611bcd573d6000803e3d6000fd5b505050506040513d601f19601f82011682018060405250810190611bf19190613988565b610160820151602001525050565b602081015115611ccf5761016081015161022081015160808401516020840151610140909301516040517ff5298aca00000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff928316600482015260248101949094526044840152169063f5298aca906064016020604051808303816000875af1158015611ca0573d6000803e3d6000fd5b505050506040513d601f19601f82011682018060405250810190611cc49190613988565b610160820151602001525b806101600151610200015173ffffffffffffffffffffffffffffffffffffffff16639dc29fac836080015183602001518460600151611d0e9190613b87565b6040517fffffffff0000000000000000000000000000000000000000000000000000000060e085901b16815273ffffffffffffffffffffffffffffffffffffffff9092166004830152602482015260440160408051808303816000875af1158015611d7d573d6000803e3d6000fd5b505050506040513d601f19601f82011682018060405250810190611da19190613b9e565b61016083015160a081019190915260c001525b5050565b611de36040518060800160405280600081526020016000815260200160008152602001600081525090565b6101408501516020860151611df7916126a0565b60608083019182526007880154604080516101208101825260088b01546fffffffffffffffffffffffffffffffff7001000000000000000000000000000000009091041681526020810188905280820187905260c0808b0151948201949094529351608085015260a0808a0151908501526101a08901519284019290925273ffffffffffffffffffffffffffffffffffffffff87811660e08501526101e0890151811661010085015291517fa589870900000000000000000000000000000000000000000000000000000000815291169163a589870991611f589190600401600061012082019050825182526020830151602083015260408301516040830152606083015160608301526080830151608083015260a083015160a083015260c083015160c083015260e083015173ffffffffffffffffffffffffffffffffffffffff80821660e0850152610100915080828601511682850152505092915050565b606060405180830381865afa158015611f75573d6000803e3d6000fd5b505050506040513d601f19601f82011682018060405250810190611f999190613bc2565b60408401526020830152808252611faf9061312f565b6001870180546fffffffffffffffffffffffffffffffff9283167001000000000000000000000000000000000292169190911790556020810151611ff29061312f565b6003870180547fffffffffffffffffffffffffffffffff00000000000000000000000000000000166fffffffffffffffffffffffffffffffff9290921691909117905560408101516120439061312f565b6002870180546fffffffffffffffffffffffffffffffff92831670010000000000000000000000000000000002921691909117905580516020808301516040808501516101008a01516101408b0151835196875294860193909352908401526060830152608082015273ffffffffffffffffffffffffffffffffffffffff8516907f804c9b842b2748a22bb64b345453a3de7ca54a6ca45ce00d415894979e22897a9060a00160405180910390a2505050505050565b60408051602081019091528354815260009081906121189088886131d5565b509150915081156122f85773ffffffffffffffffffffffffffffffffffffffff81166000908152602088905260408120600901546101c0860151516fffffffffffffffffffffffffffffffff909116919061219a9060029060301c60ff166121809190613b87565b61218b90600a613d10565b6121959087613b4c565b61312f565b9050806fffffffffffffffffffffffffffffffff16826fffffffffffffffffffffffffffffffff161161224a5773ffffffffffffffffffffffffffffffffffffffff8316600081815260208b8152604080832060090180547fffffffffffffffffffffffffffffffff00000000000000000000000000000000169055519182527faef84d3b40895fd58c561f3998000f0583abb992a52fbdc99ace8e8de4d676a5910160405180910390a26122f5565b60006122568284613d1c565b73ffffffffffffffffffffffffffffffffffffffff8516600081815260208d815260409182902060090180547fffffffffffffffffffffffffffffffff00000000000000000000000000000000166fffffffffffffffffffffffffffffffff959095169485179055905183815292935090917faef84d3b40895fd58c561f3998000f0583abb992a52fbdc99ace8e8de4d676a5910160405180910390a2505b50505b50505050505050565b6101408101516040517f70a0823100000000000000000000000000000000000000000000000000000000815233600482015260009173ffffffffffffffffffffffffffffffffffffffff16906370a0823190602401602060405180830381865afa158015612373573d6000803e3d6000fd5b505050506040513d601f19601f820116820180604052508101906123979190613988565b610140830151608080860151908501516040517ff866c31900000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff92831660048201523360248201526044810191909152929350169063f866c31990606401600060405180830381600087803b15801561242057600080fd5b505af1158015612434573d6000803e3d6000fd5b5050505080600014156122f857336000908152602086815260409182902082519182019092528554815261246d9089908990849061328a565b156124e85760038501546124a19082907501000000000000000000000000000000000000000000900461ffff166001611a9e565b6040808501519051339173ffffffffffffffffffffffffffffffffffffffff16907e058a56ea94653cdf4f152d227ace22d4c00ad99e2a43f58cb7d9e3feb295f290600090a35b5050505050505050565b60006124fd84610832565b90506125098482610a4b565b60408301516080830151612524918691849190600090611db8565b610140820151608080850151908401516101008401516040517fd7020d0a00000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff93841660048201523360248201526044810192909252606482015291169063d7020d0a90608401600060405180830381600087803b1580156125b657600080fd5b505af11580156124e8573d6000803e3d6000fd5b6003810154600090700100000000000000000000000000000000900464ffffffffff1642811415612610575050600101546fffffffffffffffffffffffffffffffff1690565b6001830154612654906fffffffffffffffffffffffffffffffff8082169161264e91700100000000000000000000000000000000909104168461330e565b906126a0565b9392505050565b50919050565b600081156b033b2e3c9fd0803ce80000006002840419048411171561268557600080fd5b506b033b2e3c9fd0803ce80000009190910260028204010490565b600081157ffffffffffffffffffffffffffffffffffffffffffe6268e1b017bfe18bffffff839004841115176126d557600080fd5b506b033b2e3c9fd0803ce800000091026b019d971e4fe8401e74000000010490565b6040517f23b872dd0000000000000000000000000000000000000000000000000000000080825273ffffffffffffffffffffffffffffffffffffffff8581166004840152841660248301526044820183905290600080606483828a5af1612762573d6000803e3d6000fd5b5061276c8561334b565b61156e576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152601960248201527f475076323a206661696c6564207472616e7366657246726f6d000000000000006044820152606401611201565b610160810151156128625760006127f382610160015183610240015161330e565b905061280c8260e00151826126a090919063ffffffff16565b610100830181905261281d9061312f565b6001840180547fffffffffffffffffffffffffffffffff00000000000000000000000000000000166fffffffffffffffffffffffffffffffff92909216919091179055505b805115611db457600061287f826101800151836102400151613415565b9050612899826101200151826126a090919063ffffffff16565b61014083018190526128aa9061312f565b6002840180546fffffffffffffffffffffffffffffffff929092167fffffffffffffffffffffffffffffffff00000000000000000000000000000000909216919091179055505050565b61292d6040518060c001604052806000815260200160008152602001600081526020016000815260200160008152602001600081525090565b6101a082015161293c57505050565b610120820151825161294d916126a0565b60208201526101408201518251612963916126a0565b6040820152606082015161026083015161024084015161298b92919064ffffffffff1661341e565b6060820181905260408301516129a0916126a0565b8082526020820151608084015160408401516129bc91906139d0565b6129c69190613b87565b6129d09190613b87565b608082018190526101a08301516129e79190612f4d565b60a0820181905215612a6f57612a126121958361010001518360a0015161266190919063ffffffff16565b600884018054600090612a389084906fffffffffffffffffffffffffffffffff16613d4d565b92506101000a8154816fffffffffffffffffffffffffffffffff02191690836fffffffffffffffffffffffffffffffff1602179055505b505050565b81546000908190819081906601000000000000900473ffffffffffffffffffffffffffffffffffffffff168015612b38576040517fb3596f0700000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff828116600483015287169063b3596f0790602401602060405180830381865afa158015612b11573d6000803e3d6000fd5b505050506040513d601f19601f82011682018060405250810190612b359190613988565b91505b50945461ffff80821697620100009092041695945092505050565b60408051808201909152600281527f3734000000000000000000000000000000000000000000000000000000000000602082015260009060808310612bc5576040517f08c379a00000000000000000000000000000000000000000000000000000000081526004016112019190613abc565b50508151600182901b1c60031615155b92915050565b60408051808201909152600281527f3734000000000000000000000000000000000000000000000000000000000000602082015260009060808310612c4d576040517f08c379a00000000000000000000000000000000000000000000000000000000081526004016112019190613abc565b50509051600191821b82011c16151590565b600080612c6b856125ca565b6004868101546040517f1da24f3e00000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff8a8116938201939093529293506000928792612d11928692911690631da24f3e90602401602060405180830381865afa158015612ced573d6000803e3d6000fd5b505050506040513d601f19601f8201168201806040525081019061264e9190613988565b612d1b9190613a50565b9050838181612d2c57612d2c613a8d565b04925050505b949350505050565b600082158015906126545750501490565b60408051808201909152600281527f3734000000000000000000000000000000000000000000000000000000000000602082015260009060808310612dbd576040517f08c379a00000000000000000000000000000000000000000000000000000000081526004016112019190613abc565b50509051600191821b1c16151590565b60068301546040517f1da24f3e00000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff86811660048301526000928392911690631da24f3e90602401602060405180830381865afa158015612e43573d6000803e3d6000fd5b505050506040513d601f19601f82011682018060405250810190612e679190613988565b90508015612e8557612e82612e7b86613565565b82906126a0565b90505b60058501546040517f70a0823100000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff8881166004830152909116906370a0823190602401602060405180830381865afa158015612ef7573d6000803e3d6000fd5b505050506040513d601f19601f82011682018060405250810190612f1b9190613988565b612f2590826139d0565b9050612f318185613a50565b9050828181612f4257612f42613a8d565b049695505050505050565b600081157fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffec7783900484111517612f8257600080fd5b506127109102611388010490565b60008115670de0b6b3a764000060028404190484111715612fb057600080fd5b50670de0b6b3a76400009190910260028204010490565b6102008101516040517f70a0823100000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff848116600483015260009283929116906370a0823190602401602060405180830381865afa15801561303e573d6000803e3d6000fd5b505050506040513d601f19601f820116820180604052508101906130629190613988565b6102208401516040517f70a0823100000000000000000000000000000000000000000000000000000000815273ffffffffffffffffffffffffffffffffffffffff8781166004830152909116906370a0823190602401602060405180830381865afa1580156130d5573d6000803e3d6000fd5b505050506040513d601f19601f820116820180604052508101906130f99190613988565b915091509250929050565b600081156127106002840419048411171561311e57600080fd5b506127109190910260028204010490565b60006fffffffffffffffffffffffffffffffff8211156131d1576040517f08c379a000000000000000000000000000000000000000000000000000000000815260206004820152602760248201527f53616665436173743a2076616c756520646f65736e27742066697420696e203160448201527f32382062697473000000000000000000000000000000000000000000000000006064820152608401611201565b5090565b60008060006131e3866135e9565b1561327a576000613214877faaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa61362d565b6000818152602087815260408083205473ffffffffffffffffffffffffffffffffffffffff168084528a8352818420825193840190925290549182905292935060d41c64ffffffffff1690508015613276576001955090935091506110bd9050565b5050505b5060009586955085945092505050565b60408051602081019091528254908190526000907faaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa166132cc57506001612d32565b6040805160208101909152835481526000906132e99087876131d5565b50509050801580156133045750825160d41c64ffffffffff16155b9695505050505050565b60008061332264ffffffffff841642613b87565b61332c9085613a50565b6301e1338090049050612d32816b033b2e3c9fd0803ce80000006139d0565b600061338b565b7f08c379a00000000000000000000000000000000000000000000000000000000060005260206004528060245250806044525060646000fd5b3d80156133ca5760208114613404576133c57f475076323a206d616c666f726d6564207472616e7366657220726573756c7400601f613352565b61265b565b823b6133fb576133fb7f475076323a206e6f74206120636f6e74726163740000000000000000000000006014613352565b6001915061265b565b3d6000803e50506000511515919050565b60006126548383425b60008061343264ffffffffff851684613b87565b90508061344e576b033b2e3c9fd0803ce8000000915050612654565b7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff81016000808060028511613484576000613489565b600285035b925066038882915c400061349d8a806126a0565b816134aa576134aa613a8d565b0491506301e133806134bc838b6126a0565b816134c9576134c9613a8d565b0490506000826134d98688613a50565b6134e39190613a50565b600290049050600082856134f7888a613a50565b6135019190613a50565b61350b9190613a50565b60069004905080826301e133806135228a8f613a50565b61352c9190613b4c565b613542906b033b2e3c9fd0803ce80000006139d0565b61354c91906139d0565b61355691906139d0565b9b9a5050505050505050505050565b6003810154600090700100000000000000000000000000000000900464ffffffffff16428114156135ab575050600201546fffffffffffffffffffffffffffffffff1690565b6002830154612654906fffffffffffffffffffffffffffffffff8082169161264e917001000000000000000000000000000000009091041684613415565b80516000907faaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa1680158015906126545750613625600182613b87565b161592915050565b815160009082167fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff8101198116825b60029190911c9081156136715760010161365c565b95945050505050565b6040518061018001604052806000815260200160008152602001600081526020016000815260200160008152602001600081526020016000815260200160008152602001600073ffffffffffffffffffffffffffffffffffffffff168152602001600073ffffffffffffffffffffffffffffffffffffffff168152602001600073ffffffffffffffffffffffffffffffffffffffff16815260200161371d613722565b905290565b60405180610280016040528060008152602001600081526020016000815260200160008152602001600081526020016000815260200160008152602001600081526020016000815260200160008152602001600081526020016000815260200160008152602001600081526020016137a66040518060200160405280600081525090565b815260006020820181905260408201819052606082018190526080820181905260a09091015290565b604051610120810167ffffffffffffffff8111828210171561381a577f4e487b7100000000000000000000000000000000000000000000000000000000600052604160045260246000fd5b60405290565b73ffffffffffffffffffffffffffffffffffffffff8116811461384257600080fd5b50565b803561385081613820565b919050565b801515811461384257600080fd5b803561385081613855565b803560ff8116811461385057600080fd5b60008060008060008587036101a081121561389957600080fd5b86359550602087013594506040870135935060608701359250610120807fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff80830112156138e457600080fd5b6138ec6137cf565b91506080880135825260a0880135602083015261390b60c08901613845565b604083015261391c60e08901613845565b606083015261010061392f818a01613845565b608084015261393f828a01613863565b60a08401526139516101408a01613845565b60c08401526139636101608a0161386e565b60e08401526139756101808a01613845565b9083015250949793965091945092919050565b60006020828403121561399a57600080fd5b5051919050565b7f4e487b7100000000000000000000000000000000000000000000000000000000600052601160045260246000fd5b600082198211156139e3576139e36139a1565b500190565b6000602082840312156139fa57600080fd5b815161265481613820565b60008060008060808587031215613a1b57600080fd5b845193506020850151925060408501519150606085015164ffffffffff81168114613a4557600080fd5b939692955090935050565b6000817fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0483118215151615613a8857613a886139a1565b500290565b7f4e487b7100000000000000000000000000000000000000000000000000000000600052601260045260246000fd5b600060208083528351808285015260005b81811015613ae957858101830151858201604001528201613acd565b81811115613afb576000604083870101525b50601f017fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe016929092016040019392505050565b600060208284031215613b4157600080fd5b815161265481613855565b600082613b82577f4e487b7100000000000000000000000000000000000000000000000000000000600052601260045260246000fd5b500490565b600082821015613b9957613b996139a1565b500390565b60008060408385031215613bb157600080fd5b505080516020909101519092909150565b600080600060608486031215613bd757600080fd5b8351925060208401519150604084015190509250925092565b600181815b80851115613c4957817fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff04821115613c2f57613c2f6139a1565b80851615613c3c57918102915b93841c9390800290613bf5565b509250929050565b600082613c6057506001612bd5565b81613c6d57506000612bd5565b8160018114613c835760028114613c8d57613ca9565b6001915050612bd5565b60ff841115613c9e57613c9e6139a1565b50506001821b612bd5565b5060208310610133831016604e8410600b8410161715613ccc575081810a612bd5565b613cd68383613bf0565b807fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff04821115613d0857613d086139a1565b029392505050565b60006126548383613c51565b60006fffffffffffffffffffffffffffffffff83811690831681811015613d4557613d456139a1565b039392505050565b60006fffffffffffffffffffffffffffffffff808316818516808303821115613d7857613d786139a1565b0194935050505056fea2646970667358221220928f7bc9e98bcbbad290d101af011047beeccbff048ab99fbfe01771761dd30d64736f6c634300080a0033";
 
 pub const MOCK_AGGREGATOR: &str = "608060405234801561001057600080fd5b50604051610385380380610385833981810160405281019061003291906100a8565b8060008190555080817f2aeb329cb9e8ae2f807e4c7661e9e511d462d317910a687d5431122fcdb8f05160405160405180910390a3506100d5565b600080fd5b6000819050919050565b61008581610072565b811461009057600080fd5b50565b6000815190506100a28161007c565b92915050565b6000602082840312156100be576100bd61006d565b5b60006100cc84828501610093565b91505092915050565b6102a1806100e46000396000f3fe608060405234801561001057600080fd5b506004361061004c5760003560e01c8063313ce567146100515780635093dc7d1461006f57806350d25bcd1461009f578063fcab1819146100bd575b600080fd5b6100596100db565b6040516100669190610154565b60405180910390f35b610089600480360381019061008491906101aa565b6100e4565b60405161009691906101f2565b60405180910390f35b6100a7610126565b6040516100b4919061021c565b60405180910390f35b6100c561012f565b6040516100d29190610250565b60405180910390f35b60006008905090565b6000816000547f2aeb329cb9e8ae2f807e4c7661e9e511d462d317910a687d5431122fcdb8f05160405160405180910390a38160008190555060019050919050565b60008054905090565b60006001905090565b600060ff82169050919050565b61014e81610138565b82525050565b60006020820190506101696000830184610145565b92915050565b600080fd5b6000819050919050565b61018781610174565b811461019257600080fd5b50565b6000813590506101a48161017e565b92915050565b6000602082840312156101c0576101bf61016f565b5b60006101ce84828501610195565b91505092915050565b60008115159050919050565b6101ec816101d7565b82525050565b600060208201905061020760008301846101e3565b92915050565b61021681610174565b82525050565b6000602082019050610231600083018461020d565b92915050565b6000819050919050565b61024a81610237565b82525050565b60006020820190506102656000830184610241565b9291505056fea264697066735822122036b72b4f960cb6dbc402d1dfb887b1568796494592f51ccfa75382048204cb9c64736f6c634300080a0033";
@@ -8,6 +8,7 @@ use verbs_rs::LocalDB;
 use super::deploy_periphery::{deploy_periphery_contracts, PeripheryAddresses};
 use super::deploy_uniswap::{deploy_uniswap_contracts, UniswapAddresses};
 use super::periphery_bytecode;
+use crate::aave::types::{EModeCategory, ReserveLimits};
 
 pub struct AaveAddresses {
     pub data_provider: Address,
@@ -15,6 +16,8 @@ pub struct AaveAddresses {
     pub token_b_oracle: Address,
     pub pool: Address,
     pub oracle: Address,
+    pub pool_configurator: Address,
+    pub acl_manager: Address,
 }
 
 pub fn deploy_aave_contracts<V: Validator>(
@@ -24,6 +27,8 @@ pub fn deploy_aave_contracts<V: Validator>(
     token_b_liquidation_threshold: u128,
     token_a_base_ltv: u128,
     token_b_base_ltv: u128,
+    e_mode_category: &EModeCategory,
+    reserve_limits: &ReserveLimits,
     uniswap_fee: u32,
 ) -> (
     Env<LocalDB, V>,
@@ -926,8 +931,8 @@ pub fn deploy_aave_contracts<V: Validator>(
                     liquidationThreshold: Uint::from(token_a_liquidation_threshold),
                     liquidationBonus: Uint::from(10500u128),
                     reserveFactor: Uint::from(1000u128),
-                    borrowCap: Uint::from(0u128),
-                    supplyCap: Uint::from(0u128),
+                    borrowCap: Uint::from(reserve_limits.token_a.borrow_cap),
+                    supplyCap: Uint::from(reserve_limits.token_a.supply_cap),
                     stableBorrowingEnabled: true,
                     borrowingEnabled: true,
                     flashLoanEnabled: true,
@@ -938,8 +943,8 @@ pub fn deploy_aave_contracts<V: Validator>(
                     liquidationThreshold: Uint::from(token_b_liquidation_threshold),
                     liquidationBonus: Uint::from(10500u128),
                     reserveFactor: Uint::from(1000u128),
-                    borrowCap: Uint::from(0u128),
-                    supplyCap: Uint::from(0u128),
+                    borrowCap: Uint::from(reserve_limits.token_b.borrow_cap),
+                    supplyCap: Uint::from(reserve_limits.token_b.supply_cap),
                     stableBorrowingEnabled: true,
                     borrowingEnabled: true,
                     flashLoanEnabled: true,
@@ -1009,16 +1014,30 @@ pub fn deploy_aave_contracts<V: Validator>(
     )
     .unwrap();
 
+    // The debt ceiling can only be set before the reserve has suppliers
+    if reserve_limits.token_a_debt_ceiling > 0. {
+        env.direct_execute(
+            admin_address,
+            pool_configurator_proxy_address,
+            aave_abi::PoolConfigurator_Implementation::setDebtCeilingCall {
+                asset: periphery_addresses.token_a,
+                newDebtCeiling: U256::from((reserve_limits.token_a_debt_ceiling * 100.) as u128),
+            },
+            U256::ZERO,
+        )
+        .unwrap();
+    }
+
     env.direct_execute(
         admin_address,
         pool_configurator_proxy_address,
         aave_abi::PoolConfigurator_Implementation::setEModeCategoryCall {
-            categoryId: 1u8,
-            ltv: 9800u16,
-            liquidationThreshold: 9850u16,
-            liquidationBonus: 10100u16,
+            categoryId: e_mode_category.id,
+            ltv: e_mode_category.ltv,
+            liquidationThreshold: e_mode_category.liquidation_threshold,
+            liquidationBonus: e_mode_category.liquidation_bonus,
             oracle: Address::ZERO,
-            label: "Stable-EMode".to_string(),
+            label: e_mode_category.label.clone(),
         },
         U256::ZERO,
     )
@@ -1029,7 +1048,7 @@ pub fn deploy_aave_contracts<V: Validator>(
         pool_configurator_proxy_address,
         aave_abi::PoolConfigurator_Implementation::setAssetEModeCategoryCall {
             asset: periphery_addresses.token_a,
-            newCategoryId: 1u8,
+            newCategoryId: e_mode_category.id,
         },
         U256::ZERO,
     )
@@ -1040,7 +1059,7 @@ pub fn deploy_aave_contracts<V: Validator>(
         pool_configurator_proxy_address,
         aave_abi::PoolConfigurator_Implementation::setAssetEModeCategoryCall {
             asset: periphery_addresses.token_b,
-            newCategoryId: 1u8,
+            newCategoryId: e_mode_category.id,
         },
         U256::ZERO,
     )
@@ -1108,6 +1127,8 @@ pub fn deploy_aave_contracts<V: Validator>(
             token_b_oracle: token_b_aggregator_address,
             pool: pool_proxy_address,
             oracle: oracle_address,
+            pool_configurator: pool_configurator_proxy_address,
+            acl_manager: acl_manager_address,
         },
     )
 }
@@ -1,34 +1,1322 @@
+use alloy_primitives::{Address, FixedBytes, Log, I256, U256};
+use rand::Rng;
+use rand_distr::{Distribution, LogNormal, Normal, Pareto, Uniform};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::str::FromStr;
+
 pub type UserRecord = (f64, f64, f64, f64, f64, f64);
 pub type UserData = Vec<UserRecord>;
+/// Health factor of a borrower, and its stable and variable debt of
+/// the token it borrows
+pub type BorrowerRecord = (U256, U256, U256);
+/// Lower tick, upper tick and liquidity of the position of a liquidity
+/// provider, and the cumulative fees it has collected in token0 and token1
+pub type PositionRecord = (i32, i32, u128, U256, U256);
+
+/// Strategy used by liquidity providers to place their position
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LiquidityStrategy {
+    /// Position over the full range of prices, that is never moved
+    FullRange,
+    /// Position `width` tick spacings either side of the price when it
+    /// is minted, that is never moved
+    FixedWidth { width: i32 },
+    /// Position `width` tick spacings either side of the price, that
+    /// is burnt and minted around the current price when the price
+    /// leaves its range
+    RebalanceOnExit { width: i32 },
+}
+
+/// Band of constant liquidity between two ticks
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LiquidityBand {
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    /// Liquidity of the band, in the same units as [SimParameters::liquidity]
+    pub liquidity: f64,
+}
+
+/// Distribution of the liquidity minted in the Uniswap pool at initialisation
+#[derive(Clone, Debug, PartialEq)]
+pub enum LiquidityProfile {
+    /// Constant liquidity `width` tick spacings either side of the
+    /// initial price
+    Uniform { width: i32 },
+    /// Liquidity following a Gaussian in ticks, centred on the initial
+    /// price, with standard deviation `sigma` tick spacings. The
+    /// Gaussian is truncated at 3 standard deviations, and approximated
+    /// by `n_bands` bands of constant liquidity either side of the price.
+    Gaussian { sigma: f64, n_bands: i32 },
+    /// Bands between fixed ticks, which should be multiples of the
+    /// tick spacing of the pool
+    Bands(Vec<LiquidityBand>),
+    /// Bands with ticks relative to the initial tick of the pool,
+    /// rounded to the nearest multiple of the tick spacing
+    RelativeBands(Vec<LiquidityBand>),
+}
+
+/// Distribution from which a parameter of an agent is drawn
+///
+/// Deserialized from externally tagged JSON, for example
+/// `{"constant": 1.1}` or `{"log_normal": {"mu": 7.0, "sigma": 1.5}}`.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParameterDistribution {
+    Constant(f64),
+    Uniform {
+        low: f64,
+        high: f64,
+    },
+    Normal {
+        mean: f64,
+        std: f64,
+    },
+    /// Distribution of `exp(X)` where `X` is normal with mean `mu` and
+    /// standard deviation `sigma`
+    LogNormal {
+        mu: f64,
+        sigma: f64,
+    },
+    /// Pareto distribution with minimum value `scale`, and tail index
+    /// `shape` (smaller values give heavier tails)
+    Pareto {
+        scale: f64,
+        shape: f64,
+    },
+}
+
+impl ParameterDistribution {
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> f64 {
+        match *self {
+            ParameterDistribution::Constant(x) => x,
+            ParameterDistribution::Uniform { low, high } => {
+                Uniform::new_inclusive(low, high).sample(rng)
+            }
+            ParameterDistribution::Normal { mean, std } => Normal::new(mean, std)
+                .expect("Standard deviation must be finite and non-negative")
+                .sample(rng),
+            ParameterDistribution::LogNormal { mu, sigma } => LogNormal::new(mu, sigma)
+                .expect("Standard deviation must be finite and non-negative")
+                .sample(rng),
+            ParameterDistribution::Pareto { scale, shape } => Pareto::new(scale, shape)
+                .expect("Pareto scale and shape must be positive")
+                .sample(rng),
+        }
+    }
+}
+
+/// Behaviour model of borrowers
+///
+/// Each borrower draws its own health factor thresholds from these
+/// distributions. A borrower keeps its health factor between its repay
+/// and re-leverage thresholds, restoring it to its target health factor
+/// when it leaves this range.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct BorrowerBehaviour {
+    /// Health factor below which the borrower repays debt or tops up
+    /// collateral, lower values being more risk tolerant
+    pub repay_threshold: ParameterDistribution,
+    /// Health factor the borrower borrows to, and restores its position
+    /// to, setting its target leverage
+    pub target_health_factor: ParameterDistribution,
+    /// Health factor above which the borrower withdraws collateral or borrows more
+    pub releverage_threshold: ParameterDistribution,
+    /// Probability of topping up collateral rather than repaying debt
+    pub top_up_probability: f64,
+    /// Probability of withdrawing excess collateral rather than borrowing more
+    pub withdraw_probability: f64,
+    /// Probability, each time the borrower is active, of repaying all its
+    /// debt and withdrawing its collateral
+    pub close_rate: f64,
+    /// Difference between the borrow rate the borrower pays and the rate
+    /// of the other interest rate mode (as a fraction per year) above
+    /// which it swaps its debt to the other mode, never swapping if `None`
+    pub rate_swap_spread: Option<f64>,
+}
+
+impl Default for BorrowerBehaviour {
+    fn default() -> Self {
+        BorrowerBehaviour {
+            repay_threshold: ParameterDistribution::Uniform {
+                low: 1.02,
+                high: 1.1,
+            },
+            target_health_factor: ParameterDistribution::Uniform {
+                low: 1.15,
+                high: 1.35,
+            },
+            releverage_threshold: ParameterDistribution::Uniform {
+                low: 1.5,
+                high: 2.0,
+            },
+            top_up_probability: 0.5,
+            withdraw_probability: 0.5,
+            close_rate: 0.0,
+            rate_swap_spread: None,
+        }
+    }
+}
+
+/// Distributions of the attributes of a population of borrowers
+///
+/// Can be read from a JSON file with [BorrowerPopulation::from_json],
+/// where omitted fields take their default values.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct BorrowerPopulation {
+    /// Value of the collateral initially supplied, in the base currency
+    /// of the Aave oracle (USD)
+    pub position_size: ParameterDistribution,
+    /// Probability of a borrower being active each step, i.e. the speed
+    /// at which it reacts to changes in its health factor
+    pub activation_rate: ParameterDistribution,
+    /// Probability of a borrower supplying token b as collateral and
+    /// borrowing token a, rather than the reverse
+    pub reverse_assets_probability: f64,
+    /// Probability of a borrower opting in to the e-mode category
+    /// `e_mode_category` before it supplies collateral
+    pub e_mode_probability: f64,
+    /// E-mode category borrowers opt in to
+    pub e_mode_category: u8,
+    /// Probability of a borrower borrowing at a stable rather than a
+    /// variable rate
+    pub stable_rate_probability: f64,
+    pub behaviour: BorrowerBehaviour,
+}
+
+impl Default for BorrowerPopulation {
+    fn default() -> Self {
+        BorrowerPopulation {
+            position_size: ParameterDistribution::LogNormal {
+                mu: 1000f64.ln(),
+                sigma: 1.0,
+            },
+            activation_rate: ParameterDistribution::Uniform {
+                low: 0.05,
+                high: 0.15,
+            },
+            reverse_assets_probability: 0.0,
+            e_mode_probability: 0.0,
+            e_mode_category: 1,
+            stable_rate_probability: 0.0,
+            behaviour: BorrowerBehaviour::default(),
+        }
+    }
+}
+
+/// Attributes of an individual borrower, drawn from a [BorrowerPopulation]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BorrowerAttributes {
+    pub position_size: f64,
+    pub activation_rate: f64,
+    /// Whether the borrower supplies token b and borrows token a
+    pub reverse_assets: bool,
+    /// E-mode category of the borrower, 0 if it does not opt in
+    pub e_mode: u8,
+    /// Interest rate mode the borrower opens its debt in
+    pub rate_mode: InterestRateMode,
+    pub repay_threshold: f64,
+    pub target_health_factor: f64,
+    pub releverage_threshold: f64,
+}
+
+impl BorrowerPopulation {
+    /// Read a population from a JSON string
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Draw the attributes of a borrower
+    ///
+    /// Thresholds are ordered so that repay <= target <= re-leverage.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> BorrowerAttributes {
+        let position_size = self.position_size.sample(rng);
+        let activation_rate = self.activation_rate.sample(rng);
+        let reverse_assets = rng.gen::<f64>() < self.reverse_assets_probability;
+        // Only drawn if borrowers can opt in, leaving the random stream
+        // of populations without e-mode unchanged
+        let e_mode =
+            match self.e_mode_probability > 0. && rng.gen::<f64>() < self.e_mode_probability {
+                true => self.e_mode_category,
+                false => 0,
+            };
+        let rate_mode = match self.stable_rate_probability > 0.
+            && rng.gen::<f64>() < self.stable_rate_probability
+        {
+            true => InterestRateMode::Stable,
+            false => InterestRateMode::Variable,
+        };
+        let repay_threshold = self.behaviour.repay_threshold.sample(rng);
+        let target_health_factor = self
+            .behaviour
+            .target_health_factor
+            .sample(rng)
+            .max(repay_threshold);
+        let releverage_threshold = self
+            .behaviour
+            .releverage_threshold
+            .sample(rng)
+            .max(target_health_factor);
+
+        BorrowerAttributes {
+            position_size,
+            activation_rate,
+            reverse_assets,
+            e_mode,
+            rate_mode,
+            repay_threshold,
+            target_health_factor,
+            releverage_threshold,
+        }
+    }
+}
+
+/// Interest rate mode of Aave debt
+///
+/// Stable debt accrues interest at the stable rate of the reserve
+/// when it was borrowed, until the user swaps it to the variable
+/// rate or it is rebalanced. Variable debt accrues interest at the
+/// current variable rate of the reserve.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterestRateMode {
+    Stable = 1,
+    #[default]
+    Variable = 2,
+}
+
+impl InterestRateMode {
+    /// The other interest rate mode
+    pub fn other(self) -> Self {
+        match self {
+            InterestRateMode::Stable => InterestRateMode::Variable,
+            InterestRateMode::Variable => InterestRateMode::Stable,
+        }
+    }
+}
+
+impl From<InterestRateMode> for U256 {
+    fn from(mode: InterestRateMode) -> Self {
+        U256::from(mode as u8)
+    }
+}
+
+/// Position of a user of the live Aave protocol, exported at a block
+///
+/// Collateral and debt are given as values in the base currency of
+/// the Aave oracle (USD), so that positions mirrored in the local
+/// deployment, where there is a single pair of tokens, have the same
+/// health factors as the live positions (given the same liquidation
+/// thresholds).
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub struct BorrowerPosition {
+    #[serde(deserialize_with = "from_str_or_number")]
+    pub user: Address,
+    pub collateral: f64,
+    pub debt: f64,
+    /// E-mode category of the user, 0 if not in e-mode
+    #[serde(default, alias = "eMode")]
+    pub e_mode: u8,
+}
+
+impl BorrowerPosition {
+    /// Read a list of positions from a JSON string
+    pub fn from_json(json: &str) -> Result<Vec<Self>, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Risk parameters of an Aave reserve, decoded from its configuration bitmap
+///
+/// Percentages are in basis points, as stored by Aave, so for example
+/// a liquidation bonus of 10500 is a bonus of 5%.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReserveConfiguration {
+    pub ltv: u64,
+    pub liquidation_threshold: u64,
+    pub liquidation_bonus: u64,
+    pub decimals: u8,
+    /// Share of the interest paid to the protocol treasury
+    pub reserve_factor: u64,
+    /// Share of the liquidation bonus paid to the protocol treasury
+    pub liquidation_protocol_fee: u64,
+    /// E-mode category of the asset, 0 if it is not in one
+    pub e_mode_category: u8,
+    /// Cap on the total borrowed, in whole tokens, 0 if uncapped
+    pub borrow_cap: u64,
+    /// Cap on the total supplied, in whole tokens, 0 if uncapped
+    pub supply_cap: u64,
+    /// Debt ceiling of the asset as isolated collateral, in the base
+    /// currency with 2 decimals, 0 if it is not isolated
+    pub debt_ceiling: u64,
+}
+
+impl ReserveConfiguration {
+    /// Decode the `ReserveConfigurationMap` of a reserve
+    pub fn from_bitmap(data: U256) -> Self {
+        let bits = |start: usize, size: usize| -> u64 {
+            ((data >> start) & ((U256::from(1) << size) - U256::from(1))).to::<u64>()
+        };
+        ReserveConfiguration {
+            ltv: bits(0, 16),
+            liquidation_threshold: bits(16, 16),
+            liquidation_bonus: bits(32, 16),
+            decimals: bits(48, 8) as u8,
+            reserve_factor: bits(64, 16),
+            liquidation_protocol_fee: bits(152, 16),
+            e_mode_category: bits(168, 8) as u8,
+            borrow_cap: bits(80, 36),
+            supply_cap: bits(116, 36),
+            debt_ceiling: bits(212, 40),
+        }
+    }
+}
+
+/// Tick of a pool, in the format returned by the Uniswap v3 subgraph
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TickData {
+    #[serde(deserialize_with = "from_str_or_number")]
+    tick_idx: i32,
+    #[serde(deserialize_with = "from_str_or_number")]
+    liquidity_net: i128,
+}
+
+/// Pool and its initialised ticks, in the format returned by the Uniswap v3 subgraph
+#[derive(Deserialize)]
+struct PoolData {
+    #[serde(deserialize_with = "from_str_or_number")]
+    tick: i32,
+    ticks: Vec<TickData>,
+}
+
+/// Deserialize a value given either as a JSON number or a string
+fn from_str_or_number<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+{
+    let value = Value::deserialize(deserializer)?;
+    let string = match value {
+        Value::String(x) => x,
+        Value::Number(x) => x.to_string(),
+        _ => return Err(serde::de::Error::custom("expected a string or number")),
+    };
+    string
+        .parse()
+        .map_err(|_| serde::de::Error::custom(format!("invalid value {}", string)))
+}
+
+impl LiquidityProfile {
+    /// Import the liquidity of a live pool
+    ///
+    /// Reads a pool in the format returned by the Uniswap v3 subgraph,
+    /// i.e. an object with the current `tick` of the pool and a list
+    /// of its `ticks`, each with `tickIdx` and `liquidityNet`. Returns
+    /// bands with ticks relative to the current tick of the pool, so
+    /// the shape of the liquidity around the price is reproduced at
+    /// the initial price of the simulation.
+    ///
+    /// Liquidity is converted from raw pool units by dividing by
+    /// 10^18, matching the 18 decimal tokens of the local deployment.
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - Pool data as a JSON string
+    ///
+    pub fn from_pool_data(json: &str) -> Result<Self, serde_json::Error> {
+        let mut pool: PoolData = serde_json::from_str(json)?;
+        pool.ticks.sort_by_key(|x| x.tick_idx);
+
+        let mut liquidity = 0i128;
+        let bands = pool
+            .ticks
+            .windows(2)
+            .filter_map(|ticks| {
+                liquidity += ticks[0].liquidity_net;
+                (liquidity > 0).then(|| LiquidityBand {
+                    tick_lower: ticks[0].tick_idx - pool.tick,
+                    tick_upper: ticks[1].tick_idx - pool.tick,
+                    liquidity: liquidity as f64 / 10f64.powi(18),
+                })
+            })
+            .collect();
+
+        Ok(LiquidityProfile::RelativeBands(bands))
+    }
+}
+
+/// E-mode category of the local deployment, to which both tokens are assigned
+///
+/// Users in the category borrow with its loan to value and liquidation
+/// threshold in place of those of their collateral, and are liquidated
+/// with its bonus. Percentages are in basis points.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EModeCategory {
+    pub id: u8,
+    pub ltv: u16,
+    pub liquidation_threshold: u16,
+    pub liquidation_bonus: u16,
+    pub label: String,
+}
+
+impl Default for EModeCategory {
+    fn default() -> Self {
+        EModeCategory {
+            id: 1,
+            ltv: 9800,
+            liquidation_threshold: 9850,
+            liquidation_bonus: 10100,
+            label: "Stable-EMode".to_string(),
+        }
+    }
+}
+
+/// Caps on the total supplied and borrowed of a reserve, in whole
+/// tokens, 0 if uncapped
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReserveCaps {
+    pub supply_cap: u64,
+    pub borrow_cap: u64,
+}
+
+/// Supply and borrow caps of the tokens of the local deployment, and
+/// the debt ceiling of token a as an isolated collateral asset
+///
+/// Users whose only collateral is an isolated asset can only borrow
+/// assets borrowable in isolation (both tokens), and the total debt
+/// of such users is capped by the debt ceiling of the asset. The
+/// default places no limits.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReserveLimits {
+    pub token_a: ReserveCaps,
+    pub token_b: ReserveCaps,
+    /// Debt ceiling of token a, in USD, token a is not isolated if 0
+    pub token_a_debt_ceiling: f64,
+}
+
+/// Process followed by the external market price of token a
+///
+/// Both processes use the `prices_mu`, `prices_sigma` and `prices_dt`
+/// of the simulation parameters.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PriceProcess {
+    /// Geometric Brownian motion
+    #[default]
+    Gbm,
+    /// Price of a token pegged to token b at the ratio of their initial
+    /// prices, such as a stablecoin or a liquid staking token. The log
+    /// deviation from the peg reverts to zero at rate `reversion`, with
+    /// volatility `prices_sigma`, and depegs occur at rate
+    /// `depeg_rate` per unit time, each dropping the price by a share
+    /// `depeg_size`.
+    Depeg {
+        reversion: f64,
+        depeg_rate: f64,
+        depeg_size: f64,
+    },
+}
+
+/// Finite inventory of liquidators, and the policy they use to manage it
+///
+/// Liquidators only cover debt they can pay for from their inventory,
+/// and in steps in which they do not liquidate, rebalance their
+/// inventory on Uniswap towards a target share of its value held in
+/// token b.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LiquidatorCapital {
+    /// Initial balance of token a of each liquidator, in whole tokens
+    pub token_a: f64,
+    /// Initial balance of token b of each liquidator, in whole tokens
+    pub token_b: f64,
+    /// Share of the value of the inventory, at oracle prices, held in token b
+    pub target_token_b_share: f64,
+    /// Deviation of the share of token b from its target above which
+    /// the liquidator rebalances
+    pub rebalance_threshold: f64,
+}
+
+/// Strategy used by liquidators to bid for priority of their liquidations
+///
+/// Bids are in the base currency of the Aave oracle (USD), and are set
+/// as the priority fee of liquidation transactions, so a validator
+/// ordering transactions by priority fee includes the highest bid
+/// first. Bids are paid on top of the cost of the gas used at the base
+/// fee, and stand for the total a liquidator pays for priority.
+///
+/// Parsed from strings of the form `fixed:<BID>`,
+/// `profit-share:<SHARE>` or `escalating:<INITIAL>:<FACTOR>:<MAX_SHARE>`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GasBidStrategy {
+    /// Constant bid
+    Fixed { bid: f64 },
+    /// Share of the expected profit of the liquidation
+    ProfitShare { share: f64 },
+    /// Bid starting at `initial`, multiplied by `factor` each time the
+    /// liquidator loses a liquidation to another, up to `max_share` of
+    /// the expected profit, and reset when it wins one
+    Escalating {
+        initial: f64,
+        factor: f64,
+        max_share: f64,
+    },
+}
+
+impl GasBidStrategy {
+    /// Bid for a liquidation
+    ///
+    /// # Arguments
+    ///
+    /// * `expected_profit` - Expected profit of the liquidation before
+    ///   the bid, in the base currency
+    /// * `losses` - Number of liquidations lost to other liquidators
+    ///   since the last one won
+    ///
+    pub fn bid(&self, expected_profit: f64, losses: u32) -> f64 {
+        match *self {
+            GasBidStrategy::Fixed { bid } => bid,
+            GasBidStrategy::ProfitShare { share } => share * expected_profit,
+            GasBidStrategy::Escalating {
+                initial,
+                factor,
+                max_share,
+            } => (initial * factor.powi(losses as i32)).min(max_share * expected_profit),
+        }
+    }
+}
+
+impl FromStr for GasBidStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let name = parts.next().unwrap_or_default();
+        let values = parts
+            .map(|x| {
+                x.parse::<f64>()
+                    .map_err(|e| format!("Invalid bid parameter {}: {}", x, e))
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        match (name, values.as_slice()) {
+            ("fixed", [bid]) => Ok(GasBidStrategy::Fixed { bid: *bid }),
+            ("profit-share", [share]) => Ok(GasBidStrategy::ProfitShare { share: *share }),
+            ("escalating", [initial, factor, max_share]) => Ok(GasBidStrategy::Escalating {
+                initial: *initial,
+                factor: *factor,
+                max_share: *max_share,
+            }),
+            _ => Err(format!("Invalid gas bid strategy {}", s)),
+        }
+    }
+}
+
+/// Process followed by the base fee of each block, in gwei
+///
+/// Gas is not charged to the ETH balances of agents, but the cost of
+/// the gas used by each transaction at the base fee is recorded in its
+/// outcome, and deducted from the realised profits of agents.
+/// Liquidators and the price agent only trade if their expected
+/// profit covers it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BaseFee {
+    /// Constant base fee
+    Constant(f64),
+    /// Base fee adjusted each block as in EIP-1559, by up to 12.5%
+    /// towards the gas target. The gas used by each block, as a share
+    /// of the target, is drawn from a normal distribution with mean 1
+    /// and standard deviation `volatility`, clamped to the block limit
+    /// of twice the target.
+    Eip1559 { initial: f64, volatility: f64 },
+}
+
+impl Default for BaseFee {
+    fn default() -> Self {
+        BaseFee::Constant(0.)
+    }
+}
+
+impl BaseFee {
+    /// Base fee of the first block, in wei
+    pub fn initial(&self) -> U256 {
+        let gwei = match *self {
+            BaseFee::Constant(x) => x,
+            BaseFee::Eip1559 { initial, .. } => initial,
+        };
+        U256::from((gwei * 1e9) as u128)
+    }
+
+    /// Base fee of the block following a block with base fee `current`, in wei
+    pub fn next<R: Rng>(&self, rng: &mut R, current: U256) -> U256 {
+        match *self {
+            BaseFee::Constant(_) => current,
+            BaseFee::Eip1559 { volatility, .. } => {
+                let utilisation = Normal::new(1., volatility)
+                    .expect("Base fee volatility must be finite and non-negative")
+                    .sample(rng)
+                    .clamp(0., 2.);
+                let current: u128 = current.to();
+                U256::from((current as f64 * (1. + (utilisation - 1.) / 8.)) as u128)
+            }
+        }
+    }
+}
 
-#[derive(Clone, Copy)]
+/// Token standing for ETH when pricing gas
+///
+/// Gas is priced in the base currency at the oracle price of the token.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GasToken {
+    #[default]
+    TokenA,
+    TokenB,
+}
+
+/// Risk parameters of a reserve, in basis points, with caps in whole tokens
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReserveParameters {
+    pub ltv: u64,
+    pub liquidation_threshold: u64,
+    pub liquidation_bonus: u64,
+    pub reserve_factor: u64,
+    /// Cap on the total supplied, 0 if uncapped
+    pub supply_cap: u64,
+    /// Cap on the total borrowed, 0 if uncapped
+    pub borrow_cap: u64,
+}
+
+/// State of a reserve observed by the risk steward at a review
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReserveObservation {
+    /// Share of the liquidity supplied to the reserve that is borrowed
+    pub utilisation: f64,
+    /// Annualised realised volatility of the oracle price of the asset
+    pub volatility: f64,
+    /// Share of the supply cap used, 0 if uncapped
+    pub supply_cap_usage: f64,
+    /// Share of the borrow cap used, 0 if uncapped
+    pub borrow_cap_usage: f64,
+    /// Increase since the last review of the debt of users in excess
+    /// of their collateral, across the pool, in the base currency
+    pub bad_debt_increase: f64,
+}
+
+/// Rule used by the risk steward to set the parameters of a reserve
+///
+/// At each review the policies are applied in turn, each to the
+/// parameters set by the previous one, starting from the current
+/// parameters of the reserve. The loan to value is then kept at most
+/// the liquidation threshold, and the liquidation bonus low enough
+/// that the collateral seized covers the debt repaid, as required by
+/// the pool configurator.
+///
+/// Parsed from strings of the form `volatility:<REFERENCE>:<SENSITIVITY>`,
+/// `caps:<THRESHOLD>:<INCREASE>`, `reserve-factor:<TARGET>:<STEP>` or
+/// `bad-debt:<THRESHOLD>:<STEP>`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RiskPolicy {
+    /// Collateral parameters set from the volatility of the asset, in
+    /// the style of Gauntlet's recommendations. Above the `reference`
+    /// volatility, the initial loan to value and liquidation threshold
+    /// are scaled down by `sensitivity` per unit of excess volatility,
+    /// to at least half their initial values, and the premium of the
+    /// liquidation bonus scaled up by the inverse.
+    Volatility { reference: f64, sensitivity: f64 },
+    /// Supply and borrow caps used above the share `threshold` raised
+    /// by the share `increase`, in the style of Chaos Labs' cap
+    /// automation. Uncapped reserves are left uncapped.
+    Caps { threshold: f64, increase: f64 },
+    /// Reserve factor raised by `step` basis points while the
+    /// utilisation of the reserve is above `target`, and lowered back
+    /// towards its initial value while below
+    ReserveFactor { target: f64, step: u64 },
+    /// Loan to value of collateral lowered, and reserve factor raised,
+    /// by `step` basis points when the bad debt of the pool grew by
+    /// more than `threshold`, in the base currency, since the last review
+    BadDebt { threshold: f64, step: u64 },
+}
+
+impl RiskPolicy {
+    /// Parameters of a reserve under the policy
+    ///
+    /// # Arguments
+    ///
+    /// * `observation` - State of the reserve
+    /// * `initial` - Parameters of the reserve at the start of the simulation
+    /// * `current` - Parameters set by the preceding policies
+    ///
+    pub fn apply(
+        &self,
+        observation: &ReserveObservation,
+        initial: &ReserveParameters,
+        current: &ReserveParameters,
+    ) -> ReserveParameters {
+        let mut parameters = *current;
+        match *self {
+            RiskPolicy::Volatility {
+                reference,
+                sensitivity,
+            } => {
+                // Assets that are not collateral are left as they are
+                if initial.liquidation_threshold > 0 {
+                    let excess = (observation.volatility - reference).max(0.);
+                    let scale = (1. - sensitivity * excess).max(0.5);
+                    let premium = initial.liquidation_bonus.saturating_sub(10000) as f64;
+                    parameters.ltv = (initial.ltv as f64 * scale) as u64;
+                    parameters.liquidation_threshold =
+                        (initial.liquidation_threshold as f64 * scale) as u64;
+                    parameters.liquidation_bonus = 10000 + (premium / scale) as u64;
+                }
+            }
+            RiskPolicy::Caps {
+                threshold,
+                increase,
+            } => {
+                let raise = |cap: u64| (cap as f64 * (1. + increase)).ceil() as u64;
+                if current.supply_cap > 0 && observation.supply_cap_usage > threshold {
+                    parameters.supply_cap = raise(current.supply_cap);
+                }
+                if current.borrow_cap > 0 && observation.borrow_cap_usage > threshold {
+                    parameters.borrow_cap = raise(current.borrow_cap);
+                }
+            }
+            RiskPolicy::ReserveFactor { target, step } => {
+                if observation.utilisation > target {
+                    parameters.reserve_factor = (current.reserve_factor + step).min(10000);
+                } else if current.reserve_factor > initial.reserve_factor {
+                    parameters.reserve_factor = current
+                        .reserve_factor
+                        .saturating_sub(step)
+                        .max(initial.reserve_factor);
+                }
+            }
+            RiskPolicy::BadDebt { threshold, step } => {
+                if observation.bad_debt_increase > threshold {
+                    parameters.ltv = current.ltv.saturating_sub(step);
+                    parameters.reserve_factor = (current.reserve_factor + step).min(10000);
+                }
+            }
+        }
+        parameters
+    }
+}
+
+impl FromStr for RiskPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let name = parts.next().unwrap_or_default();
+        let values = parts
+            .map(|x| {
+                x.parse::<f64>()
+                    .map_err(|e| format!("Invalid policy parameter {}: {}", x, e))
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        match (name, values.as_slice()) {
+            ("volatility", [reference, sensitivity]) => Ok(RiskPolicy::Volatility {
+                reference: *reference,
+                sensitivity: *sensitivity,
+            }),
+            ("caps", [threshold, increase]) => Ok(RiskPolicy::Caps {
+                threshold: *threshold,
+                increase: *increase,
+            }),
+            ("reserve-factor", [target, step]) => Ok(RiskPolicy::ReserveFactor {
+                target: *target,
+                step: *step as u64,
+            }),
+            ("bad-debt", [threshold, step]) => Ok(RiskPolicy::BadDebt {
+                threshold: *threshold,
+                step: *step as u64,
+            }),
+            _ => Err(format!("Invalid risk policy {}", s)),
+        }
+    }
+}
+
+/// Risk steward holding the risk admin role, that reviews the
+/// parameters of the reserves of token a and token b
+#[derive(Clone, Debug, PartialEq)]
+pub struct RiskStewardParameters {
+    /// Policies applied in turn at each review
+    pub policies: Vec<RiskPolicy>,
+    /// Steps between reviews of the parameters, at least 1
+    pub review_period: usize,
+    /// Number of steps of oracle prices used to estimate volatility
+    pub volatility_window: usize,
+}
+
+/// Bid of a liquidator for the liquidation of a user, and its outcome
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LiquidationBid {
+    pub step: usize,
+    pub liquidator: Address,
+    pub user: Address,
+    /// Bid, in the base currency of the Aave oracle
+    pub bid: f64,
+    /// Expected profit of the liquidation before the bid, net of the
+    /// cost of its gas, in the base currency
+    pub expected_profit: f64,
+    /// Expected cost of the gas of the liquidation at the base fee,
+    /// in the base currency
+    pub gas_cost: f64,
+    /// First liquidator of the user in the block, `None` if it was not liquidated
+    pub winner: Option<Address>,
+    /// Profit of the liquidator net of the bid, which is paid whether
+    /// or not it wins: the expected profit less the bid if it won the
+    /// liquidation, otherwise the loss of the bid and the gas
+    pub net_profit: f64,
+}
+
+/// Adversarial liquidation bundle submitted by a liquidator, and its outcome
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AdversarialBundle {
+    pub step: usize,
+    pub liquidator: Address,
+    /// Users targeted by the bundle
+    pub users: Vec<Address>,
+    /// Whether the bundle executed, if any step failed it reverted as a whole
+    pub success: bool,
+    /// Change in the value of the inventory of the liquidator, in the
+    /// base currency of the Aave oracle, at the prices before the bundle,
+    /// less the gas and priority fees paid by its transactions in the block
+    pub realised_profit: f64,
+    /// Value of the collateral seized less the debt repaid by the
+    /// liquidations, at the prices before the bundle, in the base
+    /// currency of the Aave oracle. The costs of moving the price and
+    /// unwinding the move are paid out of this gain.
+    pub liquidation_gain: f64,
+    /// Theoretical upper bound on the profit of the bundle from
+    /// SSRN 4540333, at the quoted price move of the manipulation, in
+    /// the base currency of the Aave oracle, before gas and priority
+    /// fees. See [crate::aave::liquidation::adversarial_profit_bound].
+    pub profit_bound: f64,
+}
+
+/// Sandwich of a Uniswap swap submitted by a searcher, and its outcome
+///
+/// Amounts are in whole tokens of the token paid into the front-run,
+/// which the back-run swaps back into.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Sandwich {
+    pub step: usize,
+    pub searcher: Address,
+    /// Sender of the sandwiched swap
+    pub victim: Address,
+    /// Token paid into the front-run
+    pub token: Address,
+    /// Amount paid into the front-run
+    pub front_run: f64,
+    /// Profit of the sandwich simulated against the pending swap
+    pub expected_profit: f64,
+    /// Bid of the bundle for priority, a share of its expected profit,
+    /// in the base currency of the Aave oracle
+    pub bid: f64,
+    /// Whether both legs executed, otherwise the bundle was dropped
+    pub included: bool,
+    /// Change in the balance of the searcher, less the gas and
+    /// priority fees paid by its transactions, in the token
+    pub realised_profit: f64,
+}
+
+/// Parameter of a reserve set by the risk steward
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskParameter {
+    Ltv,
+    LiquidationThreshold,
+    LiquidationBonus,
+    ReserveFactor,
+    SupplyCap,
+    BorrowCap,
+}
+
+/// Change of a parameter of a reserve submitted by the risk steward,
+/// and its outcome
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ParameterChange {
+    pub step: usize,
+    pub asset: Address,
+    pub parameter: RiskParameter,
+    /// Value before the change, in basis points or whole tokens for caps
+    pub old_value: u64,
+    /// Value set by the change
+    pub new_value: u64,
+    /// Whether the pool configurator applied the change, otherwise it reverted
+    pub success: bool,
+}
+
+/// Result of a transaction processed in a block
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransactionOutcome {
+    pub step: usize,
+    /// Position of the transaction in the block
+    pub sequence: usize,
+    pub sender: Address,
+    pub contract: Address,
+    /// 4 byte selector of the called function
+    pub function_selector: FixedBytes<4>,
+    /// Whether the transaction executed, otherwise it reverted
+    pub success: bool,
+    /// Reason the transaction reverted, with Aave and Uniswap error
+    /// codes decoded into their names
+    pub error: Option<String>,
+    pub gas_used: u64,
+    /// Cost of the gas used at the base fee of the block, in wei
+    pub gas_cost: U256,
+    /// Priority fee bid by the transaction and paid on top of its gas,
+    /// in the base currency of the Aave oracle (with 8 decimals)
+    pub priority_fee: U256,
+    /// Events emitted by the transaction
+    pub logs: Vec<Log>,
+}
+
+/// Event emitted by a protocol contract, decoded from its log
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ProtocolEvent {
+    /// Aave pool supply
+    Supply {
+        reserve: Address,
+        user: Address,
+        on_behalf_of: Address,
+        amount: U256,
+    },
+    /// Aave pool borrow
+    Borrow {
+        reserve: Address,
+        user: Address,
+        on_behalf_of: Address,
+        amount: U256,
+        interest_rate_mode: u8,
+        borrow_rate: U256,
+    },
+    /// Aave pool repayment
+    Repay {
+        reserve: Address,
+        user: Address,
+        repayer: Address,
+        amount: U256,
+        use_a_tokens: bool,
+    },
+    /// Swap of the debt of a user to the other interest rate mode,
+    /// `interest_rate_mode` being the mode it is swapped from
+    SwapBorrowRateMode {
+        reserve: Address,
+        user: Address,
+        interest_rate_mode: u8,
+    },
+    /// Rebalance of the stable borrow rate of a user
+    RebalanceStableBorrowRate { reserve: Address, user: Address },
+    /// Aave pool liquidation
+    LiquidationCall {
+        collateral_asset: Address,
+        debt_asset: Address,
+        user: Address,
+        debt_to_cover: U256,
+        liquidated_collateral_amount: U256,
+        liquidator: Address,
+        receive_a_token: bool,
+    },
+    /// Rates and indexes of an Aave reserve after an update
+    ReserveDataUpdated {
+        reserve: Address,
+        liquidity_rate: U256,
+        stable_borrow_rate: U256,
+        variable_borrow_rate: U256,
+        liquidity_index: U256,
+        variable_borrow_index: U256,
+    },
+    /// Uniswap pool swap
+    Swap {
+        sender: Address,
+        recipient: Address,
+        amount0: I256,
+        amount1: I256,
+        sqrt_price_x96: U256,
+        liquidity: u128,
+        tick: i32,
+    },
+    /// Liquidity added to a Uniswap pool position
+    Mint {
+        sender: Address,
+        owner: Address,
+        tick_lower: i32,
+        tick_upper: i32,
+        amount: u128,
+        amount0: U256,
+        amount1: U256,
+    },
+    /// Liquidity removed from a Uniswap pool position
+    Burn {
+        owner: Address,
+        tick_lower: i32,
+        tick_upper: i32,
+        amount: u128,
+        amount0: U256,
+        amount1: U256,
+    },
+    /// Mint of an Aave aToken or variable debt token
+    TokenMint {
+        caller: Address,
+        on_behalf_of: Address,
+        value: U256,
+        balance_increase: U256,
+        index: U256,
+    },
+    /// Burn of an Aave aToken or variable debt token
+    TokenBurn {
+        from: Address,
+        target: Address,
+        value: U256,
+        balance_increase: U256,
+        index: U256,
+    },
+    /// Price set on a mock Aave price aggregator
+    PriceUpdate { old_price: I256, new_price: I256 },
+}
+
+/// Decoded event, with its position in the simulation history
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EventRecord {
+    pub step: usize,
+    /// Position of the transaction that emitted the event in the block
+    pub transaction: usize,
+    /// Position of the event in the logs of the transaction
+    pub log_index: usize,
+    /// Contract that emitted the event
+    pub address: Address,
+    pub event: ProtocolEvent,
+}
+
+/// Type of a simulation agent, identified by the range of addresses
+/// agents of each type are deployed at
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AgentType {
+    Borrower,
+    Liquidator,
+    PriceAgent,
+    NoiseTrader,
+    LiquidityProvider,
+    Searcher,
+    Keeper,
+    RiskSteward,
+    /// Any other address, for example mirrored live users
+    Other,
+}
+
+impl AgentType {
+    pub fn from_address(address: Address) -> Self {
+        match U256::from_be_slice(address.as_slice()).saturating_to::<u64>() {
+            1000..=1999 => AgentType::Borrower,
+            2000..=2999 => AgentType::Liquidator,
+            3000 => AgentType::PriceAgent,
+            4000..=4999 => AgentType::NoiseTrader,
+            5000..=5999 => AgentType::LiquidityProvider,
+            6000..=6999 => AgentType::Searcher,
+            7000..=7999 => AgentType::Keeper,
+            8000..=8999 => AgentType::RiskSteward,
+            _ => AgentType::Other,
+        }
+    }
+}
+
+impl FromStr for AgentType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "borrower" => Ok(AgentType::Borrower),
+            "liquidator" => Ok(AgentType::Liquidator),
+            "price-agent" => Ok(AgentType::PriceAgent),
+            "noise-trader" => Ok(AgentType::NoiseTrader),
+            "liquidity-provider" => Ok(AgentType::LiquidityProvider),
+            "searcher" => Ok(AgentType::Searcher),
+            "keeper" => Ok(AgentType::Keeper),
+            "risk-steward" => Ok(AgentType::RiskSteward),
+            "other" => Ok(AgentType::Other),
+            _ => Err(format!("Invalid agent type {}", s)),
+        }
+    }
+}
+
+/// Policy used to order the transactions of each block
+///
+/// Searchers submit bundles as transactions with a nonce, giving the
+/// position of each transaction in the bundle. A gap in the nonces of
+/// a bundle leaves a slot for a victim transaction, for example the
+/// swap of another agent between the front-run and back-run of a
+/// sandwich. Transactions without a nonce are ordinary transactions.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ValidatorPolicy {
+    /// Highest priority fee first, grouping the transactions of each sender
+    #[default]
+    GasPriority,
+    /// Senders in random order
+    Random,
+    /// Transactions in the order they are submitted, with agents of the
+    /// types listed first ahead of others
+    FirstComeFirstServed { priority: Vec<AgentType> },
+    /// Bundles at the top of the block, each with its victim
+    /// transaction in the slot left for it
+    BundleBuilder,
+    /// Sealed bid auction of bundles, bidding with the priority fee of
+    /// their first transaction. Bundles are included in order of their
+    /// bids, and bundles calling a contract already called by a higher
+    /// bid are dropped.
+    BundleAuction,
+}
+
+#[derive(Clone)]
 pub struct SimParameters {
     pub n_borrowers: usize,
     pub n_liquidators: usize,
     pub prices_mu: f64,
     pub prices_dt: f64,
     pub prices_sigma: f64,
-    pub borrow_activation_rate: f64,
+    /// Process followed by the external price of token a
+    pub price_process: PriceProcess,
     pub token_a_initial_price: i128,
     pub token_b_initial_price: i128,
     pub token_a_liquidation_threshold: u128,
     pub token_b_liquidation_threshold: u128,
     pub token_a_base_ltv: u128,
     pub token_b_base_ltv: u128,
+    /// E-mode category both tokens are assigned to
+    pub e_mode_category: EModeCategory,
+    /// Caps of the reserves, and isolation of token a
+    pub reserve_limits: ReserveLimits,
+    /// Liquidity of the initial Uniswap position(s), L = sqrt(xy) in whole tokens
     pub liquidity: f64,
+    /// Distribution of the initial Uniswap liquidity, scaled by `liquidity`
+    /// unless given as explicit bands
+    pub liquidity_profile: LiquidityProfile,
     pub adversarial: bool,
+    /// Liquidators fund liquidations with flash loans, and hold no inventory
+    pub flash_loan_liquidation: bool,
+    /// Finite inventory of liquidators, otherwise their balances are
+    /// effectively unlimited
+    pub liquidator_capital: Option<LiquidatorCapital>,
+    /// Gas bidding strategies, assigned to liquidators in turn.
+    /// Liquidators do not bid for priority if empty.
+    pub gas_bid_strategies: Vec<GasBidStrategy>,
+    /// Policy used to order the transactions of each block
+    pub validator: ValidatorPolicy,
+    /// Searchers sandwiching the Uniswap swaps of other agents
+    pub n_searchers: usize,
+    /// Initial balance of each token of every searcher, in whole tokens
+    pub searcher_capital: f64,
+    /// Share of the expected profit of a sandwich bid by searchers for priority
+    pub searcher_bid_share: f64,
+    /// Add a keeper rebalancing the stable borrow rates of users
+    pub stable_rate_rebalancer: bool,
+    /// Risk steward adjusting the parameters of the reserves, if there is one
+    pub risk_steward: Option<RiskStewardParameters>,
+    /// Base fee of each block
+    pub base_fee: BaseFee,
+    /// Token standing for ETH when pricing gas
+    pub gas_token: GasToken,
     pub uniswap_fee: u32,
+    pub n_liquidity_providers: usize,
+    pub liquidity_provider_strategy: LiquidityStrategy,
+    /// Liquidity of each liquidity provider position, in the same units as `liquidity`
+    pub liquidity_provider_liquidity: f64,
+    pub borrower_population: BorrowerPopulation,
+    /// Live positions mirrored by additional borrowers
+    pub borrower_positions: Vec<BorrowerPosition>,
 }
 
-#[derive(Clone, Copy)]
+impl Default for SimParameters {
+    /// Token a priced at 1000 USD and token b at 1 USD, with a single
+    /// liquidator, no optional agents and no reserve limits
+    fn default() -> Self {
+        SimParameters {
+            n_borrowers: 10,
+            n_liquidators: 1,
+            prices_mu: 0f64,
+            prices_dt: 0.01f64,
+            prices_sigma: 0.3f64,
+            price_process: PriceProcess::Gbm,
+            token_a_initial_price: 100000000000i128,
+            token_b_initial_price: 100000000i128,
+            token_a_liquidation_threshold: 8000u128,
+            token_b_liquidation_threshold: 8500u128,
+            token_a_base_ltv: 7500u128,
+            token_b_base_ltv: 8000u128,
+            e_mode_category: EModeCategory::default(),
+            reserve_limits: ReserveLimits::default(),
+            liquidity: 10_f64.powf(5.),
+            liquidity_profile: LiquidityProfile::Uniform { width: 2000 },
+            adversarial: false,
+            flash_loan_liquidation: false,
+            liquidator_capital: None,
+            gas_bid_strategies: Vec::new(),
+            validator: ValidatorPolicy::GasPriority,
+            n_searchers: 0,
+            searcher_capital: 0f64,
+            searcher_bid_share: 0.9,
+            stable_rate_rebalancer: false,
+            risk_steward: None,
+            base_fee: BaseFee::Constant(0.),
+            gas_token: GasToken::TokenA,
+            uniswap_fee: 500u32,
+            n_liquidity_providers: 0,
+            liquidity_provider_strategy: LiquidityStrategy::FullRange,
+            liquidity_provider_liquidity: 0f64,
+            borrower_population: BorrowerPopulation::default(),
+            borrower_positions: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct ForkedSimParameters {
     pub n_borrowers: usize,
     pub n_liquidators: usize,
     pub prices_mu: f64,
     pub prices_dt: f64,
     pub prices_sigma: f64,
-    pub borrow_activation_rate: f64,
     pub adversarial: bool,
+    /// Liquidators fund liquidations with flash loans, and hold no inventory
+    pub flash_loan_liquidation: bool,
+    /// Finite inventory of liquidators, otherwise their balances are
+    /// effectively unlimited
+    pub liquidator_capital: Option<LiquidatorCapital>,
+    /// Gas bidding strategies, assigned to liquidators in turn.
+    /// Liquidators do not bid for priority if empty.
+    pub gas_bid_strategies: Vec<GasBidStrategy>,
+    /// Policy used to order the transactions of each block
+    pub validator: ValidatorPolicy,
+    /// Searchers sandwiching the Uniswap swaps of other agents
+    pub n_searchers: usize,
+    /// Initial balance of each token of every searcher, in whole tokens
+    pub searcher_capital: f64,
+    /// Share of the expected profit of a sandwich bid by searchers for priority
+    pub searcher_bid_share: f64,
+    /// Base fee of each block
+    pub base_fee: BaseFee,
+    /// Token standing for ETH when pricing gas, token a is WETH
+    pub gas_token: GasToken,
     pub uniswap_fee: u32,
     pub block_number: u64,
+    pub borrower_population: BorrowerPopulation,
+    /// Positions of live users, that are monitored by liquidators
+    pub borrower_positions: Vec<BorrowerPosition>,
+    /// Users with open debt at the fork block, that are monitored by liquidators
+    pub fork_users: Vec<Address>,
+}
+
+/// Counterfactual changes applied to a continuation of a checkpointed run
+///
+/// Fields left as `None` leave the corresponding part of the
+/// simulation unchanged from the checkpoint.
+#[derive(Clone, Copy, Default)]
+pub struct Branch {
+    /// Switch liquidators to/from the adversarial strategy
+    pub adversarial: Option<bool>,
+    /// Multiplicative shock applied to the external price of token a
+    pub price_shock: Option<f64>,
 }
@@ -0,0 +1,760 @@
+//! Fixed point arithmetic for Uniswap v3 prices and swap amounts
+//!
+//! Uniswap v3 stores the square root of prices as Q64.96 fixed point
+//! numbers. These helpers convert prices to that representation and
+//! compute the token amounts needed to move the price of a pool to a
+//! target, using checked full-precision arithmetic rather than
+//! truncating intermediate values.
+//!
+//! Swaps are split into steps between initialised ticks, in the same
+//! way as `UniswapV3Pool.swap`, with the liquidity of the pool
+//! updated from the `liquidityNet` of each tick that is crossed, so
+//! amounts are exact for swaps crossing any number of ticks. Swaps of
+//! exact amounts can also be simulated in sequence, for example to
+//! evaluate a sandwich of a pending swap.
+//!
+
+use super::calls;
+use alloy_primitives::{Address, U256, U512};
+use verbs_rs::env::{Env, Validator};
+use verbs_rs::DB;
+
+/// Number of fractional bits in Uniswap sqrt prices
+pub const RESOLUTION: usize = 96;
+
+/// Smallest sqrt price of a Uniswap pool, at the minimum tick
+pub const MIN_SQRT_RATIO: U256 = U256::from_limbs([4295128739, 0, 0, 0]);
+
+/// Largest sqrt price of a Uniswap pool, at the maximum tick (exclusive)
+pub const MAX_SQRT_RATIO: U256 =
+    U256::from_limbs([0x5d951d5263988d26, 0xefd1fc6a50648849, 0xfffd8963, 0]);
+
+/// Smallest tick of a Uniswap pool
+pub const MIN_TICK: i32 = -887272;
+
+/// Largest tick of a Uniswap pool
+pub const MAX_TICK: i32 = 887272;
+
+/// Factors 2^128 / sqrt(1.0001)^(2^i) for i = 1, ..., 19, from `TickMath.getSqrtRatioAtTick`
+const TICK_RATIOS: [u128; 19] = [
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x09aa508b5b7a84e1c677de54f3e99bc9,
+    0x005d6af8dedb81196699c329225ee604,
+    0x00002216e584f5fa1ea926041bedfe98,
+    0x00000000048a170391f7dc42444e8fa2,
+];
+
+/// Denominator of Uniswap pool fees, which are in hundredths of a bip
+pub const FEE_DENOMINATOR: u32 = 1_000_000;
+
+/// Convert a positive float to a Q64.96 fixed point value
+///
+/// The conversion uses the full mantissa of the float, returning
+/// `None` if the value is not finite and positive, or does not
+/// fit into 256 bits.
+pub fn to_x96(x: f64) -> Option<U256> {
+    if !x.is_finite() || x <= 0. {
+        return None;
+    }
+
+    let bits = x.to_bits();
+    let exponent = ((bits >> 52) & 0x7ff) as i64;
+    let fraction = bits & 0x000f_ffff_ffff_ffff;
+    // x = mantissa * 2^(exponent - 1075), with subnormals having an
+    // implicit exponent of 1 and no leading bit
+    let (mantissa, exponent) = match exponent {
+        0 => (fraction, 1),
+        _ => (fraction | 0x0010_0000_0000_0000, exponent),
+    };
+    let shift = exponent - 1075 + RESOLUTION as i64;
+
+    if shift >= 0 {
+        U256::from(mantissa).checked_shl(shift as usize)
+    } else {
+        // The mantissa has 53 bits, so larger shifts leave nothing
+        Some(U256::from(mantissa >> shift.unsigned_abs().min(63)))
+    }
+}
+
+/// Convert a price to the Q64.96 square root price used by Uniswap
+///
+/// Returns `None` if the price is not finite and positive, or if the
+/// square root price is outside the range of Uniswap prices.
+pub fn price_to_sqrt_price_x96(price: f64) -> Option<U256> {
+    let sqrt_price = to_x96(price.sqrt())?;
+
+    if (MIN_SQRT_RATIO..MAX_SQRT_RATIO).contains(&sqrt_price) {
+        Some(sqrt_price)
+    } else {
+        None
+    }
+}
+
+/// Convert a Q64.96 square root price to a price
+pub fn sqrt_price_x96_to_price(sqrt_price_x96: U256) -> f64 {
+    let sqrt_price: f64 = sqrt_price_x96.into();
+    (sqrt_price / 2f64.powi(RESOLUTION as i32)).powi(2)
+}
+
+/// Sqrt price at a tick, as a Q64.96 value
+///
+/// Port of `TickMath.getSqrtRatioAtTick`, so the result is identical
+/// to the price used by pools. Returns `None` if the tick is outside
+/// the range of Uniswap ticks.
+pub fn get_sqrt_ratio_at_tick(tick: i32) -> Option<U256> {
+    let abs_tick = tick.unsigned_abs();
+    if abs_tick > MAX_TICK.unsigned_abs() {
+        return None;
+    }
+
+    let mut ratio = if abs_tick & 0x1 != 0 {
+        U256::from(0xfffcb933bd6fad37aa2d162d1a594001u128)
+    } else {
+        U256::from(1) << 128
+    };
+    for (i, factor) in TICK_RATIOS.iter().enumerate() {
+        if abs_tick & (0x2 << i) != 0 {
+            ratio = (ratio * U256::from(*factor)) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    // Round up from Q128.128 to Q64.96
+    Some((ratio >> 32) + U256::from(ratio.as_limbs()[0] & 0xffff_ffff != 0))
+}
+
+/// Greatest tick with a sqrt price at or below a Q64.96 sqrt price
+///
+/// Gives the same tick as `TickMath.getTickAtSqrtRatio`, found by a
+/// binary search over [get_sqrt_ratio_at_tick]. Returns `None` if the
+/// sqrt price is outside the range of Uniswap prices.
+pub fn get_tick_at_sqrt_ratio(sqrt_price_x96: U256) -> Option<i32> {
+    if !(MIN_SQRT_RATIO..MAX_SQRT_RATIO).contains(&sqrt_price_x96) {
+        return None;
+    }
+
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if get_sqrt_ratio_at_tick(mid)? <= sqrt_price_x96 {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    Some(low)
+}
+
+/// Multiply and divide with a 512-bit intermediate product
+///
+/// Returns `None` if the denominator is zero or the result does not
+/// fit into 256 bits.
+fn mul_div(a: U256, b: U256, denominator: U256, round_up: bool) -> Option<U256> {
+    if denominator == U256::ZERO {
+        return None;
+    }
+    let product = U512::from(a) * U512::from(b);
+    let denominator = U512::from(denominator);
+    let result = if round_up {
+        product.div_ceil(denominator)
+    } else {
+        product / denominator
+    };
+
+    U256::checked_from_limbs_slice(result.as_limbs())
+}
+
+/// Amount of token0 swapped when the price moves between two sqrt prices
+///
+/// Within a range of constant liquidity L we have
+/// \Delta x = L \Delta \frac{1}{\sqrt{P}}
+/// Returns `None` if a sqrt price is zero, or the result does not fit
+/// into 256 bits.
+///
+/// # Arguments
+///
+/// * `liquidity` - Active liquidity of the pool
+/// * `sqrt_price_a_x96` - Square root price at one end of the move
+/// * `sqrt_price_b_x96` - Square root price at the other end of the move
+/// * `round_up` - Round the amount up, as for amounts paid into the pool
+///
+pub fn get_amount0_delta(
+    liquidity: u128,
+    sqrt_price_a_x96: U256,
+    sqrt_price_b_x96: U256,
+    round_up: bool,
+) -> Option<U256> {
+    let (lower, upper) = if sqrt_price_a_x96 > sqrt_price_b_x96 {
+        (sqrt_price_b_x96, sqrt_price_a_x96)
+    } else {
+        (sqrt_price_a_x96, sqrt_price_b_x96)
+    };
+    if lower == U256::ZERO {
+        return None;
+    }
+    let numerator = U256::from(liquidity) << RESOLUTION;
+    let amount = mul_div(numerator, upper - lower, upper, round_up)?;
+
+    if round_up {
+        Some(amount.div_ceil(lower))
+    } else {
+        Some(amount / lower)
+    }
+}
+
+/// Amount of token1 swapped when the price moves between two sqrt prices
+///
+/// Within a range of constant liquidity L we have
+/// \Delta y = L \Delta \sqrt{P}
+/// Returns `None` if the result does not fit into 256 bits.
+///
+/// # Arguments
+///
+/// * `liquidity` - Active liquidity of the pool
+/// * `sqrt_price_a_x96` - Square root price at one end of the move
+/// * `sqrt_price_b_x96` - Square root price at the other end of the move
+/// * `round_up` - Round the amount up, as for amounts paid into the pool
+///
+pub fn get_amount1_delta(
+    liquidity: u128,
+    sqrt_price_a_x96: U256,
+    sqrt_price_b_x96: U256,
+    round_up: bool,
+) -> Option<U256> {
+    let change_sqrt_price = if sqrt_price_a_x96 > sqrt_price_b_x96 {
+        sqrt_price_a_x96 - sqrt_price_b_x96
+    } else {
+        sqrt_price_b_x96 - sqrt_price_a_x96
+    };
+
+    mul_div(
+        U256::from(liquidity),
+        change_sqrt_price,
+        U256::from(1) << RESOLUTION,
+        round_up,
+    )
+}
+
+/// Amounts of each token needed to mint liquidity over a range of prices
+///
+/// Equivalent to `LiquidityAmounts.getAmountsForLiquidity`, but
+/// rounding up, so the amounts are sufficient to mint `liquidity`.
+///
+/// # Arguments
+///
+/// * `sqrt_price_x96` - Current sqrt price of the pool
+/// * `sqrt_price_lower_x96` - Sqrt price at the lower tick of the range
+/// * `sqrt_price_upper_x96` - Sqrt price at the upper tick of the range
+/// * `liquidity` - Liquidity to mint
+///
+pub fn get_amounts_for_liquidity(
+    sqrt_price_x96: U256,
+    sqrt_price_lower_x96: U256,
+    sqrt_price_upper_x96: U256,
+    liquidity: u128,
+) -> Option<(U256, U256)> {
+    if sqrt_price_x96 <= sqrt_price_lower_x96 {
+        let amount0 =
+            get_amount0_delta(liquidity, sqrt_price_lower_x96, sqrt_price_upper_x96, true)?;
+        Some((amount0, U256::ZERO))
+    } else if sqrt_price_x96 < sqrt_price_upper_x96 {
+        let amount0 = get_amount0_delta(liquidity, sqrt_price_x96, sqrt_price_upper_x96, true)?;
+        let amount1 = get_amount1_delta(liquidity, sqrt_price_lower_x96, sqrt_price_x96, true)?;
+        Some((amount0, amount1))
+    } else {
+        let amount1 =
+            get_amount1_delta(liquidity, sqrt_price_lower_x96, sqrt_price_upper_x96, true)?;
+        Some((U256::ZERO, amount1))
+    }
+}
+
+/// Amount that has to be paid into a pool so that `amount` remains after fees
+///
+/// Rounds up, so that the amount left after fees is at least `amount`.
+/// Returns `None` if the fee is not below 100% or the result overflows.
+///
+/// # Arguments
+///
+/// * `amount` - Amount that should be swapped after fees
+/// * `fee` - Pool fee in hundredths of a bip
+///
+pub fn add_fee(amount: U256, fee: u32) -> Option<U256> {
+    let remaining = FEE_DENOMINATOR.checked_sub(fee).filter(|x| *x > 0)?;
+    let numerator = amount.checked_mul(U256::from(FEE_DENOMINATOR))?;
+    let remaining = U256::from(remaining);
+
+    Some(numerator.div_ceil(remaining))
+}
+
+/// Access to the initialised ticks of a pool
+pub trait TickData {
+    /// Word of the bitmap of initialised ticks, as `UniswapV3Pool.tickBitmap`
+    fn tick_bitmap(&mut self, word_position: i16) -> U256;
+    /// Net liquidity added when the price crosses a tick upwards
+    fn liquidity_net(&mut self, tick: i32) -> i128;
+}
+
+/// Ticks of a pool deployed in a simulation environment
+pub struct PoolTicks<'a, D: DB, V: Validator> {
+    pub network: &'a mut Env<D, V>,
+    pub caller: Address,
+    pub pool: Address,
+}
+
+impl<'a, D: DB, V: Validator> TickData for PoolTicks<'a, D, V> {
+    fn tick_bitmap(&mut self, word_position: i16) -> U256 {
+        calls::get_tick_bitmap(self.network, self.caller, self.pool, word_position)
+    }
+
+    fn liquidity_net(&mut self, tick: i32) -> i128 {
+        calls::get_tick_liquidity_net(self.network, self.caller, self.pool, tick)
+    }
+}
+
+/// State of a pool at the start of a swap
+#[derive(Clone, Copy, Debug)]
+pub struct PoolState {
+    pub sqrt_price_x96: U256,
+    pub tick: i32,
+    pub liquidity: u128,
+    pub fee: u32,
+    pub tick_spacing: i32,
+}
+
+impl PoolState {
+    /// Read the current state of a pool
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - Simulation environment
+    /// * `caller` - Address making the calls
+    /// * `pool` - Address of the pool
+    /// * `fee` - Fee of the pool in hundredths of a bip
+    ///
+    pub fn get<D: DB, V: Validator>(
+        network: &mut Env<D, V>,
+        caller: Address,
+        pool: Address,
+        fee: u32,
+    ) -> Self {
+        let slot0 = calls::get_slot0(network, caller, pool);
+
+        PoolState {
+            sqrt_price_x96: slot0.sqrtPriceX96,
+            tick: slot0.tick,
+            liquidity: calls::get_liquidity(network, caller, pool),
+            fee,
+            tick_spacing: calls::get_tick_spacing(network, caller, pool),
+        }
+    }
+}
+
+/// Amounts of a swap, including fees paid on the amount in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapAmounts {
+    /// Amount paid into the pool, including fees
+    pub amount_in: U256,
+    /// Amount received from the pool
+    pub amount_out: U256,
+    /// Number of initialised ticks crossed by the swap
+    pub initialized_ticks_crossed: u32,
+}
+
+/// Next initialised tick in the same bitmap word, or the end of the word
+///
+/// Port of `TickBitmap.nextInitializedTickWithinOneWord`, searching
+/// at or below `tick` if `lte`, and above `tick` otherwise.
+fn next_initialized_tick_within_one_word<T: TickData>(
+    ticks: &mut T,
+    tick: i32,
+    tick_spacing: i32,
+    lte: bool,
+) -> (i32, bool) {
+    // Solidity rounds towards negative infinity for negative ticks
+    let compressed = tick.div_euclid(tick_spacing);
+    let one = U256::from(1);
+
+    if lte {
+        let (word_position, bit_position) = position(compressed);
+        let mask = (one << bit_position) - one + (one << bit_position);
+        let masked = ticks.tick_bitmap(word_position) & mask;
+
+        if masked != U256::ZERO {
+            let most_significant_bit = (masked.bit_len() - 1) as i32;
+            (
+                (compressed - (bit_position as i32 - most_significant_bit)) * tick_spacing,
+                true,
+            )
+        } else {
+            ((compressed - bit_position as i32) * tick_spacing, false)
+        }
+    } else {
+        let (word_position, bit_position) = position(compressed + 1);
+        let mask = !((one << bit_position) - one);
+        let masked = ticks.tick_bitmap(word_position) & mask;
+
+        if masked != U256::ZERO {
+            let least_significant_bit = masked.trailing_zeros() as i32;
+            (
+                (compressed + 1 + (least_significant_bit - bit_position as i32)) * tick_spacing,
+                true,
+            )
+        } else {
+            (
+                (compressed + 1 + (255 - bit_position as i32)) * tick_spacing,
+                false,
+            )
+        }
+    }
+}
+
+/// Word and bit position of a compressed tick in the tick bitmap
+fn position(compressed: i32) -> (i16, usize) {
+    ((compressed >> 8) as i16, (compressed & 0xff) as usize)
+}
+
+/// Amounts of the swap that moves the price of a pool to a target
+///
+/// Steps through the initialised ticks between the current and target
+/// prices, as `UniswapV3Pool.swap` does, and sums the amounts swapped
+/// in each step. If the target is above the current price token1 is
+/// paid in for token0, otherwise token0 is paid in for token1.
+///
+/// The amount in can be used as the exact input of a swap, and the
+/// amount out as the exact output of a swap, to move the pool to the
+/// target. Returns `None` if the target equals the current price, is
+/// outside the range of Uniswap prices, or an amount overflows.
+///
+/// # Arguments
+///
+/// * `pool` - State of the pool before the swap
+/// * `ticks` - Initialised ticks of the pool
+/// * `target` - Target sqrt price of the pool, as a Q64.96 value
+///
+pub fn get_swap_to_price<T: TickData>(
+    pool: &PoolState,
+    ticks: &mut T,
+    target: U256,
+) -> Option<SwapAmounts> {
+    if target == pool.sqrt_price_x96 || !(MIN_SQRT_RATIO..MAX_SQRT_RATIO).contains(&target) {
+        return None;
+    }
+
+    let zero_for_one = target < pool.sqrt_price_x96;
+    let mut sqrt_price = pool.sqrt_price_x96;
+    let mut tick = pool.tick;
+    let mut liquidity = pool.liquidity;
+    let mut amounts = SwapAmounts::default();
+
+    while sqrt_price != target {
+        let (tick_next, initialized) =
+            next_initialized_tick_within_one_word(ticks, tick, pool.tick_spacing, zero_for_one);
+        let tick_next = tick_next.clamp(MIN_TICK, MAX_TICK);
+        let sqrt_price_next = get_sqrt_ratio_at_tick(tick_next)?;
+
+        let step_target = if zero_for_one {
+            sqrt_price_next.max(target)
+        } else {
+            sqrt_price_next.min(target)
+        };
+
+        let (amount_in, amount_out) = if zero_for_one {
+            (
+                get_amount0_delta(liquidity, step_target, sqrt_price, true)?,
+                get_amount1_delta(liquidity, step_target, sqrt_price, false)?,
+            )
+        } else {
+            (
+                get_amount1_delta(liquidity, sqrt_price, step_target, true)?,
+                get_amount0_delta(liquidity, sqrt_price, step_target, false)?,
+            )
+        };
+        amounts.amount_in = amounts
+            .amount_in
+            .checked_add(add_fee(amount_in, pool.fee)?)?;
+        amounts.amount_out = amounts.amount_out.checked_add(amount_out)?;
+
+        if step_target == sqrt_price_next {
+            if initialized {
+                let liquidity_net = ticks.liquidity_net(tick_next);
+                let liquidity_net = if zero_for_one {
+                    liquidity_net.checked_neg()?
+                } else {
+                    liquidity_net
+                };
+                liquidity = liquidity.checked_add_signed(liquidity_net)?;
+                amounts.initialized_ticks_crossed += 1;
+            }
+            tick = if zero_for_one {
+                tick_next - 1
+            } else {
+                tick_next
+            };
+        }
+        sqrt_price = step_target;
+    }
+
+    Some(amounts)
+}
+
+/// Sqrt price after adding or removing an amount of token0
+///
+/// Port of `SqrtPriceMath.getNextSqrtPriceFromAmount0RoundingUp`,
+/// rounding up so that the price moves far enough for the amount.
+fn get_next_sqrt_price_from_amount0(
+    sqrt_price_x96: U256,
+    liquidity: u128,
+    amount: U256,
+    add: bool,
+) -> Option<U256> {
+    if amount == U256::ZERO {
+        return Some(sqrt_price_x96);
+    }
+    let numerator = U256::from(liquidity) << RESOLUTION;
+    let product = amount.checked_mul(sqrt_price_x96);
+
+    if add {
+        match product.and_then(|x| x.checked_add(numerator)) {
+            Some(denominator) => mul_div(numerator, sqrt_price_x96, denominator, true),
+            None => Some(numerator.div_ceil((numerator / sqrt_price_x96).checked_add(amount)?)),
+        }
+    } else {
+        let denominator = numerator
+            .checked_sub(product?)
+            .filter(|x| *x > U256::ZERO)?;
+        mul_div(numerator, sqrt_price_x96, denominator, true)
+    }
+}
+
+/// Sqrt price after adding or removing an amount of token1
+///
+/// Port of `SqrtPriceMath.getNextSqrtPriceFromAmount1RoundingDown`.
+fn get_next_sqrt_price_from_amount1(
+    sqrt_price_x96: U256,
+    liquidity: u128,
+    amount: U256,
+    add: bool,
+) -> Option<U256> {
+    let liquidity = U256::from(liquidity);
+    let q96 = U256::from(1) << RESOLUTION;
+
+    if add {
+        sqrt_price_x96.checked_add(mul_div(amount, q96, liquidity, false)?)
+    } else {
+        sqrt_price_x96
+            .checked_sub(mul_div(amount, q96, liquidity, true)?)
+            .filter(|x| *x > U256::ZERO)
+    }
+}
+
+/// Sqrt price, amount in, amount out and fee of one step of a swap
+///
+/// Port of `SwapMath.computeSwapStep`, swapping at most `amount_remaining`
+/// of the token in (if `exact_input`) or the token out, while moving the
+/// price no further than `sqrt_price_target_x96`.
+fn compute_swap_step(
+    sqrt_price_x96: U256,
+    sqrt_price_target_x96: U256,
+    liquidity: u128,
+    amount_remaining: U256,
+    exact_input: bool,
+    fee: u32,
+) -> Option<(U256, U256, U256, U256)> {
+    let zero_for_one = sqrt_price_x96 >= sqrt_price_target_x96;
+    let remaining_fee = U256::from(FEE_DENOMINATOR.checked_sub(fee)?);
+    let fee_denominator = U256::from(FEE_DENOMINATOR);
+
+    let delta_in = |a: U256, b: U256| match zero_for_one {
+        true => get_amount0_delta(liquidity, a, b, true),
+        false => get_amount1_delta(liquidity, a, b, true),
+    };
+    let delta_out = |a: U256, b: U256| match zero_for_one {
+        true => get_amount1_delta(liquidity, a, b, false),
+        false => get_amount0_delta(liquidity, a, b, false),
+    };
+
+    let (sqrt_price_next, max_amount) = if exact_input {
+        let amount_less_fee = mul_div(amount_remaining, remaining_fee, fee_denominator, false)?;
+        let amount_in = delta_in(sqrt_price_target_x96, sqrt_price_x96)?;
+        if amount_less_fee >= amount_in {
+            (sqrt_price_target_x96, amount_in)
+        } else if zero_for_one {
+            let next =
+                get_next_sqrt_price_from_amount0(sqrt_price_x96, liquidity, amount_less_fee, true)?;
+            (next, amount_in)
+        } else {
+            let next =
+                get_next_sqrt_price_from_amount1(sqrt_price_x96, liquidity, amount_less_fee, true)?;
+            (next, amount_in)
+        }
+    } else {
+        let amount_out = delta_out(sqrt_price_target_x96, sqrt_price_x96)?;
+        if amount_remaining >= amount_out {
+            (sqrt_price_target_x96, amount_out)
+        } else if zero_for_one {
+            let next = get_next_sqrt_price_from_amount1(
+                sqrt_price_x96,
+                liquidity,
+                amount_remaining,
+                false,
+            )?;
+            (next, amount_out)
+        } else {
+            let next = get_next_sqrt_price_from_amount0(
+                sqrt_price_x96,
+                liquidity,
+                amount_remaining,
+                false,
+            )?;
+            (next, amount_out)
+        }
+    };
+
+    let max = sqrt_price_next == sqrt_price_target_x96;
+    let amount_in = match max && exact_input {
+        true => max_amount,
+        false => delta_in(sqrt_price_next, sqrt_price_x96)?,
+    };
+    let mut amount_out = match max && !exact_input {
+        true => max_amount,
+        false => delta_out(sqrt_price_next, sqrt_price_x96)?,
+    };
+    if !exact_input && amount_out > amount_remaining {
+        amount_out = amount_remaining;
+    }
+
+    let fee_amount = if exact_input && !max {
+        amount_remaining.checked_sub(amount_in)?
+    } else {
+        mul_div(amount_in, U256::from(fee), remaining_fee, true)?
+    };
+
+    Some((sqrt_price_next, amount_in, amount_out, fee_amount))
+}
+
+/// Amounts of a swap, and the state of the pool after it
+///
+/// Port of `UniswapV3Pool.swap`, swapping `amount` of the token paid in
+/// if `exact_input`, or of the token received otherwise, and stopping
+/// early if the price reaches `sqrt_price_limit_x96`. As for the
+/// `SwapRouter`, a limit of zero means no limit. Token0 is paid in for
+/// token1 if `zero_for_one`.
+///
+/// The returned state can be passed to further calls, to simulate a
+/// sequence of swaps in the same block, as long as no liquidity is
+/// added or removed in between. Returns `None` if the limit is on the
+/// wrong side of the current price, as the pool would revert, or an
+/// amount overflows.
+///
+/// # Arguments
+///
+/// * `pool` - State of the pool before the swap
+/// * `ticks` - Initialised ticks of the pool
+/// * `zero_for_one` - Pay in token0 for token1
+/// * `exact_input` - `amount` is the amount paid in, rather than received
+/// * `amount` - Amount of the swap
+/// * `sqrt_price_limit_x96` - Sqrt price at which the swap stops, or zero
+///
+pub fn get_swap<T: TickData>(
+    pool: &PoolState,
+    ticks: &mut T,
+    zero_for_one: bool,
+    exact_input: bool,
+    amount: U256,
+    sqrt_price_limit_x96: U256,
+) -> Option<(SwapAmounts, PoolState)> {
+    let one = U256::from(1);
+    let limit = match (sqrt_price_limit_x96 == U256::ZERO, zero_for_one) {
+        (true, true) => MIN_SQRT_RATIO + one,
+        (true, false) => MAX_SQRT_RATIO - one,
+        (false, _) => sqrt_price_limit_x96,
+    };
+    let valid_limit = match zero_for_one {
+        true => limit < pool.sqrt_price_x96 && limit > MIN_SQRT_RATIO,
+        false => limit > pool.sqrt_price_x96 && limit < MAX_SQRT_RATIO,
+    };
+    if !valid_limit {
+        return None;
+    }
+
+    let mut state = *pool;
+    let mut remaining = amount;
+    let mut amounts = SwapAmounts::default();
+
+    while remaining != U256::ZERO && state.sqrt_price_x96 != limit {
+        let sqrt_price_start = state.sqrt_price_x96;
+        let (tick_next, initialized) = next_initialized_tick_within_one_word(
+            ticks,
+            state.tick,
+            state.tick_spacing,
+            zero_for_one,
+        );
+        let tick_next = tick_next.clamp(MIN_TICK, MAX_TICK);
+        let sqrt_price_next = get_sqrt_ratio_at_tick(tick_next)?;
+
+        let step_target = if (zero_for_one && sqrt_price_next < limit)
+            || (!zero_for_one && sqrt_price_next > limit)
+        {
+            limit
+        } else {
+            sqrt_price_next
+        };
+
+        let (sqrt_price, amount_in, amount_out, fee_amount) = compute_swap_step(
+            state.sqrt_price_x96,
+            step_target,
+            state.liquidity,
+            remaining,
+            exact_input,
+            state.fee,
+        )?;
+        state.sqrt_price_x96 = sqrt_price;
+
+        let paid = amount_in.checked_add(fee_amount)?;
+        remaining = match exact_input {
+            true => remaining.checked_sub(paid)?,
+            false => remaining.checked_sub(amount_out)?,
+        };
+        amounts.amount_in = amounts.amount_in.checked_add(paid)?;
+        amounts.amount_out = amounts.amount_out.checked_add(amount_out)?;
+
+        if state.sqrt_price_x96 == sqrt_price_next {
+            if initialized {
+                let liquidity_net = ticks.liquidity_net(tick_next);
+                let liquidity_net = if zero_for_one {
+                    liquidity_net.checked_neg()?
+                } else {
+                    liquidity_net
+                };
+                state.liquidity = state.liquidity.checked_add_signed(liquidity_net)?;
+                amounts.initialized_ticks_crossed += 1;
+            }
+            state.tick = if zero_for_one {
+                tick_next - 1
+            } else {
+                tick_next
+            };
+        } else if state.sqrt_price_x96 != sqrt_price_start {
+            state.tick = get_tick_at_sqrt_ratio(state.sqrt_price_x96)?;
+        }
+    }
+
+    Some((amounts, state))
+}
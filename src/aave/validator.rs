@@ -0,0 +1,297 @@
+//! Policies used to order the transactions of each block
+//!
+//! Each policy implements [Validator], and [PolicyValidator] selects
+//! one of them from a [ValidatorPolicy] in the simulation parameters.
+//!
+
+use crate::aave::types::{AgentType, ValidatorPolicy};
+use alloy_primitives::{Address, U256};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::{BTreeMap, BTreeSet};
+use verbs_rs::contract::Transaction;
+use verbs_rs::env::Validator;
+
+/// Gas priority ordering with deterministic tie-breaking
+///
+/// Orders transactions by priority fee as
+/// [GasPriorityValidator](verbs_rs::env::GasPriorityValidator), but
+/// each transaction submitted without a nonce is ordered by its own
+/// fee, rather than the fee of the first transaction of its sender,
+/// so every bid of a liquidator competes on its own. The transactions
+/// of a sender submitted with a nonce stay together in nonce order,
+/// at the fee of their first transaction. Transactions with equal
+/// fees are ordered by sender address rather than hash-map iteration
+/// order, so runs with the same seed process transactions in the same
+/// order, and simulation outputs are reproducible.
+pub struct OrderedGasPriorityValidator {}
+
+impl Validator for OrderedGasPriorityValidator {
+    fn order_transactions<R: Rng>(
+        &mut self,
+        _rng: &mut R,
+        transactions: Vec<Transaction>,
+    ) -> Vec<Transaction> {
+        by_priority_fee(transactions)
+    }
+}
+
+/// Transactions grouped by sender, each group in nonce order
+fn group_by_sender(transactions: Vec<Transaction>) -> Vec<Vec<Transaction>> {
+    let mut transaction_by_address = BTreeMap::<Address, Vec<Transaction>>::new();
+
+    for t in transactions.into_iter() {
+        transaction_by_address.entry(t.callee).or_default().push(t);
+    }
+
+    transaction_by_address
+        .into_values()
+        .map(|mut v| {
+            v.sort_by_key(|x| x.nonce);
+            v
+        })
+        .collect()
+}
+
+/// Priority fee bid by a transaction
+fn priority(transaction: &Transaction) -> U256 {
+    transaction.gas_priority_fee.unwrap_or(U256::ZERO)
+}
+
+/// Transactions in priority fee order, highest first
+///
+/// Transactions without a nonce are ordered by their own fee, and the
+/// transactions of a sender with a nonce stay together at the fee of
+/// the first. Ties are broken by sender address.
+fn by_priority_fee(transactions: Vec<Transaction>) -> Vec<Transaction> {
+    let (sequenced, independent): (Vec<Transaction>, Vec<Transaction>) =
+        transactions.into_iter().partition(|x| x.nonce.is_some());
+
+    let mut groups = group_by_sender(sequenced);
+    groups.extend(
+        group_by_sender(independent)
+            .into_iter()
+            .flatten()
+            .map(|x| vec![x]),
+    );
+    groups.sort_by_key(|x| (U256::MAX - priority(&x[0]), x[0].callee));
+
+    groups.into_iter().flatten().collect()
+}
+
+/// Senders in random order
+///
+/// Unlike [RandomValidator](verbs_rs::env::RandomValidator) the
+/// transactions of each sender are kept together, in nonce order.
+pub struct RandomOrderValidator {}
+
+impl Validator for RandomOrderValidator {
+    fn order_transactions<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        transactions: Vec<Transaction>,
+    ) -> Vec<Transaction> {
+        let mut transactions = group_by_sender(transactions);
+        transactions.shuffle(rng);
+        transactions.into_iter().flatten().collect()
+    }
+}
+
+/// Transactions in the order they are submitted
+///
+/// Agents submit transactions in the order their sets are called
+/// each step. Transactions of agents of the types in `priority` are
+/// moved ahead of others, in the order of the list, otherwise the
+/// submission order is kept.
+pub struct FirstComeFirstServedValidator {
+    pub priority: Vec<AgentType>,
+}
+
+impl Validator for FirstComeFirstServedValidator {
+    fn order_transactions<R: Rng>(
+        &mut self,
+        _rng: &mut R,
+        mut transactions: Vec<Transaction>,
+    ) -> Vec<Transaction> {
+        transactions.sort_by_key(|x| {
+            let agent_type = AgentType::from_address(x.callee);
+            self.priority
+                .iter()
+                .position(|y| *y == agent_type)
+                .unwrap_or(self.priority.len())
+        });
+        transactions
+    }
+}
+
+/// Bundles of searchers, and the remaining ordinary transactions
+///
+/// Bundles are the transactions of a sender submitted with a nonce,
+/// in nonce order, and are sorted by the priority fee of their first
+/// transaction. A gap in the nonces of a bundle is filled with the
+/// first ordinary transaction calling the same contract as the
+/// transaction before the gap, bundles whose victim cannot be found
+/// being dropped. Ordinary transactions are ordered by priority fee.
+fn build_bundles(transactions: Vec<Transaction>) -> (Vec<Vec<Transaction>>, Vec<Transaction>) {
+    let (bundled, ordinary): (Vec<Transaction>, Vec<Transaction>) =
+        transactions.into_iter().partition(|x| x.nonce.is_some());
+
+    let mut ordinary = by_priority_fee(ordinary);
+
+    let mut bundles = group_by_sender(bundled);
+    bundles.sort_by_key(|x| U256::MAX - priority(&x[0]));
+
+    let bundles = bundles
+        .into_iter()
+        .filter_map(|bundle| {
+            let mut filled = Vec::new();
+            let mut victims = Vec::new();
+
+            for (i, transaction) in bundle.iter().enumerate() {
+                filled.push(transaction.clone());
+                let gap = bundle
+                    .get(i + 1)
+                    .is_some_and(|next| next.nonce > transaction.nonce.map(|x| x + 1));
+                if gap {
+                    let victim = ordinary
+                        .iter()
+                        .enumerate()
+                        .filter(|(j, _)| !victims.contains(j))
+                        .find(|(_, x)| x.transact_to == transaction.transact_to)?
+                        .0;
+                    victims.push(victim);
+                    filled.push(ordinary[victim].clone());
+                }
+            }
+
+            Some((filled, victims))
+        })
+        .collect::<Vec<_>>();
+
+    // Victims are removed from the ordinary transactions of the block
+    let mut included = Vec::new();
+    let mut taken: Vec<usize> = Vec::new();
+    for (bundle, victims) in bundles {
+        if victims.iter().any(|x| taken.contains(x)) {
+            continue;
+        }
+        taken.extend(victims);
+        included.push(bundle);
+    }
+    let mut i = 0;
+    ordinary.retain(|_| {
+        i += 1;
+        !taken.contains(&(i - 1))
+    });
+
+    (included, ordinary)
+}
+
+/// Block builder placing bundles at the top of the block
+///
+/// Bundles are included in order of the priority fee of their first
+/// transaction, each with its victim transaction in the slot left
+/// for it, followed by ordinary transactions by priority fee.
+pub struct BundleBuilderValidator {}
+
+impl Validator for BundleBuilderValidator {
+    fn order_transactions<R: Rng>(
+        &mut self,
+        _rng: &mut R,
+        transactions: Vec<Transaction>,
+    ) -> Vec<Transaction> {
+        let (bundles, ordinary) = build_bundles(transactions);
+        bundles.into_iter().flatten().chain(ordinary).collect()
+    }
+}
+
+/// Sealed bid auction of bundles, in the style of Flashbots
+///
+/// Bundles bid with the priority fee of their first transaction, and
+/// are included at the top of the block in order of their bids. A
+/// bundle calling a contract already called by a bundle with a higher
+/// bid conflicts with it, and is dropped rather than included, so
+/// searchers competing for the same opportunity pay for it. The
+/// victims of a dropped bundle are included with the ordinary
+/// transactions, by priority fee.
+pub struct BundleAuctionValidator {}
+
+impl Validator for BundleAuctionValidator {
+    fn order_transactions<R: Rng>(
+        &mut self,
+        _rng: &mut R,
+        transactions: Vec<Transaction>,
+    ) -> Vec<Transaction> {
+        let (bundles, mut ordinary) = build_bundles(transactions);
+
+        let mut contracts = BTreeSet::new();
+        let mut included = Vec::new();
+        for bundle in bundles {
+            let searcher = bundle[0].callee;
+            let called: BTreeSet<Address> = bundle
+                .iter()
+                .filter(|x| x.callee == searcher)
+                .map(|x| x.transact_to)
+                .collect();
+            if called.is_disjoint(&contracts) {
+                contracts.extend(called);
+                included.extend(bundle);
+            } else {
+                // Victims of a dropped bundle go back to the ordinary transactions
+                ordinary.extend(bundle.into_iter().filter(|x| x.callee != searcher));
+            }
+        }
+
+        included
+            .into_iter()
+            .chain(by_priority_fee(ordinary))
+            .collect()
+    }
+}
+
+/// Validator selected by a [ValidatorPolicy]
+pub enum PolicyValidator {
+    GasPriority(OrderedGasPriorityValidator),
+    Random(RandomOrderValidator),
+    FirstComeFirstServed(FirstComeFirstServedValidator),
+    BundleBuilder(BundleBuilderValidator),
+    BundleAuction(BundleAuctionValidator),
+}
+
+impl From<&ValidatorPolicy> for PolicyValidator {
+    fn from(policy: &ValidatorPolicy) -> Self {
+        match policy {
+            ValidatorPolicy::GasPriority => {
+                PolicyValidator::GasPriority(OrderedGasPriorityValidator {})
+            }
+            ValidatorPolicy::Random => PolicyValidator::Random(RandomOrderValidator {}),
+            ValidatorPolicy::FirstComeFirstServed { priority } => {
+                PolicyValidator::FirstComeFirstServed(FirstComeFirstServedValidator {
+                    priority: priority.clone(),
+                })
+            }
+            ValidatorPolicy::BundleBuilder => {
+                PolicyValidator::BundleBuilder(BundleBuilderValidator {})
+            }
+            ValidatorPolicy::BundleAuction => {
+                PolicyValidator::BundleAuction(BundleAuctionValidator {})
+            }
+        }
+    }
+}
+
+impl Validator for PolicyValidator {
+    fn order_transactions<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        transactions: Vec<Transaction>,
+    ) -> Vec<Transaction> {
+        match self {
+            PolicyValidator::GasPriority(x) => x.order_transactions(rng, transactions),
+            PolicyValidator::Random(x) => x.order_transactions(rng, transactions),
+            PolicyValidator::FirstComeFirstServed(x) => x.order_transactions(rng, transactions),
+            PolicyValidator::BundleBuilder(x) => x.order_transactions(rng, transactions),
+            PolicyValidator::BundleAuction(x) => x.order_transactions(rng, transactions),
+        }
+    }
+}
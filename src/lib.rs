@@ -0,0 +1,5 @@
+//! Aave and Uniswap simulations implemented
+//! using the [VERBS](https://github.com/simtopia/verbs) rust library.
+//!
+
+pub mod aave;
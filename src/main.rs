@@ -1,9 +1,7 @@
-use aave::SimData;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use kdam::TqdmParallelIterator;
 use rayon::prelude::*;
-
-mod aave;
+use verbs_rust_examples::aave::{self, SimData};
 
 use std::fs;
 
@@ -22,6 +20,197 @@ struct Args {
     /// Optional Alchemy API key if running from fork
     #[arg(long)]
     key: Option<String>,
+    /// Optional step to branch into paired counterfactual runs
+    #[arg(long)]
+    branch_step: Option<usize>,
+    /// Optional price shock factor applied in the counterfactual
+    /// branch, if not set the liquidator strategy is switched instead
+    #[arg(long)]
+    price_shock: Option<f64>,
+    /// Number of Uniswap liquidity provider agents
+    #[arg(long, default_value_t = 0)]
+    n_liquidity_providers: usize,
+    /// Strategy used by liquidity providers to place positions
+    #[arg(long, value_enum, default_value_t = LiquidityStrategy::RebalanceOnExit)]
+    liquidity_strategy: LiquidityStrategy,
+    /// Width of liquidity provider positions either side of the
+    /// price, in tick spacings
+    #[arg(long, default_value_t = 10)]
+    liquidity_width: i32,
+    /// Optional standard deviation, in tick spacings, of a Gaussian
+    /// initial Uniswap liquidity profile around the initial price
+    #[arg(long)]
+    initial_liquidity_sigma: Option<f64>,
+    /// Optional file of Uniswap v3 subgraph pool data, from which the
+    /// initial Uniswap liquidity profile is imported
+    #[arg(long)]
+    initial_liquidity_file: Option<String>,
+    /// Optional JSON file of the distributions from which the
+    /// attributes of borrowers are drawn
+    #[arg(long)]
+    borrower_population: Option<String>,
+    /// Optional JSON file of live Aave positions, mirrored by
+    /// borrowers, or monitored by liquidators if running from fork
+    #[arg(long)]
+    borrower_positions: Option<String>,
+    /// Optional JSON file of the addresses of users with open debt at
+    /// the fork block, monitored by liquidators if running from fork
+    #[arg(long)]
+    fork_users: Option<String>,
+    /// Optional probability, each time a borrower is active, that it
+    /// closes its position, overriding the borrower population
+    #[arg(long)]
+    borrower_close_rate: Option<f64>,
+    /// Flag for liquidators to fund liquidations with flash loans
+    #[arg(long)]
+    flash_loan_liquidation: bool,
+    /// Optional initial balance of token a of liquidators, in whole
+    /// tokens, giving them a finite inventory
+    #[arg(long)]
+    liquidator_token_a: Option<f64>,
+    /// Optional initial balance of token b of liquidators, in whole
+    /// tokens, giving them a finite inventory
+    #[arg(long)]
+    liquidator_token_b: Option<f64>,
+    /// Share of the value of a finite liquidator inventory held in token b
+    #[arg(long, default_value_t = 0.5)]
+    liquidator_target_share: f64,
+    /// Deviation from the target share above which liquidators rebalance
+    #[arg(long, default_value_t = 0.1)]
+    liquidator_rebalance_threshold: f64,
+    /// Number of liquidator agents
+    #[arg(long, default_value_t = 1)]
+    n_liquidators: usize,
+    /// Gas bidding strategies of liquidators, assigned to them in turn,
+    /// e.g. `fixed:10`, `profit-share:0.5` or `escalating:1:1.5:0.9`
+    #[arg(long, num_args = 1..)]
+    gas_bids: Vec<aave::types::GasBidStrategy>,
+    /// Policy used to order the transactions of each block
+    #[arg(long, value_enum, default_value_t = Validator::GasPriority)]
+    validator: Validator,
+    /// Agent types whose transactions are included first by the
+    /// first-come-first-served validator, e.g. `liquidator borrower`
+    #[arg(long, num_args = 1..)]
+    priority_agents: Vec<aave::types::AgentType>,
+    /// Number of searchers sandwiching Uniswap swaps, whose bundles are
+    /// placed around their victims by the bundle validators
+    #[arg(long, default_value_t = 0)]
+    n_searchers: usize,
+    /// Initial balance of each token of searchers, in whole tokens
+    #[arg(long, default_value_t = 1000.)]
+    searcher_capital: f64,
+    /// Share of the expected profit of a sandwich bid by searchers
+    #[arg(long, default_value_t = 0.9)]
+    searcher_bid_share: f64,
+    /// Base fee of each block, in gwei, or of the first block if
+    /// `--base-fee-volatility` is set
+    #[arg(long, default_value_t = 0.)]
+    base_fee: f64,
+    /// Optional standard deviation of the gas used by each block, as a
+    /// share of the gas target, from which the base fee is updated as
+    /// in EIP-1559
+    #[arg(long)]
+    base_fee_volatility: Option<f64>,
+    /// Token standing for ETH when pricing gas
+    #[arg(long, value_enum, default_value_t = GasToken::TokenA)]
+    gas_token: GasToken,
+    /// Volatility of the price of token a
+    #[arg(long, default_value_t = 0.3)]
+    prices_sigma: f64,
+    /// Initial price of token a, in USD
+    #[arg(long, default_value_t = 1000.)]
+    token_a_price: f64,
+    /// Optional probability of a borrower opting in to e-mode,
+    /// overriding the borrower population
+    #[arg(long)]
+    e_mode_probability: Option<f64>,
+    /// Loan to value of the e-mode category, in basis points
+    #[arg(long, default_value_t = 9800)]
+    e_mode_ltv: u16,
+    /// Liquidation threshold of the e-mode category, in basis points
+    #[arg(long, default_value_t = 9850)]
+    e_mode_liquidation_threshold: u16,
+    /// Liquidation bonus of the e-mode category, in basis points
+    #[arg(long, default_value_t = 10100)]
+    e_mode_liquidation_bonus: u16,
+    /// Optional rate of depegs of token a, per unit time, in which case
+    /// its price reverts to its initial price rather than following a
+    /// geometric Brownian motion
+    #[arg(long)]
+    depeg_rate: Option<f64>,
+    /// Share of the price of token a lost in a depeg
+    #[arg(long, default_value_t = 0.05)]
+    depeg_size: f64,
+    /// Rate at which the price of token a reverts to its peg
+    #[arg(long, default_value_t = 1.)]
+    depeg_reversion: f64,
+    /// Cap on the total supply of token a, in whole tokens, 0 if uncapped
+    #[arg(long, default_value_t = 0)]
+    token_a_supply_cap: u64,
+    /// Cap on the total borrowing of token a, in whole tokens, 0 if uncapped
+    #[arg(long, default_value_t = 0)]
+    token_a_borrow_cap: u64,
+    /// Cap on the total supply of token b, in whole tokens, 0 if uncapped
+    #[arg(long, default_value_t = 0)]
+    token_b_supply_cap: u64,
+    /// Cap on the total borrowing of token b, in whole tokens, 0 if uncapped
+    #[arg(long, default_value_t = 0)]
+    token_b_borrow_cap: u64,
+    /// Debt ceiling of token a as isolated collateral, in USD, token a
+    /// is not isolated if 0
+    #[arg(long, default_value_t = 0.)]
+    token_a_debt_ceiling: f64,
+    /// Optional probability of a borrower borrowing at a stable rate,
+    /// overriding the borrower population
+    #[arg(long)]
+    stable_rate_probability: Option<f64>,
+    /// Optional difference between borrow rates, as a fraction per
+    /// year, above which borrowers swap their debt to the cheaper
+    /// interest rate mode, overriding the borrower population
+    #[arg(long)]
+    rate_swap_spread: Option<f64>,
+    /// Flag to add a keeper rebalancing the stable borrow rates of users
+    #[arg(long)]
+    stable_rate_rebalancer: bool,
+    /// Policies of a risk steward adjusting the parameters of the
+    /// reserves, applied in turn, e.g. `volatility:0.5:0.5`,
+    /// `caps:0.9:0.25`, `reserve-factor:0.8:500` or `bad-debt:100:500`.
+    /// There is no risk steward if none are given.
+    #[arg(long, num_args = 1..)]
+    risk_policies: Vec<aave::types::RiskPolicy>,
+    /// Steps between reviews of the parameters by the risk steward
+    #[arg(
+        long,
+        default_value_t = 10,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    risk_review_period: usize,
+    /// Number of steps of oracle prices used by the risk steward to
+    /// estimate volatility
+    #[arg(long, default_value_t = 20)]
+    volatility_window: usize,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum LiquidityStrategy {
+    FullRange,
+    FixedWidth,
+    RebalanceOnExit,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Validator {
+    GasPriority,
+    Random,
+    FirstComeFirstServed,
+    BundleBuilder,
+    BundleAuction,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum GasToken {
+    TokenA,
+    TokenB,
 }
 
 fn main() {
@@ -30,56 +219,242 @@ fn main() {
     let seeds = Vec::from_iter(0..args.n_seeds);
     let n_steps = args.n_steps;
 
-    let results: Vec<SimData> = match args.fork {
+    let mut borrower_population = match args.borrower_population {
+        Some(path) => {
+            let json = fs::read_to_string(path).expect("Could not read borrower population");
+            aave::types::BorrowerPopulation::from_json(&json).expect("Invalid borrower population")
+        }
+        None => aave::types::BorrowerPopulation::default(),
+    };
+    if let Some(close_rate) = args.borrower_close_rate {
+        borrower_population.behaviour.close_rate = close_rate;
+    }
+    if let Some(e_mode_probability) = args.e_mode_probability {
+        borrower_population.e_mode_probability = e_mode_probability;
+    }
+    if let Some(stable_rate_probability) = args.stable_rate_probability {
+        borrower_population.stable_rate_probability = stable_rate_probability;
+    }
+    if let Some(rate_swap_spread) = args.rate_swap_spread {
+        borrower_population.behaviour.rate_swap_spread = Some(rate_swap_spread);
+    }
+    let borrower_positions = match args.borrower_positions {
+        Some(path) => {
+            let json = fs::read_to_string(path).expect("Could not read borrower positions");
+            aave::types::BorrowerPosition::from_json(&json).expect("Invalid borrower positions")
+        }
+        None => Vec::new(),
+    };
+    let fork_users = match args.fork_users {
+        Some(path) => {
+            let json = fs::read_to_string(path).expect("Could not read fork users");
+            serde_json::from_str(&json).expect("Invalid fork users")
+        }
+        None => Vec::new(),
+    };
+
+    let liquidator_capital = match (args.liquidator_token_a, args.liquidator_token_b) {
+        (None, None) => None,
+        (token_a, token_b) => Some(aave::types::LiquidatorCapital {
+            token_a: token_a.unwrap_or(0.),
+            token_b: token_b.unwrap_or(0.),
+            target_token_b_share: args.liquidator_target_share,
+            rebalance_threshold: args.liquidator_rebalance_threshold,
+        }),
+    };
+
+    let risk_steward =
+        (!args.risk_policies.is_empty()).then(|| aave::types::RiskStewardParameters {
+            policies: args.risk_policies.clone(),
+            review_period: args.risk_review_period,
+            volatility_window: args.volatility_window,
+        });
+
+    let validator = match args.validator {
+        Validator::GasPriority => aave::types::ValidatorPolicy::GasPriority,
+        Validator::Random => aave::types::ValidatorPolicy::Random,
+        Validator::FirstComeFirstServed => aave::types::ValidatorPolicy::FirstComeFirstServed {
+            priority: args.priority_agents.clone(),
+        },
+        Validator::BundleBuilder => aave::types::ValidatorPolicy::BundleBuilder,
+        Validator::BundleAuction => aave::types::ValidatorPolicy::BundleAuction,
+    };
+
+    let base_fee = match args.base_fee_volatility {
+        Some(volatility) => aave::types::BaseFee::Eip1559 {
+            initial: args.base_fee,
+            volatility,
+        },
+        None => aave::types::BaseFee::Constant(args.base_fee),
+    };
+
+    let gas_token = match args.gas_token {
+        GasToken::TokenA => aave::types::GasToken::TokenA,
+        GasToken::TokenB => aave::types::GasToken::TokenB,
+    };
+
+    let json = match args.fork {
         true => match args.key {
             Some(k) => {
                 let params = aave::types::ForkedSimParameters {
                     n_borrowers: 10,
-                    n_liquidators: 1,
+                    n_liquidators: args.n_liquidators,
                     prices_mu: 0f64,
                     prices_dt: 0.01f64,
                     prices_sigma: 0.4f64,
-                    borrow_activation_rate: 0.1f64,
                     adversarial: false,
+                    flash_loan_liquidation: args.flash_loan_liquidation,
+                    liquidator_capital,
+                    gas_bid_strategies: args.gas_bids.clone(),
+                    validator: validator.clone(),
+                    n_searchers: args.n_searchers,
+                    searcher_capital: args.searcher_capital,
+                    searcher_bid_share: args.searcher_bid_share,
+                    base_fee,
+                    gas_token,
                     uniswap_fee: 500u32,
                     block_number: 18564279u64,
+                    borrower_population,
+                    borrower_positions,
+                    fork_users,
                 };
 
-                seeds
+                let results: Vec<SimData> = seeds
                     .par_iter()
                     .map(|i| {
                         let k = k.clone();
-                        aave::aave_sim_from_fork(*i, n_steps, params, k)
+                        aave::aave_sim_from_fork(*i, n_steps, params.clone(), k)
                     })
-                    .collect()
+                    .collect();
+                serde_json::to_string(&results)
             }
             None => panic!("Alchemy key argument required for forked simulation"),
         },
         false => {
+            let liquidity_provider_strategy = match args.liquidity_strategy {
+                LiquidityStrategy::FullRange => aave::types::LiquidityStrategy::FullRange,
+                LiquidityStrategy::FixedWidth => aave::types::LiquidityStrategy::FixedWidth {
+                    width: args.liquidity_width,
+                },
+                LiquidityStrategy::RebalanceOnExit => {
+                    aave::types::LiquidityStrategy::RebalanceOnExit {
+                        width: args.liquidity_width,
+                    }
+                }
+            };
+            // Wide uniform liquidity by default, so there can be tick crossing
+            let liquidity_profile =
+                match (args.initial_liquidity_file, args.initial_liquidity_sigma) {
+                    (Some(path), _) => {
+                        let json = fs::read_to_string(path).expect("Could not read pool data");
+                        aave::types::LiquidityProfile::from_pool_data(&json)
+                            .expect("Invalid pool data")
+                    }
+                    (None, Some(sigma)) => {
+                        aave::types::LiquidityProfile::Gaussian { sigma, n_bands: 20 }
+                    }
+                    (None, None) => aave::types::LiquidityProfile::Uniform { width: 2000 },
+                };
+            let price_process = match args.depeg_rate {
+                Some(depeg_rate) => aave::types::PriceProcess::Depeg {
+                    reversion: args.depeg_reversion,
+                    depeg_rate,
+                    depeg_size: args.depeg_size,
+                },
+                None => aave::types::PriceProcess::Gbm,
+            };
+            let e_mode_category = aave::types::EModeCategory {
+                ltv: args.e_mode_ltv,
+                liquidation_threshold: args.e_mode_liquidation_threshold,
+                liquidation_bonus: args.e_mode_liquidation_bonus,
+                ..Default::default()
+            };
+            let reserve_limits = aave::types::ReserveLimits {
+                token_a: aave::types::ReserveCaps {
+                    supply_cap: args.token_a_supply_cap,
+                    borrow_cap: args.token_a_borrow_cap,
+                },
+                token_b: aave::types::ReserveCaps {
+                    supply_cap: args.token_b_supply_cap,
+                    borrow_cap: args.token_b_borrow_cap,
+                },
+                token_a_debt_ceiling: args.token_a_debt_ceiling,
+            };
             let params = aave::types::SimParameters {
                 n_borrowers: 10,
-                n_liquidators: 1,
+                n_liquidators: args.n_liquidators,
                 prices_mu: 0f64,
                 prices_dt: 0.01f64,
-                prices_sigma: 0.3f64,
-                borrow_activation_rate: 0.1f64,
-                token_a_initial_price: 100000000000i128,
+                prices_sigma: args.prices_sigma,
+                price_process,
+                token_a_initial_price: (args.token_a_price * 1e8) as i128,
                 token_b_initial_price: 100000000i128,
                 token_a_liquidation_threshold: 8000u128,
                 token_b_liquidation_threshold: 8500u128,
                 token_a_base_ltv: 7500u128,
                 token_b_base_ltv: 8000u128,
+                e_mode_category,
+                reserve_limits,
                 liquidity: 10_f64.powf(5.),
+                liquidity_profile,
                 adversarial: false,
+                flash_loan_liquidation: args.flash_loan_liquidation,
+                liquidator_capital,
+                gas_bid_strategies: args.gas_bids.clone(),
+                validator: validator.clone(),
+                n_searchers: args.n_searchers,
+                searcher_capital: args.searcher_capital,
+                searcher_bid_share: args.searcher_bid_share,
+                stable_rate_rebalancer: args.stable_rate_rebalancer,
+                risk_steward,
+                base_fee,
+                gas_token,
                 uniswap_fee: 500u32,
+                n_liquidity_providers: args.n_liquidity_providers,
+                liquidity_provider_strategy,
+                liquidity_provider_liquidity: 10_f64.powf(4.),
+                borrower_population,
+                borrower_positions,
             };
-            seeds
-                .par_iter()
-                .tqdm()
-                .map(|i| aave::aave_sim(*i, n_steps, params))
-                .collect()
+            match args.branch_step {
+                Some(branch_step) => {
+                    let counterfactual = match args.price_shock {
+                        Some(shock) => aave::types::Branch {
+                            price_shock: Some(shock),
+                            ..Default::default()
+                        },
+                        None => aave::types::Branch {
+                            adversarial: Some(!params.adversarial),
+                            ..Default::default()
+                        },
+                    };
+                    let branches = [aave::types::Branch::default(), counterfactual];
+                    let results: Vec<Vec<SimData>> = seeds
+                        .par_iter()
+                        .tqdm()
+                        .map(|i| {
+                            aave::aave_sim_branched(
+                                *i,
+                                n_steps,
+                                branch_step,
+                                params.clone(),
+                                &branches,
+                            )
+                        })
+                        .collect();
+                    serde_json::to_string(&results)
+                }
+                None => {
+                    let results: Vec<SimData> = seeds
+                        .par_iter()
+                        .tqdm()
+                        .map(|i| aave::aave_sim(*i, n_steps, params.clone()))
+                        .collect();
+                    serde_json::to_string(&results)
+                }
+            }
         }
     };
-    let json = serde_json::to_string(&results).expect("Could not serialise to json string");
+    let json = json.expect("Could not serialise to json string");
     let _ = fs::write("sim_dat.txt", json);
 }
@@ -0,0 +1,197 @@
+use alloy_primitives::{Address, U256};
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::agent::AgentSet;
+use verbs_rs::env::{Env, GasPriorityValidator};
+use verbs_rs::utils::{address_from_hex, Eth};
+use verbs_rs::LocalDB;
+use verbs_rust_examples::aave::initialisation::{get_sqrt_price_token_a_x96, initialise_sim};
+use verbs_rust_examples::aave::protocol::{aave_abi, deploy_aave_contracts, uniswap_abi};
+use verbs_rust_examples::aave::types::InterestRateMode;
+use verbs_rust_examples::aave::{calls, types};
+
+const ADMIN: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+fn sim_parameters() -> types::SimParameters {
+    types::SimParameters {
+        n_borrowers: 2,
+        ..Default::default()
+    }
+}
+
+/// Supply 1 token a as collateral and borrow 95% of the available amount of token b
+fn supply_and_borrow(
+    env: &mut Env<LocalDB, GasPriorityValidator>,
+    rng: &mut Xoroshiro128StarStar,
+    borrower: Address,
+    pool: Address,
+    token_a: Address,
+    token_b: Address,
+) {
+    let supply = calls::supply_call(borrower, pool, token_a, U256::to_weth(1));
+    env.process_transactions(vec![supply], rng, 0);
+
+    let user_data = calls::get_user_data(env, Address::ZERO, pool, borrower);
+    assert!(user_data.totalCollateralBase > U256::ZERO);
+    assert_eq!(user_data.totalDebtBase, U256::ZERO);
+
+    // Token b is priced at 1 base unit with 8 decimals, and has 18 decimals
+    let amount = user_data.availableBorrowsBase * U256::from(10u128.pow(10)) * U256::from(95)
+        / U256::from(100);
+    let borrow = calls::borrow_call(borrower, pool, token_b, amount, InterestRateMode::Variable);
+    env.process_transactions(vec![borrow], rng, 1);
+}
+
+#[test]
+fn reserves_have_configured_risk_parameters() {
+    let params = sim_parameters();
+    let admin_address = address_from_hex(ADMIN);
+    let env =
+        Env::<LocalDB, GasPriorityValidator>::init(U256::ZERO, U256::ZERO, GasPriorityValidator {});
+
+    let (mut env, periphery_addresses, _, aave_addresses) = deploy_aave_contracts(
+        env,
+        admin_address,
+        params.token_a_liquidation_threshold,
+        params.token_b_liquidation_threshold,
+        params.token_a_base_ltv,
+        params.token_b_base_ltv,
+        &params.e_mode_category,
+        &params.reserve_limits,
+        params.uniswap_fee,
+    );
+
+    let token_a_config = calls::get_reserve_configuration_data(
+        &mut env,
+        admin_address,
+        aave_addresses.data_provider,
+        periphery_addresses.token_a,
+    );
+    assert_eq!(token_a_config.ltv, U256::from(params.token_a_base_ltv));
+    assert_eq!(
+        token_a_config.liquidationThreshold,
+        U256::from(params.token_a_liquidation_threshold)
+    );
+    assert!(token_a_config.isActive);
+
+    let token_b_config = calls::get_reserve_configuration_data(
+        &mut env,
+        admin_address,
+        aave_addresses.data_provider,
+        periphery_addresses.token_b,
+    );
+    assert_eq!(token_b_config.ltv, U256::from(params.token_b_base_ltv));
+    assert_eq!(
+        token_b_config.liquidationThreshold,
+        U256::from(params.token_b_liquidation_threshold)
+    );
+    assert!(token_b_config.borrowingEnabled);
+}
+
+#[test]
+fn uniswap_pool_initialised_at_initial_price() {
+    let params = sim_parameters();
+    let (mut env, _, _, uniswap_addresses, _) =
+        initialise_sim(params.clone(), GasPriorityValidator {});
+
+    let slot0 = calls::get_slot0(&mut env, Address::ZERO, uniswap_addresses.pool);
+
+    assert_eq!(slot0.sqrtPriceX96, get_sqrt_price_token_a_x96(&params));
+    assert!(calls::get_liquidity(&mut env, Address::ZERO, uniswap_addresses.pool) > 0);
+}
+
+#[test]
+fn borrower_can_supply_and_borrow() {
+    let params = sim_parameters();
+    let (mut env, agents, periphery_addresses, _, aave_addresses) =
+        initialise_sim(params.clone(), GasPriorityValidator {});
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let borrower = agents.borrow_agents.get_addresses()[0];
+
+    supply_and_borrow(
+        &mut env,
+        &mut rng,
+        borrower,
+        aave_addresses.pool,
+        periphery_addresses.token_a,
+        periphery_addresses.token_b,
+    );
+
+    let user_data = calls::get_user_data(&mut env, Address::ZERO, aave_addresses.pool, borrower);
+    assert!(user_data.totalDebtBase > U256::ZERO);
+    assert!(user_data.healthFactor > U256::to_weth(1));
+    assert!(calls::balance_of(&mut env, borrower, periphery_addresses.token_b) > U256::ZERO);
+}
+
+#[test]
+fn price_drop_allows_liquidation() {
+    let params = sim_parameters();
+    let (mut env, agents, periphery_addresses, uniswap_addresses, aave_addresses) =
+        initialise_sim(params.clone(), GasPriorityValidator {});
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let borrower = agents.borrow_agents.get_addresses()[0];
+    let liquidator = agents.liquidation_agents.get_addresses()[0];
+
+    supply_and_borrow(
+        &mut env,
+        &mut rng,
+        borrower,
+        aave_addresses.pool,
+        periphery_addresses.token_a,
+        periphery_addresses.token_b,
+    );
+
+    // The Aave oracle prices token a from the Uniswap pool, so selling
+    // token a into the pool drops its price
+    let price_before = calls::get_asset_price(
+        &mut env,
+        Address::ZERO,
+        aave_addresses.oracle,
+        periphery_addresses.token_a,
+    );
+    let swap = calls::uniswap_swap_call(
+        liquidator,
+        uniswap_addresses.swap_router,
+        uniswap_abi::SwapRouter::ExactInputSingleParams {
+            amountIn: U256::to_weth(500),
+            amountOutMinimum: U256::ZERO,
+            deadline: U256::MAX,
+            fee: params.uniswap_fee,
+            recipient: liquidator,
+            sqrtPriceLimitX96: U256::ZERO,
+            tokenIn: periphery_addresses.token_a,
+            tokenOut: periphery_addresses.token_b,
+        },
+    );
+    env.process_transactions(vec![swap], &mut rng, 2);
+    let price_after = calls::get_asset_price(
+        &mut env,
+        Address::ZERO,
+        aave_addresses.oracle,
+        periphery_addresses.token_a,
+    );
+    assert!(price_after < price_before);
+
+    let user_data = calls::get_user_data(&mut env, Address::ZERO, aave_addresses.pool, borrower);
+    assert!(user_data.healthFactor < U256::to_weth(1));
+
+    let (_, logs) = env
+        .direct_execute(
+            liquidator,
+            aave_addresses.pool,
+            aave_abi::Pool_Implementation::liquidationCallCall {
+                collateralAsset: periphery_addresses.token_a,
+                debtAsset: periphery_addresses.token_b,
+                user: borrower,
+                debtToCover: U256::MAX,
+                receiveAToken: false,
+            },
+            U256::ZERO,
+        )
+        .expect("Liquidation call reverted");
+    assert!(!logs.is_empty());
+
+    let user_data_after =
+        calls::get_user_data(&mut env, Address::ZERO, aave_addresses.pool, borrower);
+    assert!(user_data_after.totalDebtBase < user_data.totalDebtBase);
+}
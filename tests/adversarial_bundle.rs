@@ -0,0 +1,134 @@
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::SolEvent;
+use verbs_rs::agent::AgentSet;
+use verbs_rs::utils::{scale_data_value, Eth};
+use verbs_rust_examples::aave::protocol::{periphery_abi, AaveAddresses, UniswapAddresses};
+use verbs_rust_examples::aave::types::{BorrowerPopulation, ParameterDistribution};
+use verbs_rust_examples::aave::{calls, deployment, liquidation, types};
+
+mod common;
+use common::TestEnv;
+
+/// A borrower with $100000 of collateral, and an adversarial liquidator
+fn sim_parameters() -> types::SimParameters {
+    types::SimParameters {
+        liquidity: 10_f64.powf(4.),
+        adversarial: true,
+        borrower_population: BorrowerPopulation {
+            position_size: ParameterDistribution::Constant(100000.),
+            ..common::borrower_population()
+        },
+        ..common::sim_parameters()
+    }
+}
+
+fn health_factor(env: &mut TestEnv, user: Address, aave_addresses: &AaveAddresses) -> f64 {
+    let user_data = calls::get_user_data(env, Address::ZERO, aave_addresses.pool, user);
+    scale_data_value(user_data.healthFactor, 18, 12)
+}
+
+fn sqrt_price(env: &mut TestEnv, uniswap_addresses: &UniswapAddresses) -> f64 {
+    let sqrt_price_x96 = calls::get_slot0(env, Address::ZERO, uniswap_addresses.pool).sqrtPriceX96;
+    scale_data_value(sqrt_price_x96, 0, 0)
+}
+
+#[test]
+fn bundle_manipulates_liquidates_and_unwinds() {
+    let (mut env, mut agents, (periphery_addresses, uniswap_addresses), aave_addresses, mut rng) =
+        common::liquidatable_position(sim_parameters(), 850.);
+    let borrower = agents.borrow_agents.get_addresses()[0];
+    let liquidator = agents.liquidation_agents.get_addresses()[0];
+    assert!(health_factor(&mut env, borrower, &aave_addresses) > 1.);
+    let price = sqrt_price(&mut env, &uniswap_addresses);
+    let debt =
+        calls::get_user_data(&mut env, Address::ZERO, aave_addresses.pool, borrower).totalDebtBase;
+
+    // The manipulation, liquidation and back-run are a single transaction
+    let transactions = agents.liquidation_agents.call(&mut rng, &mut env);
+    assert_eq!(transactions.len(), 1);
+    env.process_transactions(transactions, &mut rng, 3);
+    agents.liquidation_agents.record(&mut env);
+
+    let user_data = calls::get_user_data(&mut env, Address::ZERO, aave_addresses.pool, borrower);
+    assert!(user_data.totalDebtBase < debt);
+    // The back-run restores the price, up to the fees and the liquidation
+    assert!((sqrt_price(&mut env, &uniswap_addresses) / price - 1.).abs() < 1e-3);
+
+    let bundles = agents.take_adversarial_bundles();
+    assert_eq!(bundles.len(), 1);
+    assert_eq!(bundles[0].liquidator, liquidator);
+    assert_eq!(bundles[0].users, vec![borrower]);
+    assert!(bundles[0].success);
+    // The cost of the manipulation is paid out of the liquidation gain
+    assert!(bundles[0].realised_profit > 0.);
+    assert!(bundles[0].realised_profit < bundles[0].liquidation_gain);
+    // A manipulation in a single bundle reaches the theoretical bound,
+    // up to the rounding of the oracle prices and of the pool
+    assert!((bundles[0].realised_profit / bundles[0].profit_bound - 1.).abs() < 1e-4);
+
+    // The executor returns all its balances to the liquidator
+    let logs = &env.last_events[0].logs;
+    let sweep =
+        periphery_abi::MintableERC20::Transfer::decode_log(&logs[logs.len() - 1], true).unwrap();
+    assert_eq!(sweep.to, liquidator);
+    for token in [periphery_addresses.token_a, periphery_addresses.token_b] {
+        assert_eq!(calls::balance_of(&mut env, sweep.from, token), U256::ZERO);
+    }
+}
+
+#[test]
+fn failed_bundle_reverts_every_step() {
+    let (mut env, agents, (periphery_addresses, uniswap_addresses), aave_addresses, mut rng) =
+        common::liquidatable_position(sim_parameters(), 1000.);
+    let borrower = agents.borrow_agents.get_addresses()[0];
+    let liquidator = agents.liquidation_agents.get_addresses()[0];
+    let executor = deployment::deploy_liquidation_bundle_executor(&mut env, liquidator);
+    for token in [periphery_addresses.token_a, periphery_addresses.token_b] {
+        env.direct_execute(
+            liquidator,
+            token,
+            periphery_abi::MintableERC20::approveCall {
+                spender: executor,
+                amount: U256::MAX,
+            },
+            U256::ZERO,
+        )
+        .unwrap();
+    }
+
+    let price = sqrt_price(&mut env, &uniswap_addresses);
+    let balances = [periphery_addresses.token_a, periphery_addresses.token_b]
+        .map(|token| calls::balance_of(&mut env, liquidator, token));
+
+    // The manipulation is too small to make the borrower liquidatable
+    let bundle = calls::liquidation_bundle_call(
+        liquidator,
+        executor,
+        aave_addresses.pool,
+        periphery_addresses.token_a,
+        periphery_addresses.token_b,
+        uniswap_addresses.swap_router,
+        500u32,
+        U256::to_weth(1),
+        U256::to_weth(10u128.pow(6)),
+        vec![borrower],
+    );
+    env.process_transactions(vec![bundle], &mut rng, 3);
+
+    assert!(env.last_events[0].logs.is_empty());
+    assert!(liquidation::bundle_liquidations(
+        &env.last_events,
+        aave_addresses.pool,
+        executor,
+        liquidator
+    )
+    .is_none());
+    // The swap before the failed liquidation is reverted with it
+    assert_eq!(sqrt_price(&mut env, &uniswap_addresses), price);
+    assert_eq!(
+        [periphery_addresses.token_a, periphery_addresses.token_b]
+            .map(|token| calls::balance_of(&mut env, liquidator, token)),
+        balances
+    );
+    assert!(health_factor(&mut env, borrower, &aave_addresses) > 1.);
+}
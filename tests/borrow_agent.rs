@@ -0,0 +1,397 @@
+use alloy_primitives::{Address, U256};
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::agent::{AgentSet, RecordedAgentSet};
+use verbs_rs::utils::{scale_data_value, Eth};
+use verbs_rust_examples::aave::agents::AgentStates;
+use verbs_rust_examples::aave::initialisation::initialise_sim;
+use verbs_rust_examples::aave::protocol::{periphery_abi, AaveAddresses};
+use verbs_rust_examples::aave::types::{
+    BorrowerBehaviour, BorrowerPopulation, BorrowerPosition, ParameterDistribution,
+};
+use verbs_rust_examples::aave::validator::OrderedGasPriorityValidator;
+use verbs_rust_examples::aave::{calls, types};
+
+mod common;
+use common::TestEnv;
+
+fn behaviour(top_up_probability: f64, withdraw_probability: f64) -> BorrowerBehaviour {
+    BorrowerBehaviour {
+        repay_threshold: ParameterDistribution::Constant(1.1),
+        target_health_factor: ParameterDistribution::Constant(1.25),
+        releverage_threshold: ParameterDistribution::Constant(1.5),
+        top_up_probability,
+        withdraw_probability,
+        ..Default::default()
+    }
+}
+
+/// Borrowers with 1 token a of collateral, active every step
+fn population(behaviour: BorrowerBehaviour) -> BorrowerPopulation {
+    BorrowerPopulation {
+        behaviour,
+        ..common::borrower_population()
+    }
+}
+
+fn sim_parameters(
+    n_borrowers: usize,
+    borrower_population: BorrowerPopulation,
+) -> types::SimParameters {
+    types::SimParameters {
+        n_borrowers,
+        borrower_population,
+        ..Default::default()
+    }
+}
+
+/// Run a step of the borrow agents, returning the health factor of the borrower
+fn step_borrowers(
+    env: &mut TestEnv,
+    agents: &mut AgentStates,
+    rng: &mut Xoroshiro128StarStar,
+    step: usize,
+) -> f64 {
+    let transactions = agents.borrow_agents.call(rng, env);
+    env.process_transactions(transactions, rng, step);
+    agents.borrow_agents.record(env);
+    let health_factor = agents.borrow_agents.take_records().pop().unwrap()[0].0;
+    scale_data_value(health_factor, 18, 6)
+}
+
+/// Initialise a simulation, and step the borrower until it has supplied and borrowed
+fn borrowed_position(
+    borrower_behaviour: BorrowerBehaviour,
+) -> (
+    TestEnv,
+    AgentStates,
+    common::Addresses,
+    AaveAddresses,
+    Xoroshiro128StarStar,
+) {
+    let (mut env, mut agents, addresses, aave_addresses) =
+        common::initialise(sim_parameters(1, population(borrower_behaviour)));
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+
+    step_borrowers(&mut env, &mut agents, &mut rng, 0);
+    let health_factor = step_borrowers(&mut env, &mut agents, &mut rng, 1);
+    assert_close(health_factor, 1.25);
+
+    (env, agents, addresses, aave_addresses, rng)
+}
+
+fn user_data(
+    env: &mut TestEnv,
+    agents: &AgentStates,
+    aave_addresses: &AaveAddresses,
+) -> (U256, U256) {
+    let borrower = agents.borrow_agents.get_addresses()[0];
+    let user_data = calls::get_user_data(env, Address::ZERO, aave_addresses.pool, borrower);
+    (user_data.totalCollateralBase, user_data.totalDebtBase)
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-3,
+        "{} is not close to {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn borrower_repays_when_health_factor_falls() {
+    let (mut env, mut agents, addresses, aave_addresses, mut rng) =
+        borrowed_position(behaviour(0., 0.));
+    let (collateral, debt) = user_data(&mut env, &agents, &aave_addresses);
+
+    // A 15% fall in the price of the collateral takes the health factor below 1.1
+    common::set_token_a_price(&mut env, &agents, &addresses, &mut rng, 850.);
+    let health_factor = step_borrowers(&mut env, &mut agents, &mut rng, 2);
+
+    assert_close(health_factor, 1.25);
+    let (new_collateral, new_debt) = user_data(&mut env, &agents, &aave_addresses);
+    assert!(new_collateral < collateral);
+    assert!(new_debt < debt);
+}
+
+#[test]
+fn borrower_tops_up_collateral_when_health_factor_falls() {
+    let (mut env, mut agents, addresses, aave_addresses, mut rng) =
+        borrowed_position(behaviour(1., 0.));
+    let (_, debt) = user_data(&mut env, &agents, &aave_addresses);
+
+    common::set_token_a_price(&mut env, &agents, &addresses, &mut rng, 850.);
+    let health_factor = step_borrowers(&mut env, &mut agents, &mut rng, 2);
+
+    assert_close(health_factor, 1.25);
+    assert_eq!(user_data(&mut env, &agents, &aave_addresses).1, debt);
+}
+
+#[test]
+fn borrower_releverages_when_health_factor_rises() {
+    let (mut env, mut agents, addresses, aave_addresses, mut rng) =
+        borrowed_position(behaviour(0., 0.));
+    let (_, debt) = user_data(&mut env, &agents, &aave_addresses);
+
+    // Health factor is unchanged within the thresholds
+    common::set_token_a_price(&mut env, &agents, &addresses, &mut rng, 1100.);
+    let health_factor = step_borrowers(&mut env, &mut agents, &mut rng, 2);
+    assert_close(health_factor, 1.25 * 1.1);
+    assert_eq!(user_data(&mut env, &agents, &aave_addresses).1, debt);
+
+    common::set_token_a_price(&mut env, &agents, &addresses, &mut rng, 1300.);
+    let health_factor = step_borrowers(&mut env, &mut agents, &mut rng, 3);
+    assert_close(health_factor, 1.25);
+    assert!(user_data(&mut env, &agents, &aave_addresses).1 > debt);
+}
+
+#[test]
+fn borrower_withdraws_excess_collateral() {
+    let (mut env, mut agents, addresses, aave_addresses, mut rng) =
+        borrowed_position(behaviour(0., 1.));
+    let (collateral, debt) = user_data(&mut env, &agents, &aave_addresses);
+
+    common::set_token_a_price(&mut env, &agents, &addresses, &mut rng, 1300.);
+    let health_factor = step_borrowers(&mut env, &mut agents, &mut rng, 2);
+
+    assert_close(health_factor, 1.25);
+    let (new_collateral, new_debt) = user_data(&mut env, &agents, &aave_addresses);
+    assert_eq!(new_debt, debt);
+    // Collateral is valued at the new price, 30% higher
+    assert!(new_collateral < collateral * U256::from(13) / U256::from(10));
+}
+
+#[test]
+fn borrower_closes_position() {
+    let mut borrower_behaviour = behaviour(0., 0.);
+    borrower_behaviour.close_rate = 1.0;
+    let (mut env, mut agents, _, aave_addresses, mut rng) = borrowed_position(borrower_behaviour);
+
+    let health_factor = step_borrowers(&mut env, &mut agents, &mut rng, 2);
+    assert_eq!(
+        user_data(&mut env, &agents, &aave_addresses),
+        (U256::ZERO, U256::ZERO)
+    );
+    assert!(health_factor > 1e6);
+
+    // Closed borrowers do not borrow again
+    step_borrowers(&mut env, &mut agents, &mut rng, 3);
+    assert_eq!(user_data(&mut env, &agents, &aave_addresses).1, U256::ZERO);
+}
+
+#[test]
+fn borrower_can_supply_token_b_and_borrow_token_a() {
+    let mut borrower_population = population(behaviour(0., 0.));
+    borrower_population.reverse_assets_probability = 1.;
+    let (mut env, mut agents, periphery_addresses, _, aave_addresses) = initialise_sim(
+        sim_parameters(1, borrower_population),
+        OrderedGasPriorityValidator {},
+    );
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let a_token = calls::get_a_token(
+        &mut env,
+        Address::ZERO,
+        aave_addresses.pool,
+        periphery_addresses.token_a,
+    );
+
+    // There is no liquidity of token a to borrow
+    step_borrowers(&mut env, &mut agents, &mut rng, 0);
+    step_borrowers(&mut env, &mut agents, &mut rng, 1);
+    assert_eq!(user_data(&mut env, &agents, &aave_addresses).1, U256::ZERO);
+
+    // Provide liquidity of token a from a liquidator
+    let liquidator = agents.liquidation_agents.get_addresses()[0];
+    env.direct_execute(
+        liquidator,
+        periphery_addresses.token_a,
+        periphery_abi::MintableERC20::approveCall {
+            spender: aave_addresses.pool,
+            amount: U256::MAX,
+        },
+        U256::ZERO,
+    )
+    .unwrap();
+    let supply = calls::supply_call(
+        liquidator,
+        aave_addresses.pool,
+        periphery_addresses.token_a,
+        U256::to_weth(10),
+    );
+    env.process_transactions(vec![supply], &mut rng, 2);
+    assert_eq!(
+        calls::balance_of(&mut env, a_token, periphery_addresses.token_a),
+        U256::to_weth(10)
+    );
+
+    let health_factor = step_borrowers(&mut env, &mut agents, &mut rng, 3);
+    assert_close(health_factor, 1.25);
+
+    // 1000 token b, priced at 1 with 8 decimals, are supplied
+    let (collateral, debt) = user_data(&mut env, &agents, &aave_addresses);
+    assert_eq!(collateral, U256::from(1000u128 * 10u128.pow(8)));
+    assert!(debt > U256::ZERO);
+}
+
+#[test]
+fn borrower_attributes_are_drawn_from_population() {
+    let borrower_population = BorrowerPopulation {
+        position_size: ParameterDistribution::Pareto {
+            scale: 100.,
+            shape: 1.5,
+        },
+        activation_rate: ParameterDistribution::Uniform {
+            low: 0.1,
+            high: 0.5,
+        },
+        reverse_assets_probability: 0.5,
+        behaviour: BorrowerBehaviour::default(),
+        ..Default::default()
+    };
+    let (mut env, mut agents, _, _, _) = initialise_sim(
+        sim_parameters(50, borrower_population),
+        OrderedGasPriorityValidator {},
+    );
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    agents.borrow_agents.call(&mut rng, &mut env);
+
+    let attributes: Vec<_> = agents
+        .borrow_agents
+        .agents_mut()
+        .iter()
+        .map(|agent| agent.get_attributes().unwrap())
+        .collect();
+
+    assert!(attributes.iter().all(|x| x.position_size >= 100.));
+    assert!(attributes
+        .iter()
+        .all(|x| 0.1 <= x.activation_rate && x.activation_rate <= 0.5));
+    assert!(attributes
+        .iter()
+        .all(|x| x.repay_threshold <= x.target_health_factor
+            && x.target_health_factor <= x.releverage_threshold));
+    // Borrowers are heterogeneous
+    assert!(attributes.iter().any(|x| x.reverse_assets));
+    assert!(attributes.iter().any(|x| !x.reverse_assets));
+    assert!(attributes
+        .windows(2)
+        .any(|x| x[0].position_size != x[1].position_size));
+}
+
+#[test]
+fn population_is_read_from_json() {
+    let json = r#"{
+        "position_size": {"log_normal": {"mu": 7.0, "sigma": 1.5}},
+        "activation_rate": {"constant": 0.2},
+        "behaviour": {"close_rate": 0.01}
+    }"#;
+    let population = BorrowerPopulation::from_json(json).unwrap();
+
+    assert_eq!(
+        population.position_size,
+        ParameterDistribution::LogNormal {
+            mu: 7.0,
+            sigma: 1.5
+        }
+    );
+    assert_eq!(
+        population.activation_rate,
+        ParameterDistribution::Constant(0.2)
+    );
+    assert_eq!(population.behaviour.close_rate, 0.01);
+    // Omitted fields take their default values
+    let default = BorrowerPopulation::default();
+    assert_eq!(
+        population.reverse_assets_probability,
+        default.reverse_assets_probability
+    );
+    assert_eq!(
+        population.behaviour.repay_threshold,
+        default.behaviour.repay_threshold
+    );
+
+    assert!(BorrowerPopulation::from_json(r#"{"position_size": {"gamma": 1.0}}"#).is_err());
+}
+
+#[test]
+fn positions_are_read_from_json() {
+    let json = r#"[
+        {"user": "0x00000000000000000000000000000000000000aa", "collateral": 2500.5, "debt": 1000, "e_mode": 1},
+        {"user": "0x00000000000000000000000000000000000000bb", "collateral": 100, "debt": 0}
+    ]"#;
+    let positions = BorrowerPosition::from_json(json).unwrap();
+
+    assert_eq!(
+        positions,
+        vec![
+            BorrowerPosition {
+                user: Address::with_last_byte(0xaa),
+                collateral: 2500.5,
+                debt: 1000.,
+                e_mode: 1,
+            },
+            BorrowerPosition {
+                user: Address::with_last_byte(0xbb),
+                collateral: 100.,
+                debt: 0.,
+                e_mode: 0,
+            },
+        ]
+    );
+    assert!(
+        BorrowerPosition::from_json(r#"[{"user": "0x01", "collateral": 1, "debt": 1}]"#).is_err()
+    );
+}
+
+#[test]
+fn mirrored_positions_are_opened() {
+    let positions = vec![
+        // Health factor of 0.8 * 2000 / 1000 = 1.6
+        BorrowerPosition {
+            user: Address::with_last_byte(0xaa),
+            collateral: 2000.,
+            debt: 1000.,
+            e_mode: 0,
+        },
+        // Debt above the loan to value of the collateral, with a health factor of 1.04
+        BorrowerPosition {
+            user: Address::with_last_byte(0xbb),
+            collateral: 1300.,
+            debt: 1000.,
+            e_mode: 0,
+        },
+    ];
+    let mut params = sim_parameters(1, population(behaviour(0., 0.)));
+    params.borrower_positions = positions.clone();
+    let (mut env, mut agents, _, _, aave_addresses) =
+        initialise_sim(params, OrderedGasPriorityValidator {});
+
+    let borrowers = agents.borrow_agents.get_addresses();
+    assert_eq!(borrowers.len(), 3);
+    assert_eq!(borrowers[1..], [positions[0].user, positions[1].user]);
+
+    for (position, health_factor) in positions.iter().zip([1.6, 1.04]) {
+        let user_data =
+            calls::get_user_data(&mut env, Address::ZERO, aave_addresses.pool, position.user);
+        assert_close(
+            scale_data_value(user_data.totalCollateralBase, 8, 8),
+            position.collateral,
+        );
+        assert_close(
+            scale_data_value(user_data.totalDebtBase, 8, 8),
+            position.debt,
+        );
+        assert_close(
+            scale_data_value(user_data.healthFactor, 18, 6),
+            health_factor,
+        );
+    }
+
+    // Liquidators monitor the mirrored positions, the other borrower has no debt yet
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    agents.liquidation_agents.call(&mut rng, &mut env);
+    agents.liquidation_agents.record(&mut env);
+    let records = agents.liquidation_agents.take_records();
+    assert_eq!(records[0][0].len(), 2);
+}
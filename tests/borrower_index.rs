@@ -0,0 +1,205 @@
+use alloy_primitives::{Address, U256};
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::agent::{AgentSet, RecordedAgentSet};
+use verbs_rs::utils::Eth;
+use verbs_rust_examples::aave::agents::AgentStates;
+use verbs_rust_examples::aave::indexer::BorrowerIndex;
+use verbs_rust_examples::aave::protocol::{periphery_abi, uniswap_abi, AaveAddresses};
+use verbs_rust_examples::aave::types::{
+    BorrowerBehaviour, BorrowerPopulation, BorrowerPosition, ParameterDistribution,
+};
+use verbs_rust_examples::aave::uniswap_math::price_to_sqrt_price_x96;
+use verbs_rust_examples::aave::{calls, types};
+
+mod common;
+use common::TestEnv;
+
+/// Borrowers with 1 token a of collateral, active every step
+fn population(close_rate: f64) -> BorrowerPopulation {
+    BorrowerPopulation {
+        behaviour: BorrowerBehaviour {
+            repay_threshold: ParameterDistribution::Constant(1.1),
+            releverage_threshold: ParameterDistribution::Constant(1.5),
+            close_rate,
+            ..common::borrower_behaviour()
+        },
+        ..common::borrower_population()
+    }
+}
+
+fn sim_parameters(
+    n_borrowers: usize,
+    borrower_population: BorrowerPopulation,
+    borrower_positions: Vec<BorrowerPosition>,
+) -> types::SimParameters {
+    types::SimParameters {
+        n_borrowers,
+        borrower_population,
+        borrower_positions,
+        ..Default::default()
+    }
+}
+
+fn initialise(
+    n_borrowers: usize,
+    close_rate: f64,
+    borrower_positions: Vec<BorrowerPosition>,
+) -> (TestEnv, AgentStates, common::Addresses, AaveAddresses) {
+    common::initialise(sim_parameters(
+        n_borrowers,
+        population(close_rate),
+        borrower_positions,
+    ))
+}
+
+/// Process transactions and move their events into the history, as in a simulation step
+fn process(
+    env: &mut TestEnv,
+    transactions: Vec<verbs_rs::contract::Transaction>,
+    rng: &mut Xoroshiro128StarStar,
+    step: usize,
+) {
+    env.process_transactions(transactions, rng, step);
+    env.clear_events();
+}
+
+fn step_borrowers(
+    env: &mut TestEnv,
+    agents: &mut AgentStates,
+    rng: &mut Xoroshiro128StarStar,
+    step: usize,
+) {
+    let transactions = agents.borrow_agents.call(rng, env);
+    process(env, transactions, rng, step);
+}
+
+fn indexed_users(index: &BorrowerIndex) -> Vec<Address> {
+    index.users().copied().collect()
+}
+
+#[test]
+fn borrowers_are_discovered_from_pool_events() {
+    let (mut env, mut agents, _, aave_addresses) = initialise(3, 0., Vec::new());
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let mut index = BorrowerIndex::new(aave_addresses.pool, Vec::new());
+
+    // Borrowers only supply collateral in the first step
+    step_borrowers(&mut env, &mut agents, &mut rng, 0);
+    index.update(&mut env);
+    assert!(index.is_empty());
+
+    step_borrowers(&mut env, &mut agents, &mut rng, 1);
+    index.update(&mut env);
+    assert_eq!(indexed_users(&index), agents.borrow_agents.get_addresses());
+}
+
+#[test]
+fn closed_positions_are_removed() {
+    let (mut env, mut agents, _, aave_addresses) = initialise(2, 1., Vec::new());
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let mut index = BorrowerIndex::new(aave_addresses.pool, Vec::new());
+
+    step_borrowers(&mut env, &mut agents, &mut rng, 0);
+    step_borrowers(&mut env, &mut agents, &mut rng, 1);
+    index.update(&mut env);
+    assert_eq!(index.len(), 2);
+
+    // Borrowers repay all their debt
+    step_borrowers(&mut env, &mut agents, &mut rng, 2);
+    index.update(&mut env);
+    assert!(index.is_empty());
+}
+
+#[test]
+fn positions_opened_by_other_agents_are_discovered() {
+    let (mut env, agents, (periphery_addresses, _), aave_addresses) = initialise(0, 0., Vec::new());
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let mut index = BorrowerIndex::new(aave_addresses.pool, Vec::new());
+
+    // A liquidator supplies token a and borrows token b
+    let liquidator = agents.liquidation_agents.get_addresses()[0];
+    env.direct_execute(
+        liquidator,
+        periphery_addresses.token_a,
+        periphery_abi::MintableERC20::approveCall {
+            spender: aave_addresses.pool,
+            amount: U256::MAX,
+        },
+        U256::ZERO,
+    )
+    .unwrap();
+    let supply = calls::supply_call(
+        liquidator,
+        aave_addresses.pool,
+        periphery_addresses.token_a,
+        U256::to_weth(1),
+    );
+    let borrow = calls::borrow_call(
+        liquidator,
+        aave_addresses.pool,
+        periphery_addresses.token_b,
+        U256::to_weth(100),
+        types::InterestRateMode::Variable,
+    );
+    process(&mut env, vec![supply, borrow], &mut rng, 0);
+
+    index.update(&mut env);
+    assert_eq!(indexed_users(&index), vec![liquidator]);
+}
+
+#[test]
+fn known_users_are_only_indexed_with_open_debt() {
+    let position = BorrowerPosition {
+        user: Address::with_last_byte(0xaa),
+        collateral: 2000.,
+        debt: 1000.,
+        e_mode: 0,
+    };
+    let (mut env, agents, _, aave_addresses) = initialise(1, 0., vec![position]);
+
+    // The first borrower has not supplied or borrowed yet
+    let users = agents.borrow_agents.get_addresses();
+    let mut index = BorrowerIndex::new(aave_addresses.pool, users.clone());
+    index.update(&mut env);
+
+    assert_eq!(users.len(), 2);
+    assert_eq!(indexed_users(&index), vec![position.user]);
+    assert!(!index.contains(&users[0]));
+}
+
+#[test]
+fn liquidators_monitor_discovered_borrowers() {
+    let (mut env, mut agents, (periphery_addresses, uniswap_addresses), _) =
+        initialise(2, 0., Vec::new());
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+
+    step_borrowers(&mut env, &mut agents, &mut rng, 0);
+    step_borrowers(&mut env, &mut agents, &mut rng, 1);
+
+    // A fall in the price of token a takes the borrowers below a health factor of 1
+    let trader = agents.uniswap_price_agent.get_addresses()[0];
+    let swap = calls::uniswap_swap_call(
+        trader,
+        uniswap_addresses.swap_router,
+        uniswap_abi::SwapRouter::ExactInputSingleParams {
+            amountIn: U256::to_weth(10u128.pow(9)),
+            amountOutMinimum: U256::ZERO,
+            deadline: U256::MAX,
+            fee: 500u32,
+            recipient: trader,
+            sqrtPriceLimitX96: price_to_sqrt_price_x96(700.).unwrap(),
+            tokenIn: periphery_addresses.token_a,
+            tokenOut: periphery_addresses.token_b,
+        },
+    );
+    process(&mut env, vec![swap], &mut rng, 2);
+
+    let liquidations = agents.liquidation_agents.call(&mut rng, &mut env);
+    agents.liquidation_agents.record(&mut env);
+
+    let records = agents.liquidation_agents.take_records();
+    assert_eq!(records[0][0].len(), 2);
+    assert!(records[0][0].iter().all(|x| x.5 < 1.));
+    assert_eq!(liquidations.len(), 2);
+}
@@ -0,0 +1,70 @@
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rust_examples::aave::branching::{run_steps, Checkpoint};
+use verbs_rust_examples::aave::initialisation::initialise_sim;
+use verbs_rust_examples::aave::validator::PolicyValidator;
+use verbs_rust_examples::aave::{aave_sim, aave_sim_branched, types, SimData};
+
+const N_STEPS: usize = 30;
+
+fn sim_parameters() -> types::SimParameters {
+    types::SimParameters {
+        n_borrowers: 5,
+        ..Default::default()
+    }
+}
+
+fn to_json(data: &SimData) -> String {
+    serde_json::to_string(data).unwrap()
+}
+
+#[test]
+fn restored_checkpoint_continues_the_run() {
+    let params = sim_parameters();
+    let policy = params.validator.clone();
+    let base_fee = params.base_fee;
+    let (mut env, mut agents, _, _, _) = initialise_sim(params, PolicyValidator::from(&policy));
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+
+    run_steps(&mut env, &mut agents, &mut rng, 0..10, &base_fee);
+    let checkpoint = Checkpoint::save(&mut env, &agents, &rng, 10);
+    let (mut restored_env, mut restored_agents, mut restored_rng) =
+        checkpoint.restore(PolicyValidator::from(&policy));
+
+    // The events of the last step are kept, as well as the history
+    assert!(!env.last_events.is_empty());
+    assert_eq!(restored_env.last_events.len(), env.last_events.len());
+    assert_eq!(restored_env.event_history.len(), env.event_history.len());
+
+    run_steps(&mut env, &mut agents, &mut rng, 10..N_STEPS, &base_fee);
+    run_steps(
+        &mut restored_env,
+        &mut restored_agents,
+        &mut restored_rng,
+        10..N_STEPS,
+        &base_fee,
+    );
+    assert_eq!(
+        to_json(&restored_agents.into_sim_data(101)),
+        to_json(&agents.into_sim_data(101))
+    );
+}
+
+#[test]
+fn unmodified_branch_reproduces_the_baseline() {
+    for branch_step in [0, 1, 10] {
+        let baseline = aave_sim(101, N_STEPS, sim_parameters());
+        let branches = aave_sim_branched(
+            101,
+            N_STEPS,
+            branch_step,
+            sim_parameters(),
+            &[types::Branch::default(), types::Branch::default()],
+        );
+
+        assert_eq!(branches.len(), 2);
+        for branch in branches {
+            assert_eq!(to_json(&branch), to_json(&baseline));
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Fixtures shared by the integration tests
+//!
+//! Test files include this module with `mod common;` and keep only
+//! their own setup, built on the simulations and positions below.
+#![allow(dead_code)]
+
+use alloy_primitives::{Address, U256};
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::agent::AgentSet;
+use verbs_rs::env::Env;
+use verbs_rs::utils::Eth;
+use verbs_rs::LocalDB;
+use verbs_rust_examples::aave::agents::AgentStates;
+use verbs_rust_examples::aave::calls;
+use verbs_rust_examples::aave::initialisation::initialise_sim;
+use verbs_rust_examples::aave::protocol::{
+    uniswap_abi, AaveAddresses, PeripheryAddresses, UniswapAddresses,
+};
+use verbs_rust_examples::aave::types::{
+    BorrowerBehaviour, BorrowerPopulation, ParameterDistribution, SimParameters,
+};
+use verbs_rust_examples::aave::uniswap_math::price_to_sqrt_price_x96;
+use verbs_rust_examples::aave::validator::OrderedGasPriorityValidator;
+
+pub type TestEnv = Env<LocalDB, OrderedGasPriorityValidator>;
+
+/// Addresses of the tokens and of the Uniswap contracts
+pub type Addresses = (PeripheryAddresses, UniswapAddresses);
+
+/// Borrowers keeping their position once opened at a health factor
+/// of 1.25, without repaying, releveraging, topping up or withdrawing
+pub fn borrower_behaviour() -> BorrowerBehaviour {
+    BorrowerBehaviour {
+        repay_threshold: ParameterDistribution::Constant(0.),
+        target_health_factor: ParameterDistribution::Constant(1.25),
+        releverage_threshold: ParameterDistribution::Constant(100.),
+        top_up_probability: 0.,
+        withdraw_probability: 0.,
+        ..Default::default()
+    }
+}
+
+/// Borrowers with $1000 of token a collateral, active every step,
+/// borrowing token b with [borrower_behaviour]
+pub fn borrower_population() -> BorrowerPopulation {
+    BorrowerPopulation {
+        position_size: ParameterDistribution::Constant(1000.),
+        activation_rate: ParameterDistribution::Constant(1.),
+        behaviour: borrower_behaviour(),
+        ..Default::default()
+    }
+}
+
+/// A single borrower drawn from [borrower_population]
+pub fn sim_parameters() -> SimParameters {
+    SimParameters {
+        n_borrowers: 1,
+        borrower_population: borrower_population(),
+        ..Default::default()
+    }
+}
+
+/// Initialise a simulation, grouping the token and Uniswap addresses
+pub fn initialise(params: SimParameters) -> (TestEnv, AgentStates, Addresses, AaveAddresses) {
+    let (env, agents, periphery_addresses, uniswap_addresses, aave_addresses) =
+        initialise_sim(params, OrderedGasPriorityValidator {});
+    (
+        env,
+        agents,
+        (periphery_addresses, uniswap_addresses),
+        aave_addresses,
+    )
+}
+
+/// Step the borrowers until they have supplied and borrowed
+pub fn open_positions(env: &mut TestEnv, agents: &mut AgentStates, rng: &mut Xoroshiro128StarStar) {
+    for step in 0..2 {
+        let transactions = agents.borrow_agents.call(rng, env);
+        env.process_transactions(transactions, rng, step);
+        env.clear_events();
+    }
+}
+
+/// Initialise a simulation and open the positions of its borrowers
+pub fn borrowed_position(
+    params: SimParameters,
+) -> (
+    TestEnv,
+    AgentStates,
+    Addresses,
+    AaveAddresses,
+    Xoroshiro128StarStar,
+) {
+    let (mut env, mut agents, addresses, aave_addresses) = initialise(params);
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    open_positions(&mut env, &mut agents, &mut rng);
+
+    (env, agents, addresses, aave_addresses, rng)
+}
+
+/// Swap in the Uniswap pool, which is the price source of token a,
+/// to move its price to `price`
+pub fn set_token_a_price(
+    env: &mut TestEnv,
+    agents: &AgentStates,
+    addresses: &Addresses,
+    rng: &mut Xoroshiro128StarStar,
+    price: f64,
+) {
+    let (periphery_addresses, uniswap_addresses) = addresses;
+    let trader = agents.uniswap_price_agent.get_addresses()[0];
+    let sqrt_price = calls::get_slot0(env, Address::ZERO, uniswap_addresses.pool).sqrtPriceX96;
+    let target = price_to_sqrt_price_x96(price).unwrap();
+    let (token_in, token_out) = match target < sqrt_price {
+        true => (periphery_addresses.token_a, periphery_addresses.token_b),
+        false => (periphery_addresses.token_b, periphery_addresses.token_a),
+    };
+
+    let swap = calls::uniswap_swap_call(
+        trader,
+        uniswap_addresses.swap_router,
+        uniswap_abi::SwapRouter::ExactInputSingleParams {
+            amountIn: U256::to_weth(10u128.pow(9)),
+            amountOutMinimum: U256::ZERO,
+            deadline: U256::MAX,
+            fee: 500u32,
+            recipient: trader,
+            sqrtPriceLimitX96: target,
+            tokenIn: token_in,
+            tokenOut: token_out,
+        },
+    );
+    env.process_transactions(vec![swap], rng, 2);
+    env.clear_events();
+}
+
+/// Open the positions of the borrowers, then move the price of token
+/// a to `price`, towards or past their liquidation price
+pub fn liquidatable_position(
+    params: SimParameters,
+    price: f64,
+) -> (
+    TestEnv,
+    AgentStates,
+    Addresses,
+    AaveAddresses,
+    Xoroshiro128StarStar,
+) {
+    let (mut env, agents, addresses, aave_addresses, mut rng) = borrowed_position(params);
+    set_token_a_price(&mut env, &agents, &addresses, &mut rng, price);
+
+    (env, agents, addresses, aave_addresses, rng)
+}
@@ -0,0 +1,189 @@
+use alloy_primitives::Address;
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::agent::AgentSet;
+use verbs_rs::utils::scale_data_value;
+use verbs_rust_examples::aave::agents::{AgentStates, ExternalMarket};
+use verbs_rust_examples::aave::initialisation::initialise_sim;
+use verbs_rust_examples::aave::protocol::AaveAddresses;
+use verbs_rust_examples::aave::types::{
+    BorrowerBehaviour, BorrowerPopulation, BorrowerPosition, ParameterDistribution, PriceProcess,
+    ProtocolEvent,
+};
+use verbs_rust_examples::aave::validator::OrderedGasPriorityValidator;
+use verbs_rust_examples::aave::{aave_sim, calls, types};
+
+mod common;
+use common::TestEnv;
+
+/// Borrowers with $1000 of token a collateral, active every step,
+/// opting in to e-mode with probability `e_mode_probability`
+fn sim_parameters(e_mode_probability: f64, target_health_factor: f64) -> types::SimParameters {
+    types::SimParameters {
+        borrower_population: BorrowerPopulation {
+            e_mode_probability,
+            behaviour: BorrowerBehaviour {
+                target_health_factor: ParameterDistribution::Constant(target_health_factor),
+                ..common::borrower_behaviour()
+            },
+            ..common::borrower_population()
+        },
+        ..common::sim_parameters()
+    }
+}
+
+/// Collateral, debt and liquidation threshold of the borrower
+fn borrower_data(
+    env: &mut TestEnv,
+    agents: &AgentStates,
+    aave_addresses: &AaveAddresses,
+) -> (f64, f64, f64) {
+    let borrower = agents.borrow_agents.get_addresses()[0];
+    let user_data = calls::get_user_data(env, Address::ZERO, aave_addresses.pool, borrower);
+    (
+        scale_data_value(user_data.totalCollateralBase, 8, 8),
+        scale_data_value(user_data.totalDebtBase, 8, 8),
+        scale_data_value(user_data.currentLiquidationThreshold, 4, 4),
+    )
+}
+
+#[test]
+fn assets_are_assigned_to_e_mode_category() {
+    let (mut env, _, periphery_addresses, _, aave_addresses) =
+        initialise_sim(sim_parameters(0., 1.25), OrderedGasPriorityValidator {});
+
+    let category = calls::get_e_mode_category_data(&mut env, Address::ZERO, aave_addresses.pool, 1);
+    assert_eq!(category.ltv, 9800);
+    assert_eq!(category.liquidationThreshold, 9850);
+    assert_eq!(category.liquidationBonus, 10100);
+    assert_eq!(category.label, "Stable-EMode");
+
+    for token in [periphery_addresses.token_a, periphery_addresses.token_b] {
+        let configuration =
+            calls::get_reserve_configuration(&mut env, Address::ZERO, aave_addresses.pool, token);
+        assert_eq!(configuration.e_mode_category, 1);
+    }
+}
+
+#[test]
+fn borrowers_opt_in_to_e_mode() {
+    let (mut env, agents, _, aave_addresses, _) =
+        common::borrowed_position(sim_parameters(0., 1.25));
+    let borrower = agents.borrow_agents.get_addresses()[0];
+    assert_eq!(
+        calls::get_user_e_mode(&mut env, Address::ZERO, aave_addresses.pool, borrower),
+        0
+    );
+    let (collateral, debt, liquidation_threshold) =
+        borrower_data(&mut env, &agents, &aave_addresses);
+    assert_eq!(liquidation_threshold, 0.8);
+    assert!((debt / collateral - 0.8 / 1.25).abs() < 1e-3);
+
+    // In e-mode the borrower borrows at the threshold of the category
+    let (mut env, agents, _, aave_addresses, _) =
+        common::borrowed_position(sim_parameters(1., 1.25));
+    assert_eq!(
+        calls::get_user_e_mode(&mut env, Address::ZERO, aave_addresses.pool, borrower),
+        1
+    );
+    let (collateral, debt, liquidation_threshold) =
+        borrower_data(&mut env, &agents, &aave_addresses);
+    assert_eq!(liquidation_threshold, 0.985);
+    assert!((debt / collateral - 0.985 / 1.25).abs() < 1e-3);
+}
+
+#[test]
+fn e_mode_collateral_is_liquidated_with_category_bonus() {
+    let (mut env, mut agents, addresses, aave_addresses, mut rng) =
+        common::borrowed_position(sim_parameters(1., 1.25));
+
+    // The collateral is worth less than the debt plus the 1% bonus of
+    // the category, so all of it can be seized
+    common::set_token_a_price(&mut env, &agents, &addresses, &mut rng, 700.);
+    let (collateral, debt, _) = borrower_data(&mut env, &agents, &aave_addresses);
+    assert!(collateral < 1.01 * debt);
+
+    let transactions = agents.liquidation_agents.call(&mut rng, &mut env);
+    env.process_transactions(transactions, &mut rng, 3);
+
+    let (new_collateral, new_debt, _) = borrower_data(&mut env, &agents, &aave_addresses);
+    assert!(new_collateral < 1e-2 * collateral);
+    assert!((debt - new_debt - collateral / 1.01).abs() < 1e-2 * debt);
+}
+
+#[test]
+fn mirrored_positions_open_in_e_mode() {
+    // Debt above the threshold of token a, with a health factor of 0.985 * 1000 / 900
+    let position = BorrowerPosition {
+        user: Address::with_last_byte(0xaa),
+        collateral: 1000.,
+        debt: 900.,
+        e_mode: 3,
+    };
+    let mut params = sim_parameters(0., 1.25);
+    params.borrower_positions = vec![position];
+    let (mut env, _, _, _, aave_addresses) = initialise_sim(params, OrderedGasPriorityValidator {});
+
+    assert_eq!(
+        calls::get_user_e_mode(&mut env, Address::ZERO, aave_addresses.pool, position.user),
+        1
+    );
+    let user_data =
+        calls::get_user_data(&mut env, Address::ZERO, aave_addresses.pool, position.user);
+    let health_factor = scale_data_value(user_data.healthFactor, 18, 6);
+    assert!((health_factor - 0.985 * 1000. / 900.).abs() < 1e-3);
+}
+
+#[test]
+fn depeg_price_reverts_to_peg_and_jumps() {
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let process = |depeg_rate: f64| PriceProcess::Depeg {
+        reversion: 10.,
+        depeg_rate,
+        depeg_size: 0.05,
+    };
+
+    // Without depegs the price stays close to the peg
+    let mut market = ExternalMarket::new(0.01, 0., 0.01, process(0.), 100000000, 100000000);
+    for _ in 0..1000 {
+        market.update(&mut rng, 0.);
+        assert!((market.get_price_token_a() - 1.).abs() < 0.01);
+    }
+
+    // A depeg every step, at a rate of 1 / dt
+    let mut market = ExternalMarket::new(0.01, 0., 0., process(100.), 100000000, 100000000);
+    market.update(&mut rng, 0.);
+    assert!((market.get_price_token_a() - 0.95).abs() < 1e-6);
+    market.update(&mut rng, 0.);
+    assert!(market.get_price_token_a() < 0.95);
+}
+
+#[test]
+fn correlated_pair_with_depegs_is_simulated() {
+    // Stablecoin pair, with borrowers in e-mode borrowing up to 90%
+    // of the loan to value of the category, liquidated by depegs of 15%
+    let params = types::SimParameters {
+        token_a_initial_price: 100000000i128,
+        prices_sigma: 0.005f64,
+        price_process: PriceProcess::Depeg {
+            reversion: 1.,
+            depeg_rate: 1.,
+            depeg_size: 0.15,
+        },
+        n_borrowers: 5,
+        ..sim_parameters(1., 1.)
+    };
+    let data = aave_sim(101, 100, params);
+
+    let prices: Vec<f64> = data
+        .uniswap_price_agent
+        .iter()
+        .map(|x| x.0 as f64 / x.1 as f64)
+        .collect();
+    assert!(prices.iter().all(|x| 0.5 < *x && *x < 1.05));
+    assert!(prices.iter().any(|x| *x < 0.9));
+    assert!(data
+        .events
+        .iter()
+        .any(|x| matches!(x.event, ProtocolEvent::LiquidationCall { .. })));
+}
@@ -0,0 +1,186 @@
+use alloy_primitives::{I256, U256};
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::agent::{AgentSet, SimState};
+use verbs_rs::contract::Transaction;
+use verbs_rs::utils::{address_from_hex, Eth};
+use verbs_rust_examples::aave::events::decode_events;
+use verbs_rust_examples::aave::outcomes::process_block;
+use verbs_rust_examples::aave::protocol::aave_abi;
+use verbs_rust_examples::aave::types::{EventRecord, ProtocolEvent};
+use verbs_rust_examples::aave::{aave_sim, calls, types};
+
+mod common;
+
+const ADMIN: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+fn sim_parameters() -> types::SimParameters {
+    types::SimParameters {
+        n_borrowers: 2,
+        ..Default::default()
+    }
+}
+
+fn is_ordered(events: &[EventRecord]) -> bool {
+    events.windows(2).all(|x| {
+        (x[0].step, x[0].transaction, x[0].log_index)
+            < (x[1].step, x[1].transaction, x[1].log_index)
+    })
+}
+
+#[test]
+fn pool_events_are_decoded() {
+    let (mut env, agents, (periphery_addresses, _), aave_addresses) =
+        common::initialise(sim_parameters());
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let borrower = agents.borrow_agents.get_addresses()[0];
+
+    let supply = calls::supply_call(
+        borrower,
+        aave_addresses.pool,
+        periphery_addresses.token_a,
+        U256::to_weth(1),
+    );
+    let borrow = calls::borrow_call(
+        borrower,
+        aave_addresses.pool,
+        periphery_addresses.token_b,
+        U256::to_weth(100),
+        types::InterestRateMode::Variable,
+    );
+    let outcomes = process_block(&mut env, vec![supply, borrow], &mut rng, 3);
+    let events = decode_events(&outcomes);
+
+    assert!(is_ordered(&events));
+    assert!(events.iter().all(|x| x.step == 3));
+
+    let supply = events
+        .iter()
+        .find(|x| matches!(x.event, ProtocolEvent::Supply { .. }))
+        .unwrap();
+    assert_eq!(supply.transaction, 0);
+    assert_eq!(supply.address, aave_addresses.pool);
+    assert_eq!(
+        supply.event,
+        ProtocolEvent::Supply {
+            reserve: periphery_addresses.token_a,
+            user: borrower,
+            on_behalf_of: borrower,
+            amount: U256::to_weth(1),
+        }
+    );
+
+    let borrow = events
+        .iter()
+        .find(|x| matches!(x.event, ProtocolEvent::Borrow { .. }))
+        .unwrap();
+    assert_eq!(borrow.transaction, 1);
+    let ProtocolEvent::Borrow {
+        reserve,
+        on_behalf_of,
+        amount,
+        interest_rate_mode,
+        ..
+    } = borrow.event
+    else {
+        unreachable!()
+    };
+    assert_eq!(reserve, periphery_addresses.token_b);
+    assert_eq!(on_behalf_of, borrower);
+    assert_eq!(amount, U256::to_weth(100));
+    assert_eq!(interest_rate_mode, 2);
+
+    // Both transactions update their reserve, and mint aTokens or debt tokens
+    let count = |f: fn(&ProtocolEvent) -> bool| events.iter().filter(|x| f(&x.event)).count();
+    assert_eq!(
+        count(|x| matches!(x, ProtocolEvent::ReserveDataUpdated { .. })),
+        2
+    );
+    assert_eq!(count(|x| matches!(x, ProtocolEvent::TokenMint { .. })), 2);
+}
+
+#[test]
+fn aggregator_price_updates_are_decoded() {
+    let (mut env, _, _, aave_addresses) = common::initialise(sim_parameters());
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+
+    let update = Transaction::basic(
+        address_from_hex(ADMIN),
+        aave_addresses.token_b_oracle,
+        aave_abi::MockAggregator::setValueCall {
+            value: I256::try_from(99000000).unwrap(),
+        },
+        true,
+    );
+    let outcomes = process_block(&mut env, vec![update], &mut rng, 0);
+    let events = decode_events(&outcomes);
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].address, aave_addresses.token_b_oracle);
+    assert_eq!(
+        events[0].event,
+        ProtocolEvent::PriceUpdate {
+            old_price: I256::try_from(100000000).unwrap(),
+            new_price: I256::try_from(99000000).unwrap(),
+        }
+    );
+}
+
+#[test]
+fn swaps_reconstruct_pool_state() {
+    let (mut env, mut agents, (periphery_addresses, uniswap_addresses), _) =
+        common::initialise(sim_parameters());
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let pool = uniswap_addresses.pool;
+    let balance_0 = calls::balance_of(&mut env, pool, periphery_addresses.token_a);
+
+    for step in 0..10 {
+        env.clear_events();
+        let transactions = agents.call_agents(&mut rng, &mut env);
+        let outcomes = process_block(&mut env, transactions, &mut rng, step);
+        agents.observe_outcomes(outcomes);
+        agents.record_agents(&mut env);
+    }
+    let events = decode_events(&agents.outcomes);
+    assert!(is_ordered(&events));
+
+    let swaps: Vec<(I256, U256)> = events
+        .iter()
+        .filter(|x| x.address == pool)
+        .filter_map(|x| match x.event {
+            ProtocolEvent::Swap {
+                amount0,
+                sqrt_price_x96,
+                ..
+            } => Some((amount0, sqrt_price_x96)),
+            _ => None,
+        })
+        .collect();
+    assert!(!swaps.is_empty());
+
+    // The balance of the pool changes by the amounts swapped, and
+    // the last swap sets the current price
+    let swapped = swaps.iter().fold(I256::ZERO, |acc, x| acc + x.0);
+    let balance_1 = calls::balance_of(&mut env, pool, periphery_addresses.token_a);
+    assert_eq!(
+        I256::from_raw(balance_1) - I256::from_raw(balance_0),
+        swapped
+    );
+    let slot0 = calls::get_slot0(&mut env, periphery_addresses.token_a, pool);
+    assert_eq!(swaps.last().unwrap().1, slot0.sqrtPriceX96);
+}
+
+#[test]
+fn events_are_recorded_in_simulation_data() {
+    let data = aave_sim(101, 20, sim_parameters());
+
+    assert!(is_ordered(&data.events));
+    assert!(data.events.iter().all(|x| data
+        .transaction_outcomes
+        .iter()
+        .any(|y| y.step == x.step && y.sequence == x.transaction && y.success)));
+    assert!(data
+        .events
+        .iter()
+        .any(|x| matches!(x.event, ProtocolEvent::Swap { .. })));
+}
@@ -0,0 +1,196 @@
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::SolValue;
+use verbs_rs::agent::AgentSet;
+use verbs_rs::contract::Transaction;
+use verbs_rs::utils::{scale_data_value, Eth};
+use verbs_rust_examples::aave::agents::AgentStates;
+use verbs_rust_examples::aave::outcomes::process_block;
+use verbs_rust_examples::aave::protocol::{aave_abi, AaveAddresses};
+use verbs_rust_examples::aave::{calls, deployment, types};
+
+mod common;
+use common::TestEnv;
+
+fn sim_parameters() -> types::SimParameters {
+    types::SimParameters {
+        flash_loan_liquidation: true,
+        ..common::sim_parameters()
+    }
+}
+
+fn borrower_debt(env: &mut TestEnv, agents: &AgentStates, aave_addresses: &AaveAddresses) -> f64 {
+    let borrower = agents.borrow_agents.get_addresses()[0];
+    let user_data = calls::get_user_data(env, Address::ZERO, aave_addresses.pool, borrower);
+    scale_data_value(user_data.totalDebtBase, 8, 8)
+}
+
+#[test]
+fn liquidators_hold_no_inventory() {
+    let (mut env, agents, (periphery_addresses, _), _, _) =
+        common::liquidatable_position(sim_parameters(), 1000.);
+    let liquidator = agents.liquidation_agents.get_addresses()[0];
+
+    assert_eq!(
+        calls::balance_of(&mut env, liquidator, periphery_addresses.token_a),
+        U256::ZERO
+    );
+    assert_eq!(
+        calls::balance_of(&mut env, liquidator, periphery_addresses.token_b),
+        U256::ZERO
+    );
+}
+
+#[test]
+fn flash_loan_funds_profitable_liquidation() {
+    for price in [780., 700.] {
+        let (mut env, mut agents, (periphery_addresses, _), aave_addresses, mut rng) =
+            common::liquidatable_position(sim_parameters(), price);
+        let liquidator = agents.liquidation_agents.get_addresses()[0];
+        let debt = borrower_debt(&mut env, &agents, &aave_addresses);
+
+        let transactions = agents.liquidation_agents.call(&mut rng, &mut env);
+        assert_eq!(transactions.len(), 1);
+        env.process_transactions(transactions, &mut rng, 3);
+        assert!(!env.last_events[0].logs.is_empty());
+
+        assert!(borrower_debt(&mut env, &agents, &aave_addresses) < debt);
+        // The profit is paid in the debt asset, and no collateral is left over
+        assert!(calls::balance_of(&mut env, liquidator, periphery_addresses.token_b) > U256::ZERO);
+        assert_eq!(
+            calls::balance_of(&mut env, liquidator, periphery_addresses.token_a),
+            U256::ZERO
+        );
+    }
+}
+
+#[test]
+fn unprofitable_flash_loan_liquidation_reverts() {
+    let (mut env, agents, (periphery_addresses, uniswap_addresses), aave_addresses, mut rng) =
+        common::liquidatable_position(sim_parameters(), 780.);
+    let liquidator = agents.liquidation_agents.get_addresses()[0];
+    let borrower = agents.borrow_agents.get_addresses()[0];
+    let receiver = deployment::deploy_flash_liquidation_receiver(
+        &mut env,
+        liquidator,
+        aave_addresses.pool,
+        liquidator,
+    );
+    let debt = borrower_debt(&mut env, &agents, &aave_addresses);
+
+    // The collateral cannot be sold through a pool with a 1% fee, which
+    // does not exist, so the loan cannot be repaid
+    let liquidation = calls::flash_loan_liquidation_call(
+        liquidator,
+        aave_addresses.pool,
+        receiver,
+        periphery_addresses.token_a,
+        periphery_addresses.token_b,
+        borrower,
+        U256::to_weth(100),
+        uniswap_addresses.swap_router,
+        10000u32,
+    );
+    env.process_transactions(vec![liquidation], &mut rng, 3);
+
+    // Reverted transactions are recorded without any logs
+    assert!(env.last_events[0].logs.is_empty());
+    assert_eq!(borrower_debt(&mut env, &agents, &aave_addresses), debt);
+    for token in [periphery_addresses.token_a, periphery_addresses.token_b] {
+        assert_eq!(calls::balance_of(&mut env, receiver, token), U256::ZERO);
+        assert_eq!(calls::balance_of(&mut env, liquidator, token), U256::ZERO);
+    }
+}
+
+#[test]
+fn healthy_positions_cannot_be_flash_liquidated() {
+    let (mut env, agents, (periphery_addresses, uniswap_addresses), aave_addresses, mut rng) =
+        common::liquidatable_position(sim_parameters(), 1000.);
+    let liquidator = agents.liquidation_agents.get_addresses()[0];
+    let borrower = agents.borrow_agents.get_addresses()[0];
+    let receiver = deployment::deploy_flash_liquidation_receiver(
+        &mut env,
+        liquidator,
+        aave_addresses.pool,
+        liquidator,
+    );
+
+    let liquidation = calls::flash_loan_liquidation_call(
+        liquidator,
+        aave_addresses.pool,
+        receiver,
+        periphery_addresses.token_a,
+        periphery_addresses.token_b,
+        borrower,
+        U256::to_weth(100),
+        uniswap_addresses.swap_router,
+        500u32,
+    );
+    env.process_transactions(vec![liquidation], &mut rng, 3);
+
+    assert!(env.last_events[0].logs.is_empty());
+    assert_eq!(
+        calls::balance_of(&mut env, liquidator, periphery_addresses.token_b),
+        U256::ZERO
+    );
+}
+
+#[test]
+fn receiver_only_accepts_loans_of_its_liquidator() {
+    let (mut env, agents, (periphery_addresses, uniswap_addresses), aave_addresses, mut rng) =
+        common::liquidatable_position(sim_parameters(), 780.);
+    let liquidator = agents.liquidation_agents.get_addresses()[0];
+    let borrower = agents.borrow_agents.get_addresses()[0];
+    let trader = agents.uniswap_price_agent.get_addresses()[0];
+    let receiver = deployment::deploy_flash_liquidation_receiver(
+        &mut env,
+        liquidator,
+        aave_addresses.pool,
+        liquidator,
+    );
+    let debt = borrower_debt(&mut env, &agents, &aave_addresses);
+
+    // A profitable liquidation through the receiver of another liquidator
+    let liquidation = |sender: Address| {
+        calls::flash_loan_liquidation_call(
+            sender,
+            aave_addresses.pool,
+            receiver,
+            periphery_addresses.token_a,
+            periphery_addresses.token_b,
+            borrower,
+            U256::to_weth(100),
+            uniswap_addresses.swap_router,
+            500u32,
+        )
+    };
+    // A call to the receiver that does not come from the pool, and would
+    // otherwise succeed as there is nothing to repay or swap
+    let params = (
+        periphery_addresses.token_b,
+        borrower,
+        uniswap_addresses.swap_router,
+        U256::from(500u32),
+    )
+        .abi_encode();
+    let direct = Transaction::basic(
+        trader,
+        receiver,
+        aave_abi::FlashLiquidationReceiver::executeOperationCall {
+            asset: periphery_addresses.token_b,
+            amount: U256::ZERO,
+            premium: U256::ZERO,
+            initiator: liquidator,
+            params: params.into(),
+        },
+        false,
+    );
+
+    let outcomes = process_block(&mut env, vec![liquidation(trader), direct], &mut rng, 3);
+    assert!(outcomes.iter().all(|x| !x.success));
+    assert_eq!(borrower_debt(&mut env, &agents, &aave_addresses), debt);
+
+    // The same liquidation started by the liquidator goes through
+    let outcomes = process_block(&mut env, vec![liquidation(liquidator)], &mut rng, 4);
+    assert!(outcomes[0].success);
+    assert!(borrower_debt(&mut env, &agents, &aave_addresses) < debt);
+}
@@ -0,0 +1,162 @@
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::agent::AgentSet;
+use verbs_rust_examples::aave::agents::AgentStates;
+use verbs_rust_examples::aave::outcomes::process_block;
+use verbs_rust_examples::aave::types::GasBidStrategy;
+use verbs_rust_examples::aave::{gas, types};
+
+mod common;
+use common::TestEnv;
+
+fn sim_parameters(
+    gas_bid_strategies: Vec<GasBidStrategy>,
+    flash_loan_liquidation: bool,
+) -> types::SimParameters {
+    types::SimParameters {
+        n_liquidators: 2,
+        flash_loan_liquidation,
+        gas_bid_strategies,
+        ..common::sim_parameters()
+    }
+}
+
+/// Two liquidators competing for a borrower with a health factor below 0.95
+fn liquidatable_position(
+    gas_bid_strategies: Vec<GasBidStrategy>,
+    flash_loan_liquidation: bool,
+) -> (TestEnv, AgentStates, Xoroshiro128StarStar) {
+    let (env, agents, _, _, rng) = common::liquidatable_position(
+        sim_parameters(gas_bid_strategies, flash_loan_liquidation),
+        700.,
+    );
+    (env, agents, rng)
+}
+
+/// Liquidators bid and their transactions are processed and recorded
+fn step_liquidators(env: &mut TestEnv, agents: &mut AgentStates, rng: &mut Xoroshiro128StarStar) {
+    let transactions = agents.liquidation_agents.call(rng, env);
+    env.process_transactions(transactions, rng, 3);
+    agents.liquidation_agents.record(env);
+}
+
+#[test]
+fn strategies_are_parsed() {
+    assert_eq!(
+        "fixed:10".parse::<GasBidStrategy>(),
+        Ok(GasBidStrategy::Fixed { bid: 10. })
+    );
+    assert_eq!(
+        "profit-share:0.5".parse::<GasBidStrategy>(),
+        Ok(GasBidStrategy::ProfitShare { share: 0.5 })
+    );
+    assert_eq!(
+        "escalating:1:2:0.9".parse::<GasBidStrategy>(),
+        Ok(GasBidStrategy::Escalating {
+            initial: 1.,
+            factor: 2.,
+            max_share: 0.9
+        })
+    );
+    assert!("fixed".parse::<GasBidStrategy>().is_err());
+    assert!("auction:1".parse::<GasBidStrategy>().is_err());
+}
+
+#[test]
+fn escalating_bids_rise_with_losses_up_to_share_of_profit() {
+    let strategy = GasBidStrategy::Escalating {
+        initial: 1.,
+        factor: 2.,
+        max_share: 0.5,
+    };
+    assert_eq!(strategy.bid(100., 0), 1.);
+    assert_eq!(strategy.bid(100., 3), 8.);
+    assert_eq!(strategy.bid(100., 10), 50.);
+    assert_eq!(GasBidStrategy::ProfitShare { share: 0.2 }.bid(50., 4), 10.);
+}
+
+#[test]
+fn highest_bid_wins_liquidation() {
+    let strategies = vec![
+        GasBidStrategy::Fixed { bid: 1. },
+        GasBidStrategy::Fixed { bid: 5. },
+    ];
+    let (mut env, mut agents, mut rng) = liquidatable_position(strategies, false);
+    let liquidators = agents.liquidation_agents.get_addresses();
+    let borrower = agents.borrow_agents.get_addresses()[0];
+
+    step_liquidators(&mut env, &mut agents, &mut rng);
+
+    let bids = agents.take_liquidation_bids();
+    assert_eq!(bids.len(), 2);
+    assert!(bids.iter().all(|x| x.user == borrower));
+    assert!(bids.iter().all(|x| x.winner == Some(liquidators[1])));
+    assert_eq!(
+        bids.iter()
+            .map(|x| (x.liquidator, x.bid))
+            .collect::<Vec<_>>(),
+        vec![(liquidators[0], 1.), (liquidators[1], 5.)]
+    );
+    assert!(bids.iter().all(|x| x.expected_profit > x.bid));
+    // Both bids are paid, the loser also losing its gas
+    assert_eq!(bids[0].net_profit, -1.);
+    assert_eq!(bids[1].net_profit, bids[1].expected_profit - 5.);
+}
+
+#[test]
+fn bids_are_paid_as_priority_fees() {
+    let strategies = vec![
+        GasBidStrategy::Fixed { bid: 1. },
+        GasBidStrategy::Fixed { bid: 5. },
+    ];
+    let (mut env, mut agents, mut rng) = liquidatable_position(strategies, false);
+
+    let transactions = agents.liquidation_agents.call(&mut rng, &mut env);
+    let outcomes = process_block(&mut env, transactions, &mut rng, 3);
+
+    // The losing liquidation reverts, but its bid is still paid
+    assert_eq!(
+        outcomes
+            .iter()
+            .map(|x| (x.success, x.priority_fee))
+            .collect::<Vec<_>>(),
+        vec![
+            (true, gas::priority_fee(5.)),
+            (false, gas::priority_fee(1.))
+        ]
+    );
+}
+
+#[test]
+fn flash_loan_liquidator_is_identified_as_winner() {
+    let strategies = vec![
+        GasBidStrategy::ProfitShare { share: 0.5 },
+        GasBidStrategy::ProfitShare { share: 0.1 },
+    ];
+    let (mut env, mut agents, mut rng) = liquidatable_position(strategies, true);
+    let liquidators = agents.liquidation_agents.get_addresses();
+
+    step_liquidators(&mut env, &mut agents, &mut rng);
+
+    let bids = agents.take_liquidation_bids();
+    assert_eq!(bids.len(), 2);
+    assert!(bids.iter().all(|x| x.winner == Some(liquidators[0])));
+}
+
+#[test]
+fn liquidations_are_dropped_if_bid_exceeds_profit() {
+    let strategies = vec![GasBidStrategy::Fixed { bid: 1e9 }];
+    let (mut env, mut agents, mut rng) = liquidatable_position(strategies, false);
+
+    let transactions = agents.liquidation_agents.call(&mut rng, &mut env);
+    assert!(transactions.is_empty());
+    assert!(agents.take_liquidation_bids().is_empty());
+}
+
+#[test]
+fn liquidators_without_strategy_do_not_bid() {
+    let (mut env, mut agents, mut rng) = liquidatable_position(Vec::new(), false);
+
+    let transactions = agents.liquidation_agents.call(&mut rng, &mut env);
+    assert_eq!(transactions.len(), 2);
+    assert!(transactions.iter().all(|x| x.gas_priority_fee.is_none()));
+}
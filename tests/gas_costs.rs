@@ -0,0 +1,156 @@
+use alloy_primitives::U256;
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::agent::AgentSet;
+use verbs_rust_examples::aave::agents::AgentStates;
+use verbs_rust_examples::aave::initialisation::initialise_sim;
+use verbs_rust_examples::aave::outcomes::process_block;
+use verbs_rust_examples::aave::types::{BaseFee, GasToken};
+use verbs_rust_examples::aave::validator::OrderedGasPriorityValidator;
+use verbs_rust_examples::aave::{aave_sim, gas, types};
+
+mod common;
+use common::TestEnv;
+
+fn sim_parameters(base_fee: BaseFee) -> types::SimParameters {
+    types::SimParameters {
+        base_fee,
+        ..common::sim_parameters()
+    }
+}
+
+/// Step the borrower until it has supplied and borrowed, then move
+/// the price of token a to 780 so that it can be liquidated
+fn liquidatable_position(base_fee: BaseFee) -> (TestEnv, AgentStates, Xoroshiro128StarStar) {
+    let (env, agents, _, _, rng) = common::liquidatable_position(sim_parameters(base_fee), 780.);
+    (env, agents, rng)
+}
+
+#[test]
+fn eip1559_base_fee_moves_by_at_most_an_eighth() {
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let constant = BaseFee::Constant(30.);
+    assert_eq!(constant.initial(), U256::from(30_000_000_000u64));
+    assert_eq!(
+        constant.next(&mut rng, constant.initial()),
+        constant.initial()
+    );
+
+    let process = BaseFee::Eip1559 {
+        initial: 30.,
+        volatility: 0.5,
+    };
+    let mut base_fee = process.initial();
+    let mut changed = false;
+
+    for _ in 0..100 {
+        let next = process.next(&mut rng, base_fee);
+        let ratio = f64::from(next) / f64::from(base_fee);
+        assert!((0.875 - 1e-9..=1.125 + 1e-9).contains(&ratio));
+        changed |= next != base_fee;
+        base_fee = next;
+    }
+    assert!(changed);
+}
+
+#[test]
+fn outcomes_record_cost_of_gas() {
+    let (mut env, mut agents, _) = liquidatable_position(BaseFee::Constant(20.));
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let liquidator = agents.liquidation_agents.get_addresses()[0];
+    let eth_balance = |env: &mut TestEnv| {
+        env.evm_state().context.evm.db.accounts[&liquidator]
+            .info
+            .balance
+    };
+    let balance = eth_balance(&mut env);
+
+    let transactions = agents.liquidation_agents.call(&mut rng, &mut env);
+    let outcomes = process_block(&mut env, transactions, &mut rng, 3);
+
+    assert_eq!(outcomes.len(), 1);
+    assert!(outcomes[0].success);
+    assert_eq!(
+        outcomes[0].gas_cost,
+        U256::from(outcomes[0].gas_used) * U256::from(20_000_000_000u64)
+    );
+    // Gas is accounted for, but not charged to the ETH balances of agents
+    assert_eq!(eth_balance(&mut env), balance);
+}
+
+#[test]
+fn liquidations_not_covering_gas_are_skipped() {
+    // The liquidation of a position of $1000 covers the cost of its
+    // gas at 20 gwei (around $6), but not at 1000 gwei (around $300)
+    let (mut env, mut agents, mut rng) = liquidatable_position(BaseFee::Constant(20.));
+    let transactions = agents.liquidation_agents.call(&mut rng, &mut env);
+    assert_eq!(transactions.len(), 1);
+
+    let (mut env, mut agents, mut rng) = liquidatable_position(BaseFee::Constant(1000.));
+    assert!(gas::base_fee(&mut env) > U256::ZERO);
+    let transactions = agents.liquidation_agents.call(&mut rng, &mut env);
+    assert!(transactions.is_empty());
+}
+
+#[test]
+fn gas_is_priced_at_gas_token() {
+    // With token b ($1) standing for ETH the gas of the liquidation
+    // at 1000 gwei costs around $0.4, so it is not skipped
+    let params = types::SimParameters {
+        gas_token: GasToken::TokenB,
+        ..sim_parameters(BaseFee::Constant(1000.))
+    };
+    let (mut env, mut agents, (periphery_addresses, _), aave_addresses, mut rng) =
+        common::liquidatable_position(params, 780.);
+    let transactions = agents.liquidation_agents.call(&mut rng, &mut env);
+    assert_eq!(transactions.len(), 1);
+
+    let outcomes = process_block(&mut env, transactions, &mut rng, 3);
+    assert!(outcomes[0].success);
+    let pricer = gas::GasPricer::new(aave_addresses.oracle, periphery_addresses.token_b);
+    assert_eq!(
+        pricer.fees_paid(&mut env, &outcomes),
+        outcomes[0].gas_cost / U256::from(10u64.pow(10))
+    );
+}
+
+#[test]
+fn arbitrage_not_covering_gas_is_skipped() {
+    let trades = |base_fee: BaseFee| {
+        let (mut env, mut agents, _, _, _) =
+            initialise_sim(sim_parameters(base_fee), OrderedGasPriorityValidator {});
+        let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+        (0..10)
+            .map(|step| {
+                let transactions = agents.uniswap_price_agent.call(&mut rng, &mut env);
+                let n = transactions.len();
+                env.process_transactions(transactions, &mut rng, step);
+                env.clear_events();
+                n
+            })
+            .sum::<usize>()
+    };
+
+    // Without gas costs the pool is moved to the external price every
+    // step, but small price moves are not worth arbitraging at a high
+    // base fee
+    assert_eq!(trades(BaseFee::Constant(0.)), 10);
+    assert!(trades(BaseFee::Constant(1000.)) < 10);
+    assert_eq!(trades(BaseFee::Constant(10f64.powi(6))), 0);
+}
+
+#[test]
+fn base_fees_are_recorded_in_simulation_data() {
+    let process = BaseFee::Eip1559 {
+        initial: 30.,
+        volatility: 0.5,
+    };
+    let data = aave_sim(101, 20, sim_parameters(process));
+
+    assert_eq!(data.base_fees.len(), 20);
+    assert_eq!(data.base_fees[0], process.initial());
+    assert!(data
+        .transaction_outcomes
+        .iter()
+        .all(|x| x.gas_cost == U256::from(x.gas_used) * data.base_fees[x.step]));
+}
@@ -0,0 +1,298 @@
+use alloy_primitives::{Address, I256, U256};
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::agent::AgentSet;
+use verbs_rs::utils::{scale_data_value, Eth};
+use verbs_rust_examples::aave::agents::AgentStates;
+use verbs_rust_examples::aave::initialisation::initialise_sim;
+use verbs_rust_examples::aave::liquidation::{
+    adversarial_profit_bound, close_factor, optimal_debt_to_cover, optimal_debt_to_cover_within,
+    LiquidationTerms,
+};
+use verbs_rust_examples::aave::protocol::AaveAddresses;
+use verbs_rust_examples::aave::types::{BorrowerPopulation, ReserveConfiguration};
+use verbs_rust_examples::aave::validator::OrderedGasPriorityValidator;
+use verbs_rust_examples::aave::{calls, types};
+
+mod common;
+use common::TestEnv;
+
+const WAD: u128 = 10u128.pow(18);
+
+/// Position of 1 token a of collateral at a price of 1000, and `debt` token b
+fn terms(health_factor: f64, debt: u128) -> LiquidationTerms {
+    LiquidationTerms {
+        health_factor: U256::from((health_factor * 1e6) as u128 * 10u128.pow(12)),
+        user_debt: U256::from(debt * WAD),
+        user_collateral: U256::from(WAD),
+        debt_price: U256::from(10u128.pow(8)),
+        collateral_price: U256::from(1000 * 10u128.pow(8)),
+        debt_decimals: 18,
+        collateral_decimals: 18,
+        liquidation_bonus: 10500,
+        liquidation_protocol_fee: 1000,
+        flash_loan_premium: 0,
+    }
+}
+
+#[test]
+fn close_factor_depends_on_health_factor() {
+    assert_eq!(close_factor(U256::from(99 * WAD / 100)), 5000);
+    assert_eq!(close_factor(U256::from(95 * WAD / 100)), 10000);
+    assert_eq!(close_factor(U256::from(80 * WAD / 100)), 10000);
+}
+
+#[test]
+fn reserve_configuration_is_decoded() {
+    let data = U256::from(7500u64)
+        | U256::from(8000u64) << 16
+        | U256::from(10500u64) << 32
+        | U256::from(18u64) << 48
+        | U256::from(1u64) << 56
+        | U256::from(1500u64) << 64
+        | U256::from(1000u64) << 152
+        | U256::from(1u64) << 168;
+
+    assert_eq!(
+        ReserveConfiguration::from_bitmap(data),
+        ReserveConfiguration {
+            ltv: 7500,
+            liquidation_threshold: 8000,
+            liquidation_bonus: 10500,
+            decimals: 18,
+            reserve_factor: 1500,
+            liquidation_protocol_fee: 1000,
+            e_mode_category: 1,
+            borrow_cap: 0,
+            supply_cap: 0,
+            debt_ceiling: 0,
+        }
+    );
+}
+
+#[test]
+fn collateral_received_includes_bonus_less_protocol_fee() {
+    let terms = terms(0.9, 800);
+
+    // 500 token b is worth 0.5 token a, plus a 5% bonus, 10% of which is paid to the protocol
+    let (collateral, debt_covered) = terms.collateral_received(U256::from(500 * WAD));
+    assert_eq!(debt_covered, U256::from(500 * WAD));
+    assert_eq!(collateral, U256::from(5225 * WAD / 10000));
+
+    // Covering more than the collateral is worth is limited by the collateral
+    let (collateral, debt_covered) = terms.collateral_received(U256::from(800 * WAD));
+    assert!(collateral < U256::from(WAD));
+    assert_eq!(debt_covered, terms.max_debt_to_cover());
+}
+
+#[test]
+fn adversarial_profit_bound_grows_with_the_price_move() {
+    // Without a manipulation the bound is the bonus less the protocol fee
+    let bound = adversarial_profit_bound(1000., 1., 10500, 1000, 0., 3000);
+    assert!((bound - 45.).abs() < 1e-9);
+
+    // Lowering the collateral price by a fifth seizes a quarter more
+    // collateral, out of which the fees of the round trip are paid
+    let bound = adversarial_profit_bound(1000., 1.25, 10500, 1000, 18000., 3000);
+    assert!((bound - (306.25 - 54.)).abs() < 1e-9);
+}
+
+#[test]
+fn max_debt_to_cover_respects_close_factor() {
+    // Half the debt can be covered above a health factor of 0.95
+    assert_eq!(terms(0.97, 800).max_debt_to_cover(), U256::from(400 * WAD));
+    // All of it below, as long as it is covered by the collateral
+    assert_eq!(terms(0.9, 800).max_debt_to_cover(), U256::from(800 * WAD));
+    assert_eq!(
+        terms(0.9, 990).max_debt_to_cover(),
+        U256::from(1000 * WAD) * U256::from(10000) / U256::from(10500)
+    );
+}
+
+#[test]
+fn full_amount_is_covered_without_slippage() {
+    let terms = terms(0.9, 800);
+    // Collateral sold at the oracle price
+    let (amount, profit) = optimal_debt_to_cover(&terms, |x| Some(x * U256::from(1000))).unwrap();
+
+    assert_eq!(amount, U256::from(800 * WAD));
+    // 4.5% of the 800 covered, in the base currency with 8 decimals
+    assert_eq!(profit, I256::try_from(36 * 10i128.pow(8)).unwrap());
+}
+
+#[test]
+fn partial_amount_is_covered_with_slippage() {
+    let terms = terms(0.9, 800);
+    // Price received falls linearly with the amount sold, x token a
+    // sell for 1000x(1 - x/2) token b
+    let proceeds = |x: U256| {
+        let x = x.to::<u128>() as f64 / 1e18;
+        let out = 1000. * x * (1. - 0.5 * x);
+        Some(U256::from((out * 1e18) as u128))
+    };
+    let (amount, profit) = optimal_debt_to_cover(&terms, proceeds).unwrap();
+
+    let collateral = terms.collateral_received(amount).0.to::<u128>() as f64 / 1e18;
+    let covered = amount.to::<u128>() as f64 / 1e18;
+    assert!(covered < 800.);
+    assert!(profit > I256::ZERO);
+    // Marginal proceeds of the collateral equal the marginal cost of the debt
+    let marginal = 1000. * (1. - collateral) * 1.045 / 1000.;
+    assert!((marginal - 1.).abs() < 1e-3, "{}", marginal);
+}
+
+#[test]
+fn unprofitable_liquidations_are_skipped() {
+    let terms = terms(0.9, 800);
+    // Collateral can only be sold at 90% of the oracle price
+    assert!(optimal_debt_to_cover(&terms, |x| Some(x * U256::from(900))).is_none());
+    assert!(optimal_debt_to_cover(&terms, |_| None).is_none());
+}
+
+#[test]
+fn debt_covered_is_limited_by_budget() {
+    let terms = terms(0.9, 800);
+    let proceeds = |x: U256| Some(x * U256::from(1000));
+
+    let (amount, _) =
+        optimal_debt_to_cover_within(&terms, U256::from(300 * WAD), proceeds).unwrap();
+    assert_eq!(amount, U256::from(300 * WAD));
+    assert!(optimal_debt_to_cover_within(&terms, U256::ZERO, proceeds).is_none());
+}
+
+fn sim_parameters(reverse_assets_probability: f64) -> types::SimParameters {
+    types::SimParameters {
+        borrower_population: BorrowerPopulation {
+            reverse_assets_probability,
+            ..common::borrower_population()
+        },
+        ..common::sim_parameters()
+    }
+}
+
+/// Step the borrower until it has supplied and borrowed, at a health factor of 1.25
+fn borrowed_position(
+    reverse_assets_probability: f64,
+) -> (
+    TestEnv,
+    AgentStates,
+    common::Addresses,
+    AaveAddresses,
+    Xoroshiro128StarStar,
+) {
+    let (mut env, mut agents, addresses, aave_addresses) =
+        common::initialise(sim_parameters(reverse_assets_probability));
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+
+    if reverse_assets_probability > 0. {
+        // Provide liquidity of token a to borrow
+        let liquidator = agents.liquidation_agents.get_addresses()[0];
+        let supply = calls::supply_call(
+            liquidator,
+            aave_addresses.pool,
+            addresses.0.token_a,
+            U256::to_weth(10),
+        );
+        env.process_transactions(vec![supply], &mut rng, 0);
+    }
+    common::open_positions(&mut env, &mut agents, &mut rng);
+
+    (env, agents, addresses, aave_addresses, rng)
+}
+
+fn step_liquidators(env: &mut TestEnv, agents: &mut AgentStates, rng: &mut Xoroshiro128StarStar) {
+    let transactions = agents.liquidation_agents.call(rng, env);
+    env.process_transactions(transactions, rng, 3);
+    env.clear_events();
+}
+
+fn borrower_data(
+    env: &mut TestEnv,
+    agents: &AgentStates,
+    aave_addresses: &AaveAddresses,
+) -> (f64, f64, f64) {
+    let borrower = agents.borrow_agents.get_addresses()[0];
+    let user_data = calls::get_user_data(env, Address::ZERO, aave_addresses.pool, borrower);
+    (
+        scale_data_value(user_data.totalCollateralBase, 8, 8),
+        scale_data_value(user_data.totalDebtBase, 8, 8),
+        scale_data_value(user_data.healthFactor, 18, 6),
+    )
+}
+
+#[test]
+fn reserve_configuration_is_read_from_pool() {
+    let (mut env, _, periphery_addresses, _, aave_addresses) =
+        initialise_sim(sim_parameters(0.), OrderedGasPriorityValidator {});
+
+    let configuration = calls::get_reserve_configuration(
+        &mut env,
+        Address::ZERO,
+        aave_addresses.pool,
+        periphery_addresses.token_a,
+    );
+    assert_eq!(
+        configuration,
+        ReserveConfiguration {
+            ltv: 7500,
+            liquidation_threshold: 8000,
+            liquidation_bonus: 10500,
+            decimals: 18,
+            reserve_factor: 1000,
+            liquidation_protocol_fee: 1000,
+            e_mode_category: 1,
+            borrow_cap: 0,
+            supply_cap: 0,
+            debt_ceiling: 0,
+        }
+    );
+}
+
+#[test]
+fn half_the_debt_is_covered_above_close_factor_threshold() {
+    let (mut env, mut agents, addresses, aave_addresses, mut rng) = borrowed_position(0.);
+
+    common::set_token_a_price(&mut env, &agents, &addresses, &mut rng, 780.);
+    let (_, debt, health_factor) = borrower_data(&mut env, &agents, &aave_addresses);
+    assert!(0.95 < health_factor && health_factor < 1.);
+
+    step_liquidators(&mut env, &mut agents, &mut rng);
+    let (_, new_debt, _) = borrower_data(&mut env, &agents, &aave_addresses);
+    assert!((new_debt - 0.5 * debt).abs() < 1e-2 * debt);
+}
+
+#[test]
+fn all_the_debt_is_covered_below_close_factor_threshold() {
+    let (mut env, mut agents, addresses, aave_addresses, mut rng) = borrowed_position(0.);
+
+    common::set_token_a_price(&mut env, &agents, &addresses, &mut rng, 700.);
+    let (collateral, debt, health_factor) = borrower_data(&mut env, &agents, &aave_addresses);
+    assert!(health_factor < 0.95);
+
+    step_liquidators(&mut env, &mut agents, &mut rng);
+    let (new_collateral, new_debt, _) = borrower_data(&mut env, &agents, &aave_addresses);
+    assert!(new_debt < 1e-2);
+    // Collateral worth the debt plus the 5% bonus is seized
+    assert!((collateral - new_collateral - 1.05 * debt).abs() < 1e-2 * debt);
+}
+
+#[test]
+fn token_b_collateral_is_liquidated_for_token_a_debt() {
+    let (mut env, mut agents, addresses, aave_addresses, mut rng) = borrowed_position(1.);
+    let (collateral, debt, _) = borrower_data(&mut env, &agents, &aave_addresses);
+    assert!(debt > 0.);
+
+    // The value of the token a debt rises
+    common::set_token_a_price(&mut env, &agents, &addresses, &mut rng, 1300.);
+    let (_, _, health_factor) = borrower_data(&mut env, &agents, &aave_addresses);
+    assert!(health_factor < 1.);
+
+    let liquidator = agents.liquidation_agents.get_addresses()[0];
+    let token_b = addresses.0.token_b;
+    let balance = calls::balance_of(&mut env, liquidator, token_b);
+    step_liquidators(&mut env, &mut agents, &mut rng);
+
+    let (new_collateral, _, _) = borrower_data(&mut env, &agents, &aave_addresses);
+    assert!(new_collateral < collateral);
+    assert!(calls::balance_of(&mut env, liquidator, token_b) > balance);
+}
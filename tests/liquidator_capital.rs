@@ -0,0 +1,107 @@
+use alloy_primitives::Address;
+use verbs_rs::agent::AgentSet;
+use verbs_rs::utils::scale_data_value;
+use verbs_rust_examples::aave::agents::AgentStates;
+use verbs_rust_examples::aave::protocol::AaveAddresses;
+use verbs_rust_examples::aave::types::LiquidatorCapital;
+use verbs_rust_examples::aave::{calls, types};
+
+mod common;
+use common::TestEnv;
+
+fn sim_parameters(liquidator_capital: LiquidatorCapital) -> types::SimParameters {
+    types::SimParameters {
+        liquidator_capital: Some(liquidator_capital),
+        ..common::sim_parameters()
+    }
+}
+
+/// Inventory that is never rebalanced
+fn capital(token_a: f64, token_b: f64) -> LiquidatorCapital {
+    LiquidatorCapital {
+        token_a,
+        token_b,
+        target_token_b_share: 0.5,
+        rebalance_threshold: 1.,
+    }
+}
+
+fn balance(env: &mut TestEnv, agents: &AgentStates, token: Address) -> f64 {
+    let liquidator = agents.liquidation_agents.get_addresses()[0];
+    scale_data_value(calls::balance_of(env, liquidator, token), 18, 6)
+}
+
+fn borrower_debt(env: &mut TestEnv, agents: &AgentStates, aave_addresses: &AaveAddresses) -> f64 {
+    let borrower = agents.borrow_agents.get_addresses()[0];
+    let user_data = calls::get_user_data(env, Address::ZERO, aave_addresses.pool, borrower);
+    scale_data_value(user_data.totalDebtBase, 8, 8)
+}
+
+#[test]
+fn liquidators_are_given_finite_inventory() {
+    let (mut env, agents, (periphery_addresses, _), _, _) =
+        common::liquidatable_position(sim_parameters(capital(2., 500.)), 1000.);
+
+    assert_eq!(balance(&mut env, &agents, periphery_addresses.token_a), 2.);
+    assert_eq!(
+        balance(&mut env, &agents, periphery_addresses.token_b),
+        500.
+    );
+}
+
+#[test]
+fn debt_covered_is_limited_by_inventory() {
+    let (mut env, mut agents, (periphery_addresses, _), aave_addresses, mut rng) =
+        common::liquidatable_position(sim_parameters(capital(0., 100.)), 700.);
+    let debt = borrower_debt(&mut env, &agents, &aave_addresses);
+    assert!(debt > 500.);
+
+    let transactions = agents.liquidation_agents.call(&mut rng, &mut env);
+    assert_eq!(transactions.len(), 1);
+    env.process_transactions(transactions, &mut rng, 3);
+
+    // The whole inventory of token b is used to cover part of the debt
+    let new_debt = borrower_debt(&mut env, &agents, &aave_addresses);
+    assert!((debt - new_debt - 100.).abs() < 1.);
+    assert!(balance(&mut env, &agents, periphery_addresses.token_b) < 1e-3);
+    assert!(balance(&mut env, &agents, periphery_addresses.token_a) > 0.);
+}
+
+#[test]
+fn liquidations_that_cannot_be_funded_are_skipped() {
+    let (mut env, mut agents, _, aave_addresses, mut rng) =
+        common::liquidatable_position(sim_parameters(capital(10., 0.)), 700.);
+    let debt = borrower_debt(&mut env, &agents, &aave_addresses);
+
+    let transactions = agents.liquidation_agents.call(&mut rng, &mut env);
+    assert!(transactions.is_empty());
+    assert_eq!(borrower_debt(&mut env, &agents, &aave_addresses), debt);
+}
+
+#[test]
+fn inventory_is_rebalanced_towards_target() {
+    let capital = LiquidatorCapital {
+        token_a: 10.,
+        token_b: 0.,
+        target_token_b_share: 0.5,
+        rebalance_threshold: 0.1,
+    };
+    // No positions can be liquidated
+    let (mut env, mut agents, (periphery_addresses, _), _, mut rng) =
+        common::liquidatable_position(sim_parameters(capital), 1000.);
+
+    let transactions = agents.liquidation_agents.call(&mut rng, &mut env);
+    assert_eq!(transactions.len(), 1);
+    env.process_transactions(transactions, &mut rng, 3);
+
+    // Half the token a is sold, for around 5000 token b
+    let token_a = balance(&mut env, &agents, periphery_addresses.token_a);
+    let token_b = balance(&mut env, &agents, periphery_addresses.token_b);
+    assert!((token_a - 5.).abs() < 1e-3);
+    assert!((token_b - 5000.).abs() < 100.);
+
+    // Once within the threshold of the target the inventory is left alone
+    env.clear_events();
+    let transactions = agents.liquidation_agents.call(&mut rng, &mut env);
+    assert!(transactions.is_empty());
+}
@@ -0,0 +1,172 @@
+use alloy_primitives::Address;
+use verbs_rust_examples::aave::initialisation::{get_init_positions, initialise_sim};
+use verbs_rust_examples::aave::types::{LiquidityBand, LiquidityProfile};
+use verbs_rust_examples::aave::uniswap_math::get_tick_at_sqrt_ratio;
+use verbs_rust_examples::aave::validator::OrderedGasPriorityValidator;
+use verbs_rust_examples::aave::{calls, types};
+
+const RAW: f64 = 1e18;
+
+fn sim_parameters(liquidity_profile: LiquidityProfile) -> types::SimParameters {
+    types::SimParameters {
+        n_borrowers: 1,
+        liquidity_profile,
+        ..Default::default()
+    }
+}
+
+/// Initialise a simulation, returning the pool tick, active liquidity and liquidityNet at the given ticks
+fn initial_liquidity(profile: LiquidityProfile, ticks: &[i32]) -> (i32, u128, Vec<i128>) {
+    let (mut env, _, _, uniswap_addresses, _) =
+        initialise_sim(sim_parameters(profile), OrderedGasPriorityValidator {});
+    let pool = uniswap_addresses.pool;
+    let slot0 = calls::get_slot0(&mut env, Address::ZERO, pool);
+    assert_eq!(get_tick_at_sqrt_ratio(slot0.sqrtPriceX96), Some(slot0.tick));
+
+    let liquidity_net = ticks
+        .iter()
+        .map(|tick| calls::get_tick_liquidity_net(&mut env, Address::ZERO, pool, *tick))
+        .collect();
+
+    (
+        slot0.tick,
+        calls::get_liquidity(&mut env, Address::ZERO, pool),
+        liquidity_net,
+    )
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        ((actual - expected) / expected).abs() < 1e-9,
+        "{} is not close to {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn uniform_profile_mints_configured_liquidity() {
+    let params = sim_parameters(LiquidityProfile::Uniform { width: 2000 });
+    // Initial price of 1000 is at tick 69081
+    let positions = get_init_positions(&params, 69081, 10);
+    assert_eq!(positions.len(), 1);
+    assert_eq!((positions[0].0, positions[0].1), (49080, 89090));
+    assert_close(positions[0].2 as f64, 1e5 * RAW);
+
+    let (_, liquidity, liquidity_net) =
+        initial_liquidity(params.liquidity_profile, &[49080, 89090]);
+    assert_close(liquidity as f64, 1e5 * RAW);
+    assert_eq!(liquidity_net[0], liquidity as i128);
+    assert_eq!(liquidity_net[1], -(liquidity as i128));
+}
+
+#[test]
+fn bands_are_minted_at_given_ticks() {
+    let bands = vec![
+        LiquidityBand {
+            tick_lower: 68000,
+            tick_upper: 70000,
+            liquidity: 1e4,
+        },
+        LiquidityBand {
+            tick_lower: 69000,
+            tick_upper: 69500,
+            liquidity: 5e4,
+        },
+    ];
+    let (_, liquidity, liquidity_net) = initial_liquidity(
+        LiquidityProfile::Bands(bands),
+        &[68000, 69000, 69500, 70000],
+    );
+
+    assert_close(liquidity as f64, 6e4 * RAW);
+    assert_close(liquidity_net[0] as f64, 1e4 * RAW);
+    assert_close(liquidity_net[1] as f64, 5e4 * RAW);
+    assert_close(liquidity_net[2] as f64, -5e4 * RAW);
+    assert_close(liquidity_net[3] as f64, -1e4 * RAW);
+}
+
+#[test]
+#[should_panic(expected = "multiples of the tick spacing")]
+fn bands_must_be_aligned_to_tick_spacing() {
+    let params = sim_parameters(LiquidityProfile::Bands(vec![LiquidityBand {
+        tick_lower: 68005,
+        tick_upper: 70000,
+        liquidity: 1e4,
+    }]));
+    get_init_positions(&params, 69081, 10);
+}
+
+#[test]
+fn gaussian_profile_decays_away_from_price() {
+    let params = sim_parameters(LiquidityProfile::Gaussian {
+        sigma: 100.,
+        n_bands: 10,
+    });
+    let positions = get_init_positions(&params, 69081, 10);
+
+    // Bands of 30 tick spacings covering 3 standard deviations either side
+    assert_eq!(positions.len(), 20);
+    assert_eq!(positions[0].0, 69080 - 3000);
+    assert_eq!(positions[19].1, 69080 + 3000);
+    for pair in positions.windows(2) {
+        assert_eq!(pair[0].1, pair[1].0);
+    }
+    // Symmetric around the band containing the price, with the peak at the price
+    for i in 0..10 {
+        assert_eq!(positions[i].2, positions[19 - i].2);
+    }
+    assert!(positions[..10].windows(2).all(|x| x[0].2 < x[1].2));
+
+    let (_, liquidity, _) = initial_liquidity(params.liquidity_profile, &[]);
+    assert_close(liquidity as f64, positions[10].2 as f64);
+    assert_close(liquidity as f64, 1e5 * RAW * (-0.5 * 0.15f64.powi(2)).exp());
+}
+
+#[test]
+fn profile_is_imported_from_pool_data() {
+    let json = r#"{
+        "tick": "201005",
+        "ticks": [
+            {"tickIdx": "201200", "liquidityNet": "-3000000000000000000000"},
+            {"tickIdx": "200000", "liquidityNet": "1000000000000000000000"},
+            {"tickIdx": "200800", "liquidityNet": "2000000000000000000000"},
+            {"tickIdx": 202000, "liquidityNet": "0"}
+        ]
+    }"#;
+    let profile = LiquidityProfile::from_pool_data(json).unwrap();
+    let bands = vec![
+        LiquidityBand {
+            tick_lower: -1005,
+            tick_upper: -205,
+            liquidity: 1000.,
+        },
+        LiquidityBand {
+            tick_lower: -205,
+            tick_upper: 195,
+            liquidity: 3000.,
+        },
+    ];
+    assert_eq!(profile, LiquidityProfile::RelativeBands(bands));
+
+    // Bands are placed relative to the initial tick, rounded to the tick spacing
+    let params = sim_parameters(profile);
+    let positions = get_init_positions(&params, 69081, 10);
+    assert_eq!(
+        positions,
+        vec![
+            (68080, 68880, 10u128.pow(21)),
+            (68880, 69280, 3 * 10u128.pow(21)),
+        ]
+    );
+
+    let (tick, liquidity, _) = initial_liquidity(params.liquidity_profile, &[]);
+    assert_eq!(tick, 69081);
+    assert_close(liquidity as f64, 3000. * RAW);
+}
+
+#[test]
+fn invalid_pool_data_is_rejected() {
+    let json = r#"{"tick": "1", "ticks": [{"tickIdx": "a", "liquidityNet": "1"}]}"#;
+    assert!(LiquidityProfile::from_pool_data(json).is_err());
+}
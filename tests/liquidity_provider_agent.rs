@@ -0,0 +1,162 @@
+use alloy_primitives::{Address, U256};
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::agent::{AgentSet, RecordedAgentSet};
+use verbs_rs::utils::Eth;
+use verbs_rust_examples::aave::agents::AgentStates;
+use verbs_rust_examples::aave::initialisation::initialise_sim;
+use verbs_rust_examples::aave::protocol::uniswap_abi;
+use verbs_rust_examples::aave::types::{LiquidityStrategy, PositionRecord};
+use verbs_rust_examples::aave::validator::OrderedGasPriorityValidator;
+use verbs_rust_examples::aave::{calls, types};
+
+mod common;
+use common::TestEnv;
+
+fn sim_parameters(strategy: LiquidityStrategy) -> types::SimParameters {
+    types::SimParameters {
+        n_borrowers: 1,
+        n_liquidity_providers: 1,
+        liquidity_provider_strategy: strategy,
+        liquidity_provider_liquidity: 10_f64.powf(4.),
+        ..Default::default()
+    }
+}
+
+/// Run a step of the liquidity provider agents, returning their records
+fn step_liquidity_providers(
+    env: &mut TestEnv,
+    agents: &mut AgentStates,
+    rng: &mut Xoroshiro128StarStar,
+    step: usize,
+) -> Vec<PositionRecord> {
+    let transactions = agents.liquidity_provider_agents.call(rng, env);
+    env.process_transactions(transactions, rng, step);
+    agents.liquidity_provider_agents.record(env);
+    agents
+        .liquidity_provider_agents
+        .take_records()
+        .pop()
+        .unwrap()
+}
+
+/// Sell token a into the pool, moving its price down
+fn sell_token_a(
+    env: &mut TestEnv,
+    rng: &mut Xoroshiro128StarStar,
+    trader: Address,
+    swap_router: Address,
+    tokens: (Address, Address),
+    amount: u128,
+) {
+    let swap = calls::uniswap_swap_call(
+        trader,
+        swap_router,
+        uniswap_abi::SwapRouter::ExactInputSingleParams {
+            amountIn: U256::to_weth(amount),
+            amountOutMinimum: U256::ZERO,
+            deadline: U256::MAX,
+            fee: 500u32,
+            recipient: trader,
+            sqrtPriceLimitX96: U256::ZERO,
+            tokenIn: tokens.0,
+            tokenOut: tokens.1,
+        },
+    );
+    env.process_transactions(vec![swap], rng, 0);
+}
+
+#[test]
+fn full_range_position_is_minted() {
+    let (mut env, mut agents, _, uniswap_addresses, _) = initialise_sim(
+        sim_parameters(LiquidityStrategy::FullRange),
+        OrderedGasPriorityValidator {},
+    );
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let liquidity_before = calls::get_liquidity(&mut env, Address::ZERO, uniswap_addresses.pool);
+
+    let records = step_liquidity_providers(&mut env, &mut agents, &mut rng, 0);
+    let (tick_lower, tick_upper, liquidity, _, _) = records[0];
+
+    assert_eq!((tick_lower, tick_upper), (-887270, 887270));
+    // Minted liquidity is rounded down from the token amounts
+    let expected = 10u128.pow(22);
+    assert!(liquidity <= expected && liquidity > expected - 10);
+    assert_eq!(
+        calls::get_liquidity(&mut env, Address::ZERO, uniswap_addresses.pool),
+        liquidity_before + liquidity
+    );
+
+    // The position is kept on subsequent steps
+    let records = step_liquidity_providers(&mut env, &mut agents, &mut rng, 1);
+    assert_eq!(records[0].0, tick_lower);
+    assert_eq!(records[0].2, liquidity);
+}
+
+#[test]
+fn fixed_width_position_is_not_moved() {
+    let (mut env, mut agents, periphery_addresses, uniswap_addresses, _) = initialise_sim(
+        sim_parameters(LiquidityStrategy::FixedWidth { width: 2 }),
+        OrderedGasPriorityValidator {},
+    );
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let trader = agents.liquidation_agents.get_addresses()[0];
+
+    let tick = calls::get_slot0(&mut env, Address::ZERO, uniswap_addresses.pool).tick;
+    let records = step_liquidity_providers(&mut env, &mut agents, &mut rng, 0);
+    let (tick_lower, tick_upper, _, _, _) = records[0];
+    assert!(tick_lower <= tick && tick < tick_upper);
+    assert_eq!(tick_upper - tick_lower, 50);
+
+    sell_token_a(
+        &mut env,
+        &mut rng,
+        trader,
+        uniswap_addresses.swap_router,
+        (periphery_addresses.token_a, periphery_addresses.token_b),
+        50,
+    );
+    let tick = calls::get_slot0(&mut env, Address::ZERO, uniswap_addresses.pool).tick;
+    assert!(tick < tick_lower);
+
+    let records = step_liquidity_providers(&mut env, &mut agents, &mut rng, 1);
+    assert_eq!((records[0].0, records[0].1), (tick_lower, tick_upper));
+}
+
+#[test]
+fn position_is_rebalanced_when_price_leaves_range() {
+    let (mut env, mut agents, periphery_addresses, uniswap_addresses, _) = initialise_sim(
+        sim_parameters(LiquidityStrategy::RebalanceOnExit { width: 2 }),
+        OrderedGasPriorityValidator {},
+    );
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let trader = agents.liquidation_agents.get_addresses()[0];
+    let provider = agents.liquidity_provider_agents.get_addresses()[0];
+
+    let records = step_liquidity_providers(&mut env, &mut agents, &mut rng, 0);
+    let (tick_lower, _, _, _, _) = records[0];
+
+    sell_token_a(
+        &mut env,
+        &mut rng,
+        trader,
+        uniswap_addresses.swap_router,
+        (periphery_addresses.token_a, periphery_addresses.token_b),
+        50,
+    );
+    let tick = calls::get_slot0(&mut env, Address::ZERO, uniswap_addresses.pool).tick;
+    assert!(tick < tick_lower);
+
+    let records = step_liquidity_providers(&mut env, &mut agents, &mut rng, 1);
+    let (new_tick_lower, new_tick_upper, liquidity, fees0, _) = records[0];
+
+    // The old position is burnt and a new one minted around the current price
+    assert!(new_tick_lower <= tick && tick < new_tick_upper);
+    assert!(liquidity > 0);
+    assert_eq!(
+        calls::get_position_ids(&mut env, provider, uniswap_addresses.nft_position_manager).len(),
+        1
+    );
+    // Fees on token a sold through the old position were collected
+    assert!(fees0 > U256::ZERO);
+}
@@ -0,0 +1,146 @@
+//! Regression snapshots of full simulation runs
+//!
+//! Runs short simulations with fixed seeds and compares a compact
+//! summary of the output against the snapshots committed in
+//! `tests/snapshots`. If a change is expected to alter simulation
+//! outcomes, update the snapshots by running
+//!
+//! ```text
+//! BLESS_SNAPSHOTS=1 cargo test --test regression
+//! ```
+//!
+//! and commit the updated files alongside the change.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use verbs_rust_examples::aave::{aave_sim, types, SimData};
+
+const N_STEPS: usize = 50;
+
+fn sim_parameters() -> types::SimParameters {
+    types::SimParameters {
+        n_borrowers: 5,
+        n_liquidity_providers: 2,
+        liquidity_provider_strategy: types::LiquidityStrategy::RebalanceOnExit { width: 5 },
+        liquidity_provider_liquidity: 10_f64.powf(4.),
+        ..Default::default()
+    }
+}
+
+/// 64-bit FNV-1a hash, stable across platforms and Rust versions
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Hex digest of the json serialisation of a simulation output
+fn digest<T: Serialize>(output: &T) -> String {
+    let serialised = serde_json::to_string(output).expect("Could not serialise to json string");
+    format!("{:016x}", fnv1a(serialised.as_bytes()))
+}
+
+/// Compact summary of a run
+///
+/// The baseline digest covers the agent records and prices, and each
+/// of the other outputs has its own digest, so adding or changing an
+/// output does not invalidate the snapshot of the others.
+fn summarise(data: &SimData) -> Value {
+    let final_health_factors: Vec<f64> = match data.liquidation_agents.last() {
+        Some(records) => records[0].iter().map(|x| x.5).collect(),
+        None => Vec::new(),
+    };
+
+    let baseline = (
+        &data.borrow_agents,
+        &data.liquidation_agents,
+        &data.uniswap_price_agent,
+        &data.uniswap_noise_agents,
+    );
+
+    json!({
+        "seed": data.seed,
+        "n_steps": N_STEPS,
+        "final_prices": data.uniswap_price_agent.last(),
+        "final_health_factors": final_health_factors,
+        "digest": digest(&baseline),
+        "outputs": {
+            "liquidity_provider_agents": digest(&data.liquidity_provider_agents),
+            "liquidation_bids": digest(&data.liquidation_bids),
+            "adversarial_bundles": digest(&data.adversarial_bundles),
+            "sandwiches": digest(&data.sandwiches),
+            "parameter_changes": digest(&data.parameter_changes),
+            "base_fees": digest(&data.base_fees),
+            "transaction_outcomes": digest(&data.transaction_outcomes),
+            "events": digest(&data.events),
+        },
+    })
+}
+
+/// Top level and output fields that differ between two summaries
+fn changed_fields(expected: &Value, actual: &Value) -> Vec<String> {
+    let mut changed: Vec<String> = ["final_prices", "final_health_factors", "digest"]
+        .into_iter()
+        .filter(|k| expected.get(k) != actual.get(k))
+        .map(String::from)
+        .collect();
+    let outputs = |x: &Value| x.get("outputs").and_then(Value::as_object).cloned();
+    let (expected, actual) = (
+        outputs(expected).unwrap_or_default(),
+        outputs(actual).unwrap_or_default(),
+    );
+    let mut keys: Vec<&String> = expected.keys().chain(actual.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    changed.extend(
+        keys.into_iter()
+            .filter(|k| expected.get(*k) != actual.get(*k))
+            .map(|k| format!("outputs.{}", k)),
+    );
+    changed
+}
+
+fn check_snapshot(seed: u64) {
+    let summary = summarise(&aave_sim(seed, N_STEPS, sim_parameters()));
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots")
+        .join(format!("aave_sim_seed_{}.json", seed));
+
+    if env::var_os("BLESS_SNAPSHOTS").is_some() {
+        let contents = serde_json::to_string_pretty(&summary).unwrap();
+        fs::write(&path, contents + "\n").expect("Could not write snapshot");
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "Missing snapshot {}, run with BLESS_SNAPSHOTS=1 to create it",
+            path.display()
+        )
+    });
+    let expected: Value = serde_json::from_str(&expected).expect("Invalid snapshot");
+
+    assert!(
+        summary == expected,
+        "Simulation output for seed {} changed in {:?}\nexpected: {}\nactual: {}\n\
+         If this is intended, run with BLESS_SNAPSHOTS=1 to update the snapshot",
+        seed,
+        changed_fields(&expected, &summary),
+        serde_json::to_string_pretty(&expected).unwrap(),
+        serde_json::to_string_pretty(&summary).unwrap(),
+    );
+}
+
+#[test]
+fn aave_sim_seed_0() {
+    check_snapshot(0);
+}
+
+#[test]
+fn aave_sim_seed_1() {
+    check_snapshot(1);
+}
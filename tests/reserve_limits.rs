@@ -0,0 +1,248 @@
+use alloy_primitives::{Address, U256};
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::agent::AgentSet;
+use verbs_rs::utils::scale_data_value;
+use verbs_rust_examples::aave::agents::AgentStates;
+use verbs_rust_examples::aave::initialisation::initialise_sim;
+use verbs_rust_examples::aave::outcomes::process_block;
+use verbs_rust_examples::aave::protocol::{AaveAddresses, PeripheryAddresses};
+use verbs_rust_examples::aave::types::{
+    ParameterDistribution, ReserveCaps, ReserveLimits, TransactionOutcome,
+};
+use verbs_rust_examples::aave::validator::OrderedGasPriorityValidator;
+use verbs_rust_examples::aave::{calls, limits, types};
+
+mod common;
+use common::TestEnv;
+
+/// Borrowers with $1000 of token a collateral, active every step,
+/// borrowing token b at a health factor of 1.25
+fn sim_parameters(n_borrowers: usize, reserve_limits: ReserveLimits) -> types::SimParameters {
+    types::SimParameters {
+        n_borrowers,
+        reserve_limits,
+        ..common::sim_parameters()
+    }
+}
+
+/// Run the borrowers for a number of steps, passing them the outcomes
+/// of their transactions, and return all the outcomes
+fn run_borrowers(
+    env: &mut TestEnv,
+    agents: &mut AgentStates,
+    rng: &mut Xoroshiro128StarStar,
+    n_steps: usize,
+) -> Vec<TransactionOutcome> {
+    (0..n_steps)
+        .flat_map(|step| {
+            let transactions = agents.borrow_agents.call(rng, env);
+            let outcomes = process_block(env, transactions, rng, step);
+            env.clear_events();
+            agents.observe_outcomes(outcomes.clone());
+            outcomes
+        })
+        .collect()
+}
+
+/// Collateral and debt of each borrower, in USD
+fn positions(
+    env: &mut TestEnv,
+    agents: &AgentStates,
+    aave_addresses: &AaveAddresses,
+) -> Vec<(f64, f64)> {
+    agents
+        .borrow_agents
+        .get_addresses()
+        .into_iter()
+        .map(|borrower| {
+            let user_data = calls::get_user_data(env, Address::ZERO, aave_addresses.pool, borrower);
+            (
+                scale_data_value(user_data.totalCollateralBase, 8, 8),
+                scale_data_value(user_data.totalDebtBase, 8, 8),
+            )
+        })
+        .collect()
+}
+
+fn errors(outcomes: &[TransactionOutcome]) -> Vec<&str> {
+    outcomes.iter().filter_map(|x| x.error.as_deref()).collect()
+}
+
+#[test]
+fn limits_are_read_from_pool() {
+    let reserve_limits = ReserveLimits {
+        token_a: ReserveCaps {
+            supply_cap: 2000,
+            borrow_cap: 1000,
+        },
+        token_b: ReserveCaps {
+            supply_cap: 0,
+            borrow_cap: 500_000,
+        },
+        token_a_debt_ceiling: 1_000_000.,
+    };
+    let (mut env, _, periphery_addresses, _, aave_addresses): (
+        TestEnv,
+        _,
+        PeripheryAddresses,
+        _,
+        AaveAddresses,
+    ) = initialise_sim(
+        sim_parameters(1, reserve_limits),
+        OrderedGasPriorityValidator {},
+    );
+
+    let token_a = calls::get_reserve_configuration(
+        &mut env,
+        Address::ZERO,
+        aave_addresses.pool,
+        periphery_addresses.token_a,
+    );
+    assert_eq!(token_a.supply_cap, 2000);
+    assert_eq!(token_a.borrow_cap, 1000);
+    assert_eq!(token_a.debt_ceiling, 100_000_000);
+
+    let token_b = calls::get_reserve_configuration(
+        &mut env,
+        Address::ZERO,
+        aave_addresses.pool,
+        periphery_addresses.token_b,
+    );
+    assert_eq!(token_b.supply_cap, 0);
+    assert_eq!(token_b.borrow_cap, 500_000);
+    assert_eq!(token_b.debt_ceiling, 0);
+
+    assert_eq!(
+        limits::supply_headroom(&mut env, aave_addresses.pool, periphery_addresses.token_a),
+        Some(U256::from(2000u128 * 10u128.pow(18)))
+    );
+    assert_eq!(
+        limits::supply_headroom(&mut env, aave_addresses.pool, periphery_addresses.token_b),
+        None
+    );
+    assert_eq!(
+        limits::debt_ceiling_headroom(
+            &mut env,
+            aave_addresses.pool,
+            periphery_addresses.token_a,
+            18
+        ),
+        Some(U256::from(1_000_000u128 * 10u128.pow(18)))
+    );
+}
+
+#[test]
+fn admin_leaves_token_b_supply_headroom() {
+    let reserve_limits = ReserveLimits {
+        token_b: ReserveCaps {
+            supply_cap: 10_000,
+            borrow_cap: 0,
+        },
+        ..Default::default()
+    };
+    let (mut env, _, periphery_addresses, _, aave_addresses): (TestEnv, _, _, _, _) =
+        initialise_sim(
+            sim_parameters(1, reserve_limits),
+            OrderedGasPriorityValidator {},
+        );
+
+    assert_eq!(
+        limits::supply_headroom(&mut env, aave_addresses.pool, periphery_addresses.token_b),
+        Some(U256::from(5_000u128 * 10u128.pow(18)))
+    );
+}
+
+#[test]
+fn supplies_are_limited_by_supply_cap() {
+    // Room for the $900 of collateral of two of the three borrowers
+    let reserve_limits = ReserveLimits {
+        token_a: ReserveCaps {
+            supply_cap: 2,
+            borrow_cap: 0,
+        },
+        ..Default::default()
+    };
+    let mut params = sim_parameters(3, reserve_limits);
+    params.borrower_population.position_size = ParameterDistribution::Constant(900.);
+    let (mut env, mut agents, _, _, aave_addresses) =
+        initialise_sim(params, OrderedGasPriorityValidator {});
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+
+    // Borrowers size their supplies against the headroom before the
+    // block, so the last to be included reverts
+    let outcomes = run_borrowers(&mut env, &mut agents, &mut rng, 1);
+    assert_eq!(errors(&outcomes), ["SUPPLY_CAP_EXCEEDED"]);
+
+    // The borrower that reverted retries with the remaining headroom
+    let outcomes = run_borrowers(&mut env, &mut agents, &mut rng, 5);
+    assert!(errors(&outcomes).is_empty());
+
+    let positions = positions(&mut env, &agents, &aave_addresses);
+    assert!(positions.iter().all(|x| x.0 > 0. && x.1 > 0.));
+    let collateral: f64 = positions.iter().map(|x| x.0).sum();
+    assert!((collateral - 2000.).abs() < 0.1);
+}
+
+#[test]
+fn borrows_are_limited_by_borrow_cap() {
+    // Each borrower targets $640 of debt
+    let reserve_limits = ReserveLimits {
+        token_b: ReserveCaps {
+            supply_cap: 0,
+            borrow_cap: 1000,
+        },
+        ..Default::default()
+    };
+    let (mut env, mut agents, _, _, aave_addresses) = initialise_sim(
+        sim_parameters(3, reserve_limits),
+        OrderedGasPriorityValidator {},
+    );
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+
+    let outcomes = run_borrowers(&mut env, &mut agents, &mut rng, 5);
+
+    let debt: f64 = positions(&mut env, &agents, &aave_addresses)
+        .iter()
+        .map(|x| x.1)
+        .sum();
+    assert!(debt <= 1000.);
+    assert!(debt > 990.);
+    assert!(errors(&outcomes)
+        .iter()
+        .all(|x| *x == "BORROW_CAP_EXCEEDED"));
+}
+
+#[test]
+fn isolated_borrows_are_limited_by_debt_ceiling() {
+    let reserve_limits = ReserveLimits {
+        token_a_debt_ceiling: 1000.,
+        ..Default::default()
+    };
+    let (mut env, mut agents, periphery_addresses, _, aave_addresses) = initialise_sim(
+        sim_parameters(3, reserve_limits),
+        OrderedGasPriorityValidator {},
+    );
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+
+    let outcomes = run_borrowers(&mut env, &mut agents, &mut rng, 5);
+
+    let debt: f64 = positions(&mut env, &agents, &aave_addresses)
+        .iter()
+        .map(|x| x.1)
+        .sum();
+    // The ceiling counts borrowed principal, the debt includes interest
+    assert!(debt < 1000.1);
+    assert!(debt > 990.);
+    assert!(errors(&outcomes)
+        .iter()
+        .all(|x| *x == "DEBT_CEILING_EXCEEDED"));
+
+    let reserve = calls::get_reserve_data(
+        &mut env,
+        Address::ZERO,
+        aave_addresses.pool,
+        periphery_addresses.token_a,
+    );
+    assert!(reserve.isolationModeTotalDebt > 99_000);
+}
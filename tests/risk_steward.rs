@@ -0,0 +1,345 @@
+use alloy_primitives::{Address, U256};
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::agent::{Agent, SimState};
+use verbs_rs::utils::address_from_hex;
+use verbs_rust_examples::aave::agents::AgentStates;
+use verbs_rust_examples::aave::initialisation::initialise_sim;
+use verbs_rust_examples::aave::outcomes::process_block;
+use verbs_rust_examples::aave::protocol::{aave_abi, AaveAddresses};
+use verbs_rust_examples::aave::types::{
+    BorrowerPopulation, ParameterChange, ParameterDistribution, ReserveCaps, ReserveLimits,
+    ReserveObservation, ReserveParameters, RiskParameter, RiskPolicy, RiskStewardParameters,
+};
+use verbs_rust_examples::aave::validator::OrderedGasPriorityValidator;
+use verbs_rust_examples::aave::{calls, limits, types};
+
+mod common;
+use common::TestEnv;
+
+const INITIAL: ReserveParameters = ReserveParameters {
+    ltv: 7500,
+    liquidation_threshold: 8000,
+    liquidation_bonus: 10500,
+    reserve_factor: 1000,
+    supply_cap: 100,
+    borrow_cap: 0,
+};
+
+/// Borrowers with $900 of token a collateral, active every step,
+/// borrowing token b at a health factor of 1.25, and a risk steward
+/// reviewing the reserves every `review_period` steps
+fn sim_parameters(
+    n_borrowers: usize,
+    reserve_limits: ReserveLimits,
+    policies: Vec<RiskPolicy>,
+    review_period: usize,
+) -> types::SimParameters {
+    types::SimParameters {
+        n_borrowers,
+        reserve_limits,
+        risk_steward: Some(RiskStewardParameters {
+            policies,
+            review_period,
+            volatility_window: 20,
+        }),
+        borrower_population: BorrowerPopulation {
+            position_size: ParameterDistribution::Constant(900.),
+            ..common::borrower_population()
+        },
+        ..common::sim_parameters()
+    }
+}
+
+/// Run all the agents for a number of steps, returning the parameter
+/// changes of the risk steward
+fn run(
+    env: &mut TestEnv,
+    agents: &mut AgentStates,
+    rng: &mut Xoroshiro128StarStar,
+    steps: std::ops::Range<usize>,
+) -> Vec<ParameterChange> {
+    for step in steps {
+        let transactions = agents.call_agents(rng, env);
+        let outcomes = process_block(env, transactions, rng, step);
+        env.clear_events();
+        agents.observe_outcomes(outcomes);
+    }
+    agents.take_parameter_changes()
+}
+
+fn configuration(
+    env: &mut TestEnv,
+    aave_addresses: &AaveAddresses,
+    token: Address,
+) -> types::ReserveConfiguration {
+    calls::get_reserve_configuration(env, Address::ZERO, aave_addresses.pool, token)
+}
+
+#[test]
+fn policies_are_parsed() {
+    assert_eq!(
+        "volatility:0.5:0.25".parse::<RiskPolicy>(),
+        Ok(RiskPolicy::Volatility {
+            reference: 0.5,
+            sensitivity: 0.25
+        })
+    );
+    assert_eq!(
+        "caps:0.9:0.5".parse::<RiskPolicy>(),
+        Ok(RiskPolicy::Caps {
+            threshold: 0.9,
+            increase: 0.5
+        })
+    );
+    assert_eq!(
+        "reserve-factor:0.8:500".parse::<RiskPolicy>(),
+        Ok(RiskPolicy::ReserveFactor {
+            target: 0.8,
+            step: 500
+        })
+    );
+    assert_eq!(
+        "bad-debt:100:250".parse::<RiskPolicy>(),
+        Ok(RiskPolicy::BadDebt {
+            threshold: 100.,
+            step: 250
+        })
+    );
+    assert!("caps:0.9".parse::<RiskPolicy>().is_err());
+    assert!("static".parse::<RiskPolicy>().is_err());
+}
+
+#[test]
+fn volatility_scales_collateral_parameters() {
+    let policy = RiskPolicy::Volatility {
+        reference: 0.5,
+        sensitivity: 0.5,
+    };
+    let observation = |volatility| ReserveObservation {
+        volatility,
+        ..Default::default()
+    };
+
+    // At the reference volatility the initial parameters are restored
+    let current = ReserveParameters {
+        ltv: 5000,
+        ..INITIAL
+    };
+    assert_eq!(policy.apply(&observation(0.5), &INITIAL, &current), INITIAL);
+
+    // 50% excess volatility scales the parameters by 75%
+    let parameters = policy.apply(&observation(1.), &INITIAL, &INITIAL);
+    assert_eq!(parameters.ltv, 5625);
+    assert_eq!(parameters.liquidation_threshold, 6000);
+    assert_eq!(parameters.liquidation_bonus, 10666);
+
+    // Down to half the initial values
+    let parameters = policy.apply(&observation(10.), &INITIAL, &INITIAL);
+    assert_eq!(parameters.ltv, 3750);
+    assert_eq!(parameters.liquidation_threshold, 4000);
+}
+
+#[test]
+fn caps_reserve_factor_and_bad_debt_policies() {
+    let caps = RiskPolicy::Caps {
+        threshold: 0.9,
+        increase: 0.25,
+    };
+    let observation = ReserveObservation {
+        supply_cap_usage: 0.95,
+        borrow_cap_usage: 0.95,
+        ..Default::default()
+    };
+    // Uncapped borrowing is left uncapped
+    let parameters = caps.apply(&observation, &INITIAL, &INITIAL);
+    assert_eq!((parameters.supply_cap, parameters.borrow_cap), (125, 0));
+    let observation = ReserveObservation {
+        supply_cap_usage: 0.5,
+        ..observation
+    };
+    assert_eq!(caps.apply(&observation, &INITIAL, &INITIAL), INITIAL);
+
+    let reserve_factor = RiskPolicy::ReserveFactor {
+        target: 0.8,
+        step: 500,
+    };
+    let utilisation = |utilisation| ReserveObservation {
+        utilisation,
+        ..Default::default()
+    };
+    let raised = reserve_factor.apply(&utilisation(0.9), &INITIAL, &INITIAL);
+    assert_eq!(raised.reserve_factor, 1500);
+    let raised = reserve_factor.apply(&utilisation(0.9), &INITIAL, &raised);
+    assert_eq!(raised.reserve_factor, 2000);
+    let lowered = reserve_factor.apply(&utilisation(0.5), &INITIAL, &raised);
+    assert_eq!(lowered.reserve_factor, 1500);
+    let lowered = reserve_factor.apply(&utilisation(0.5), &INITIAL, &lowered);
+    let lowered = reserve_factor.apply(&utilisation(0.5), &INITIAL, &lowered);
+    assert_eq!(lowered, INITIAL);
+
+    let bad_debt = RiskPolicy::BadDebt {
+        threshold: 100.,
+        step: 250,
+    };
+    let increase = |bad_debt_increase| ReserveObservation {
+        bad_debt_increase,
+        ..Default::default()
+    };
+    assert_eq!(bad_debt.apply(&increase(50.), &INITIAL, &INITIAL), INITIAL);
+    let parameters = bad_debt.apply(&increase(150.), &INITIAL, &INITIAL);
+    assert_eq!(parameters.ltv, 7250);
+    assert_eq!(parameters.reserve_factor, 1250);
+}
+
+#[test]
+fn steward_is_risk_admin_and_applies_changes() {
+    // Any volatility scales the collateral parameters down
+    let policies = vec![RiskPolicy::Volatility {
+        reference: 0.,
+        sensitivity: 0.5,
+    }];
+    let (mut env, mut agents, periphery_addresses, _, aave_addresses) = initialise_sim(
+        sim_parameters(1, ReserveLimits::default(), policies, 5),
+        OrderedGasPriorityValidator {},
+    );
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let steward = agents.risk_steward.as_ref().unwrap().get_address();
+    let is_risk_admin = env
+        .direct_call(
+            Address::ZERO,
+            aave_addresses.acl_manager,
+            aave_abi::ACLManager::isRiskAdminCall { admin: steward },
+            U256::ZERO,
+        )
+        .unwrap()
+        .0
+        ._0;
+    assert!(is_risk_admin);
+
+    // Nothing is submitted between reviews
+    assert!(run(&mut env, &mut agents, &mut rng, 0..4).is_empty());
+
+    let changes = run(&mut env, &mut agents, &mut rng, 4..5);
+    assert!(changes.iter().all(|x| x.step == 4 && x.success));
+    let token_a: Vec<&ParameterChange> = changes
+        .iter()
+        .filter(|x| x.asset == periphery_addresses.token_a)
+        .collect();
+    let parameters: Vec<RiskParameter> = token_a.iter().map(|x| x.parameter).collect();
+    assert_eq!(
+        parameters,
+        [
+            RiskParameter::Ltv,
+            RiskParameter::LiquidationThreshold,
+            RiskParameter::LiquidationBonus
+        ]
+    );
+    assert_eq!(token_a[0].old_value, 7500);
+    assert!(token_a[0].new_value < 7500);
+    assert!(token_a[1].new_value < 8000);
+    assert!(token_a[2].new_value > 10500);
+
+    let configuration = configuration(&mut env, &aave_addresses, periphery_addresses.token_a);
+    assert_eq!(configuration.ltv, token_a[0].new_value);
+    assert_eq!(configuration.liquidation_threshold, token_a[1].new_value);
+    assert_eq!(configuration.liquidation_bonus, token_a[2].new_value);
+}
+
+#[test]
+fn steward_raises_supply_cap_in_use() {
+    // Room for the collateral of two of the three borrowers, so the
+    // steward doubles the cap at its first review
+    let reserve_limits = ReserveLimits {
+        token_a: ReserveCaps {
+            supply_cap: 2,
+            borrow_cap: 0,
+        },
+        ..Default::default()
+    };
+    let policies = vec![RiskPolicy::Caps {
+        threshold: 0.8,
+        increase: 1.,
+    }];
+    let (mut env, mut agents, periphery_addresses, _, aave_addresses) = initialise_sim(
+        sim_parameters(3, reserve_limits, policies, 2),
+        OrderedGasPriorityValidator {},
+    );
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+
+    let changes = run(&mut env, &mut agents, &mut rng, 0..2);
+    assert_eq!(
+        changes,
+        [ParameterChange {
+            step: 1,
+            asset: periphery_addresses.token_a,
+            parameter: RiskParameter::SupplyCap,
+            old_value: 2,
+            new_value: 4,
+            success: true,
+        }]
+    );
+    assert_eq!(
+        configuration(&mut env, &aave_addresses, periphery_addresses.token_a).supply_cap,
+        4
+    );
+    // The cap was full, so the headroom is the increase
+    assert!(
+        limits::supply_headroom(&mut env, aave_addresses.pool, periphery_addresses.token_a)
+            .unwrap()
+            > U256::from(10u128.pow(18))
+    );
+}
+
+#[test]
+fn rejected_changes_are_logged() {
+    let policies = vec![RiskPolicy::ReserveFactor {
+        target: 0.,
+        step: 500,
+    }];
+    let (mut env, mut agents, periphery_addresses, _, aave_addresses) = initialise_sim(
+        sim_parameters(1, ReserveLimits::default(), policies, 1),
+        OrderedGasPriorityValidator {},
+    );
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+
+    // Token b is borrowed from the second step, so its reserve factor
+    // is raised from the second review
+    let changes = run(&mut env, &mut agents, &mut rng, 0..3);
+    assert!(changes.iter().all(|x| x.success));
+    assert!(changes
+        .iter()
+        .any(|x| x.asset == periphery_addresses.token_b
+            && x.parameter == RiskParameter::ReserveFactor
+            && x.new_value == 1500));
+
+    // Without the risk admin role the configurator rejects changes
+    let admin = address_from_hex("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+    let steward = agents.risk_steward.as_ref().unwrap().get_address();
+    env.direct_execute(
+        admin,
+        aave_addresses.acl_manager,
+        aave_abi::ACLManager::removeRiskAdminCall { admin: steward },
+        U256::ZERO,
+    )
+    .unwrap();
+    let reserve_factor =
+        configuration(&mut env, &aave_addresses, periphery_addresses.token_b).reserve_factor;
+
+    let changes = run(&mut env, &mut agents, &mut rng, 3..4);
+    assert!(!changes.is_empty());
+    assert!(changes.iter().all(|x| !x.success));
+    assert_eq!(
+        configuration(&mut env, &aave_addresses, periphery_addresses.token_b).reserve_factor,
+        reserve_factor
+    );
+}
+
+#[test]
+#[should_panic(expected = "Review period of the risk steward must be at least 1 step")]
+fn zero_review_period_is_rejected() {
+    initialise_sim(
+        sim_parameters(1, ReserveLimits::default(), Vec::new(), 0),
+        OrderedGasPriorityValidator {},
+    );
+}
@@ -0,0 +1,229 @@
+use alloy_primitives::{Address, U256};
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::agent::AgentSet;
+use verbs_rs::contract::Transaction;
+use verbs_rs::env::Env;
+use verbs_rs::utils::Eth;
+use verbs_rs::LocalDB;
+use verbs_rust_examples::aave::agents::{AgentStates, OutcomeObserver};
+use verbs_rust_examples::aave::initialisation::initialise_sim;
+use verbs_rust_examples::aave::outcomes::process_block;
+use verbs_rust_examples::aave::protocol::{uniswap_abi, PeripheryAddresses, UniswapAddresses};
+use verbs_rust_examples::aave::types::{BaseFee, ValidatorPolicy};
+use verbs_rust_examples::aave::validator::PolicyValidator;
+use verbs_rust_examples::aave::{aave_sim, calls, gas, types};
+
+type TestEnv = Env<LocalDB, PolicyValidator>;
+
+fn sim_parameters(validator: ValidatorPolicy) -> types::SimParameters {
+    types::SimParameters {
+        n_borrowers: 1,
+        validator,
+        n_searchers: 1,
+        searcher_capital: 1000f64,
+        ..Default::default()
+    }
+}
+
+fn initialise(
+    validator: ValidatorPolicy,
+) -> (TestEnv, AgentStates, (PeripheryAddresses, UniswapAddresses)) {
+    let policy = PolicyValidator::from(&validator);
+    let (env, agents, periphery_addresses, uniswap_addresses, _) =
+        initialise_sim(sim_parameters(validator), policy);
+    (env, agents, (periphery_addresses, uniswap_addresses))
+}
+
+/// Sale of 100 token a by the noise trader
+fn victim_swap(
+    trader: Address,
+    (periphery_addresses, uniswap_addresses): &(PeripheryAddresses, UniswapAddresses),
+    amount_out_minimum: U256,
+) -> Transaction {
+    calls::uniswap_swap_call(
+        trader,
+        uniswap_addresses.swap_router,
+        uniswap_abi::SwapRouter::ExactInputSingleParams {
+            amountIn: U256::to_weth(100),
+            amountOutMinimum: amount_out_minimum,
+            deadline: U256::MAX,
+            fee: 500u32,
+            recipient: trader,
+            sqrtPriceLimitX96: U256::ZERO,
+            tokenIn: periphery_addresses.token_a,
+            tokenOut: periphery_addresses.token_b,
+        },
+    )
+}
+
+fn quote_victim_swap(
+    env: &mut TestEnv,
+    (periphery_addresses, uniswap_addresses): &(PeripheryAddresses, UniswapAddresses),
+) -> U256 {
+    calls::quote_v2_exact_input_swap(
+        env,
+        Address::ZERO,
+        periphery_addresses.token_a,
+        periphery_addresses.token_b,
+        500u32,
+        U256::to_weth(100),
+        uniswap_addresses.quoter_address,
+    )
+    .unwrap()
+    .amountOut
+}
+
+/// Submit the victim swap and any sandwich of it, returning the
+/// amount of token b received by the victim and the sandwiches
+fn process_sandwich(
+    env: &mut TestEnv,
+    agents: &mut AgentStates,
+    addresses: &(PeripheryAddresses, UniswapAddresses),
+    amount_out_minimum: U256,
+) -> (U256, Vec<types::Sandwich>) {
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let trader = agents.uniswap_noise_agents.get_addresses()[0];
+    let token_b = addresses.0.token_b;
+
+    let victim = victim_swap(trader, addresses, amount_out_minimum);
+    let searcher = &mut agents.searcher_agents[0];
+    let mut transactions = searcher.search(&mut rng, env, std::slice::from_ref(&victim));
+    transactions.push(victim);
+
+    let balance = calls::balance_of(env, trader, token_b);
+    env.process_transactions(transactions, &mut rng, 0);
+    searcher.record(env);
+
+    (
+        calls::balance_of(env, trader, token_b) - balance,
+        searcher.take_sandwiches(),
+    )
+}
+
+#[test]
+fn sandwich_worsens_victim_execution() {
+    let (mut env, mut agents, addresses) = initialise(ValidatorPolicy::BundleBuilder);
+    let quote = quote_victim_swap(&mut env, &addresses);
+    let trader = agents.uniswap_noise_agents.get_addresses()[0];
+
+    let (received, sandwiches) = process_sandwich(&mut env, &mut agents, &addresses, U256::ZERO);
+
+    assert_eq!(sandwiches.len(), 1);
+    let sandwich = &sandwiches[0];
+    assert!(sandwich.included);
+    assert_eq!(sandwich.victim, trader);
+    assert_eq!(sandwich.token, addresses.0.token_a);
+    // The searcher realises the profit it simulated, at the expense of the victim
+    assert!(sandwich.realised_profit > 0.);
+    assert!((sandwich.realised_profit - sandwich.expected_profit).abs() < 1e-6);
+    assert!(received < quote);
+}
+
+#[test]
+fn sandwich_profit_is_net_of_fees() {
+    let validator = ValidatorPolicy::BundleBuilder;
+    let params = types::SimParameters {
+        base_fee: BaseFee::Constant(20.),
+        ..sim_parameters(validator.clone())
+    };
+    let (mut env, mut agents, periphery_addresses, uniswap_addresses, aave_addresses) =
+        initialise_sim(params, PolicyValidator::from(&validator));
+    let addresses = (periphery_addresses, uniswap_addresses);
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let trader = agents.uniswap_noise_agents.get_addresses()[0];
+
+    let victim = victim_swap(trader, &addresses, U256::ZERO);
+    let searcher = &mut agents.searcher_agents[0];
+    let mut transactions = searcher.search(&mut rng, &mut env, std::slice::from_ref(&victim));
+    transactions.push(victim);
+    let outcomes: Vec<_> = process_block(&mut env, transactions, &mut rng, 0)
+        .into_iter()
+        .filter(|x| x.sender == searcher.get_address())
+        .collect();
+    let gas_cost: U256 = outcomes.iter().map(|x| x.gas_cost).sum();
+    searcher.observe_outcomes(outcomes);
+    searcher.record(&mut env);
+
+    // The front-run is paid in token a, which stands for ETH
+    let sandwich = &searcher.take_sandwiches()[0];
+    assert!(sandwich.included);
+    assert_eq!(sandwich.token, addresses.0.token_a);
+    let gas_cost = f64::from(gas_cost) / 1e18;
+    assert!(gas_cost > 0.);
+    // The bid is valued at the oracle price of token a
+    let price = calls::get_asset_price(
+        &mut env,
+        Address::ZERO,
+        aave_addresses.oracle,
+        sandwich.token,
+    );
+    let bid = sandwich.bid / (f64::from(price) / 1e8);
+    let expected = sandwich.expected_profit - gas_cost - bid;
+    assert!((sandwich.realised_profit - expected).abs() < 1e-6);
+}
+
+#[test]
+fn sandwich_bids_in_base_currency() {
+    let (mut env, mut agents, addresses) = initialise(ValidatorPolicy::BundleBuilder);
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let trader = agents.uniswap_noise_agents.get_addresses()[0];
+
+    let victim = victim_swap(trader, &addresses, U256::ZERO);
+    let searcher = &mut agents.searcher_agents[0];
+    let transactions = searcher.search(&mut rng, &mut env, std::slice::from_ref(&victim));
+    env.process_transactions(transactions.clone(), &mut rng, 0);
+    searcher.record(&mut env);
+
+    // A share of the profit in token a is valued at its oracle price of
+    // about $1000, in the same unit as the bids of liquidators
+    let sandwich = &searcher.take_sandwiches()[0];
+    assert!((sandwich.bid / (0.9 * 1000. * sandwich.expected_profit) - 1.).abs() < 1e-4);
+    // The bid is paid once, by the front-run
+    assert_eq!(
+        transactions
+            .iter()
+            .map(|x| x.gas_priority_fee)
+            .collect::<Vec<_>>(),
+        vec![Some(gas::priority_fee(sandwich.bid)), None]
+    );
+}
+
+#[test]
+fn slippage_limit_protects_victim() {
+    let (mut env, mut agents, addresses) = initialise(ValidatorPolicy::BundleBuilder);
+    let quote = quote_victim_swap(&mut env, &addresses);
+
+    // Any front-run would make the swap revert
+    let (received, sandwiches) = process_sandwich(&mut env, &mut agents, &addresses, quote);
+
+    assert!(sandwiches.is_empty());
+    assert_eq!(received, quote);
+}
+
+#[test]
+fn sandwich_is_not_placed_around_victim_without_bundles() {
+    let (mut env, mut agents, addresses) = initialise(ValidatorPolicy::GasPriority);
+    let quote = quote_victim_swap(&mut env, &addresses);
+
+    // The two legs execute back to back ahead of the victim, so the
+    // searcher only pays fees, and the victim trades close to its quote
+    let (received, sandwiches) = process_sandwich(&mut env, &mut agents, &addresses, U256::ZERO);
+
+    assert!(sandwiches[0].included);
+    assert!(sandwiches[0].realised_profit < 0.);
+    assert!(received > quote * U256::from(999) / U256::from(1000));
+}
+
+#[test]
+fn sandwiches_are_recorded_in_simulation_data() {
+    let data = aave_sim(101, 20, sim_parameters(ValidatorPolicy::BundleAuction));
+
+    assert!(!data.sandwiches.is_empty());
+    assert!(data.sandwiches.windows(2).all(|x| x[0].step <= x[1].step));
+    assert!(data
+        .sandwiches
+        .iter()
+        .filter(|x| x.included)
+        .all(|x| x.realised_profit > 0.));
+}
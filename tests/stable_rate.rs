@@ -0,0 +1,186 @@
+use alloy_primitives::U256;
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::agent::{Agent, AgentSet, RecordedAgentSet, SimState};
+use verbs_rust_examples::aave::agents::AgentStates;
+use verbs_rust_examples::aave::events::decode_events;
+use verbs_rust_examples::aave::initialisation::initialise_sim;
+use verbs_rust_examples::aave::outcomes::process_block;
+use verbs_rust_examples::aave::types;
+use verbs_rust_examples::aave::types::{
+    BorrowerBehaviour, BorrowerPopulation, BorrowerRecord, ParameterDistribution, ProtocolEvent,
+    ReserveCaps, ReserveLimits, TransactionOutcome,
+};
+use verbs_rust_examples::aave::validator::OrderedGasPriorityValidator;
+
+mod common;
+use common::TestEnv;
+
+/// A borrower with `position_size` of token a collateral, active every
+/// step, borrowing token b at a health factor of 1.25
+fn sim_parameters(
+    position_size: f64,
+    stable_rate_probability: f64,
+    rate_swap_spread: Option<f64>,
+) -> types::SimParameters {
+    types::SimParameters {
+        borrower_population: BorrowerPopulation {
+            position_size: ParameterDistribution::Constant(position_size),
+            stable_rate_probability,
+            behaviour: BorrowerBehaviour {
+                rate_swap_spread,
+                ..common::borrower_behaviour()
+            },
+            ..common::borrower_population()
+        },
+        ..common::sim_parameters()
+    }
+}
+
+/// Run the borrower and keeper for a number of steps, returning the
+/// outcomes of their transactions and the last record of the borrower
+fn run(
+    env: &mut TestEnv,
+    agents: &mut AgentStates,
+    rng: &mut Xoroshiro128StarStar,
+    n_steps: usize,
+) -> (Vec<TransactionOutcome>, BorrowerRecord) {
+    let outcomes = (0..n_steps)
+        .flat_map(|step| {
+            let transactions = agents.call_agents(rng, env);
+            let outcomes = process_block(env, transactions, rng, step);
+            env.clear_events();
+            agents.observe_outcomes(outcomes.clone());
+            outcomes
+        })
+        .collect();
+    agents.borrow_agents.record(env);
+    let record = agents.borrow_agents.take_records().pop().unwrap()[0];
+    (outcomes, record)
+}
+
+fn events(outcomes: &[TransactionOutcome]) -> Vec<ProtocolEvent> {
+    decode_events(outcomes)
+        .into_iter()
+        .map(|x| x.event)
+        .collect()
+}
+
+#[test]
+fn borrowers_choose_interest_rate_mode() {
+    for (stable_rate_probability, mode) in [(0., 2), (1., 1)] {
+        let (mut env, mut agents, _, _, _) = initialise_sim(
+            sim_parameters(1000., stable_rate_probability, None),
+            OrderedGasPriorityValidator {},
+        );
+        let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+        let (outcomes, (_, stable_debt, variable_debt)) = run(&mut env, &mut agents, &mut rng, 2);
+
+        assert!(outcomes.iter().all(|x| x.success));
+        let borrows: Vec<u8> = events(&outcomes)
+            .into_iter()
+            .filter_map(|x| match x {
+                ProtocolEvent::Borrow {
+                    interest_rate_mode, ..
+                } => Some(interest_rate_mode),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(borrows, [mode]);
+
+        // Debt of about 640 token b, at a health factor of 1.25 at the
+        // initial price of token a
+        let debt = match mode {
+            1 => {
+                assert_eq!(variable_debt, U256::ZERO);
+                stable_debt
+            }
+            _ => {
+                assert_eq!(stable_debt, U256::ZERO);
+                variable_debt
+            }
+        };
+        let debt = debt.to::<u128>() as f64 / 1e18;
+        assert!((debt / 640. - 1.).abs() < 0.05);
+    }
+}
+
+#[test]
+fn stable_borrows_are_limited_by_liquidity() {
+    // Only 25% of the 10000 token b supplied, half the supply cap, can
+    // be borrowed at a stable rate
+    let mut params = sim_parameters(1e6, 1., None);
+    params.reserve_limits = ReserveLimits {
+        token_b: ReserveCaps {
+            supply_cap: 20000,
+            borrow_cap: 0,
+        },
+        ..Default::default()
+    };
+    let (mut env, mut agents, _, _, _) = initialise_sim(params, OrderedGasPriorityValidator {});
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+
+    let (outcomes, (_, stable_debt, _)) = run(&mut env, &mut agents, &mut rng, 2);
+    assert!(outcomes.iter().all(|x| x.success));
+    let stable_debt = stable_debt.to::<u128>() as f64 / 1e18;
+    assert!((stable_debt - 2500.).abs() < 1e-2);
+}
+
+#[test]
+fn borrowers_swap_to_cheaper_rate_mode() {
+    // The variable rate is below the stable rate, so stable borrowers swap
+    let (mut env, mut agents, _, _, _) = initialise_sim(
+        sim_parameters(1000., 1., Some(0.)),
+        OrderedGasPriorityValidator {},
+    );
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+
+    let (outcomes, (_, stable_debt, variable_debt)) = run(&mut env, &mut agents, &mut rng, 3);
+    assert!(outcomes.iter().all(|x| x.success));
+    assert!(events(&outcomes).iter().any(|x| matches!(
+        x,
+        ProtocolEvent::SwapBorrowRateMode {
+            interest_rate_mode: 1,
+            ..
+        }
+    )));
+    assert_eq!(stable_debt, U256::ZERO);
+    assert!(variable_debt > U256::ZERO);
+
+    // Variable borrowers do not swap to the more expensive stable rate
+    let (mut env, mut agents, _, _, _) = initialise_sim(
+        sim_parameters(1000., 0., Some(0.)),
+        OrderedGasPriorityValidator {},
+    );
+    let (outcomes, (_, stable_debt, _)) = run(&mut env, &mut agents, &mut rng, 3);
+    assert!(!events(&outcomes)
+        .iter()
+        .any(|x| matches!(x, ProtocolEvent::SwapBorrowRateMode { .. })));
+    assert_eq!(stable_debt, U256::ZERO);
+}
+
+#[test]
+fn keeper_rebalances_stable_rates() {
+    // A stable borrower using most of the 10000 token b supplied, so
+    // the reserve is heavily used with a low liquidity rate
+    let mut params = sim_parameters(1e6, 1., None);
+    params.reserve_limits = ReserveLimits {
+        token_b: ReserveCaps {
+            supply_cap: 10000,
+            borrow_cap: 0,
+        },
+        ..Default::default()
+    };
+    params.stable_rate_rebalancer = true;
+    let (mut env, mut agents, _, _, _) = initialise_sim(params, OrderedGasPriorityValidator {});
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let borrower = agents.borrow_agents.get_addresses()[0];
+
+    let (outcomes, _) = run(&mut env, &mut agents, &mut rng, 20);
+    assert!(outcomes.iter().all(|x| x.success));
+    assert!(events(&outcomes).iter().any(
+        |x| matches!(x, ProtocolEvent::RebalanceStableBorrowRate { user, .. } if *user == borrower)
+    ));
+    let keeper = agents.rate_rebalancer.as_ref().unwrap().get_address();
+    assert!(outcomes.iter().any(|x| x.sender == keeper));
+}
@@ -0,0 +1,139 @@
+use alloy_primitives::U256;
+use alloy_sol_types::{Revert, SolError};
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::agent::AgentSet;
+use verbs_rs::utils::Eth;
+use verbs_rust_examples::aave::outcomes::{decode_error, process_block};
+use verbs_rust_examples::aave::protocol::uniswap_abi;
+use verbs_rust_examples::aave::{aave_sim, calls, types};
+
+mod common;
+
+fn sim_parameters() -> types::SimParameters {
+    types::SimParameters {
+        n_borrowers: 1,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn error_codes_are_decoded() {
+    let revert = |x: &str| decode_error(&Revert::from(x).abi_encode());
+
+    assert_eq!(
+        revert("45").as_deref(),
+        Some("HEALTH_FACTOR_NOT_BELOW_THRESHOLD")
+    );
+    assert_eq!(revert("1").as_deref(), Some("CALLER_NOT_POOL_ADMIN"));
+    assert_eq!(revert("STF").as_deref(), Some("TRANSFER_FROM_FAILED"));
+    // Unknown codes and descriptive reasons are returned unchanged
+    assert_eq!(revert("0").as_deref(), Some("0"));
+    assert_eq!(
+        revert("Too little received").as_deref(),
+        Some("Too little received")
+    );
+    assert_eq!(decode_error(&[]), None);
+}
+
+#[test]
+fn reverted_liquidation_is_reported_to_liquidator() {
+    let (mut env, mut agents, (periphery_addresses, _), aave_addresses) =
+        common::initialise(sim_parameters());
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let borrower = agents.borrow_agents.get_addresses()[0];
+    let liquidator = agents.liquidation_agents.get_addresses()[0];
+
+    let supply = calls::supply_call(
+        borrower,
+        aave_addresses.pool,
+        periphery_addresses.token_a,
+        U256::to_weth(1),
+    );
+    let borrow = calls::borrow_call(
+        borrower,
+        aave_addresses.pool,
+        periphery_addresses.token_b,
+        U256::to_weth(100),
+        types::InterestRateMode::Variable,
+    );
+    let outcomes = process_block(&mut env, vec![supply, borrow], &mut rng, 0);
+    assert!(outcomes.iter().all(|x| x.success && x.error.is_none()));
+    env.clear_events();
+
+    // The borrower is healthy, so the liquidation reverts
+    let liquidation = calls::liquidation_call(
+        periphery_addresses.token_a,
+        periphery_addresses.token_b,
+        borrower,
+        aave_addresses.pool,
+        liquidator,
+        U256::to_weth(50),
+    );
+    let outcomes = process_block(&mut env, vec![liquidation], &mut rng, 1);
+    agents.observe_outcomes(outcomes);
+
+    let outcomes = agents.liquidation_agents.agents_mut()[0].last_outcomes();
+    assert_eq!(outcomes.len(), 1);
+    let outcome = &outcomes[0];
+    assert_eq!(outcome.step, 1);
+    assert_eq!(outcome.sender, liquidator);
+    assert_eq!(outcome.contract, aave_addresses.pool);
+    assert!(!outcome.success);
+    assert_eq!(
+        outcome.error.as_deref(),
+        Some("HEALTH_FACTOR_NOT_BELOW_THRESHOLD")
+    );
+    assert!(outcome.gas_used > 0);
+    assert!(outcome.logs.is_empty());
+
+    // The reverted liquidation is recorded as an unsuccessful event
+    assert!(!env.last_events[0].success);
+    assert_eq!(agents.outcomes.len(), 1);
+}
+
+#[test]
+fn successful_swap_reports_gas_and_logs() {
+    let (mut env, agents, (periphery_addresses, uniswap_addresses), _) =
+        common::initialise(sim_parameters());
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let trader = agents.uniswap_noise_agents.get_addresses()[0];
+
+    let swap = calls::uniswap_swap_call(
+        trader,
+        uniswap_addresses.swap_router,
+        uniswap_abi::SwapRouter::ExactInputSingleParams {
+            amountIn: U256::to_weth(1),
+            amountOutMinimum: U256::ZERO,
+            deadline: U256::MAX,
+            fee: 500u32,
+            recipient: trader,
+            sqrtPriceLimitX96: U256::ZERO,
+            tokenIn: periphery_addresses.token_a,
+            tokenOut: periphery_addresses.token_b,
+        },
+    );
+    let outcomes = process_block(&mut env, vec![swap], &mut rng, 0);
+
+    assert_eq!(outcomes.len(), 1);
+    assert!(outcomes[0].success);
+    assert_eq!(outcomes[0].error, None);
+    assert!(outcomes[0].gas_used > 21000);
+    assert_eq!(outcomes[0].logs, env.last_events[0].logs);
+    assert!(!outcomes[0].logs.is_empty());
+}
+
+#[test]
+fn outcomes_are_recorded_in_simulation_data() {
+    let data = aave_sim(101, 20, sim_parameters());
+
+    assert!(!data.transaction_outcomes.is_empty());
+    assert!(data
+        .transaction_outcomes
+        .windows(2)
+        .all(|x| (x[0].step, x[0].sequence) < (x[1].step, x[1].sequence)));
+    assert!(data
+        .transaction_outcomes
+        .iter()
+        .all(|x| x.gas_used > 0 && (x.error.is_none() || !x.success)));
+}
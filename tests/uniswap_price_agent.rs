@@ -0,0 +1,358 @@
+//! Property tests of the swap sizes used by the Uniswap price agent
+//!
+//! Each case deploys a pool with a random initial price, liquidity
+//! and additional positions, executes the swap the price agent
+//! computes to reach a random target price, and checks the pool
+//! price after the swap. Swap amounts computed across ticks are
+//! cross-checked against quotes from `Quoter_v2`.
+
+use alloy_primitives::{Address, U256};
+use proptest::prelude::*;
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::agent::AgentSet;
+use verbs_rs::env::Env;
+use verbs_rs::LocalDB;
+use verbs_rust_examples::aave::initialisation::{get_sqrt_price_token_a_x96, initialise_sim};
+use verbs_rust_examples::aave::protocol::uniswap_abi;
+use verbs_rust_examples::aave::uniswap_math::{
+    get_sqrt_ratio_at_tick, get_swap, get_swap_to_price, price_to_sqrt_price_x96,
+    sqrt_price_x96_to_price, PoolState, PoolTicks, MAX_SQRT_RATIO, MAX_TICK, MIN_SQRT_RATIO,
+    MIN_TICK,
+};
+use verbs_rust_examples::aave::validator::OrderedGasPriorityValidator;
+use verbs_rust_examples::aave::{calls, types};
+
+const FEE: u32 = 500;
+
+// Relative tolerance of the pool price after a swap
+const TOLERANCE: f64 = 1e-12;
+
+fn sim_parameters(price: f64, liquidity: f64) -> types::SimParameters {
+    types::SimParameters {
+        n_borrowers: 1,
+        token_a_initial_price: (price * 1e8) as i128,
+        liquidity,
+        uniswap_fee: FEE,
+        ..Default::default()
+    }
+}
+
+/// Mint positions around the current tick of the pool, given as offsets and widths in multiples of the tick spacing
+fn mint_positions(
+    env: &mut Env<LocalDB, OrderedGasPriorityValidator>,
+    owner: Address,
+    nft_position_manager: Address,
+    pool: Address,
+    tokens: (Address, Address),
+    positions: &[(i32, i32, u32)],
+) {
+    let tick = calls::get_slot0(env, Address::ZERO, pool).tick;
+    let tick_spacing = calls::get_tick_spacing(env, Address::ZERO, pool);
+    let tick = tick.div_euclid(tick_spacing);
+
+    for (offset, width, size) in positions {
+        let amount = U256::from(10).pow(U256::from(18 + size));
+        env.direct_execute(
+            owner,
+            nft_position_manager,
+            uniswap_abi::NonfungiblePositionManager::mintCall {
+                params: uniswap_abi::NonfungiblePositionManager::MintParams {
+                    amount0Desired: amount,
+                    amount0Min: U256::ZERO,
+                    amount1Desired: amount,
+                    amount1Min: U256::ZERO,
+                    deadline: U256::MAX,
+                    fee: FEE,
+                    recipient: owner,
+                    tickLower: (tick + offset) * tick_spacing,
+                    tickUpper: (tick + offset + width) * tick_spacing,
+                    token0: tokens.0,
+                    token1: tokens.1,
+                },
+            },
+            U256::ZERO,
+        )
+        .expect("Minting position failed");
+    }
+}
+
+/// Swap the pool towards `price_ratio` times its initial price, returning the target and final sqrt prices
+///
+/// Additional positions are minted before the swap, so the swap can cross initialised ticks.
+fn swap_to_target(
+    price: f64,
+    liquidity: f64,
+    price_ratio: f64,
+    positions: &[(i32, i32, u32)],
+) -> (U256, U256) {
+    let params = sim_parameters(price, liquidity);
+    let (mut env, mut agents, periphery_addresses, uniswap_addresses, _) =
+        initialise_sim(params.clone(), OrderedGasPriorityValidator {});
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    let agent_address = agents.uniswap_price_agent.get_addresses()[0];
+    let pool = uniswap_addresses.pool;
+
+    let sqrt_price_before = calls::get_slot0(&mut env, Address::ZERO, pool).sqrtPriceX96;
+    assert_eq!(sqrt_price_before, get_sqrt_price_token_a_x96(&params));
+    mint_positions(
+        &mut env,
+        agent_address,
+        uniswap_addresses.nft_position_manager,
+        pool,
+        (periphery_addresses.token_a, periphery_addresses.token_b),
+        positions,
+    );
+    let target =
+        price_to_sqrt_price_x96(sqrt_price_x96_to_price(sqrt_price_before) * price_ratio).unwrap();
+
+    let agent = agents.uniswap_price_agent.agent_mut();
+    let transaction = if target > sqrt_price_before {
+        let params = agent
+            .get_swap_size_to_increase_uniswap_price(&mut env, target)
+            .unwrap();
+        calls::uniswap_swap_call(agent_address, uniswap_addresses.swap_router, params)
+    } else {
+        let params = agent
+            .get_swap_size_to_decrease_uniswap_price(&mut env, target)
+            .unwrap();
+        calls::uniswap_swap_call_exact_output(agent_address, uniswap_addresses.swap_router, params)
+    };
+    env.process_transactions(vec![transaction], &mut rng, 0);
+
+    let sqrt_price_after = calls::get_slot0(&mut env, Address::ZERO, pool).sqrtPriceX96;
+
+    (target, sqrt_price_after)
+}
+
+fn relative_error(actual: U256, expected: U256) -> f64 {
+    let actual: f64 = actual.into();
+    let expected: f64 = expected.into();
+    ((actual - expected) / expected).abs()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn swap_moves_pool_to_higher_target(
+        price in 1f64..10_000f64,
+        liquidity in 1e3f64..1e7f64,
+        price_ratio in 1.001f64..1.25f64,
+    ) {
+        let (target, sqrt_price_after) = swap_to_target(price, liquidity, price_ratio, &[]);
+        prop_assert!(relative_error(sqrt_price_after, target) < TOLERANCE);
+    }
+
+    #[test]
+    fn swap_moves_pool_to_lower_target(
+        price in 1f64..10_000f64,
+        liquidity in 1e3f64..1e7f64,
+        price_ratio in 0.8f64..0.999f64,
+    ) {
+        let (target, sqrt_price_after) = swap_to_target(price, liquidity, price_ratio, &[]);
+        prop_assert!(relative_error(sqrt_price_after, target) < TOLERANCE);
+    }
+
+    #[test]
+    fn swap_moves_pool_to_target_across_ticks(
+        price_ratio in 0.8f64..1.25f64,
+        positions in prop::collection::vec((-200i32..200, 1i32..100, 0u32..4), 1..8),
+    ) {
+        let (target, sqrt_price_after) = swap_to_target(1000., 1e5, price_ratio, &positions);
+        prop_assert!(relative_error(sqrt_price_after, target) < TOLERANCE);
+    }
+
+    #[test]
+    fn swap_amounts_match_quoter(
+        price_ratio in 0.8f64..1.25f64,
+        positions in prop::collection::vec((-200i32..200, 1i32..100, 0u32..4), 1..8),
+    ) {
+        let (mut env, agents, periphery_addresses, uniswap_addresses, _) =
+            initialise_sim(sim_parameters(1000., 1e5), OrderedGasPriorityValidator {});
+        let agent_address = agents.uniswap_price_agent.get_addresses()[0];
+        let pool = uniswap_addresses.pool;
+        let (token0, token1) = (periphery_addresses.token_a, periphery_addresses.token_b);
+        mint_positions(
+            &mut env,
+            agent_address,
+            uniswap_addresses.nft_position_manager,
+            pool,
+            (token0, token1),
+            &positions,
+        );
+
+        let state = PoolState::get(&mut env, Address::ZERO, pool, FEE);
+        let target = price_to_sqrt_price_x96(sqrt_price_x96_to_price(state.sqrt_price_x96) * price_ratio)
+            .unwrap();
+        let mut ticks = PoolTicks { network: &mut env, caller: Address::ZERO, pool };
+        let swap = get_swap_to_price(&state, &mut ticks, target).unwrap();
+        let (token_in, token_out) = if target > state.sqrt_price_x96 {
+            (token1, token0)
+        } else {
+            (token0, token1)
+        };
+
+        let exact_input = calls::quote_v2_exact_input_swap(
+            &mut env,
+            agent_address,
+            token_in,
+            token_out,
+            FEE,
+            swap.amount_in,
+            uniswap_addresses.quoter_address,
+        )
+        .unwrap();
+        prop_assert!(relative_error(exact_input.sqrtPriceX96After, target) < TOLERANCE);
+        prop_assert!(relative_error(exact_input.amountOut, swap.amount_out) < TOLERANCE);
+
+        let exact_output = calls::quote_v2_exact_output_swap(
+            &mut env,
+            agent_address,
+            token_in,
+            token_out,
+            FEE,
+            swap.amount_out,
+            uniswap_addresses.quoter_address,
+        )
+        .unwrap();
+        prop_assert!(relative_error(exact_output.sqrtPriceX96After, target) < TOLERANCE);
+        prop_assert!(relative_error(exact_output.amountIn, swap.amount_in) < TOLERANCE);
+    }
+
+    #[test]
+    fn simulated_swaps_match_quoter(
+        amount in 1f64..10_000f64,
+        zero_for_one in any::<bool>(),
+        exact_input in any::<bool>(),
+        positions in prop::collection::vec((-200i32..200, 1i32..100, 0u32..4), 1..8),
+    ) {
+        let (mut env, agents, periphery_addresses, uniswap_addresses, _) =
+            initialise_sim(sim_parameters(1000., 1e5), OrderedGasPriorityValidator {});
+        let agent_address = agents.uniswap_price_agent.get_addresses()[0];
+        let pool = uniswap_addresses.pool;
+        let (token0, token1) = (periphery_addresses.token_a, periphery_addresses.token_b);
+        mint_positions(
+            &mut env,
+            agent_address,
+            uniswap_addresses.nft_position_manager,
+            pool,
+            (token0, token1),
+            &positions,
+        );
+
+        // Amounts of token1 are scaled by the price, so swaps of either
+        // token move the pool by similar amounts
+        let (token_in, token_out) = if zero_for_one { (token0, token1) } else { (token1, token0) };
+        let token_amount = match zero_for_one == exact_input {
+            true => amount,
+            false => amount * 1000.,
+        };
+        let token_amount = U256::from((token_amount * 1e6) as u128) * U256::from(10u64.pow(12));
+
+        let state = PoolState::get(&mut env, Address::ZERO, pool, FEE);
+        let mut ticks = PoolTicks { network: &mut env, caller: Address::ZERO, pool };
+        let (swap, state_after) =
+            get_swap(&state, &mut ticks, zero_for_one, exact_input, token_amount, U256::ZERO).unwrap();
+        // Swaps larger than the liquidity of the pool are only partially
+        // filled, running the price to its limit
+        let limit = match zero_for_one {
+            true => MIN_SQRT_RATIO + U256::from(1),
+            false => MAX_SQRT_RATIO - U256::from(1),
+        };
+
+        if exact_input {
+            let quote = calls::quote_v2_exact_input_swap(
+                &mut env,
+                agent_address,
+                token_in,
+                token_out,
+                FEE,
+                token_amount,
+                uniswap_addresses.quoter_address,
+            )
+            .unwrap();
+            prop_assert!(swap.amount_in == token_amount || state_after.sqrt_price_x96 == limit);
+            prop_assert!(swap.amount_in <= token_amount);
+            prop_assert_eq!(swap.amount_out, quote.amountOut);
+            prop_assert_eq!(state_after.sqrt_price_x96, quote.sqrtPriceX96After);
+        } else {
+            let quote = calls::quote_v2_exact_output_swap(
+                &mut env,
+                agent_address,
+                token_in,
+                token_out,
+                FEE,
+                token_amount,
+                uniswap_addresses.quoter_address,
+            );
+            match quote {
+                Some(quote) => {
+                    prop_assert_eq!(swap.amount_out, token_amount);
+                    prop_assert_eq!(swap.amount_in, quote.amountIn);
+                    prop_assert_eq!(state_after.sqrt_price_x96, quote.sqrtPriceX96After);
+                }
+                // The quoter rejects partially filled outputs
+                None => {
+                    prop_assert!(swap.amount_out < token_amount);
+                    prop_assert_eq!(state_after.sqrt_price_x96, limit);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn swap_sizes_in_wrong_direction_are_none(price_ratio in 0.5f64..2f64) {
+        prop_assume!((price_ratio - 1.).abs() > 1e-6);
+        let (mut env, mut agents, _, uniswap_addresses, _) =
+            initialise_sim(sim_parameters(1000., 1e5), OrderedGasPriorityValidator {});
+        let sqrt_price = calls::get_slot0(&mut env, Address::ZERO, uniswap_addresses.pool).sqrtPriceX96;
+        let target = price_to_sqrt_price_x96(sqrt_price_x96_to_price(sqrt_price) * price_ratio).unwrap();
+        let agent = agents.uniswap_price_agent.agent_mut();
+
+        if price_ratio > 1. {
+            prop_assert!(agent.get_swap_size_to_decrease_uniswap_price(&mut env, target).is_none());
+        } else {
+            prop_assert!(agent.get_swap_size_to_increase_uniswap_price(&mut env, target).is_none());
+        }
+        prop_assert!(agent.get_swap_size_to_increase_uniswap_price(&mut env, sqrt_price).is_none());
+    }
+}
+
+#[test]
+fn sqrt_ratio_at_tick_bounds() {
+    assert_eq!(get_sqrt_ratio_at_tick(MIN_TICK), Some(MIN_SQRT_RATIO));
+    assert_eq!(get_sqrt_ratio_at_tick(MAX_TICK), Some(MAX_SQRT_RATIO));
+    assert_eq!(get_sqrt_ratio_at_tick(0), Some(U256::from(1) << 96));
+    assert_eq!(get_sqrt_ratio_at_tick(MIN_TICK - 1), None);
+    assert_eq!(get_sqrt_ratio_at_tick(MAX_TICK + 1), None);
+}
+
+proptest! {
+    #[test]
+    fn sqrt_ratio_at_tick_matches_float(tick in MIN_TICK..=MAX_TICK) {
+        let sqrt_price = get_sqrt_ratio_at_tick(tick).unwrap();
+        let expected = 1.0001f64.powf(tick as f64 / 2.);
+        let actual = sqrt_price_x96_to_price(sqrt_price).sqrt();
+        prop_assert!((actual / expected - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sqrt_price_round_trips(exponent in -30f64..30f64) {
+        let price = 10f64.powf(exponent);
+        let sqrt_price = price_to_sqrt_price_x96(price).unwrap();
+        prop_assert!((sqrt_price_x96_to_price(sqrt_price) / price - 1.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn sqrt_price_rejects_invalid_prices(price in prop_oneof![
+        Just(0f64),
+        Just(f64::NAN),
+        Just(f64::INFINITY),
+        -1e30f64..0f64,
+        1e-300f64..1e-39f64,
+        1e39f64..1e300f64,
+    ]) {
+        prop_assert!(price_to_sqrt_price_x96(price).is_none());
+    }
+}
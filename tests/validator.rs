@@ -0,0 +1,255 @@
+use alloy_primitives::{Address, U256};
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro128StarStar;
+use verbs_rs::contract::Transaction;
+use verbs_rs::env::Validator;
+use verbs_rust_examples::aave::protocol::periphery_abi;
+use verbs_rust_examples::aave::types::{AgentType, ValidatorPolicy};
+use verbs_rust_examples::aave::validator::PolicyValidator;
+
+fn agent(id: u16) -> Address {
+    let mut address = [0u8; 20];
+    address[18..].copy_from_slice(&id.to_be_bytes());
+    Address::from(address)
+}
+
+fn transaction(sender: u16, contract: u16, nonce: Option<u64>, fee: u64) -> Transaction {
+    let mut transaction = Transaction::basic(
+        agent(sender),
+        agent(contract),
+        periphery_abi::MintableERC20::approveCall {
+            spender: agent(contract),
+            amount: U256::from(fee),
+        },
+        false,
+    );
+    transaction.nonce = nonce;
+    transaction.gas_priority_fee = Some(U256::from(fee));
+    transaction
+}
+
+fn order(policy: ValidatorPolicy, transactions: Vec<Transaction>) -> Vec<(u16, Option<u64>)> {
+    let mut rng = Xoroshiro128StarStar::seed_from_u64(101);
+    PolicyValidator::from(&policy)
+        .order_transactions(&mut rng, transactions)
+        .into_iter()
+        .map(|x| {
+            let id = x.callee.as_slice();
+            (u16::from_be_bytes([id[18], id[19]]), x.nonce)
+        })
+        .collect()
+}
+
+#[test]
+fn agent_types_are_identified_from_addresses() {
+    assert_eq!(AgentType::from_address(agent(1005)), AgentType::Borrower);
+    assert_eq!(AgentType::from_address(agent(2001)), AgentType::Liquidator);
+    assert_eq!(AgentType::from_address(agent(3000)), AgentType::PriceAgent);
+    assert_eq!(AgentType::from_address(agent(4002)), AgentType::NoiseTrader);
+    assert_eq!(
+        AgentType::from_address(agent(5000)),
+        AgentType::LiquidityProvider
+    );
+    assert_eq!(
+        AgentType::from_address(Address::repeat_byte(0xaa)),
+        AgentType::Other
+    );
+    assert_eq!("noise-trader".parse(), Ok(AgentType::NoiseTrader));
+    assert_eq!(AgentType::from_address(agent(6000)), AgentType::Searcher);
+    assert!("validator".parse::<AgentType>().is_err());
+}
+
+#[test]
+fn gas_priority_orders_senders_by_fee() {
+    let transactions = vec![
+        transaction(1000, 1, None, 1),
+        transaction(2000, 1, Some(1), 5),
+        transaction(2000, 1, Some(0), 5),
+        transaction(4000, 1, None, 3),
+    ];
+
+    assert_eq!(
+        order(ValidatorPolicy::GasPriority, transactions),
+        vec![(2000, Some(0)), (2000, Some(1)), (4000, None), (1000, None)]
+    );
+}
+
+#[test]
+fn gas_priority_orders_each_bid_by_its_own_fee() {
+    // The second bid of liquidator 2000 does not ride on its first
+    let transactions = vec![
+        transaction(2000, 1, None, 1),
+        transaction(2000, 2, None, 7),
+        transaction(2001, 1, None, 5),
+    ];
+
+    assert_eq!(
+        order(ValidatorPolicy::GasPriority, transactions),
+        vec![(2000, None), (2001, None), (2000, None)]
+    );
+}
+
+#[test]
+fn random_order_keeps_sender_transactions_together() {
+    let transactions: Vec<Transaction> = (0..20)
+        .flat_map(|i| {
+            [
+                transaction(1000 + i, 1, Some(0), 0),
+                transaction(1000 + i, 1, Some(1), 0),
+            ]
+        })
+        .collect();
+
+    let ordered = order(ValidatorPolicy::Random, transactions);
+    let senders: Vec<u16> = ordered.iter().step_by(2).map(|x| x.0).collect();
+
+    assert!(ordered
+        .chunks(2)
+        .all(|x| x[0].0 == x[1].0 && x[0].1 == Some(0) && x[1].1 == Some(1)));
+    assert_ne!(senders, (1000..1020).collect::<Vec<u16>>());
+}
+
+#[test]
+fn first_come_first_served_prioritises_agent_types() {
+    let transactions = vec![
+        transaction(4000, 1, None, 9),
+        transaction(1000, 1, None, 0),
+        transaction(2000, 1, None, 0),
+        transaction(1001, 1, None, 9),
+    ];
+
+    let policy = ValidatorPolicy::FirstComeFirstServed {
+        priority: vec![AgentType::Liquidator, AgentType::Borrower],
+    };
+    assert_eq!(
+        order(policy, transactions.clone()),
+        vec![(2000, None), (1000, None), (1001, None), (4000, None)]
+    );
+
+    // Without priorities transactions are included as submitted, ignoring fees
+    let policy = ValidatorPolicy::FirstComeFirstServed {
+        priority: Vec::new(),
+    };
+    assert_eq!(
+        order(policy, transactions),
+        vec![(4000, None), (1000, None), (2000, None), (1001, None)]
+    );
+}
+
+#[test]
+fn bundle_builder_places_victim_between_front_and_back_run() {
+    let transactions = vec![
+        transaction(1000, 2, None, 1),
+        transaction(4000, 1, None, 2),
+        transaction(6000, 1, Some(0), 10),
+        transaction(6000, 1, Some(2), 10),
+    ];
+
+    assert_eq!(
+        order(ValidatorPolicy::BundleBuilder, transactions),
+        vec![(6000, Some(0)), (4000, None), (6000, Some(2)), (1000, None)]
+    );
+}
+
+#[test]
+fn bundle_without_victim_is_dropped() {
+    let transactions = vec![
+        transaction(1000, 2, None, 1),
+        transaction(6000, 1, Some(0), 10),
+        transaction(6000, 1, Some(2), 10),
+    ];
+
+    assert_eq!(
+        order(ValidatorPolicy::BundleBuilder, transactions),
+        vec![(1000, None)]
+    );
+}
+
+#[test]
+fn bundles_competing_for_a_victim_go_to_the_highest_bid() {
+    let transactions = vec![
+        transaction(4000, 1, None, 2),
+        transaction(6000, 1, Some(0), 10),
+        transaction(6000, 1, Some(2), 10),
+        transaction(6001, 1, Some(0), 20),
+        transaction(6001, 1, Some(2), 20),
+    ];
+
+    assert_eq!(
+        order(ValidatorPolicy::BundleBuilder, transactions),
+        vec![(6001, Some(0)), (4000, None), (6001, Some(2))]
+    );
+}
+
+#[test]
+fn bundle_auction_drops_conflicting_bundles() {
+    let transactions = vec![
+        transaction(1000, 3, None, 1),
+        transaction(6000, 1, Some(0), 10),
+        transaction(6000, 2, Some(1), 10),
+        transaction(6001, 2, Some(0), 20),
+        transaction(6002, 3, Some(0), 5),
+    ];
+
+    // The builder includes every bundle
+    assert_eq!(
+        order(ValidatorPolicy::BundleBuilder, transactions.clone()),
+        vec![
+            (6001, Some(0)),
+            (6000, Some(0)),
+            (6000, Some(1)),
+            (6002, Some(0)),
+            (1000, None)
+        ]
+    );
+    // The second bundle calls a contract of the winning bundle
+    assert_eq!(
+        order(ValidatorPolicy::BundleAuction, transactions),
+        vec![(6001, Some(0)), (6002, Some(0)), (1000, None)]
+    );
+}
+
+#[test]
+fn bundle_auction_includes_a_shared_victim_once() {
+    let transactions = vec![
+        transaction(1000, 2, None, 1),
+        transaction(4000, 1, None, 2),
+        transaction(6000, 1, Some(0), 10),
+        transaction(6000, 1, Some(2), 10),
+        transaction(6001, 1, Some(0), 20),
+        transaction(6001, 1, Some(2), 20),
+    ];
+
+    assert_eq!(
+        order(ValidatorPolicy::BundleAuction, transactions),
+        vec![(6001, Some(0)), (4000, None), (6001, Some(2)), (1000, None)]
+    );
+}
+
+#[test]
+fn bundle_auction_keeps_victims_of_dropped_bundles() {
+    let transactions = vec![
+        transaction(1000, 3, None, 1),
+        transaction(1001, 3, None, 3),
+        transaction(4000, 1, None, 5),
+        transaction(4001, 2, None, 2),
+        transaction(6000, 1, Some(0), 20),
+        transaction(6000, 1, Some(2), 20),
+        transaction(6001, 2, Some(0), 10),
+        transaction(6001, 1, Some(2), 10),
+    ];
+
+    // The second bundle calls the pool of the winning bundle, and its
+    // victim is included by priority fee
+    assert_eq!(
+        order(ValidatorPolicy::BundleAuction, transactions),
+        vec![
+            (6000, Some(0)),
+            (4000, None),
+            (6000, Some(2)),
+            (1001, None),
+            (4001, None),
+            (1000, None)
+        ]
+    );
+}